@@ -0,0 +1 @@
+"""Long-lived app tokens for headless clients."""
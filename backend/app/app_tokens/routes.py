@@ -0,0 +1,94 @@
+"""App token login and management for the current user."""
+
+import logging
+from typing import Annotated
+
+from fastapi import APIRouter, Depends, HTTPException, Request, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.app_tokens.schemas import AppTokenCreate, AppTokenCreated, AppTokenLogin, AppTokenResponse
+from app.app_tokens.service import create_app_token, find_by_token, get_app_token, list_app_tokens, to_response
+from app.auth.dependencies import get_current_user
+from app.auth.jwt import create_access_token
+from app.config import get_settings
+from app.db.session import get_db
+from app.limiter import limiter
+from app.users.models import TokenPair, User
+from app.users.service import get_user_by_email
+
+router = APIRouter(prefix="/api", tags=["app-tokens"])
+log = logging.getLogger(__name__)
+
+
+@router.post("/auth/token", response_model=TokenPair)
+@limiter.limit("30/minute")
+async def login_with_app_token(
+    request: Request,
+    body: AppTokenLogin,
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> TokenPair:
+    """Exchange an app token for an access token. No refresh token is issued: the client presents
+    the app token again, so revoking it ends the session within one access token lifetime."""
+    row = await find_by_token(session, body.token)
+    if row is None:
+        log.warning("App token login failed: unknown or revoked token")
+        raise HTTPException(
+            status_code=status.HTTP_401_UNAUTHORIZED,
+            detail="Invalid or revoked app token",
+        )
+    user = await get_user_by_email(session, row.user_email)
+    if not user:
+        raise HTTPException(
+            status_code=status.HTTP_401_UNAUTHORIZED,
+            detail="Invalid or revoked app token",
+        )
+    if not user.is_enabled:
+        log.warning("App token login rejected for disabled account email=%s", user.email)
+        raise HTTPException(
+            status_code=status.HTTP_403_FORBIDDEN,
+            detail="Account is disabled",
+        )
+    log.info("App token login for email=%s (token %s)", user.email, row.name)
+    settings = get_settings()
+    return TokenPair(
+        access_token=create_access_token(user.email),
+        refresh_token="",
+        expires_in=settings.access_token_expire_minutes * 60,
+    )
+
+
+@router.get("/app-tokens", response_model=list[AppTokenResponse])
+async def get_my_app_tokens(
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> list[AppTokenResponse]:
+    """List the current user's app tokens, newest first."""
+    return [to_response(t) for t in await list_app_tokens(session, current_user.email)]
+
+
+@router.post("/app-tokens", response_model=AppTokenCreated, status_code=status.HTTP_201_CREATED)
+@limiter.limit("10/minute")
+async def create_my_app_token(
+    request: Request,
+    body: AppTokenCreate,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> AppTokenCreated:
+    """Create an app token. The token is only returned in this response."""
+    row, token = await create_app_token(session, current_user.email, body.name)
+    log.info("User %s created app token %s", current_user.email, row.name)
+    return AppTokenCreated(**to_response(row).model_dump(), token=token)
+
+
+@router.delete("/app-tokens/{token_id}", status_code=status.HTTP_204_NO_CONTENT)
+async def revoke_my_app_token(
+    token_id: int,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> None:
+    """Revoke an app token; clients using it can no longer log in."""
+    row = await get_app_token(session, current_user.email, token_id)
+    if row is None:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="App token not found")
+    await session.delete(row)
+    log.info("User %s revoked app token %s", current_user.email, row.name)
@@ -0,0 +1,34 @@
+"""Pydantic schemas for the app tokens API."""
+
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel, Field
+
+
+class AppTokenCreate(BaseModel):
+    """Name shown in the token list (e.g. the host it is used on)."""
+
+    name: str = Field(..., min_length=1, max_length=255)
+
+
+class AppTokenResponse(BaseModel):
+    """Token as listed; the secret itself is never returned again after creation."""
+
+    id: int
+    name: str
+    prefix: str
+    created_at: datetime
+    last_used_at: Optional[datetime] = None
+
+
+class AppTokenCreated(AppTokenResponse):
+    """Returned once on creation; `token` is the secret to configure on the client."""
+
+    token: str
+
+
+class AppTokenLogin(BaseModel):
+    """Exchange an app token for an access token."""
+
+    token: str = Field(..., min_length=1, max_length=255)
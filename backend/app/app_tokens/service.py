@@ -0,0 +1,78 @@
+"""Create, look up and revoke app tokens."""
+
+from __future__ import annotations
+
+import hashlib
+import secrets
+from datetime import datetime, timezone
+from typing import Optional
+
+from sqlalchemy import delete, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.app_tokens.models import AppToken
+from app.app_tokens.schemas import AppTokenResponse
+
+TOKEN_PREFIX = "bbx_"
+
+
+def hash_token(token: str) -> str:
+    """SHA-256 hex of the token. Tokens are random 256-bit values, so no salt or slow hash is needed."""
+    return hashlib.sha256(token.encode("utf-8")).hexdigest()
+
+
+def to_response(row: AppToken) -> AppTokenResponse:
+    """Map an AppToken row to the API schema (without the secret)."""
+    return AppTokenResponse(
+        id=row.id,
+        name=row.name,
+        prefix=row.prefix,
+        created_at=row.created_at,
+        last_used_at=row.last_used_at,
+    )
+
+
+async def create_app_token(session: AsyncSession, user_email: str, name: str) -> tuple[AppToken, str]:
+    """Store a new token for the user. Returns the row and the plaintext token (shown once)."""
+    token = TOKEN_PREFIX + secrets.token_urlsafe(32)
+    row = AppToken(
+        user_email=user_email,
+        name=name[:255],
+        token_hash=hash_token(token),
+        prefix=token[: len(TOKEN_PREFIX) + 6],
+        created_at=datetime.now(timezone.utc),
+    )
+    session.add(row)
+    await session.flush()
+    return row, token
+
+
+async def list_app_tokens(session: AsyncSession, user_email: str) -> list[AppToken]:
+    """All tokens of a user, newest first."""
+    result = await session.execute(
+        select(AppToken).where(AppToken.user_email == user_email).order_by(AppToken.created_at.desc())
+    )
+    return list(result.scalars().all())
+
+
+async def get_app_token(session: AsyncSession, user_email: str, token_id: int) -> Optional[AppToken]:
+    """Return the user's token with this id or None."""
+    result = await session.execute(
+        select(AppToken).where(AppToken.user_email == user_email, AppToken.id == token_id)
+    )
+    return result.scalar_one_or_none()
+
+
+async def find_by_token(session: AsyncSession, token: str) -> Optional[AppToken]:
+    """Look up a presented token by its hash and record the use."""
+    result = await session.execute(select(AppToken).where(AppToken.token_hash == hash_token(token.strip())))
+    row = result.scalar_one_or_none()
+    if row is not None:
+        row.last_used_at = datetime.now(timezone.utc)
+        await session.flush()
+    return row
+
+
+async def delete_user_app_tokens(session: AsyncSession, user_email: str) -> None:
+    """Revoke every token of a user (account deletion)."""
+    await session.execute(delete(AppToken).where(AppToken.user_email == user_email))
@@ -77,3 +77,25 @@ def get_subject_from_refresh(token: str) -> Optional[str]:
     if not payload or payload.get("type") != "refresh":
         return None
     return payload.get("sub")
+
+
+# Confirmation tokens for deleting one's own account are short-lived and single-purpose.
+ACCOUNT_DELETION_TOKEN_MINUTES = 10
+
+
+def create_account_deletion_token(subject: str) -> str:
+    """Create a short-lived JWT confirming that the user asked to delete their account."""
+    settings = get_settings()
+    expire = datetime.now(timezone.utc) + timedelta(minutes=ACCOUNT_DELETION_TOKEN_MINUTES)
+    to_encode: dict[str, Any] = {"sub": subject, "exp": expire, "type": "delete_account"}
+    return jwt.encode(
+        to_encode, settings.jwt_secret, algorithm=settings.jwt_algorithm
+    )
+
+
+def get_subject_from_deletion_token(token: str) -> Optional[str]:
+    """Return subject (email) if token is a valid account deletion token."""
+    payload = decode_token(token)
+    if not payload or payload.get("type") != "delete_account":
+        return None
+    return payload.get("sub")
@@ -43,9 +43,43 @@ def _add_google_sub_preferences_columns(conn) -> None:
         conn.execute(text("ALTER TABLE users ADD COLUMN storage_used_bytes BIGINT DEFAULT 0 NOT NULL"))
 
 
+def _add_is_enabled_column_if_missing(conn) -> None:
+    """Add users.is_enabled (default enabled) if the column does not exist (migration)."""
+    cursor = conn.execute(text("PRAGMA table_info(users)"))
+    rows = cursor.fetchall()
+    if any(row[1] == "is_enabled" for row in rows):
+        return
+    conn.execute(text("ALTER TABLE users ADD COLUMN is_enabled BOOLEAN DEFAULT 1 NOT NULL"))
+
+
+def _add_blake3_hash_column_if_missing(conn) -> None:
+    """Add file_hashes.blake3_hash if the column does not exist (migration)."""
+    cursor = conn.execute(text("PRAGMA table_info(file_hashes)"))
+    rows = cursor.fetchall()
+    if any(row[1] == "blake3_hash" for row in rows):
+        return
+    conn.execute(text("ALTER TABLE file_hashes ADD COLUMN blake3_hash VARCHAR(64)"))
+
+
+def _add_oauth_client_columns_if_missing(conn) -> None:
+    """Add the desktop loopback redirect and PKCE columns to the OAuth tables if missing."""
+    names = {row[1] for row in conn.execute(text("PRAGMA table_info(oauth_states)")).fetchall()}
+    if "client_redirect" not in names:
+        conn.execute(text("ALTER TABLE oauth_states ADD COLUMN client_redirect VARCHAR(255)"))
+    if "client_state" not in names:
+        conn.execute(text("ALTER TABLE oauth_states ADD COLUMN client_state VARCHAR(128)"))
+    if "code_challenge" not in names:
+        conn.execute(text("ALTER TABLE oauth_states ADD COLUMN code_challenge VARCHAR(128)"))
+    names = {row[1] for row in conn.execute(text("PRAGMA table_info(oauth_exchanges)")).fetchall()}
+    if "code_challenge" not in names:
+        conn.execute(text("ALTER TABLE oauth_exchanges ADD COLUMN code_challenge VARCHAR(128)"))
+
+
 async def init_db() -> None:
     """Create tables if they do not exist, then run migrations."""
-    # Register models so create_all includes oauth + telemetry tables.
+    # Register models so create_all includes devices, app tokens, oauth + telemetry tables.
+    from app.app_tokens import models as _app_tokens_models  # noqa: F401
+    from app.devices import models as _devices_models  # noqa: F401
     from app.oauth import models as _oauth_models  # noqa: F401
     from app.telemetry import models as _telemetry_models  # noqa: F401
 
@@ -53,6 +87,9 @@ async def init_db() -> None:
         await conn.run_sync(Base.metadata.create_all)
         await conn.run_sync(_add_storage_limit_column_if_missing)
         await conn.run_sync(_add_google_sub_preferences_columns)
+        await conn.run_sync(_add_is_enabled_column_if_missing)
+        await conn.run_sync(_add_blake3_hash_column_if_missing)
+        await conn.run_sync(_add_oauth_client_columns_if_missing)
 
 
 @asynccontextmanager
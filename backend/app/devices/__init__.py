@@ -0,0 +1 @@
+"""Devices: registered client installs per user (list, revoke)."""
@@ -0,0 +1,78 @@
+"""Device registration and management for the current user."""
+
+import logging
+from typing import Annotated
+
+from fastapi import APIRouter, Depends, HTTPException, Path, Request, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.auth.dependencies import get_current_user
+from app.db.session import get_db
+from app.devices.schemas import DeviceRegister, DeviceResponse
+from app.devices.service import get_device, list_devices, revoke_device, to_response, upsert_device
+from app.limiter import limiter
+from app.users.models import User
+
+router = APIRouter(prefix="/api", tags=["devices"])
+log = logging.getLogger(__name__)
+
+DeviceId = Annotated[str, Path(min_length=1, max_length=64)]
+
+
+@router.put("/devices/{device_id}", response_model=DeviceResponse)
+@limiter.limit("120/minute")
+async def register_device(
+    request: Request,
+    device_id: DeviceId,
+    body: DeviceRegister,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> DeviceResponse:
+    """Register this device or update its name, version, last-seen and last-sync.
+    Returns 410 Gone when the user revoked the device, so the client can wipe its credentials."""
+    existing = await get_device(session, current_user.email, device_id)
+    if existing is not None and existing.revoked_at is not None:
+        log.warning("Revoked device %s of %s tried to register", device_id, current_user.email)
+        raise HTTPException(status_code=status.HTTP_410_GONE, detail="Device revoked")
+    device = await upsert_device(session, current_user.email, device_id, body)
+    if existing is None:
+        log.info("Registered device %s (%s) for %s", device_id, body.name, current_user.email)
+    return to_response(device)
+
+
+@router.get("/devices", response_model=list[DeviceResponse])
+async def get_my_devices(
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> list[DeviceResponse]:
+    """List the current user's devices, most recently seen first."""
+    return [to_response(d) for d in await list_devices(session, current_user.email)]
+
+
+@router.post("/devices/{device_id}/revoke", response_model=DeviceResponse)
+async def revoke_my_device(
+    device_id: DeviceId,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> DeviceResponse:
+    """Revoke one of the current user's devices (e.g. a lost laptop)."""
+    device = await get_device(session, current_user.email, device_id)
+    if device is None:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="Device not found")
+    await revoke_device(session, device)
+    log.info("User %s revoked device %s (%s)", current_user.email, device_id, device.name)
+    return to_response(device)
+
+
+@router.delete("/devices/{device_id}", status_code=status.HTTP_204_NO_CONTENT)
+async def delete_my_device(
+    device_id: DeviceId,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> None:
+    """Forget a device entirely. A deleted device registers again on its next start."""
+    device = await get_device(session, current_user.email, device_id)
+    if device is None:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="Device not found")
+    await session.delete(device)
+    log.info("User %s removed device %s", current_user.email, device_id)
@@ -0,0 +1,30 @@
+"""Pydantic schemas for the devices API."""
+
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel, Field
+
+
+class DeviceRegister(BaseModel):
+    """Sent by a client on start and after each sync (registers on first call)."""
+
+    name: str = Field(..., min_length=1, max_length=255)
+    platform: str = Field("", max_length=32)
+    client_version: str = Field("", max_length=64)
+    last_sync_at: Optional[datetime] = None
+    last_sync_ok: Optional[bool] = None
+
+
+class DeviceResponse(BaseModel):
+    """Device as shown in the client's device list."""
+
+    device_id: str
+    name: str
+    platform: str
+    client_version: str
+    created_at: datetime
+    last_seen_at: datetime
+    last_sync_at: Optional[datetime] = None
+    last_sync_ok: Optional[bool] = None
+    revoked: bool = False
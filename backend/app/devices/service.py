@@ -0,0 +1,70 @@
+"""Register, list and revoke devices."""
+
+from __future__ import annotations
+
+from datetime import datetime, timezone
+from typing import Optional
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.devices.models import Device
+from app.devices.schemas import DeviceRegister, DeviceResponse
+
+
+def to_response(device: Device) -> DeviceResponse:
+    """Map a Device row to the API schema."""
+    return DeviceResponse(
+        device_id=device.device_id,
+        name=device.name,
+        platform=device.platform,
+        client_version=device.client_version,
+        created_at=device.created_at,
+        last_seen_at=device.last_seen_at,
+        last_sync_at=device.last_sync_at,
+        last_sync_ok=device.last_sync_ok,
+        revoked=device.revoked_at is not None,
+    )
+
+
+async def get_device(session: AsyncSession, user_email: str, device_id: str) -> Optional[Device]:
+    """Return the user's device with this id or None."""
+    result = await session.execute(
+        select(Device).where(Device.user_email == user_email, Device.device_id == device_id)
+    )
+    return result.scalar_one_or_none()
+
+
+async def upsert_device(
+    session: AsyncSession, user_email: str, device_id: str, body: DeviceRegister
+) -> Device:
+    """Insert or update the device and bump last_seen_at. Caller checks revoked_at first."""
+    now = datetime.now(timezone.utc)
+    device = await get_device(session, user_email, device_id)
+    if device is None:
+        device = Device(user_email=user_email, device_id=device_id[:64], created_at=now)
+        session.add(device)
+    device.name = body.name[:255]
+    device.platform = body.platform[:32]
+    device.client_version = body.client_version[:64]
+    device.last_seen_at = now
+    if body.last_sync_at is not None:
+        device.last_sync_at = body.last_sync_at
+        device.last_sync_ok = body.last_sync_ok
+    await session.flush()
+    return device
+
+
+async def list_devices(session: AsyncSession, user_email: str) -> list[Device]:
+    """All devices of a user, most recently seen first (revoked ones included)."""
+    result = await session.execute(
+        select(Device).where(Device.user_email == user_email).order_by(Device.last_seen_at.desc())
+    )
+    return list(result.scalars().all())
+
+
+async def revoke_device(session: AsyncSession, device: Device) -> None:
+    """Mark the device revoked; it can no longer register until deleted by the user."""
+    if device.revoked_at is None:
+        device.revoked_at = datetime.now(timezone.utc)
+        await session.flush()
@@ -1,5 +1,7 @@
 """SQLAlchemy model for storing content hashes per user/path."""
 
+from typing import Optional
+
 from sqlalchemy import String
 from sqlalchemy.orm import Mapped, mapped_column
 
@@ -14,3 +16,5 @@ class FileHash(Base):
     user_email: Mapped[str] = mapped_column(String(255), primary_key=True)
     path: Mapped[str] = mapped_column(String(2048), primary_key=True)
     content_hash: Mapped[str] = mapped_column(String(64), nullable=False)  # SHA-256 hex
+    # BLAKE3 hex; only for files stored while the blake3 package was installed
+    blake3_hash: Mapped[Optional[str]] = mapped_column(String(64), nullable=True)
@@ -1,19 +1,29 @@
-"""Content-hash storage for fast sync comparison. Hash is SHA-256 of file body."""
+"""Content-hash storage for fast sync comparison. Hash is SHA-256 of file body, plus BLAKE3
+when the optional ``blake3`` package is installed."""
 
 import hashlib
 import logging
-from typing import Dict, List
+from typing import Dict, List, Optional
 
 from sqlalchemy import select
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.files.hash_model import FileHash
 
+try:
+    import blake3 as _blake3
+except ImportError:  # optional: without it the server offers SHA-256 only
+    _blake3 = None
+
 log = logging.getLogger(__name__)
 
 
-async def get_hashes_for_paths(session: AsyncSession, user_email: str, paths: List[str]) -> Dict[str, str]:
-    """Return dict path -> content_hash for paths that have a stored hash. Missing paths are omitted."""
+def supported_hash_algorithms() -> List[str]:
+    """Hash algorithms listed in ``/api/meta/capabilities``, SHA-256 first."""
+    return ["sha256", "blake3"] if _blake3 is not None else ["sha256"]
+
+
+async def _hashes_for_paths(session: AsyncSession, user_email: str, paths: List[str], column) -> Dict[str, str]:
     out: Dict[str, str] = {}
     chunk = 500  # stay under SQLite parameter limit
     for i in range(0, len(paths), chunk):
@@ -21,23 +31,59 @@ async def get_hashes_for_paths(session: AsyncSession, user_email: str, paths: Li
         if not part:
             continue
         result = await session.execute(
-            select(FileHash.path, FileHash.content_hash).where(
+            select(FileHash.path, column).where(
                 FileHash.user_email == user_email,
                 FileHash.path.in_(part),
             )
         )
         for row in result.all():
-            out[row[0]] = row[1]
+            if row[1] is not None:
+                out[row[0]] = row[1]
     return out
 
 
-async def set_hash(session: AsyncSession, user_email: str, path: str, content_hash: str) -> None:
-    """Store or update content hash for a file. Caller must commit."""
+async def get_hashes_for_paths(session: AsyncSession, user_email: str, paths: List[str]) -> Dict[str, str]:
+    """Return dict path -> content_hash for paths that have a stored hash. Missing paths are omitted."""
+    return await _hashes_for_paths(session, user_email, paths, FileHash.content_hash)
+
+
+async def get_blake3_hashes_for_paths(session: AsyncSession, user_email: str, paths: List[str]) -> Dict[str, str]:
+    """Like ``get_hashes_for_paths`` for BLAKE3; files stored without one are omitted."""
+    return await _hashes_for_paths(session, user_email, paths, FileHash.blake3_hash)
+
+
+async def set_hash(
+    session: AsyncSession,
+    user_email: str,
+    path: str,
+    content_hash: str,
+    blake3_hash: Optional[str] = None,
+) -> None:
+    """Store or update content hashes for a file. Caller must commit."""
     row = await session.get(FileHash, (user_email, path))
     if row:
         row.content_hash = content_hash
+        row.blake3_hash = blake3_hash
     else:
-        session.add(FileHash(user_email=user_email, path=path, content_hash=content_hash))
+        session.add(FileHash(user_email=user_email, path=path, content_hash=content_hash, blake3_hash=blake3_hash))
+
+
+async def move_hash(session: AsyncSession, user_email: str, from_path: str, to_path: str) -> None:
+    """Carry a moved file's hashes over to its new path. Caller must commit."""
+    row = await session.get(FileHash, (user_email, from_path))
+    if row is None:
+        return
+    content_hash, blake3_hash = row.content_hash, row.blake3_hash
+    await session.delete(row)
+    await session.flush()
+    await set_hash(session, user_email, to_path, content_hash, blake3_hash)
+
+
+async def copy_hash(session: AsyncSession, user_email: str, from_path: str, to_path: str) -> None:
+    """Give a copied file the hashes of its source. Caller must commit."""
+    row = await session.get(FileHash, (user_email, from_path))
+    if row is not None:
+        await set_hash(session, user_email, to_path, row.content_hash, row.blake3_hash)
 
 
 async def delete_hash(session: AsyncSession, user_email: str, path: str) -> None:
@@ -52,6 +98,26 @@ def compute_hash(body: bytes) -> str:
     return hashlib.sha256(body).hexdigest()
 
 
-def get_hasher():
-    """Return a new SHA-256 hasher instance."""
-    return hashlib.sha256()
+class ContentHasher:
+    """SHA-256 and, when available, BLAKE3 over the same stream."""
+
+    def __init__(self) -> None:
+        self._sha256 = hashlib.sha256()
+        self._blake3 = _blake3.blake3() if _blake3 is not None else None
+
+    def update(self, data: bytes) -> None:
+        self._sha256.update(data)
+        if self._blake3 is not None:
+            self._blake3.update(data)
+
+    def hexdigest(self) -> str:
+        """SHA-256 hex digest."""
+        return self._sha256.hexdigest()
+
+    def blake3_hexdigest(self) -> Optional[str]:
+        return self._blake3.hexdigest() if self._blake3 is not None else None
+
+
+def get_hasher() -> ContentHasher:
+    """Return a new hasher; ``hexdigest()`` is SHA-256."""
+    return ContentHasher()
@@ -1,23 +1,33 @@
 """File API routes: list, upload, download, delete."""
 
+import gzip
 import hashlib
+import json
 import logging
+import math
 import os
 import shutil
 import tempfile
+import time
 import uuid
 from pathlib import Path
 from typing import Annotated, List, Optional
 
 from fastapi import APIRouter, Depends, HTTPException, Request, status
-from fastapi.responses import FileResponse, StreamingResponse
+from fastapi.responses import FileResponse, Response, StreamingResponse
+from pydantic import BaseModel, Field
 from sqlalchemy.ext.asyncio import AsyncSession
+from starlette.background import BackgroundTask
+from starlette.concurrency import run_in_threadpool
 
 from app.auth.dependencies import get_current_user
 from app.db.session import get_db
 from app.files.hash_store import (
     get_hashes_for_paths,
+    get_blake3_hashes_for_paths,
     set_hash,
+    copy_hash,
+    move_hash,
     delete_hash,
     get_hasher,
 )
@@ -31,24 +41,70 @@ from app.files.quota import (
 from app.config import get_settings
 from app.files.storage import delete_file as storage_delete_file
 from app.files.storage import (
+    build_folder_zip,
+    copy_file as storage_copy_file,
     list_directories_recursive,
     list_files_recursive,
     make_directory,
+    move_file as storage_move_file,
     resolve_user_path,
+    search_files as storage_search_files,
     user_base_path,
 )
+from app.files.thumbnails import can_thumbnail, clamp_size, render_thumbnail
 from app.limiter import limiter
 from app.users.models import User
 
 router = APIRouter(prefix="/api/files", tags=["files"])
 log = logging.getLogger(__name__)
 
+# Listings at least this large (JSON bytes) are gzipped for clients that accept it.
+LIST_GZIP_MIN_BYTES = 64 * 1024
+
+# Paths per batch delete request; clients split larger deletes.
+DELETE_BATCH_MAX = 1000
+
+
+class DeleteBatchRequest(BaseModel):
+    paths: List[str] = Field(max_length=DELETE_BATCH_MAX)
+
 
 def _normalize_path_param(path: Optional[str]) -> str:
     """Return path from query string. Do not replace + with space: filenames may contain +."""
     return path or ""
 
 
+# Client mtimes further in the future than this are ignored (wrong clock on the client).
+MTIME_MAX_FUTURE_SECONDS = 24 * 3600
+
+
+def _parse_mtime_param(value: Optional[str]) -> Optional[float]:
+    """
+    Parse the optional ``mtime`` query param of uploads (Unix seconds, server clock).
+    Returns None when absent or implausible; raises 400 when not a number.
+    """
+    if value is None or not value.strip():
+        return None
+    try:
+        mtime = float(value)
+    except ValueError:
+        raise HTTPException(status_code=400, detail="mtime must be a number of seconds")
+    if not math.isfinite(mtime) or mtime < 0 or mtime > time.time() + MTIME_MAX_FUTURE_SECONDS:
+        log.warning("Ignoring implausible upload mtime %r", value)
+        return None
+    return mtime
+
+
+def _apply_mtime(target: Path, mtime: Optional[float]) -> float:
+    """Set the stored file's mtime to the client's (when given); return the resulting mtime."""
+    if mtime is not None:
+        try:
+            os.utime(target, (mtime, mtime))
+        except OSError as e:
+            log.warning("Cannot set mtime of %s: %s", target, e)
+    return target.stat().st_mtime
+
+
 @router.get("/storage")
 @limiter.limit("60/minute")
 async def get_storage(
@@ -96,6 +152,7 @@ async def get_storage(
 @limiter.limit("60/minute")
 async def list_files(
     request: Request,
+    response: Response,
     current_user: Annotated[User, Depends(get_current_user)],
     session: Annotated[AsyncSession, Depends(get_db)],
 ) -> List[dict]:
@@ -110,13 +167,50 @@ async def list_files(
     result = list_files_recursive(base)
     paths = [r["path"] for r in result]
     hashes = await get_hashes_for_paths(session, current_user.email, paths)
+    blake3_hashes = await get_blake3_hashes_for_paths(session, current_user.email, paths)
     for r in result:
         if r["path"] in hashes:
             r["hash"] = hashes[r["path"]]
+        if r["path"] in blake3_hashes:
+            r["blake3"] = blake3_hashes[r["path"]]
+    # Lets clients detect a listing cut short on the way (they refuse to delete from it)
+    response.headers["X-Total-Count"] = str(len(result))
     log.info("list_files user=%s count=%d", current_user.email, len(result))
+    if "gzip" in request.headers.get("accept-encoding", "").lower():
+        body = json.dumps(result, separators=(",", ":")).encode()
+        if len(body) >= LIST_GZIP_MIN_BYTES:
+            compressed = await run_in_threadpool(gzip.compress, body, 6)
+            return Response(
+                content=compressed,
+                media_type="application/json",
+                headers={"Content-Encoding": "gzip", "Vary": "Accept-Encoding", "X-Total-Count": str(len(result))},
+            )
     return result
 
 
+@router.get("/search", response_model=List[dict])
+@limiter.limit("60/minute")
+async def search_files(
+    request: Request,
+    current_user: Annotated[User, Depends(get_current_user)],
+    q: str,
+    content: bool = False,
+    limit: int = 50,
+) -> List[dict]:
+    """Search the current user's files by name; with ``content=true`` also inside small text files.
+
+    Every word of ``q`` must match. Results carry ``match`` ("name" or "content") and, for content
+    matches, a ``snippet``. Older servers answer 404; clients then search the file list themselves.
+    """
+    if not q.strip():
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail="Query parameter 'q' is required")
+    base = user_base_path(current_user.email)
+    base.mkdir(parents=True, exist_ok=True)
+    results = await run_in_threadpool(storage_search_files, base, q, content, max(1, min(limit, 500)))
+    log.info("search_files user=%s content=%s count=%d", current_user.email, content, len(results))
+    return results
+
+
 @router.get("/folders", response_model=List[dict])
 @limiter.limit("60/minute")
 async def list_folders(
@@ -192,7 +286,7 @@ async def upload_init(
     request: Request,
     current_user: Annotated[User, Depends(get_current_user)],
 ) -> dict:
-    """Initialize a chunked upload. Returns an upload_id."""
+    """Initialize a chunked upload. Returns an upload_id. Optional ``mtime`` as for ``/upload``."""
     path_param = _normalize_path_param(request.query_params.get("path"))
     if not path_param:
         raise HTTPException(status_code=400, detail="path required")
@@ -202,8 +296,11 @@ async def upload_init(
     upload_dir = user_base / ".uploads" / upload_id
     upload_dir.mkdir(parents=True, exist_ok=True)
 
-    # Store the intended path in a metadata file
+    # Store the intended path (and the client's mtime) in metadata files
     (upload_dir / ".path").write_text(path_param, encoding="utf-8")
+    mtime = _parse_mtime_param(request.query_params.get("mtime"))
+    if mtime is not None:
+        (upload_dir / ".mtime").write_text(repr(mtime), encoding="utf-8")
 
     log.info("upload_init user=%s path=%s upload_id=%s", current_user.email, path_param, upload_id)
     return {"upload_id": upload_id}
@@ -255,6 +352,8 @@ async def upload_finalize(
         raise HTTPException(status_code=400, detail="Invalid upload state")
 
     path_param = path_file.read_text(encoding="utf-8")
+    mtime_file = upload_dir / ".mtime"
+    mtime = _parse_mtime_param(mtime_file.read_text(encoding="utf-8")) if mtime_file.exists() else None
     try:
         target = resolve_user_path(current_user.email, path_param)
     except ValueError as e:
@@ -309,12 +408,14 @@ async def upload_finalize(
 
         # Atomic move to final destination
         shutil.move(temp_path, target)
+        stored_mtime = _apply_mtime(target, mtime)
 
         # Update cached usage
         current_user.storage_used_bytes += (total_size - old_size)
         session.add(current_user)
         content_hash = hasher.hexdigest()
-        await set_hash(session, current_user.email, path_param, content_hash)
+        blake3_hash = hasher.blake3_hexdigest()
+        await set_hash(session, current_user.email, path_param, content_hash, blake3_hash)
 
         # Cleanup
         shutil.rmtree(upload_dir)
@@ -323,7 +424,7 @@ async def upload_finalize(
             "upload_finalize user=%s path=%s size=%d hash=%s",
             current_user.email, path_param, total_size, content_hash
         )
-        return {"path": path_param, "size": total_size, "hash": content_hash}
+        return {"path": path_param, "size": total_size, "hash": content_hash, "blake3": blake3_hash, "mtime": stored_mtime}
     except Exception as e:
         if os.path.exists(temp_path):
             os.remove(temp_path)
@@ -342,9 +443,12 @@ async def upload_file(
 ) -> dict:
     """
     Upload a file by streaming the request body directly to a temporary file.
-    Enforces quota during streaming to fail fast.
+    Enforces quota during streaming to fail fast. Optional query param ``mtime`` (Unix seconds)
+    becomes the stored file's mtime, so other devices see when the file was modified rather
+    than when it was uploaded.
     """
     path_param = _normalize_path_param(request.query_params.get("path"))
+    mtime = _parse_mtime_param(request.query_params.get("mtime"))
     if not path_param or not path_param.strip():
         raise HTTPException(
             status_code=status.HTTP_400_BAD_REQUEST,
@@ -415,8 +519,10 @@ async def upload_file(
 
         # Atomic move to final destination
         shutil.move(temp_path, target)
+        stored_mtime = _apply_mtime(target, mtime)
         content_hash = hasher.hexdigest()
-        await set_hash(session, current_user.email, path_param, content_hash)
+        blake3_hash = hasher.blake3_hexdigest()
+        await set_hash(session, current_user.email, path_param, content_hash, blake3_hash)
 
         # Update cached usage
         current_user.storage_used_bytes += (bytes_written - old_size)
@@ -426,7 +532,7 @@ async def upload_file(
             "upload_file user=%s path=%s size=%d resolved=%s",
             current_user.email, path_param, bytes_written, target
         )
-        return {"path": path_param, "size": bytes_written, "hash": content_hash}
+        return {"path": path_param, "size": bytes_written, "hash": content_hash, "blake3": blake3_hash, "mtime": stored_mtime}
 
     except Exception as e:
         if os.path.exists(temp_path):
@@ -437,6 +543,86 @@ async def upload_file(
         raise HTTPException(status_code=500, detail="Internal server error during upload")
 
 
+@router.post("/move")
+@limiter.limit("600/minute")  # Bulk sync renames
+async def move_file(
+    request: Request,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> dict:
+    """
+    Move or rename a file. Query params: from, to (relative paths).
+    Case-only renames are supported. Returns 409 if another file exists at ``to``.
+    """
+    from_param = _normalize_path_param(request.query_params.get("from"))
+    to_param = _normalize_path_param(request.query_params.get("to"))
+    if not from_param.strip() or not to_param.strip():
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="Query parameters 'from' and 'to' are required",
+        )
+    try:
+        await run_in_threadpool(storage_move_file, current_user.email, from_param, to_param)
+    except ValueError as e:
+        log.warning("move_file rejected from=%r to=%r: %s", from_param, to_param, e)
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e))
+    except FileNotFoundError:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="File not found")
+    except FileExistsError as e:
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e))
+    if from_param != to_param:
+        await move_hash(session, current_user.email, from_param, to_param)
+    log.info("move_file user=%s from=%s to=%s", current_user.email, from_param, to_param)
+    return {"from": from_param, "to": to_param, "moved": True}
+
+
+@router.post("/copy")
+@limiter.limit("600/minute")
+async def copy_file(
+    request: Request,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> dict:
+    """
+    Copy a file on the server. Query params: from, to (relative paths). Counts toward the
+    storage limits like an upload (507). Returns 409 if anything exists at ``to``.
+    """
+    from_param = _normalize_path_param(request.query_params.get("from"))
+    to_param = _normalize_path_param(request.query_params.get("to"))
+    if not from_param.strip() or not to_param.strip():
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="Query parameters 'from' and 'to' are required",
+        )
+    try:
+        source = resolve_user_path(current_user.email, from_param)
+    except ValueError as e:
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e))
+    if not source.is_file():
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="File not found")
+    size = source.stat().st_size
+    server_limit = get_server_storage_limit_bytes()
+    user_limit = get_user_storage_limit_bytes(server_limit, current_user.storage_limit_bytes)
+    if server_limit is not None and await get_total_used_bytes(session) + size > server_limit:
+        raise HTTPException(status_code=507, detail="Server storage limit reached")
+    if user_limit is not None and await get_user_used_bytes(session, current_user.email) + size > user_limit:
+        raise HTTPException(status_code=507, detail="Your storage limit has been reached")
+    try:
+        size = await run_in_threadpool(storage_copy_file, current_user.email, from_param, to_param)
+    except ValueError as e:
+        log.warning("copy_file rejected from=%r to=%r: %s", from_param, to_param, e)
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e))
+    except FileNotFoundError:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="File not found")
+    except FileExistsError as e:
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e))
+    await copy_hash(session, current_user.email, from_param, to_param)
+    current_user.storage_used_bytes += size
+    session.add(current_user)
+    log.info("copy_file user=%s from=%s to=%s size=%d", current_user.email, from_param, to_param, size)
+    return {"from": from_param, "to": to_param, "size": size, "copied": True}
+
+
 @router.get("/download")
 @limiter.limit("600/minute")  # Bulk sync: same as upload
 async def download_file(
@@ -473,6 +659,65 @@ async def download_file(
     )
 
 
+@router.get("/download-zip")
+@limiter.limit("10/minute")
+async def download_folder_zip(
+    request: Request,
+    current_user: Annotated[User, Depends(get_current_user)],
+) -> FileResponse:
+    """
+    Download a folder as a zip. Query param: path (relative folder path; empty for all files).
+    Paths in the archive are relative to the folder. 404 when the folder does not exist.
+    """
+    path_param = _normalize_path_param(request.query_params.get("path"))
+    try:
+        archive = await run_in_threadpool(build_folder_zip, current_user.email, path_param)
+    except ValueError as e:
+        log.warning("download_folder_zip rejected path=%r: %s", path_param, e)
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e))
+    except FileNotFoundError:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="Folder not found")
+    name = path_param.strip("/").rsplit("/", 1)[-1] or "brandybox"
+    log.info("download_folder_zip user=%s path=%s", current_user.email, path_param)
+    return FileResponse(
+        path=archive,
+        media_type="application/zip",
+        filename=f"{name}.zip",
+        background=BackgroundTask(archive.unlink, missing_ok=True),
+    )
+
+
+@router.get("/thumbnail")
+@limiter.limit("600/minute")  # A file browser page requests many at once
+async def get_thumbnail(
+    request: Request,
+    current_user: Annotated[User, Depends(get_current_user)],
+    size: int = 256,
+) -> Response:
+    """
+    JPEG preview of an image. Query params: path (relative path), size (longest edge in pixels,
+    32-1024). 404 when the file does not exist, 415 when no preview can be made for it.
+    """
+    path_param = _normalize_path_param(request.query_params.get("path"))
+    if not path_param or not path_param.strip():
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="Query parameter 'path' is required",
+        )
+    try:
+        target = resolve_user_path(current_user.email, path_param)
+    except ValueError as e:
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e))
+    if not target.exists() or not target.is_file():
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="File not found")
+    if not can_thumbnail(target):
+        raise HTTPException(status_code=status.HTTP_415_UNSUPPORTED_MEDIA_TYPE, detail="No preview for this file type")
+    data = await run_in_threadpool(render_thumbnail, target, clamp_size(size))
+    if data is None:
+        raise HTTPException(status_code=status.HTTP_415_UNSUPPORTED_MEDIA_TYPE, detail="Preview could not be created")
+    return Response(content=data, media_type="image/jpeg", headers={"Cache-Control": "private, max-age=86400"})
+
+
 @router.delete("/delete")
 @limiter.limit("600/minute")  # Bulk sync
 async def delete_file(
@@ -519,3 +764,45 @@ async def delete_file(
     await delete_hash(session, current_user.email, path_param)
     log.info("delete_file user=%s path=%s", current_user.email, path_param)
     return {"path": path_param, "deleted": True}
+
+
+@router.post("/delete-batch")
+@limiter.limit("120/minute")
+async def delete_files_batch(
+    request: Request,
+    body: DeleteBatchRequest,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> dict:
+    """
+    Delete up to ``DELETE_BATCH_MAX`` files in one request (JSON body ``{"paths": [...]}``).
+    Paths that do not exist are reported in ``not_found``; invalid ones in ``failed`` with the
+    reason. The others are deleted even when some fail.
+    """
+    deleted: List[str] = []
+    not_found: List[str] = []
+    failed: List[dict] = []
+    freed = 0
+    for path_param in body.paths:
+        try:
+            target = resolve_user_path(current_user.email, path_param)
+            size = target.stat().st_size if target.is_file() else 0
+            storage_delete_file(current_user.email, path_param)
+        except FileNotFoundError:
+            not_found.append(path_param)
+            continue
+        except (ValueError, OSError) as e:
+            log.warning("delete_files_batch rejected path=%r: %s", path_param, e)
+            failed.append({"path": path_param, "error": str(e)})
+            continue
+        await delete_hash(session, current_user.email, path_param)
+        freed += size
+        deleted.append(path_param)
+    current_user.storage_used_bytes = max(0, current_user.storage_used_bytes - freed)
+    session.add(current_user)
+    log.info(
+        "delete_files_batch user=%s deleted=%d not_found=%d failed=%d",
+        current_user.email, len(deleted), len(not_found), len(failed),
+    )
+    return {"deleted": deleted, "not_found": not_found, "failed": failed}
+
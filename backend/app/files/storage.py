@@ -1,8 +1,12 @@
 """Safe path resolution under base dir (no directory traversal)."""
 
 import logging
+import os
 import re
+import shutil
+import tempfile
 import unicodedata
+import zipfile
 from pathlib import Path
 from typing import List, Optional
 
@@ -99,7 +103,11 @@ def delete_file(email: str, relative_path: str) -> None:
         raise ValueError(f"Not a file: {relative_path}")
     target.unlink()
     # Remove empty parent directories so folder deletions propagate
-    parent = target.parent
+    _remove_empty_parents(base, target.parent)
+
+
+def _remove_empty_parents(base: Path, parent: Path) -> None:
+    """Remove ``parent`` and its ancestors while they are empty, stopping at ``base``."""
     while parent != base and parent.exists():
         try:
             if not any(parent.iterdir()):
@@ -111,6 +119,63 @@ def delete_file(email: str, relative_path: str) -> None:
             break
 
 
+def move_file(email: str, from_path: str, to_path: str) -> None:
+    """
+    Move or rename a file under the user's folder, creating parent directories as needed.
+    Case-only renames (``readme.md`` -> ``README.md``) work on case-insensitive filesystems too:
+    the file is renamed via a temporary name. Empty source directories are removed.
+    Raises ValueError for invalid paths, FileNotFoundError if the source does not exist and
+    FileExistsError if a different file already exists at the destination.
+    """
+    base = user_base_path(email)
+    source = resolve_user_path(email, from_path)
+    target = resolve_user_path(email, to_path)
+    if not source.exists():
+        raise FileNotFoundError(f"File not found: {from_path}")
+    if not source.is_file():
+        raise ValueError(f"Not a file: {from_path}")
+    if source == target:
+        return
+    case_only = str(source).lower() == str(target).lower()
+    if target.exists() and not (case_only and target.samefile(source)):
+        raise FileExistsError(f"A file already exists at: {to_path}")
+    target.parent.mkdir(parents=True, exist_ok=True)
+    if case_only:
+        temp = source.with_name(f".{source.name}.brandybox-rename")
+        source.rename(temp)
+        temp.rename(target)
+    else:
+        source.rename(target)
+    _remove_empty_parents(base, source.parent)
+
+
+def copy_file(email: str, from_path: str, to_path: str) -> int:
+    """
+    Copy a file under the user's folder (content and mtime), creating parent directories as
+    needed. The copy is written under a temporary name first, so a failed copy leaves nothing
+    behind. Returns the size copied. Raises ValueError for invalid paths, FileNotFoundError if
+    the source does not exist and FileExistsError if anything exists at the destination.
+    """
+    source = resolve_user_path(email, from_path)
+    target = resolve_user_path(email, to_path)
+    if not source.exists():
+        raise FileNotFoundError(f"File not found: {from_path}")
+    if not source.is_file():
+        raise ValueError(f"Not a file: {from_path}")
+    if target.exists():
+        raise FileExistsError(f"A file already exists at: {to_path}")
+    target.parent.mkdir(parents=True, exist_ok=True)
+    fd, temp_path = tempfile.mkstemp(dir=target.parent, prefix=".bb_copy_")
+    os.close(fd)
+    try:
+        shutil.copy2(source, temp_path)
+        os.replace(temp_path, target)
+    except OSError:
+        Path(temp_path).unlink(missing_ok=True)
+        raise
+    return target.stat().st_size
+
+
 def list_files_recursive(root: Path) -> List[dict]:
     """
     List all files under root with relative path, mtime and size.
@@ -137,6 +202,62 @@ def list_files_recursive(root: Path) -> List[dict]:
     return result
 
 
+# Files searched by content when requested: plain text only, small enough to read at once.
+SEARCHABLE_TEXT_SUFFIXES = {
+    ".txt", ".md", ".csv", ".json", ".xml", ".html", ".htm", ".log", ".ini", ".yaml", ".yml",
+    ".py", ".rs", ".js", ".ts", ".sh",
+}
+SEARCH_CONTENT_MAX_BYTES = 1024 * 1024
+SEARCH_SNIPPET_CHARS = 40
+
+
+def _content_snippet(path: Path, terms: List[str]) -> Optional[str]:
+    """Return text around the first term if every term occurs in the file, else None."""
+    if path.suffix.lower() not in SEARCHABLE_TEXT_SUFFIXES:
+        return None
+    try:
+        if path.stat().st_size > SEARCH_CONTENT_MAX_BYTES:
+            return None
+        text = path.read_text(encoding="utf-8", errors="ignore")
+    except OSError:
+        return None
+    lower = text.lower()
+    if not all(t in lower for t in terms):
+        return None
+    i = lower.find(terms[0])
+    start = max(0, i - SEARCH_SNIPPET_CHARS)
+    end = min(len(text), i + len(terms[0]) + SEARCH_SNIPPET_CHARS)
+    return " ".join(text[start:end].split())
+
+
+def search_files(root: Path, query: str, content: bool = False, limit: int = 50) -> List[dict]:
+    """
+    Find files under ``root`` whose path contains every word of ``query`` (case-insensitive).
+    With ``content``, small text files whose content contains every word also match.
+
+    Returns ``{"path", "mtime", "size", "match": "name"|"content", "snippet"?}``, file-name
+    matches first, then shorter paths.
+    """
+    terms = [t.lower() for t in query.split()]
+    if not terms:
+        return []
+    results: List[dict] = []
+    for entry in list_files_recursive(root):
+        lower = entry["path"].lower()
+        name = lower.rsplit("/", 1)[-1]
+        if all(t in lower for t in terms):
+            rank = 0 if all(t in name for t in terms) else 1
+            results.append({**entry, "match": "name", "_rank": rank})
+        elif content:
+            snippet = _content_snippet(root / entry["path"], terms)
+            if snippet is not None:
+                results.append({**entry, "match": "content", "snippet": snippet, "_rank": 2})
+    results.sort(key=lambda r: (r["_rank"], len(r["path"]), r["path"]))
+    for r in results:
+        del r["_rank"]
+    return results[:limit]
+
+
 def list_directories_recursive(root: Path) -> List[dict]:
     """
     List all directories under ``root`` with relative path and mtime.
@@ -180,3 +301,30 @@ def make_directory(email: str, relative_path: str) -> dict:
         raise FileExistsError(f"A file already exists at: {relative_path}")
     target.mkdir(parents=True, exist_ok=True)
     return {"path": relative_path, "created": True}
+
+
+def build_folder_zip(email: str, relative_path: str) -> Path:
+    """
+    Write a zip of the files under a folder of the user's (the whole root for ``""``), with
+    paths relative to that folder. Returns the path of a temporary file the caller must delete.
+    Raises ValueError for invalid paths and FileNotFoundError if the folder does not exist.
+    Blocking; run in a thread.
+    """
+    folder = resolve_user_path(email, relative_path)
+    if not folder.is_dir():
+        raise FileNotFoundError(f"Folder not found: {relative_path}")
+    fd, temp_path = tempfile.mkstemp(dir=get_settings().storage_base_path, prefix=".bb_zip_", suffix=".zip")
+    os.close(fd)
+    count = 0
+    try:
+        with zipfile.ZipFile(temp_path, "w", compression=zipfile.ZIP_DEFLATED) as zf:
+            for path in sorted(folder.rglob("*")):
+                if not path.is_file() or path.name.startswith(".bb_"):
+                    continue
+                zf.write(path, path.relative_to(folder).as_posix())
+                count += 1
+    except OSError:
+        Path(temp_path).unlink(missing_ok=True)
+        raise
+    log.info("Folder zip for %s: %r, %d file(s)", email, relative_path, count)
+    return Path(temp_path)
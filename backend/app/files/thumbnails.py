@@ -0,0 +1,49 @@
+"""Small JPEG previews of image files for the client file browser and activity feed."""
+
+import io
+import logging
+from pathlib import Path
+from typing import Optional
+
+log = logging.getLogger(__name__)
+
+THUMBNAIL_SUFFIXES = {".jpg", ".jpeg", ".png", ".gif", ".webp", ".bmp", ".tif", ".tiff"}
+THUMBNAIL_MIN_SIZE = 32
+THUMBNAIL_MAX_SIZE = 1024
+# Larger sources are not decoded on the Pi (memory); the client shows a generic icon instead.
+THUMBNAIL_MAX_SOURCE_BYTES = 50 * 1024 * 1024
+
+
+def clamp_size(size: int) -> int:
+    """Longest edge in pixels, limited to THUMBNAIL_MIN_SIZE..THUMBNAIL_MAX_SIZE."""
+    return max(THUMBNAIL_MIN_SIZE, min(size, THUMBNAIL_MAX_SIZE))
+
+
+def can_thumbnail(path: Path) -> bool:
+    return path.suffix.lower() in THUMBNAIL_SUFFIXES
+
+
+def render_thumbnail(path: Path, size: int) -> Optional[bytes]:
+    """
+    Return a JPEG whose longest edge is at most ``size`` pixels, or None when the file is not a
+    supported image, too large, or Pillow is not installed.
+    """
+    if not can_thumbnail(path):
+        return None
+    try:
+        if path.stat().st_size > THUMBNAIL_MAX_SOURCE_BYTES:
+            return None
+        from PIL import Image, ImageOps
+    except (OSError, ImportError) as e:
+        log.warning("Thumbnail unavailable for %s: %s", path, e)
+        return None
+    try:
+        with Image.open(path) as img:
+            img = ImageOps.exif_transpose(img)
+            img.thumbnail((size, size))
+            out = io.BytesIO()
+            img.convert("RGB").save(out, format="JPEG", quality=80)
+            return out.getvalue()
+    except Exception as e:
+        log.info("Thumbnail failed for %s: %s", path, e)
+        return None
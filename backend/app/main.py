@@ -14,9 +14,12 @@ from slowapi.errors import RateLimitExceeded
 from app.config import get_settings
 from app.db.session import get_session, init_db
 from app.files.hash_model import FileHash  # noqa: F401  # register for create_all
+from app.app_tokens.routes import router as app_tokens_router
+from app.devices.routes import router as devices_router
 from app.files.routes import router as files_router
 from app.meta.routes import router as meta_router
 from app.oauth.routes import router as oauth_router
+from app.request_id import RequestIdFilter, accept_request_id, request_id_var
 from app.telemetry.routes import router as telemetry_router
 from app.users.routes import router as users_router
 from app.users.service import ensure_admin_exists
@@ -29,7 +32,7 @@ def _setup_logging() -> None:
     settings = get_settings()
     level = getattr(logging, settings.log_level.upper(), logging.INFO)
     fmt = logging.Formatter(
-        "%(asctime)s [%(levelname)s] %(name)s: %(message)s",
+        "%(asctime)s [%(levelname)s] [%(request_id)s] %(name)s: %(message)s",
         datefmt="%Y-%m-%d %H:%M:%S",
     )
     root = logging.getLogger("app")
@@ -38,12 +41,14 @@ def _setup_logging() -> None:
     sh = logging.StreamHandler()
     sh.setLevel(level)
     sh.setFormatter(fmt)
+    sh.addFilter(RequestIdFilter())
     root.addHandler(sh)
     if settings.log_file and str(settings.log_file).strip():
         try:
             fh = logging.FileHandler(settings.log_file, encoding="utf-8")
             fh.setLevel(level)
             fh.setFormatter(fmt)
+            fh.addFilter(RequestIdFilter())
             root.addHandler(fh)
             root.info("Logging to file %s", settings.log_file)
         except OSError as e:
@@ -88,6 +93,19 @@ async def add_security_headers(request: Request, call_next):
     return response
 
 
+@app.middleware("http")
+async def add_request_id(request: Request, call_next):
+    """Tag log lines with the request id and echo it in ``X-Request-Id``."""
+    request_id = accept_request_id(request.headers.get("X-Request-Id"))
+    token = request_id_var.set(request_id)
+    try:
+        response = await call_next(request)
+    finally:
+        request_id_var.reset(token)
+    response.headers["X-Request-Id"] = request_id
+    return response
+
+
 @app.exception_handler(Exception)
 async def generic_exception_handler(request: Request, exc: Exception):
     """Return generic 500 without leaking stack trace or internals."""
@@ -114,6 +132,8 @@ app.include_router(users_router)
 app.include_router(oauth_router)
 app.include_router(meta_router)
 app.include_router(telemetry_router)
+app.include_router(devices_router)
+app.include_router(app_tokens_router)
 app.include_router(files_router)
 
 _spa_root: Path | None = None
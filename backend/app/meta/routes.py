@@ -4,6 +4,7 @@ from fastapi import APIRouter
 from pydantic import BaseModel
 
 from app.config import get_settings
+from app.files.hash_store import supported_hash_algorithms
 from app.limiter import limiter
 
 router = APIRouter(prefix="/api/meta", tags=["meta"])
@@ -31,3 +32,26 @@ def api_version() -> VersionResponse:
         min_supported_client_version=s.min_supported_client_version,
         google_signin_available=google_ok,
     )
+
+
+class CapabilitiesResponse(BaseModel):
+    """Optional features clients may negotiate; absent on servers older than this endpoint."""
+
+    hash_algorithms: list[str]
+    upload_mtime: bool
+    file_move: bool
+    file_copy: bool
+    folder_zip: bool
+
+
+@router.get("/capabilities", response_model=CapabilitiesResponse)
+@limiter.exempt
+def api_capabilities() -> CapabilitiesResponse:
+    """Return content-hash algorithms (SHA-256 always first) and other optional features."""
+    return CapabilitiesResponse(
+        hash_algorithms=supported_hash_algorithms(),
+        upload_mtime=True,
+        file_move=True,
+        file_copy=True,
+        folder_zip=True,
+    )
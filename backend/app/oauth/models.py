@@ -1,6 +1,7 @@
 """OAuth transient storage (SQLite)."""
 
 from datetime import datetime
+from typing import Optional
 
 from sqlalchemy import DateTime, String, Text, func
 from sqlalchemy.orm import Mapped, mapped_column
@@ -9,11 +10,14 @@ from app.db.session import Base
 
 
 class OAuthState(Base):
-    """CSRF state for Google OAuth start."""
+    """CSRF state for Google OAuth start. Desktop logins also keep their loopback redirect."""
 
     __tablename__ = "oauth_states"
 
     state: Mapped[str] = mapped_column(String(128), primary_key=True)
+    client_redirect: Mapped[Optional[str]] = mapped_column(String(255), nullable=True)
+    client_state: Mapped[Optional[str]] = mapped_column(String(128), nullable=True)
+    code_challenge: Mapped[Optional[str]] = mapped_column(String(128), nullable=True)
     created_at: Mapped[datetime] = mapped_column(
         DateTime(timezone=True), server_default=func.now(), nullable=False
     )
@@ -27,6 +31,7 @@ class OAuthExchange(Base):
     id: Mapped[str] = mapped_column(String(36), primary_key=True)
     access_token: Mapped[str] = mapped_column(Text, nullable=False)
     refresh_token: Mapped[str] = mapped_column(Text, nullable=False)
+    code_challenge: Mapped[Optional[str]] = mapped_column(String(128), nullable=True)
     created_at: Mapped[datetime] = mapped_column(
         DateTime(timezone=True), server_default=func.now(), nullable=False
     )
@@ -2,11 +2,14 @@
 
 from __future__ import annotations
 
+import base64
+import hashlib
 import logging
 import secrets
 import uuid
 from datetime import datetime, timedelta, timezone
 from typing import Annotated
+from urllib.parse import urlencode, urlsplit
 
 from fastapi import APIRouter, Depends, HTTPException, Query, Request, status
 from fastapi.responses import RedirectResponse
@@ -42,26 +45,73 @@ def _google_redirect_uri(settings, request: Request) -> str:
     return _public_origin(settings, request) + "/api/auth/google/callback"
 
 
+def _is_loopback_redirect(url: str) -> bool:
+    """Desktop clients may only ask to be redirected to http://127.0.0.1:<port>/<path>."""
+    try:
+        parts = urlsplit(url)
+        port = parts.port
+    except ValueError:
+        return False
+    return (
+        parts.scheme == "http"
+        and parts.hostname == "127.0.0.1"
+        and parts.netloc == f"127.0.0.1:{port}"
+        and port is not None
+        and not parts.query
+        and not parts.fragment
+    )
+
+
+def _pkce_challenge(verifier: str) -> str:
+    """S256 code challenge (RFC 7636) for a verifier."""
+    digest = hashlib.sha256(verifier.encode("ascii", errors="replace")).digest()
+    return base64.urlsafe_b64encode(digest).rstrip(b"=").decode("ascii")
+
+
 @router.get("/google/start")
 @limiter.limit("30/minute")
 async def google_oauth_start(
     request: Request,
     session: Annotated[AsyncSession, Depends(get_db)],
+    client_redirect: Annotated[str | None, Query(max_length=255)] = None,
+    client_state: Annotated[str | None, Query(max_length=128)] = None,
+    code_challenge: Annotated[str | None, Query(min_length=43, max_length=128)] = None,
 ) -> RedirectResponse:
-    """Redirect browser to Google consent screen."""
+    """Redirect browser to Google consent screen.
+
+    Desktop clients pass a loopback ``client_redirect`` with their own ``client_state`` and a
+    PKCE ``code_challenge``; the callback then redirects there instead of to the SPA.
+    """
     settings = get_settings()
     if not settings.google_client_id or not settings.google_client_secret:
         raise HTTPException(
             status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
             detail="Google sign-in is not configured on this server",
         )
+    if client_redirect is not None:
+        if not _is_loopback_redirect(client_redirect):
+            raise HTTPException(
+                status_code=status.HTTP_400_BAD_REQUEST,
+                detail="client_redirect must be http://127.0.0.1:<port>/...",
+            )
+        if not client_state or not code_challenge:
+            raise HTTPException(
+                status_code=status.HTTP_400_BAD_REQUEST,
+                detail="client_redirect requires client_state and code_challenge",
+            )
     cutoff = datetime.now(timezone.utc) - timedelta(minutes=15)
     await session.execute(delete(OAuthState).where(OAuthState.created_at < cutoff))
     state = secrets.token_urlsafe(32)
-    session.add(OAuthState(state=state))
+    session.add(
+        OAuthState(
+            state=state,
+            client_redirect=client_redirect,
+            client_state=client_state if client_redirect else None,
+            code_challenge=code_challenge if client_redirect else None,
+        )
+    )
     await session.commit()
     redirect_uri = _google_redirect_uri(settings, request)
-    from urllib.parse import urlencode
 
     q = urlencode(
         {
@@ -88,13 +138,33 @@ async def google_oauth_callback(
     state: Annotated[str | None, Query()] = None,
     error: Annotated[str | None, Query()] = None,
 ) -> RedirectResponse:
-    """Google redirects here; we issue one-time exchange and redirect to SPA login."""
+    """Google redirects here; we issue one-time exchange and redirect to SPA login
+    (or to the desktop client's loopback redirect given at start)."""
     settings = get_settings()
     base = _public_origin(settings, request)
 
+    row = None
+    if state:
+        res = await session.execute(select(OAuthState).where(OAuthState.state == state))
+        row = res.scalar_one_or_none()
+    client_redirect = row.client_redirect if row else None
+    client_state = row.client_state if row else None
+    code_challenge = row.code_challenge if row else None
+
     def red(path: str) -> RedirectResponse:
+        # path is "/login?<query>"; desktop logins get the same query plus their own state
+        if client_redirect:
+            query = path.split("?", 1)[1]
+            return RedirectResponse(
+                url=f"{client_redirect}?{query}&{urlencode({'state': client_state})}",
+                status_code=302,
+            )
         return RedirectResponse(url=f"{base}{path}", status_code=302)
 
+    if row:
+        await session.delete(row)
+        await session.flush()
+
     if error:
         log.warning("Google OAuth error query=%s", error)
         await log_server_event(
@@ -116,8 +186,6 @@ async def google_oauth_callback(
         await session.commit()
         return red("/login?error=oauth_invalid")
 
-    res = await session.execute(select(OAuthState).where(OAuthState.state == state))
-    row = res.scalar_one_or_none()
     if not row:
         log.warning("Google OAuth invalid state")
         await log_server_event(
@@ -129,9 +197,6 @@ async def google_oauth_callback(
         await session.commit()
         return red("/login?error=oauth_invalid")
 
-    await session.delete(row)
-    await session.flush()
-
     redirect_uri = _google_redirect_uri(settings, request)
     token_json = await exchange_authorization_code(settings, code=code, redirect_uri=redirect_uri)
     if not token_json or "access_token" not in token_json:
@@ -169,6 +234,11 @@ async def google_oauth_callback(
         await session.commit()
         return red("/login?error=no_account")
 
+    if not user.is_enabled:
+        log.warning("Google OAuth rejected: account disabled email=%s", email)
+        await session.commit()
+        return red("/login?error=account_disabled")
+
     if sub and user.google_sub and user.google_sub != sub:
         log.error("Google sub mismatch for email=%s", email)
         await session.commit()
@@ -186,6 +256,7 @@ async def google_oauth_callback(
             id=ex_id,
             access_token=jwt_access,
             refresh_token=jwt_refresh,
+            code_challenge=code_challenge,
         )
     )
     await session.commit()
@@ -200,7 +271,7 @@ async def oauth_complete(
     body: OAuthCompleteRequest,
     session: Annotated[AsyncSession, Depends(get_db)],
 ) -> TokenPair:
-    """Redeem one-time exchange id for JWT pair (SPA)."""
+    """Redeem one-time exchange id for JWT pair (SPA, or desktop client with its PKCE verifier)."""
     res = await session.execute(select(OAuthExchange).where(OAuthExchange.id == body.exchange.strip()))
     row = res.scalar_one_or_none()
     if not row:
@@ -214,6 +285,14 @@ async def oauth_complete(
         await session.commit()
         raise HTTPException(status_code=status.HTTP_401_UNAUTHORIZED, detail="Exchange expired")
 
+    if row.code_challenge and not (
+        body.code_verifier
+        and secrets.compare_digest(_pkce_challenge(body.code_verifier), row.code_challenge)
+    ):
+        await session.delete(row)
+        await session.commit()
+        raise HTTPException(status_code=status.HTTP_401_UNAUTHORIZED, detail="Invalid code verifier")
+
     access = row.access_token
     refresh = row.refresh_token
     await session.delete(row)
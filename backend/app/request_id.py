@@ -0,0 +1,27 @@
+"""Request ids: each request's id (the client's ``X-Request-Id`` or a new one) is added to every
+log line written while handling it, so client errors can be matched with server log lines."""
+
+import contextvars
+import logging
+import re
+import uuid
+
+request_id_var: contextvars.ContextVar[str] = contextvars.ContextVar("request_id", default="-")
+
+# Client ids are UUIDs; anything else (or nothing) gets a fresh id.
+_VALID_REQUEST_ID = re.compile(r"^[A-Za-z0-9-]{8,64}$")
+
+
+def accept_request_id(header: str | None) -> str:
+    """The client's request id when well-formed, else a new one."""
+    if header and _VALID_REQUEST_ID.match(header):
+        return header
+    return str(uuid.uuid4())
+
+
+class RequestIdFilter(logging.Filter):
+    """Set ``record.request_id`` for the ``%(request_id)s`` log format field."""
+
+    def filter(self, record: logging.LogRecord) -> bool:
+        record.request_id = request_id_var.get()
+        return True
@@ -9,8 +9,18 @@ from app.auth.dependencies import get_current_admin, get_current_user
 from app.config import get_settings
 from app.db.session import get_db
 from app.limiter import limiter
-from app.telemetry.schemas import ClientConnectionResponse, ClientPingRequest, ServerEventResponse
-from app.telemetry.service import list_client_connections, list_server_events, upsert_client_ping
+from app.telemetry.schemas import (
+    ClientConnectionResponse,
+    ClientCrashReport,
+    ClientPingRequest,
+    ServerEventResponse,
+)
+from app.telemetry.service import (
+    list_client_connections,
+    list_server_events,
+    log_server_event,
+    upsert_client_ping,
+)
 from app.users.models import User
 
 router = APIRouter(prefix="/api", tags=["telemetry"])
@@ -37,6 +47,29 @@ async def client_ping(
     )
 
 
+@router.post("/clients/crash", status_code=status.HTTP_204_NO_CONTENT)
+@limiter.limit("10/minute")
+async def client_crash(
+    request: Request,
+    body: ClientCrashReport,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> None:
+    """Store a client crash report as a server event (visible in the admin event list)."""
+    await log_server_event(
+        session,
+        level="error",
+        category="client_crash",
+        message=f"{body.client_type} {body.client_version}: {body.message}",
+        detail={
+            "os": body.os,
+            "occurred_at": body.occurred_at.isoformat() if body.occurred_at else None,
+            "backtrace": body.backtrace,
+        },
+        user_email=current_user.email,
+    )
+
+
 @router.get("/admin/clients", response_model=list[ClientConnectionResponse])
 @limiter.limit("60/minute")
 async def admin_list_clients(
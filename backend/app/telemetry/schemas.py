@@ -15,6 +15,17 @@ class ClientPingRequest(BaseModel):
     last_sync_ok: Optional[bool] = None
 
 
+class ClientCrashReport(BaseModel):
+    """Crash report uploaded by a desktop client after a panic."""
+
+    client_type: str = Field(..., min_length=1, max_length=32)
+    client_version: str = Field(..., min_length=1, max_length=64)
+    os: str = Field("", max_length=64)
+    occurred_at: Optional[datetime] = None
+    message: str = Field(..., min_length=1, max_length=2000)
+    backtrace: str = Field("", max_length=32000)
+
+
 class ClientConnectionResponse(BaseModel):
     """Row returned to admins."""
 
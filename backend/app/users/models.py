@@ -4,7 +4,7 @@ from datetime import datetime
 from typing import Optional
 
 from pydantic import BaseModel, ConfigDict, EmailStr, Field
-from sqlalchemy import BigInteger, Boolean, DateTime, String, Text, func
+from sqlalchemy import BigInteger, Boolean, DateTime, ForeignKey, String, Text, func
 from sqlalchemy.orm import Mapped, mapped_column
 
 from app.db.session import Base
@@ -25,6 +25,8 @@ class User(Base):
     )
     # Optional per-user storage limit (bytes). None = use server limit only.
     storage_limit_bytes: Mapped[Optional[int]] = mapped_column(BigInteger, nullable=True)
+    # Suspended accounts (is_enabled=False) cannot log in or use existing tokens; files are kept.
+    is_enabled: Mapped[bool] = mapped_column(Boolean, default=True, nullable=False)
     # Cached storage usage (bytes).
     storage_used_bytes: Mapped[int] = mapped_column(BigInteger, default=0, nullable=False)
     # Google account subject (sub), set after successful Google sign-in for this user.
@@ -33,6 +35,17 @@ class User(Base):
     preferences_json: Mapped[Optional[str]] = mapped_column(Text, nullable=True)
 
 
+class PasswordResetToken(Base):
+    """Pending forgot-password request. Only a SHA-256 hash of the emailed code is stored; the
+    row is deleted when the code is redeemed, expires, or a newer code is requested."""
+
+    __tablename__ = "password_reset_tokens"
+
+    token_hash: Mapped[str] = mapped_column(String(64), primary_key=True)
+    user_email: Mapped[str] = mapped_column(String(255), ForeignKey("users.email", ondelete="CASCADE"), nullable=False)
+    expires_at: Mapped[datetime] = mapped_column(DateTime(timezone=True), nullable=False)
+
+
 # Pydantic schemas for API
 class UserCreate(BaseModel):
     """Payload for admin creating a new user."""
@@ -51,6 +64,7 @@ class UserResponse(BaseModel):
     first_name: str
     last_name: str
     is_admin: bool
+    is_enabled: bool = True
     created_at: datetime
     storage_used_bytes: Optional[int] = None
     storage_limit_bytes: Optional[int] = None
@@ -62,6 +76,26 @@ class UserCreateResponse(UserResponse):
     temp_password: Optional[str] = None
 
 
+class PasswordResetResponse(BaseModel):
+    """Response for admin password reset. temp_password only when SMTP is not configured (e.g. E2E)."""
+
+    detail: str
+    temp_password: Optional[str] = None
+
+
+class PasswordResetRequest(BaseModel):
+    """Self-service password reset request body (forgot password)."""
+
+    email: EmailStr
+
+
+class PasswordResetConfirm(BaseModel):
+    """Redeem the emailed reset code and set a new password."""
+
+    token: str = Field(..., min_length=1, max_length=255)
+    new_password: str
+
+
 class UserLogin(BaseModel):
     """Login request body."""
 
@@ -91,12 +125,41 @@ class ChangePassword(BaseModel):
     new_password: str
 
 
-class UserStorageLimitUpdate(BaseModel):
-    """Request body for admin to set a user's storage limit (bytes). None = no per-user limit."""
+class UserUpdate(BaseModel):
+    """Request body for admin updating a user. Only fields present in the body are changed.
+    storage_limit_bytes: max bytes, or null for no per-user limit."""
 
+    first_name: Optional[str] = None
+    last_name: Optional[str] = None
+    is_admin: Optional[bool] = None
     storage_limit_bytes: Optional[int] = None
 
 
+class AccountDeletionRequest(BaseModel):
+    """Step 1 of deleting one's own account: confirm with the current password."""
+
+    password: str
+
+
+class AccountDeletionToken(BaseModel):
+    """Returned by step 1; send confirmation_token to /users/me/delete within expires_in seconds."""
+
+    confirmation_token: str
+    expires_in: int
+
+
+class AccountDeletionConfirm(BaseModel):
+    """Step 2 of deleting one's own account."""
+
+    confirmation_token: str
+
+
+class UserEnabledUpdate(BaseModel):
+    """Request body for admin to suspend (enabled=False) or re-enable a user."""
+
+    enabled: bool
+
+
 class UserPreferences(BaseModel):
     """User-controlled appearance and favorites (persisted as JSON on User)."""
 
@@ -119,3 +182,4 @@ class OAuthCompleteRequest(BaseModel):
     """Exchange one-time id from OAuth redirect for JWT tokens."""
 
     exchange: str
+    code_verifier: Optional[str] = None
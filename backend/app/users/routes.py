@@ -3,15 +3,20 @@
 import logging
 from typing import Annotated
 
-from fastapi import APIRouter, Depends, HTTPException, Request, status
+from fastapi import APIRouter, BackgroundTasks, Depends, HTTPException, Request, status
 from fastapi.responses import FileResponse
-from sqlalchemy import select
+from sqlalchemy import func, select
+from starlette.background import BackgroundTask
+from starlette.concurrency import run_in_threadpool
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.auth.dependencies import get_current_admin, get_current_user
 from app.auth.jwt import (
+    ACCOUNT_DELETION_TOKEN_MINUTES,
     create_access_token,
+    create_account_deletion_token,
     create_refresh_token,
+    get_subject_from_deletion_token,
     get_subject_from_refresh,
     hash_password,
     verify_password,
@@ -24,17 +29,24 @@ from app.files.quota import (
     get_user_used_bytes,
 )
 from app.users.models import (
+    AccountDeletionConfirm,
+    AccountDeletionRequest,
+    AccountDeletionToken,
     ChangePassword,
+    PasswordResetConfirm,
+    PasswordResetRequest,
+    PasswordResetResponse,
     RefreshRequest,
     TokenPair,
     User,
     UserCreate,
     UserCreateResponse,
+    UserEnabledUpdate,
     UserLogin,
     UserPreferences,
     UserPreferencesPatch,
     UserResponse,
-    UserStorageLimitUpdate,
+    UserUpdate,
 )
 from app.limiter import limiter
 from app.users.background_image import (
@@ -44,10 +56,16 @@ from app.users.background_image import (
     save_user_background_image_bytes,
 )
 from app.users.service import (
+    build_account_export,
+    create_password_reset_token,
     create_user as do_create_user,
+    delete_account,
     get_user_by_email,
     patch_user_preferences,
     read_user_preferences,
+    redeem_password_reset_token,
+    reset_user_password,
+    send_password_reset_email,
 )
 
 router = APIRouter(prefix="/api", tags=["users"])
@@ -69,6 +87,12 @@ async def login(
             status_code=status.HTTP_401_UNAUTHORIZED,
             detail="Invalid email or password",
         )
+    if not user.is_enabled:
+        log.warning("Login rejected for disabled account email=%s", user.email)
+        raise HTTPException(
+            status_code=status.HTTP_403_FORBIDDEN,
+            detail="Account is disabled",
+        )
     log.info("Login successful for email=%s", user.email)
     settings = get_settings()
     access = create_access_token(user.email)
@@ -102,6 +126,12 @@ async def refresh(
             status_code=status.HTTP_401_UNAUTHORIZED,
             detail="User not found",
         )
+    if not user.is_enabled:
+        log.warning("Refresh rejected for disabled account email=%s", user.email)
+        raise HTTPException(
+            status_code=status.HTTP_403_FORBIDDEN,
+            detail="Account is disabled",
+        )
     log.info("Refresh successful for email=%s", user.email)
     settings = get_settings()
     access = create_access_token(user.email)
@@ -247,6 +277,136 @@ async def change_password(
     return {"detail": "Password updated"}
 
 
+PASSWORD_RESET_SENT = "If the account exists, a reset code has been sent by email"
+
+
+async def _send_reset_code(email: str, token: str, first_name: str) -> None:
+    """Background task: send failures are only logged, so the response never depends on them."""
+    try:
+        await send_password_reset_email(email, token, first_name)
+    except Exception as e:
+        log.warning("Password reset email to %s failed: %s", email, e)
+
+
+@router.post("/auth/password-reset", status_code=status.HTTP_202_ACCEPTED)
+@limiter.limit("5/hour")
+async def request_password_reset(
+    request: Request,
+    body: PasswordResetRequest,
+    background_tasks: BackgroundTasks,
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> dict:
+    """
+    Forgot password: email a single-use reset code (valid PASSWORD_RESET_TOKEN_MINUTES). The
+    password only changes when the code is redeemed at /auth/password-reset/confirm. Same 202 whether
+    or not the account exists; the mail is sent after the response, so send failures and timing do
+    not reveal accounts either. 503 only when the server has no SMTP configured at all.
+    """
+    settings = get_settings()
+    if not settings.smtp_host or not settings.smtp_from:
+        raise HTTPException(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            detail="Password reset by email is not available on this server. Ask the admin to reset it.",
+        )
+    user = await get_user_by_email(session, body.email)
+    if not user or not user.is_enabled:
+        log.info("Password reset requested for unknown or disabled email=%s", body.email)
+        return {"detail": PASSWORD_RESET_SENT}
+    token = await create_password_reset_token(session, user)
+    await session.commit()
+    background_tasks.add_task(_send_reset_code, user.email, token, user.first_name)
+    log.info("Password reset code issued for email=%s", user.email)
+    return {"detail": PASSWORD_RESET_SENT}
+
+
+@router.post("/auth/password-reset/confirm")
+@limiter.limit("10/minute")
+async def confirm_password_reset(
+    request: Request,
+    body: PasswordResetConfirm,
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> dict:
+    """Set a new password with the emailed reset code. The code works once and expires."""
+    if not body.new_password or len(body.new_password.strip()) < 8:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="New password must be at least 8 characters",
+        )
+    user = await redeem_password_reset_token(session, body.token, body.new_password)
+    await session.commit()
+    if user is None:
+        log.warning("Password reset with invalid or expired code")
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="Invalid or expired reset code",
+        )
+    log.info("Password reset completed for email=%s", user.email)
+    return {"detail": "Password updated"}
+
+
+@router.get("/users/me/export")
+@limiter.limit("5/hour")
+async def export_my_account(
+    request: Request,
+    current_user: Annotated[User, Depends(get_current_user)],
+) -> FileResponse:
+    """Download a zip of the current user's profile, preferences and all files (data portability)."""
+    path = await run_in_threadpool(build_account_export, current_user)
+    filename = f"brandybox-export-{current_user.email}.zip"
+    return FileResponse(
+        path=path,
+        media_type="application/zip",
+        filename=filename,
+        background=BackgroundTask(path.unlink, missing_ok=True),
+    )
+
+
+@router.post("/users/me/delete-request", response_model=AccountDeletionToken)
+@limiter.limit("10/minute")
+async def request_account_deletion(
+    request: Request,
+    body: AccountDeletionRequest,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> AccountDeletionToken:
+    """Step 1 of deleting one's own account: check the password and return a short-lived confirmation token."""
+    if not verify_password(body.password, current_user.password_hash):
+        log.warning("Account deletion request with wrong password for email=%s", current_user.email)
+        raise HTTPException(
+            status_code=status.HTTP_401_UNAUTHORIZED,
+            detail="Password is incorrect",
+        )
+    if current_user.is_admin:
+        admins = await session.scalar(select(func.count()).select_from(User).where(User.is_admin.is_(True)))
+        if (admins or 0) <= 1:
+            raise HTTPException(
+                status_code=status.HTTP_400_BAD_REQUEST,
+                detail="The last administrator cannot delete their account",
+            )
+    log.info("Account deletion requested by email=%s", current_user.email)
+    return AccountDeletionToken(
+        confirmation_token=create_account_deletion_token(current_user.email),
+        expires_in=ACCOUNT_DELETION_TOKEN_MINUTES * 60,
+    )
+
+
+@router.post("/users/me/delete", status_code=status.HTTP_204_NO_CONTENT)
+@limiter.limit("10/minute")
+async def confirm_account_deletion(
+    request: Request,
+    body: AccountDeletionConfirm,
+    current_user: Annotated[User, Depends(get_current_user)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> None:
+    """Step 2: delete the current user and all their files. Requires the token from /users/me/delete-request."""
+    if get_subject_from_deletion_token(body.confirmation_token) != current_user.email:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="Invalid or expired confirmation token",
+        )
+    await delete_account(session, current_user)
+
+
 # Header sent by E2E runner so backend returns temp_password and skips sending email (SMTP not required).
 E2E_RETURN_TEMP_PASSWORD_HEADER = "X-E2E-Return-Temp-Password"
 
@@ -302,25 +462,44 @@ async def admin_list_users(
 
 
 @router.patch("/users/{email}", response_model=UserResponse)
-async def admin_update_user_storage_limit(
+async def admin_update_user(
     email: str,
-    payload: UserStorageLimitUpdate,
+    payload: UserUpdate,
     current_user: Annotated[User, Depends(get_current_admin)],
     session: Annotated[AsyncSession, Depends(get_db)],
 ) -> UserResponse:
-    """Update a user's storage limit (admin only). storage_limit_bytes: max bytes or null for server default."""
+    """Update a user's names, admin flag and/or storage limit (admin only). Fields missing from the body are left unchanged."""
     user = await get_user_by_email(session, email)
     if not user:
         raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="User not found")
+    fields = payload.model_fields_set
     if payload.storage_limit_bytes is not None and payload.storage_limit_bytes < 0:
         raise HTTPException(
             status_code=status.HTTP_400_BAD_REQUEST,
             detail="storage_limit_bytes must be non-negative",
         )
-    user.storage_limit_bytes = payload.storage_limit_bytes
+    for name in ("first_name", "last_name"):
+        if name in fields and not (getattr(payload, name) or "").strip():
+            raise HTTPException(
+                status_code=status.HTTP_400_BAD_REQUEST,
+                detail=f"{name} must not be empty",
+            )
+    if "is_admin" in fields and payload.is_admin is False and email == current_user.email:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="Cannot remove your own admin rights",
+        )
+    if "first_name" in fields:
+        user.first_name = payload.first_name.strip()
+    if "last_name" in fields:
+        user.last_name = payload.last_name.strip()
+    if "is_admin" in fields and payload.is_admin is not None:
+        user.is_admin = payload.is_admin
+    if "storage_limit_bytes" in fields:
+        user.storage_limit_bytes = payload.storage_limit_bytes
     await session.commit()
     await session.refresh(user)
-    log.info("Admin %s set storage_limit for %s to %s", current_user.email, email, payload.storage_limit_bytes)
+    log.info("Admin %s updated user %s fields=%s", current_user.email, email, sorted(fields))
     data = UserResponse.model_validate(user).model_dump()
     data["storage_used_bytes"] = await get_user_used_bytes(session, user.email)
     data["storage_limit_bytes"] = get_user_storage_limit_bytes(
@@ -329,6 +508,59 @@ async def admin_update_user_storage_limit(
     return UserResponse(**data)
 
 
+@router.put("/users/{email}/enabled", response_model=UserResponse)
+async def admin_set_user_enabled(
+    email: str,
+    payload: UserEnabledUpdate,
+    current_user: Annotated[User, Depends(get_current_admin)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> UserResponse:
+    """Suspend or re-enable a user (admin only). Suspended users cannot log in; their files are kept."""
+    if email == current_user.email and not payload.enabled:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="Cannot disable your own account",
+        )
+    user = await get_user_by_email(session, email)
+    if not user:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="User not found")
+    user.is_enabled = payload.enabled
+    await session.commit()
+    await session.refresh(user)
+    log.info("Admin %s set enabled=%s for %s", current_user.email, payload.enabled, email)
+    data = UserResponse.model_validate(user).model_dump()
+    data["storage_used_bytes"] = user.storage_used_bytes
+    data["storage_limit_bytes"] = get_user_storage_limit_bytes(
+        get_server_storage_limit_bytes(), user.storage_limit_bytes
+    )
+    return UserResponse(**data)
+
+
+@router.post("/users/{email}/reset-password", response_model=PasswordResetResponse)
+async def admin_reset_password(
+    request: Request,
+    email: str,
+    current_user: Annotated[User, Depends(get_current_admin)],
+    session: Annotated[AsyncSession, Depends(get_db)],
+) -> PasswordResetResponse:
+    """Reset a user's password (admin only). New temp password is sent by email, or returned when E2E header is set or SMTP not configured."""
+    user = await get_user_by_email(session, email)
+    if not user:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="User not found")
+    e2e_return_password = (request.headers.get(E2E_RETURN_TEMP_PASSWORD_HEADER) or "").strip().lower() in ("true", "1")
+    try:
+        temp_password = await reset_user_password(session, user, skip_email=e2e_return_password)
+    except RuntimeError:
+        raise HTTPException(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            detail="Email could not be sent. Check SMTP configuration.",
+        )
+    log.info("Admin %s reset password for email=%s", current_user.email, email)
+    if e2e_return_password or not get_settings().smtp_host or not get_settings().smtp_from:
+        return PasswordResetResponse(detail="Password reset", temp_password=temp_password)
+    return PasswordResetResponse(detail="Password reset; new password sent by email")
+
+
 @router.delete("/users/{email}")
 async def admin_delete_user(
     email: str,
@@ -1,30 +1,62 @@
 """User service: create user, send password email."""
 
+import hashlib
+import json
 import logging
+import os
 import secrets
+import shutil
+import tempfile
+import zipfile
+from datetime import datetime, timedelta, timezone
 from email.message import EmailMessage
+from pathlib import Path
 from typing import Optional
 
 import aiosmtplib
-from sqlalchemy import select
+from sqlalchemy import delete, select
 from sqlalchemy.ext.asyncio import AsyncSession
 
+from app.app_tokens.service import delete_user_app_tokens
 from app.auth.jwt import hash_password
 from app.config import get_settings
 from app.files.quota import get_disk_usage_bytes
 from app.files.storage import user_base_path
 from app.users.background_image import USER_BACKGROUND_SENTINEL, clear_user_background_image_files
-from app.users.models import User, UserCreate, UserPreferences, UserPreferencesPatch
+from app.users.models import PasswordResetToken, User, UserCreate, UserPreferences, UserPreferencesPatch, UserResponse
 
 log = logging.getLogger(__name__)
 
 
+async def _send_email(msg: EmailMessage, to_email: str) -> None:
+    """Send via the configured SMTP server. Raises RuntimeError on failure."""
+    settings = get_settings()
+    # Port 587 = STARTTLS (connect plain then upgrade). Port 465 = direct TLS.
+    use_tls = settings.smtp_port == 465
+    start_tls = settings.smtp_port == 587
+    try:
+        await aiosmtplib.send(
+            msg,
+            hostname=settings.smtp_host,
+            port=settings.smtp_port,
+            username=settings.smtp_user or None,
+            password=settings.smtp_password or None,
+            use_tls=use_tls,
+            start_tls=start_tls,
+        )
+    except Exception as e:
+        log.warning("SMTP send failed to %s: %s", to_email, e)
+        raise RuntimeError("Email could not be sent. Check SMTP configuration.") from e
+
+
 async def send_password_email(
     to_email: str,
     temp_password: str,
     first_name: str,
+    reset: bool = False,
 ) -> None:
-    """Send the temporary password to the user's email. Raises RuntimeError on failure."""
+    """Send the temporary password to the user's email. Raises RuntimeError on failure.
+    reset=True words the mail as an admin password reset instead of a new account."""
     settings = get_settings()
     if not settings.smtp_host or not settings.smtp_from:
         raise RuntimeError("SMTP not configured (BRANDYBOX_SMTP_HOST / SMTP_FROM)")
@@ -32,9 +64,14 @@ async def send_password_email(
     msg["From"] = settings.smtp_from
     msg["To"] = to_email
     msg["Subject"] = "Your Brandy Box password"
+    intro = (
+        "Your Brandy Box password has been reset by an administrator."
+        if reset
+        else "Your Brandy Box account has been created."
+    )
     msg.set_content(f"""Hello {first_name},
 
-Your Brandy Box account has been created. Use this password to log in (you can change it later in settings):
+{intro} Use this password to log in (you can change it later in settings):
 
   {temp_password}
 
@@ -43,22 +80,75 @@ Log in at the Brandy Box desktop app with your email and this password.
 Best regards,
 Brandy Box
 """)
-    # Port 587 = STARTTLS (connect plain then upgrade). Port 465 = direct TLS.
-    use_tls = settings.smtp_port == 465
-    start_tls = settings.smtp_port == 587
-    try:
-        await aiosmtplib.send(
-            msg,
-            hostname=settings.smtp_host,
-            port=settings.smtp_port,
-            username=settings.smtp_user or None,
-            password=settings.smtp_password or None,
-            use_tls=use_tls,
-            start_tls=start_tls,
+    await _send_email(msg, to_email)
+
+
+PASSWORD_RESET_TOKEN_MINUTES = 30
+
+
+async def send_password_reset_email(to_email: str, token: str, first_name: str) -> None:
+    """Email the one-time reset code. Raises RuntimeError on failure."""
+    settings = get_settings()
+    if not settings.smtp_host or not settings.smtp_from:
+        raise RuntimeError("SMTP not configured (BRANDYBOX_SMTP_HOST / SMTP_FROM)")
+    msg = EmailMessage()
+    msg["From"] = settings.smtp_from
+    msg["To"] = to_email
+    msg["Subject"] = "Reset your Brandy Box password"
+    msg.set_content(f"""Hello {first_name},
+
+Someone asked to reset the password of your Brandy Box account. To choose a new password, enter
+this code in the Brandy Box desktop app (Forgot password?) within {PASSWORD_RESET_TOKEN_MINUTES} minutes:
+
+  {token}
+
+If you did not ask for this, ignore this email. Your password stays unchanged.
+
+Best regards,
+Brandy Box
+""")
+    await _send_email(msg, to_email)
+
+
+def _hash_reset_token(token: str) -> str:
+    return hashlib.sha256(token.strip().encode("utf-8")).hexdigest()
+
+
+async def create_password_reset_token(session: AsyncSession, user: User) -> str:
+    """Issue a single-use reset code for the user, replacing any earlier one. The password is
+    not touched until the code is redeemed. Caller must commit session."""
+    await session.execute(delete(PasswordResetToken).where(PasswordResetToken.user_email == user.email))
+    token = secrets.token_urlsafe(16)
+    session.add(
+        PasswordResetToken(
+            token_hash=_hash_reset_token(token),
+            user_email=user.email,
+            expires_at=datetime.now(timezone.utc) + timedelta(minutes=PASSWORD_RESET_TOKEN_MINUTES),
         )
-    except Exception as e:
-        log.warning("SMTP send failed to %s: %s", to_email, e)
-        raise RuntimeError("Email could not be sent. Check SMTP configuration.") from e
+    )
+    await session.flush()
+    return token
+
+
+async def redeem_password_reset_token(session: AsyncSession, token: str, new_password: str) -> Optional[User]:
+    """Set the new password if the code is valid and unexpired; the code is consumed either way.
+    Returns the user, or None for unknown, expired or disabled accounts. Caller must commit session."""
+    row = await session.get(PasswordResetToken, _hash_reset_token(token))
+    if row is None:
+        return None
+    await session.delete(row)
+    await session.flush()
+    expires = row.expires_at
+    if expires.tzinfo is None:
+        expires = expires.replace(tzinfo=timezone.utc)
+    if datetime.now(timezone.utc) > expires:
+        return None
+    user = await get_user_by_email(session, row.user_email)
+    if not user or not user.is_enabled:
+        return None
+    user.password_hash = hash_password(new_password)
+    await session.flush()
+    return user
 
 
 async def get_user_by_email(session: AsyncSession, email: str) -> Optional[User]:
@@ -113,6 +203,28 @@ async def create_user(
     return user, temp_password
 
 
+async def reset_user_password(
+    session: AsyncSession,
+    user: User,
+    skip_email: bool = False,
+) -> str:
+    """
+    Replace the user's password with a new temporary one and send it by email (unless skip_email
+    or SMTP is not configured). Returns the temporary password. Caller must commit session.
+    """
+    temp_password = secrets.token_urlsafe(12)
+    user.password_hash = hash_password(temp_password)
+    await session.flush()
+    settings = get_settings()
+    if skip_email:
+        log.info("Password for %s reset (E2E/skip_email); temp password returned in response", user.email)
+    elif settings.smtp_host and settings.smtp_from:
+        await send_password_email(user.email, temp_password, user.first_name, reset=True)
+    else:
+        log.info("Password for %s reset; SMTP not configured. Temporary password returned to admin", user.email)
+    return temp_password
+
+
 async def ensure_admin_exists(session: AsyncSession) -> None:
     """
     If BRANDYBOX_ADMIN_EMAIL and BRANDYBOX_ADMIN_INITIAL_PASSWORD are set
@@ -197,3 +309,46 @@ async def patch_user_preferences(
     user.preferences_json = merged.model_dump_json()
     await session.flush()
     return merged
+
+
+def build_account_export(user: User) -> Path:
+    """
+    Write a zip with account.json (profile and preferences) and all of the user's files under
+    files/. Returns the path of a temporary file the caller must delete. Blocking; run in a thread.
+    """
+    settings = get_settings()
+    fd, temp_path = tempfile.mkstemp(dir=settings.storage_base_path, prefix=".bb_export_", suffix=".zip")
+    os.close(fd)
+    profile = {
+        "exported_at": datetime.now(timezone.utc).isoformat(),
+        "user": UserResponse.model_validate(user).model_dump(mode="json"),
+        "preferences": read_user_preferences(user).model_dump(mode="json"),
+    }
+    base = user_base_path(user.email)
+    count = 0
+    with zipfile.ZipFile(temp_path, "w", compression=zipfile.ZIP_DEFLATED) as zf:
+        zf.writestr("account.json", json.dumps(profile, indent=2))
+        if base.is_dir():
+            for path in sorted(base.rglob("*")):
+                if not path.is_file() or path.name.startswith(".bb_"):
+                    continue
+                zf.write(path, "files/" + path.relative_to(base).as_posix())
+                count += 1
+    log.info("Account export for %s: %d file(s)", user.email, count)
+    return Path(temp_path)
+
+
+async def delete_account(session: AsyncSession, user: User) -> None:
+    """Delete the user row and their storage folder. Caller must commit session."""
+    email = user.email
+    await delete_user_app_tokens(session, email)
+    await session.execute(delete(PasswordResetToken).where(PasswordResetToken.user_email == email))
+    await session.delete(user)
+    await session.flush()
+    try:
+        base = user_base_path(email)
+        if base.is_dir():
+            shutil.rmtree(base)
+    except (OSError, ValueError) as e:
+        log.warning("Could not remove storage folder of deleted account %s: %s", email, e)
+    log.info("Account %s deleted by its owner", email)
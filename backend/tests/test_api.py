@@ -1,5 +1,7 @@
 """API tests with TestClient: health, login, me, file list."""
 
+import hashlib
+
 import pytest
 from fastapi.testclient import TestClient
 
@@ -22,6 +24,15 @@ def test_health(client: TestClient) -> None:
     assert r.json() == {"status": "ok"}
 
 
+def test_request_id_is_echoed_or_generated(client: TestClient) -> None:
+    """A client's X-Request-Id comes back unchanged; malformed or missing ids get a new one."""
+    rid = "3f2b6c1e-8d4a-4b7e-9c0f-1a2b3c4d5e6f"
+    assert client.get("/health", headers={"X-Request-Id": rid}).headers["X-Request-Id"] == rid
+    replaced = client.get("/health", headers={"X-Request-Id": "not valid!"}).headers["X-Request-Id"]
+    assert replaced != "not valid!" and len(replaced) == 36
+    assert len(client.get("/health").headers["X-Request-Id"]) == 36
+
+
 def test_login_success(client: TestClient) -> None:
     """POST /api/auth/login with bootstrap admin returns tokens."""
     r = client.post(
@@ -74,3 +85,400 @@ def test_me_with_token(client: TestClient) -> None:
     assert data["email"] == "test@example.com"
     assert data["is_admin"] is True
     assert "password" not in data
+
+
+def test_client_crash_report_is_listed_for_admin(client: TestClient) -> None:
+    """POST /api/clients/crash stores a client_crash event visible in /api/admin/events."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    r = client.post(
+        "/api/clients/crash",
+        headers=headers,
+        json={
+            "client_type": "tauri",
+            "client_version": "1.1.0",
+            "os": "linux",
+            "message": "panicked at src/sync.rs:1:1: boom",
+            "backtrace": "0: brandybox_lib::sync::run_sync",
+        },
+    )
+    assert r.status_code == 204
+    events = client.get("/api/admin/events", headers=headers).json()
+    crash = next(e for e in events if e["category"] == "client_crash")
+    assert "boom" in crash["message"]
+    assert crash["user_email"] == "test@example.com"
+
+
+def test_admin_reset_password_returns_working_temp_password(client: TestClient) -> None:
+    """POST /api/users/{email}/reset-password replaces the password; the old one stops working."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {
+        "Authorization": f"Bearer {login_r.json()['access_token']}",
+        "X-E2E-Return-Temp-Password": "true",
+    }
+    created = client.post(
+        "/api/users",
+        headers=headers,
+        json={"email": "reset@example.com", "first_name": "Re", "last_name": "Set"},
+    )
+    assert created.status_code == 200
+    old_password = created.json()["temp_password"]
+    r = client.post("/api/users/reset@example.com/reset-password", headers=headers)
+    assert r.status_code == 200
+    new_password = r.json()["temp_password"]
+    assert new_password and new_password != old_password
+    old_login = client.post("/api/auth/login", json={"email": "reset@example.com", "password": old_password})
+    assert old_login.status_code == 401
+    new_login = client.post("/api/auth/login", json={"email": "reset@example.com", "password": new_password})
+    assert new_login.status_code == 200
+    missing = client.post("/api/users/nobody@example.com/reset-password", headers=headers)
+    assert missing.status_code == 404
+
+
+def test_password_reset_request_needs_smtp_and_does_not_reveal_accounts(client: TestClient, monkeypatch) -> None:
+    """POST /api/auth/password-reset: 503 without SMTP; otherwise the same 202 for known and unknown emails
+    and failed sends. The password only changes when the emailed code is redeemed, and only once."""
+    r = client.post("/api/auth/password-reset", json={"email": "test@example.com"})
+    assert r.status_code == 503
+
+    sent = []
+
+    async def fake_send(email, token, first_name):
+        sent.append((email, token))
+
+    monkeypatch.setenv("BRANDYBOX_SMTP_HOST", "smtp.example.com")
+    monkeypatch.setenv("BRANDYBOX_SMTP_FROM", "box@example.com")
+    monkeypatch.setattr("app.users.routes.send_password_reset_email", fake_send)
+    known = client.post("/api/auth/password-reset", json={"email": "test@example.com"})
+    unknown = client.post("/api/auth/password-reset", json={"email": "nobody@example.com"})
+    assert known.status_code == unknown.status_code == 202
+    assert known.json() == unknown.json()
+    assert [e for e, _ in sent] == ["test@example.com"]
+    # Requesting a reset does not touch the password
+    old_login = client.post("/api/auth/login", json={"email": "test@example.com", "password": "testpass123"})
+    assert old_login.status_code == 200
+
+    async def failing_send(email, token, first_name):
+        raise RuntimeError("smtp down")
+
+    monkeypatch.setattr("app.users.routes.send_password_reset_email", failing_send)
+    failed = client.post("/api/auth/password-reset", json={"email": "test@example.com"})
+    assert failed.status_code == 202
+    assert failed.json() == unknown.json()
+
+    # The failed request replaced the first code
+    stale = client.post(
+        "/api/auth/password-reset/confirm",
+        json={"token": sent[0][1], "new_password": "new-secret-123"},
+    )
+    assert stale.status_code == 400
+
+    monkeypatch.setattr("app.users.routes.send_password_reset_email", fake_send)
+    client.post("/api/auth/password-reset", json={"email": "test@example.com"})
+    token = sent[-1][1]
+    short = client.post("/api/auth/password-reset/confirm", json={"token": token, "new_password": "short"})
+    assert short.status_code == 400
+    ok = client.post("/api/auth/password-reset/confirm", json={"token": token, "new_password": "new-secret-123"})
+    assert ok.status_code == 200, ok.text
+    reused = client.post("/api/auth/password-reset/confirm", json={"token": token, "new_password": "other-secret-1"})
+    assert reused.status_code == 400
+    new_login = client.post("/api/auth/login", json={"email": "test@example.com", "password": "new-secret-123"})
+    assert new_login.status_code == 200
+    # Restore the shared test password
+    client.post(
+        "/api/auth/change-password",
+        headers={"Authorization": f"Bearer {new_login.json()['access_token']}"},
+        json={"current_password": "new-secret-123", "new_password": "testpass123"},
+    )
+
+
+@pytest.mark.asyncio
+async def test_password_reset_code_expires(session_factory) -> None:
+    from datetime import datetime, timedelta, timezone
+
+    from sqlalchemy import select
+
+    from app.users.models import PasswordResetToken
+    from app.users.service import create_password_reset_token, get_user_by_email, redeem_password_reset_token
+
+    async with session_factory() as session:
+        user = await get_user_by_email(session, "test@example.com")
+        token = await create_password_reset_token(session, user)
+        row = (await session.execute(select(PasswordResetToken))).scalars().one()
+        assert token not in row.token_hash
+        row.expires_at = datetime.now(timezone.utc) - timedelta(minutes=1)
+        await session.flush()
+        assert await redeem_password_reset_token(session, token, "new-secret-123") is None
+        assert (await session.execute(select(PasswordResetToken))).scalars().first() is None
+
+
+def test_admin_disable_user_blocks_login(client: TestClient) -> None:
+    """PUT /api/users/{email}/enabled suspends a user: login fails until re-enabled."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {
+        "Authorization": f"Bearer {login_r.json()['access_token']}",
+        "X-E2E-Return-Temp-Password": "true",
+    }
+    created = client.post(
+        "/api/users",
+        headers=headers,
+        json={"email": "suspend@example.com", "first_name": "Sus", "last_name": "Pend"},
+    )
+    password = created.json()["temp_password"]
+    r = client.put("/api/users/suspend@example.com/enabled", headers=headers, json={"enabled": False})
+    assert r.status_code == 200
+    assert r.json()["is_enabled"] is False
+    blocked = client.post("/api/auth/login", json={"email": "suspend@example.com", "password": password})
+    assert blocked.status_code == 403
+    client.put("/api/users/suspend@example.com/enabled", headers=headers, json={"enabled": True})
+    allowed = client.post("/api/auth/login", json={"email": "suspend@example.com", "password": password})
+    assert allowed.status_code == 200
+    own = client.put("/api/users/test@example.com/enabled", headers=headers, json={"enabled": False})
+    assert own.status_code == 400
+
+
+def test_admin_update_user_profile_fields(client: TestClient) -> None:
+    """PATCH /api/users/{email} changes only the given fields; admins cannot demote themselves."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {
+        "Authorization": f"Bearer {login_r.json()['access_token']}",
+        "X-E2E-Return-Temp-Password": "true",
+    }
+    client.post(
+        "/api/users",
+        headers=headers,
+        json={"email": "typo@example.com", "first_name": "Jhon", "last_name": "Doe"},
+    )
+    r = client.patch("/api/users/typo@example.com", headers=headers, json={"first_name": "John", "is_admin": True})
+    assert r.status_code == 200
+    data = r.json()
+    assert data["first_name"] == "John"
+    assert data["last_name"] == "Doe"
+    assert data["is_admin"] is True
+    empty = client.patch("/api/users/typo@example.com", headers=headers, json={"last_name": "  "})
+    assert empty.status_code == 400
+    own = client.patch("/api/users/test@example.com", headers=headers, json={"is_admin": False})
+    assert own.status_code == 400
+
+
+def test_device_register_list_and_revoke(client: TestClient) -> None:
+    """PUT /api/devices/{id} registers a device; after revoke it gets 410 Gone."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    body = {"name": "laptop", "platform": "linux", "client_version": "1.1.0"}
+    r = client.put("/api/devices/dev-1", headers=headers, json=body)
+    assert r.status_code == 200
+    assert r.json()["revoked"] is False
+    devices = client.get("/api/devices", headers=headers).json()
+    assert [d["device_id"] for d in devices] == ["dev-1"]
+    revoked = client.post("/api/devices/dev-1/revoke", headers=headers)
+    assert revoked.json()["revoked"] is True
+    again = client.put("/api/devices/dev-1", headers=headers, json=body)
+    assert again.status_code == 410
+    assert client.delete("/api/devices/dev-1", headers=headers).status_code == 204
+    assert client.put("/api/devices/dev-1", headers=headers, json=body).status_code == 200
+
+
+def test_app_token_login_and_revoke(client: TestClient) -> None:
+    """An app token logs in via POST /api/auth/token until it is revoked; only its hash is stored."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    created = client.post("/api/app-tokens", headers=headers, json={"name": "nas"})
+    assert created.status_code == 201, created.text
+    token = created.json()["token"]
+    token_id = created.json()["id"]
+    listed = client.get("/api/app-tokens", headers=headers).json()
+    assert [t["name"] for t in listed] == ["nas"]
+    assert "token" not in listed[0]
+
+    r = client.post("/api/auth/token", json={"token": token})
+    assert r.status_code == 200, r.text
+    assert r.json()["refresh_token"] == ""
+    me = client.get("/api/users/me", headers={"Authorization": f"Bearer {r.json()['access_token']}"})
+    assert me.json()["email"] == "test@example.com"
+    assert client.post("/api/auth/token", json={"token": token + "x"}).status_code == 401
+
+    assert client.delete(f"/api/app-tokens/{token_id}", headers=headers).status_code == 204
+    assert client.post("/api/auth/token", json={"token": token}).status_code == 401
+    assert client.delete(f"/api/app-tokens/{token_id}", headers=headers).status_code == 404
+
+
+@pytest.mark.asyncio
+async def test_app_token_stored_hashed(session_factory) -> None:
+    from sqlalchemy import select
+
+    from app.app_tokens.models import AppToken
+    from app.app_tokens.service import create_app_token, find_by_token
+
+    async with session_factory() as session:
+        row, token = await create_app_token(session, "test@example.com", "kiosk")
+        stored = (await session.execute(select(AppToken).where(AppToken.id == row.id))).scalar_one()
+        assert token not in (stored.token_hash, stored.prefix)
+        assert (await find_by_token(session, token)).id == row.id
+        await session.delete(stored)
+
+
+def test_export_and_delete_own_account(client: TestClient) -> None:
+    """A user can export their data as zip and delete their account with a confirmation token."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    admin_headers = {
+        "Authorization": f"Bearer {login_r.json()['access_token']}",
+        "X-E2E-Return-Temp-Password": "true",
+    }
+    created = client.post(
+        "/api/users",
+        headers=admin_headers,
+        json={"email": "leaving@example.com", "first_name": "Lea", "last_name": "Ving"},
+    )
+    password = created.json()["temp_password"]
+    user_login = client.post("/api/auth/login", json={"email": "leaving@example.com", "password": password})
+    headers = {"Authorization": f"Bearer {user_login.json()['access_token']}"}
+    export = client.get("/api/users/me/export", headers=headers)
+    assert export.status_code == 200
+    assert export.content[:2] == b"PK"
+    wrong = client.post("/api/users/me/delete-request", headers=headers, json={"password": "nope"})
+    assert wrong.status_code == 401
+    token = client.post("/api/users/me/delete-request", headers=headers, json={"password": password})
+    assert token.status_code == 200
+    confirm = client.post(
+        "/api/users/me/delete",
+        headers=headers,
+        json={"confirmation_token": token.json()["confirmation_token"]},
+    )
+    assert confirm.status_code == 204
+    gone = client.post("/api/auth/login", json={"email": "leaving@example.com", "password": password})
+    assert gone.status_code == 401
+    last_admin = client.post("/api/users/me/delete-request", headers=admin_headers, json={"password": "testpass123"})
+    assert last_admin.status_code == 400
+
+
+def test_search_files_by_name_and_content(client: TestClient) -> None:
+    """Search matches file names, and text file contents when content=true."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    for path, body in [
+        ("Taxes/receipt-2024.pdf", b"%PDF"),
+        ("notes/todo.txt", b"remember the Receipt for the new laptop"),
+        ("photo.jpg", b"\xff\xd8"),
+    ]:
+        r = client.post("/api/files/upload", headers=headers, params={"path": path}, content=body)
+        assert r.status_code == 200
+    by_name = client.get("/api/files/search", headers=headers, params={"q": "receipt"})
+    assert by_name.status_code == 200
+    assert [r["path"] for r in by_name.json()] == ["Taxes/receipt-2024.pdf"]
+    with_content = client.get("/api/files/search", headers=headers, params={"q": "receipt", "content": "true"})
+    results = with_content.json()
+    assert [r["match"] for r in results] == ["name", "content"]
+    assert "laptop" in results[1]["snippet"]
+    assert client.get("/api/files/search", headers=headers, params={"q": " "}).status_code == 400
+
+
+def test_move_file_including_case_only_rename(client: TestClient) -> None:
+    """Files can be moved to another folder and renamed by case only; existing targets give 409."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    client.post("/api/files/upload", headers=headers, params={"path": "readme.md"}, content=b"hello")
+    client.post("/api/files/upload", headers=headers, params={"path": "other.md"}, content=b"x")
+    r = client.post("/api/files/move", headers=headers, params={"from": "readme.md", "to": "README.md"})
+    assert r.status_code == 200
+    r = client.post("/api/files/move", headers=headers, params={"from": "README.md", "to": "docs/README.md"})
+    assert r.status_code == 200
+    listed = {f["path"]: f for f in client.get("/api/files/list", headers=headers).json()}
+    assert "docs/README.md" in listed and "readme.md" not in listed and "README.md" not in listed
+    assert listed["docs/README.md"]["hash"] == hashlib.sha256(b"hello").hexdigest()
+    r = client.post("/api/files/move", headers=headers, params={"from": "other.md", "to": "docs/README.md"})
+    assert r.status_code == 409
+    r = client.post("/api/files/move", headers=headers, params={"from": "missing.md", "to": "x.md"})
+    assert r.status_code == 404
+
+
+def test_copy_file_keeps_source_and_hash(client: TestClient) -> None:
+    """A copy lists with the source's hash and both files remain; existing targets give 409."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    client.post("/api/files/upload", headers=headers, params={"path": "plan.txt"}, content=b"draft")
+    r = client.post("/api/files/copy", headers=headers, params={"from": "plan.txt", "to": "archive/plan.txt"})
+    assert r.status_code == 200
+    assert r.json()["size"] == 5
+    listed = {f["path"]: f for f in client.get("/api/files/list", headers=headers).json()}
+    assert "plan.txt" in listed
+    assert listed["archive/plan.txt"]["hash"] == hashlib.sha256(b"draft").hexdigest()
+    r = client.post("/api/files/copy", headers=headers, params={"from": "plan.txt", "to": "archive/plan.txt"})
+    assert r.status_code == 409
+    r = client.post("/api/files/copy", headers=headers, params={"from": "missing.txt", "to": "x.txt"})
+    assert r.status_code == 404
+
+
+def test_delete_batch_reports_each_path(client: TestClient) -> None:
+    """Existing files are deleted; missing and invalid paths are reported, not fatal."""
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    for path in ["old/a.txt", "old/b.txt", "keep.txt"]:
+        client.post("/api/files/upload", headers=headers, params={"path": path}, content=b"x")
+    r = client.post(
+        "/api/files/delete-batch",
+        headers=headers,
+        json={"paths": ["old/a.txt", "old/b.txt", "gone.txt", "../escape.txt"]},
+    )
+    assert r.status_code == 200
+    j = r.json()
+    assert j["deleted"] == ["old/a.txt", "old/b.txt"]
+    assert j["not_found"] == ["gone.txt"]
+    assert [f["path"] for f in j["failed"]] == ["../escape.txt"]
+    listed = [f["path"] for f in client.get("/api/files/list", headers=headers).json()]
+    assert listed == ["keep.txt"]
+
+
+def test_thumbnail_for_image_and_unsupported_type(client: TestClient) -> None:
+    """Images get a JPEG preview no larger than the requested size; other files get 415."""
+    image = pytest.importorskip("PIL.Image")
+    import io
+
+    login_r = client.post(
+        "/api/auth/login",
+        json={"email": "test@example.com", "password": "testpass123"},
+    )
+    headers = {"Authorization": f"Bearer {login_r.json()['access_token']}"}
+    png = io.BytesIO()
+    image.new("RGB", (400, 200), "red").save(png, format="PNG")
+    client.post("/api/files/upload", headers=headers, params={"path": "pic.png"}, content=png.getvalue())
+    client.post("/api/files/upload", headers=headers, params={"path": "doc.txt"}, content=b"text")
+    r = client.get("/api/files/thumbnail", headers=headers, params={"path": "pic.png", "size": 100})
+    assert r.status_code == 200
+    assert r.headers["content-type"] == "image/jpeg"
+    assert image.open(io.BytesIO(r.content)).size == (100, 50)
+    assert client.get("/api/files/thumbnail", headers=headers, params={"path": "doc.txt"}).status_code == 415
+    assert client.get("/api/files/thumbnail", headers=headers, params={"path": "nope.png"}).status_code == 404
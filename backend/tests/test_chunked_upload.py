@@ -73,6 +73,15 @@ def test_chunked_upload_success(auth_headers):
     assert target.exists()
     assert target.read_bytes() == chunk1 + chunk2
 
+def test_chunked_upload_keeps_client_mtime(auth_headers):
+    response = client.post("/api/files/upload/init?path=big.bin&mtime=1700000000", headers=auth_headers)
+    upload_id = response.json()["upload_id"]
+    client.post(f"/api/files/upload/chunk?upload_id={upload_id}&index=0", content=b"data", headers=auth_headers)
+    response = client.post(f"/api/files/upload/finalize?upload_id={upload_id}", headers=auth_headers)
+    assert response.status_code == 200
+    assert response.json()["mtime"] == 1700000000
+    assert (user_base_path("test@example.com") / "big.bin").stat().st_mtime == 1700000000
+
 def test_chunked_upload_invalid_id(auth_headers):
     response = client.post("/api/files/upload/chunk?upload_id=invalid&index=0", content=b"data", headers=auth_headers)
     assert response.status_code == 404
@@ -5,6 +5,7 @@ from pathlib import Path
 from unittest.mock import MagicMock
 
 from app.files.storage import (
+    build_folder_zip,
     delete_file,
     list_directories_recursive,
     list_files_recursive,
@@ -260,3 +261,28 @@ def test_make_directory_rejects_traversal(monkeypatch, tmp_path) -> None:
     (tmp_path / "u@x.co").mkdir()
     with pytest.raises(ValueError):
         make_directory("u@x.co", "../escape")
+
+
+def test_build_folder_zip_keeps_paths_relative_to_folder(monkeypatch, tmp_path) -> None:
+    """The zip holds the folder's files (not temp files) with paths relative to the folder."""
+    import zipfile
+    from app.files import storage
+    mock_settings = MagicMock()
+    mock_settings.storage_base_path = tmp_path
+    monkeypatch.setattr(storage, "get_settings", lambda: mock_settings)
+    user_dir = tmp_path / "u@x.co"
+    (user_dir / "photos" / "2024").mkdir(parents=True)
+    (user_dir / "photos" / "a.jpg").write_bytes(b"a")
+    (user_dir / "photos" / "2024" / "b.jpg").write_bytes(b"b")
+    (user_dir / "photos" / ".bb_upload_x").write_bytes(b"partial")
+    (user_dir / "other.txt").write_text("x")
+    archive = build_folder_zip("u@x.co", "photos")
+    try:
+        with zipfile.ZipFile(archive) as zf:
+            assert sorted(zf.namelist()) == ["2024/b.jpg", "a.jpg"]
+            assert zf.read("2024/b.jpg") == b"b"
+    finally:
+        archive.unlink()
+    with pytest.raises(FileNotFoundError):
+        build_folder_zip("u@x.co", "missing")
+
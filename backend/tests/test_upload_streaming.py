@@ -60,6 +60,52 @@ def test_upload_streaming_success(auth_headers):
     assert target.exists()
     assert target.read_bytes() == content
 
+def test_upload_keeps_client_mtime(auth_headers):
+    response = client.post(
+        "/api/files/upload?path=dated.txt&mtime=1700000000.5",
+        content=b"old file",
+        headers=auth_headers
+    )
+    assert response.status_code == 200
+    assert abs(response.json()["mtime"] - 1700000000.5) < 0.001
+    target = user_base_path("test@example.com") / "dated.txt"
+    assert abs(target.stat().st_mtime - 1700000000.5) < 0.001
+
+    # Far future (wrong client clock): ignored, the upload time is kept
+    response = client.post("/api/files/upload?path=future.txt&mtime=99999999999", content=b"x", headers=auth_headers)
+    assert response.status_code == 200
+    assert response.json()["mtime"] < 99999999999
+
+    response = client.post("/api/files/upload?path=bad.txt&mtime=yesterday", content=b"x", headers=auth_headers)
+    assert response.status_code == 400
+
+def test_upload_lists_blake3_hash_when_available(auth_headers):
+    blake3 = pytest.importorskip("blake3")
+    response = client.post("/api/files/upload?path=b3.txt", content=b"abc", headers=auth_headers)
+    assert response.status_code == 200
+    expected = blake3.blake3(b"abc").hexdigest()
+    assert response.json()["blake3"] == expected
+    listed = {f["path"]: f for f in client.get("/api/files/list", headers=auth_headers).json()}
+    assert listed["b3.txt"]["blake3"] == expected
+    assert listed["b3.txt"]["hash"] == response.json()["hash"]
+
+def test_list_reports_total_count(auth_headers):
+    client.post("/api/files/upload?path=one.txt", content=b"1", headers=auth_headers)
+    client.post("/api/files/upload?path=dir/two.txt", content=b"2", headers=auth_headers)
+    response = client.get("/api/files/list", headers=auth_headers)
+    assert response.status_code == 200
+    assert response.headers["X-Total-Count"] == str(len(response.json())) == "2"
+
+def test_list_is_gzipped_when_large_and_accepted(auth_headers, monkeypatch):
+    monkeypatch.setattr("app.files.routes.LIST_GZIP_MIN_BYTES", 0)
+    client.post("/api/files/upload?path=one.txt", content=b"1", headers=auth_headers)
+    response = client.get("/api/files/list", headers={**auth_headers, "Accept-Encoding": "gzip"})
+    assert response.headers["Content-Encoding"] == "gzip"
+    assert response.headers["X-Total-Count"] == "1"
+    assert [f["path"] for f in response.json()] == ["one.txt"]
+    plain = client.get("/api/files/list", headers={**auth_headers, "Accept-Encoding": "identity"})
+    assert "Content-Encoding" not in plain.headers
+
 def test_upload_streaming_quota_exceeded(auth_headers, monkeypatch):
     # Set a very small limit
     monkeypatch.setenv("BRANDYBOX_STORAGE_LIMIT", "100MB")
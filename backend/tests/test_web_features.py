@@ -7,7 +7,7 @@ import pytest
 from fastapi.testclient import TestClient
 
 from app.main import app
-from app.oauth.models import OAuthExchange
+from app.oauth.models import OAuthExchange, OAuthState
 from app.auth.jwt import create_access_token, create_refresh_token
 
 
@@ -35,6 +35,15 @@ def test_meta_version(client: TestClient) -> None:
     assert j.get("google_signin_available") is False
 
 
+def test_meta_capabilities_always_offer_sha256(client: TestClient) -> None:
+    r = client.get("/api/meta/capabilities")
+    assert r.status_code == 200
+    j = r.json()
+    assert j["hash_algorithms"][0] == "sha256"
+    assert j["upload_mtime"] is True
+    assert j["folder_zip"] is True
+
+
 def test_preferences_roundtrip(client: TestClient) -> None:
     h = _auth_headers(client)
     r = client.get("/api/users/me/preferences", headers=h)
@@ -146,6 +155,100 @@ async def test_oauth_complete_success(client: TestClient, session_factory) -> No
     assert "access_token" in r.json()
 
 
+@pytest.mark.asyncio
+async def test_oauth_complete_requires_pkce_verifier(client: TestClient, session_factory) -> None:
+    from app.oauth.routes import _pkce_challenge
+
+    verifier = "v" * 43
+    ex_id = str(uuid.uuid4())
+    async with session_factory() as session:
+        session.add(
+            OAuthExchange(
+                id=ex_id,
+                access_token=create_access_token("test@example.com"),
+                refresh_token=create_refresh_token("test@example.com"),
+                code_challenge=_pkce_challenge(verifier),
+            )
+        )
+    r = client.post("/api/auth/oauth/complete", json={"exchange": ex_id, "code_verifier": "w" * 43})
+    assert r.status_code == 401
+    # A failed verifier burns the exchange id
+    r = client.post("/api/auth/oauth/complete", json={"exchange": ex_id, "code_verifier": verifier})
+    assert r.status_code == 401
+
+    ex_id = str(uuid.uuid4())
+    async with session_factory() as session:
+        session.add(
+            OAuthExchange(
+                id=ex_id,
+                access_token=create_access_token("test@example.com"),
+                refresh_token=create_refresh_token("test@example.com"),
+                code_challenge=_pkce_challenge(verifier),
+            )
+        )
+    r = client.post("/api/auth/oauth/complete", json={"exchange": ex_id, "code_verifier": verifier})
+    assert r.status_code == 200, r.text
+
+
+def test_oauth_start_accepts_only_loopback_redirect(client: TestClient, monkeypatch) -> None:
+    from app.oauth import routes as oauth_routes
+
+    settings = oauth_routes.get_settings().model_copy(
+        update={"google_client_id": "id", "google_client_secret": "secret"}
+    )
+    monkeypatch.setattr(oauth_routes, "get_settings", lambda: settings)
+    pkce = {"client_state": "s" * 43, "code_challenge": "c" * 43}
+    for bad in (
+        "http://evil.example:8080/callback",
+        "https://127.0.0.1:8080/callback",
+        "http://localhost:8080/callback",
+        "http://127.0.0.1/callback",
+        "http://user@127.0.0.1:8080/callback",
+        "http://127.0.0.1:8080/callback?next=x",
+    ):
+        r = client.get(
+            "/api/auth/google/start",
+            params={"client_redirect": bad, **pkce},
+            follow_redirects=False,
+        )
+        assert r.status_code == 400, bad
+    r = client.get(
+        "/api/auth/google/start",
+        params={"client_redirect": "http://127.0.0.1:53123/callback"},
+        follow_redirects=False,
+    )
+    assert r.status_code == 400
+    r = client.get(
+        "/api/auth/google/start",
+        params={"client_redirect": "http://127.0.0.1:53123/callback", **pkce},
+        follow_redirects=False,
+    )
+    assert r.status_code == 302
+    assert r.headers["location"].startswith("https://accounts.google.com/")
+
+
+@pytest.mark.asyncio
+async def test_oauth_callback_redirects_to_loopback(client: TestClient, session_factory) -> None:
+    async with session_factory() as session:
+        session.add(
+            OAuthState(
+                state="google-state-1",
+                client_redirect="http://127.0.0.1:53123/callback",
+                client_state="desktop-state",
+                code_challenge="c" * 43,
+            )
+        )
+    r = client.get(
+        "/api/auth/google/callback",
+        params={"state": "google-state-1", "error": "access_denied"},
+        follow_redirects=False,
+    )
+    assert r.status_code == 302
+    assert r.headers["location"] == (
+        "http://127.0.0.1:53123/callback?error=oauth_denied&state=desktop-state"
+    )
+
+
 def test_admin_clients_and_events(client: TestClient) -> None:
     h = _auth_headers(client)
     client.post(
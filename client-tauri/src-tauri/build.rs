@@ -1,3 +1,17 @@
 fn main() {
+    // Short commit hash for get_app_info / bug reports; "unknown" outside a git checkout
+    let commit = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BRANDYBOX_GIT_COMMIT={}", commit);
+    println!(
+        "cargo:rustc-env=BRANDYBOX_BUILD_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_default()
+    );
     tauri_build::build()
 }
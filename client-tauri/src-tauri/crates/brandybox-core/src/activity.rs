@@ -0,0 +1,98 @@
+//! Recent per-file activity (uploads, downloads, deletes) for the "what just changed" feed.
+//! Kept as a ring buffer in memory and persisted to recent_activity.json next to sync_state.json.
+
+use crate::{config, metadata_crypto};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Maximum number of events kept (oldest are dropped first).
+const ACTIVITY_MAX: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Uploaded,
+    Downloaded,
+    /// Removed from the sync folder because it was deleted on the server.
+    DeletedLocally,
+    /// Removed from the server because it was deleted in the sync folder.
+    DeletedRemotely,
+    /// Renamed by case only (`readme.md` → `README.md`); the path is the new name.
+    Renamed,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    /// RFC 3339 timestamp.
+    pub at: String,
+    pub kind: ActivityKind,
+    /// Path relative to the sync folder, forward slashes.
+    pub path: String,
+}
+
+/// Newest first. None until loaded from disk on first use.
+static ACTIVITY: Mutex<Option<VecDeque<ActivityEvent>>> = Mutex::new(None);
+
+fn load() -> VecDeque<ActivityEvent> {
+    metadata_crypto::read(&config::get_activity_path())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn with_activity<T>(f: impl FnOnce(&mut VecDeque<ActivityEvent>) -> T) -> Option<T> {
+    let mut guard = ACTIVITY.lock().ok()?;
+    Some(f(guard.get_or_insert_with(load)))
+}
+
+/// Record one event (in memory; call `persist` to write it out).
+pub fn record(kind: ActivityKind, path: &str) {
+    let event = ActivityEvent {
+        at: chrono::Utc::now().to_rfc3339(),
+        kind,
+        path: path.to_string(),
+    };
+    with_activity(|a| {
+        a.push_front(event);
+        a.truncate(ACTIVITY_MAX);
+    });
+}
+
+/// Write the buffer to recent_activity.json.
+pub fn persist() {
+    let content = match with_activity(|a| serde_json::to_string_pretty(a)) {
+        Some(Ok(c)) => c,
+        _ => return,
+    };
+    if let Err(e) = metadata_crypto::write(&config::get_activity_path(), &content) {
+        log::warn!("Could not save recent activity: {}", e);
+    }
+}
+
+/// Drop all events (e.g. after a reset).
+pub fn clear() {
+    with_activity(|a| a.clear());
+}
+
+/// Up to `limit` most recent events, newest first.
+pub fn get_recent(limit: usize) -> Vec<ActivityEvent> {
+    with_activity(|a| a.iter().take(limit).cloned().collect()).unwrap_or_default()
+}
+
+/// Up to `limit` distinct paths that were most recently uploaded or downloaded, newest first.
+pub fn recent_transferred_paths(limit: usize) -> Vec<String> {
+    with_activity(|a| {
+        let mut out: Vec<String> = Vec::new();
+        for e in a.iter() {
+            if out.len() >= limit {
+                break;
+            }
+            let transferred = matches!(e.kind, ActivityKind::Uploaded | ActivityKind::Downloaded);
+            if transferred && !out.contains(&e.path) {
+                out.push(e.path.clone());
+            }
+        }
+        out
+    })
+    .unwrap_or_default()
+}
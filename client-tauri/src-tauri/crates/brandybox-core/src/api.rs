@@ -0,0 +1,1347 @@
+//! HTTP client for Brandy Box backend API. Matches Python client endpoints and behavior.
+
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long to wait for the TCP/TLS connection. Short, so an unreachable server fails fast;
+/// the per-client `timeout` then covers the (much slower) request and transfer.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait for the first byte of a zip download or account export: the server builds the whole zip
+/// before it answers.
+const ZIP_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// File transfers fail only when no bytes moved for this long, however big the file or slow the
+/// link. Downloads get it per read of the response body; uploads through `send_upload`.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How the last failed request on this thread failed. Recorded where the response (or its
+/// absence) is seen, so the sync engine can tell login, quota, server and network problems apart
+/// without reading error messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestFailure {
+    /// No usable response: connection, DNS, timeout or a transfer that stopped moving.
+    Network,
+    /// The server answered with this error status.
+    Status(u16),
+    /// The file listing had fewer entries than the server's `X-Total-Count`.
+    IncompleteListing,
+}
+
+thread_local! {
+    static LAST_FAILURE: Cell<Option<RequestFailure>> = const { Cell::new(None) };
+}
+
+fn record_failure(failure: Option<RequestFailure>) {
+    LAST_FAILURE.with(|f| f.set(failure));
+}
+
+/// Record the outcome of a request: a success clears the previous failure.
+fn record_response(result: &Result<reqwest::blocking::Response, String>) {
+    record_failure(match result {
+        Ok(r) if r.status().is_success() => None,
+        Ok(r) => Some(RequestFailure::Status(r.status().as_u16())),
+        Err(_) => Some(RequestFailure::Network),
+    });
+}
+
+/// The failure of the last request on this thread, if it failed; clears it.
+pub fn take_last_failure() -> Option<RequestFailure> {
+    LAST_FAILURE.with(Cell::take)
+}
+
+/// Client builder with the shared connect timeout and the given request/transfer timeout
+/// (`None`: no limit).
+pub(crate) fn client_builder<T: Into<Option<Duration>>>(timeout: T) -> reqwest::blocking::ClientBuilder {
+    reqwest::blocking::Client::builder().connect_timeout(CONNECT_TIMEOUT).timeout(timeout)
+}
+
+/// When an upload body last moved, and whether its watchdog gave up.
+struct UploadProgress {
+    last: std::sync::Mutex<std::time::Instant>,
+    aborted: std::sync::atomic::AtomicBool,
+}
+
+/// Upload body that records progress in `UploadProgress` and fails once the upload was abandoned.
+struct ProgressReader<R> {
+    inner: R,
+    progress: std::sync::Arc<UploadProgress>,
+}
+
+impl<R: std::io::Read> std::io::Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.progress.aborted.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "upload abandoned"));
+        }
+        let n = self.inner.read(buf)?;
+        *self.progress.last.lock().unwrap() = std::time::Instant::now();
+        Ok(n)
+    }
+}
+
+/// Send `request` with `reader` (`len` bytes) as body. Fails when no body bytes were taken for
+/// `idle`, or no response came that long after the last one, instead of after a fixed total time.
+/// reqwest cannot cancel a blocking request, so the stalled one is left on its thread; its body
+/// read fails from then on and the connection's own errors end it.
+fn send_upload<R: std::io::Read + Send + 'static>(
+    request: reqwest::blocking::RequestBuilder,
+    reader: R,
+    len: u64,
+    idle: Duration,
+) -> Result<reqwest::blocking::Response, String> {
+    let progress = std::sync::Arc::new(UploadProgress {
+        last: std::sync::Mutex::new(std::time::Instant::now()),
+        aborted: std::sync::atomic::AtomicBool::new(false),
+    });
+    let body = reqwest::blocking::Body::sized(ProgressReader { inner: reader, progress: progress.clone() }, len);
+    let request = request.body(body);
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(request.send_traced());
+    });
+    loop {
+        match rx.recv_timeout(Duration::from_secs(1).min(idle)) {
+            // The request ran on its own thread; record its outcome on the caller's
+            Ok(result) => {
+                record_response(&result);
+                return result;
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Err("upload thread panicked".to_string()),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                if progress.last.lock().unwrap().elapsed() >= idle {
+                    progress.aborted.store(true, std::sync::atomic::Ordering::Relaxed);
+                    record_failure(Some(RequestFailure::Network));
+                    return Err(format!("upload stalled: no progress for {} s", idle.as_secs()));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ApiClient {
+    pub base_url: String,
+    pub access_token: Option<String>,
+    /// Built on first use and shared by clones, so repeated calls reuse pooled connections.
+    http: std::sync::Arc<std::sync::OnceLock<reqwest::blocking::Client>>,
+}
+
+#[derive(Serialize)]
+struct LoginBody {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+pub struct LoginResponse {
+    pub access_token: String,
+    /// Empty for app-token logins when the server issues no refresh token.
+    #[serde(default)]
+    pub refresh_token: String,
+    /// Access token lifetime in seconds.
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct RefreshBody {
+    refresh_token: String,
+}
+
+#[derive(Serialize)]
+struct AppTokenBody {
+    token: String,
+}
+
+#[derive(Serialize)]
+struct OAuthCompleteBody {
+    exchange: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_verifier: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PasswordResetBody {
+    email: String,
+}
+
+#[derive(Serialize)]
+struct PasswordResetConfirmBody {
+    token: String,
+    new_password: String,
+}
+
+#[derive(Serialize)]
+struct ChangePasswordBody {
+    current_password: String,
+    new_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct User {
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub is_admin: Option<bool>,
+    /// False for suspended accounts. Missing on older servers (treated as enabled).
+    pub is_enabled: Option<bool>,
+    /// Bytes currently stored by the user (server-side cached usage).
+    pub storage_used_bytes: Option<i64>,
+    pub storage_limit_bytes: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct StorageInfo {
+    pub used_bytes: i64,
+    pub limit_bytes: Option<i64>,
+    /// Total bytes on the server (Pi) filesystem containing storage. Optional for backward compatibility.
+    #[serde(default)]
+    pub server_disk_total_bytes: Option<i64>,
+    /// Used bytes on that filesystem (total - free). Optional for backward compatibility.
+    #[serde(default)]
+    pub server_disk_used_bytes: Option<i64>,
+    /// Path that was used for server disk stats (for debugging).
+    #[serde(default)]
+    pub server_disk_path: Option<String>,
+}
+
+/// Response of `/api/meta/version`; also serves as the "is this a Brandy Box backend" probe.
+#[derive(Deserialize)]
+pub struct ServerVersion {
+    pub api_version: String,
+    pub min_supported_client_version: Option<String>,
+    #[serde(default)]
+    pub google_signin_available: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FileItem {
+    pub path: String,
+    pub mtime: f64,
+    pub hash: Option<String>,
+    /// Size in bytes (API 0.3.0+).
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// BLAKE3 content hash, on servers that list `blake3` in their capabilities.
+    #[serde(default)]
+    pub blake3: Option<String>,
+}
+
+/// Content hash algorithms, in order of preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Sha256,
+    Blake3,
+}
+
+/// Response of `/api/meta/capabilities`. Servers without the endpoint support SHA-256 only.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Capabilities {
+    #[serde(default)]
+    pub hash_algorithms: Vec<String>,
+}
+
+impl Capabilities {
+    /// Best hash algorithm both sides support.
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        if self.hash_algorithms.iter().any(|a| a == "blake3") {
+            HashAlgorithm::Blake3
+        } else {
+            HashAlgorithm::Sha256
+        }
+    }
+}
+
+/// One result of the server-side search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub path: String,
+    #[serde(default)]
+    pub mtime: f64,
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// "name" or "content".
+    #[serde(rename = "match")]
+    pub match_kind: String,
+    /// Text around the match for content matches.
+    #[serde(default)]
+    pub snippet: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CreateUserBody {
+    email: String,
+    first_name: String,
+    last_name: String,
+}
+
+#[derive(Serialize)]
+struct UpdateUserBody {
+    storage_limit_bytes: Option<i64>,
+}
+
+/// A registered client install, as listed in Settings → Devices.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Device {
+    pub device_id: String,
+    pub name: String,
+    pub platform: String,
+    pub client_version: String,
+    pub created_at: String,
+    pub last_seen_at: String,
+    pub last_sync_at: Option<String>,
+    pub last_sync_ok: Option<bool>,
+    pub revoked: bool,
+}
+
+/// Partial admin update: fields left as None are not sent and stay unchanged on the server.
+#[derive(Serialize)]
+struct UpdateUserProfileBody<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_admin: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct UserEnabledBody {
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct DeleteBatchBody<'a> {
+    paths: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct DeleteBatchFailure {
+    path: String,
+    error: String,
+}
+
+#[derive(Deserialize)]
+struct DeleteBatchResponse {
+    #[serde(default)]
+    failed: Vec<DeleteBatchFailure>,
+}
+
+/// Paths per batch delete request (the server accepts up to 1000).
+pub const DELETE_BATCH_SIZE: usize = 500;
+
+/// Compare a listing with the server's `X-Total-Count` (absent on older servers).
+fn check_listing_complete(received: usize, total: Option<usize>) -> Result<(), String> {
+    match total {
+        Some(total) if total != received => {
+            record_failure(Some(RequestFailure::IncompleteListing));
+            Err(format!(
+            "Incomplete file listing from server ({} of {} files); nothing was changed",
+                received, total
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `&mtime=…` for upload URLs (millisecond precision), or nothing.
+fn mtime_param(mtime: Option<f64>) -> String {
+    match mtime.filter(|m| m.is_finite() && *m >= 0.0) {
+        Some(m) => format!("&mtime={:.3}", m),
+        None => String::new(),
+    }
+}
+
+/// Stream a response body into `dest`; a partial file is removed. Returns the bytes written.
+fn save_response(mut r: reqwest::blocking::Response, dest: &Path) -> Result<u64, String> {
+    let mut file = File::create(dest).map_err(|e| e.to_string())?;
+    match r.copy_to(&mut file) {
+        Ok(n) => Ok(n),
+        Err(e) => {
+            drop(file);
+            let _ = std::fs::remove_file(dest);
+            record_failure(Some(RequestFailure::Network));
+            Err(format!("failed to read response body: {}", e))
+        }
+    }
+}
+
+/// Log every request when set; see `set_http_trace`.
+static HTTP_TRACE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Turn HTTP trace logging on or off for all clients (from `config::get_http_debug`).
+pub fn set_http_trace(enabled: bool) {
+    HTTP_TRACE.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Query parameters whose values are credentials.
+const SECRET_PARAMS: &[&str] = &["token", "access_token", "refresh_token", "code", "exchange", "password"];
+
+/// `url` for the log, with credential query values replaced.
+fn redact_url(url: &reqwest::Url) -> String {
+    let mut redacted = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if SECRET_PARAMS.contains(&k.as_ref()) { "REDACTED".into() } else { v.into_owned() };
+                (k.into_owned(), v)
+            })
+            .collect();
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    redacted.to_string()
+}
+
+/// Id sent as `X-Request-Id` with every request, so client errors can be matched with the
+/// server's log; kept in the response's extensions for error messages.
+#[derive(Clone)]
+struct RequestId(String);
+
+/// `send` with an `X-Request-Id` header. Errors name the request id. While HTTP tracing is on,
+/// also logs method, URL, status, duration and sizes; headers (the bearer token) and bodies are
+/// never logged.
+trait SendTraced {
+    fn send_traced(self) -> Result<reqwest::blocking::Response, String>;
+}
+
+impl SendTraced for reqwest::blocking::RequestBuilder {
+    fn send_traced(self) -> Result<reqwest::blocking::Response, String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (client, request) = self.header("X-Request-Id", &id).build_split();
+        let request = request.map_err(|e| e.to_string())?;
+        let trace = HTTP_TRACE.load(std::sync::atomic::Ordering::Relaxed).then(|| {
+            let sent = match request.body() {
+                Some(body) => body.as_bytes().map_or("streamed".to_string(), |b| format!("{} B", b.len())),
+                None => "0 B".to_string(),
+            };
+            (request.method().clone(), redact_url(request.url()), sent, std::time::Instant::now())
+        });
+        let result = client.execute(request).map_err(|e| e.to_string());
+        record_response(&result);
+        if let Some((method, url, sent, started)) = trace {
+            let ms = started.elapsed().as_millis();
+            match &result {
+                Ok(r) => log::info!(
+                    "HTTP {} {} -> {} in {} ms (sent {}, received {}, request {})",
+                    method,
+                    url,
+                    r.status().as_u16(),
+                    ms,
+                    sent,
+                    r.content_length().map_or("streamed".to_string(), |n| format!("{} B", n)),
+                    id
+                ),
+                Err(e) => log::info!("HTTP {} {} failed after {} ms (request {}): {}", method, url, ms, id, e),
+            }
+        }
+        match result {
+            Ok(mut r) => {
+                r.extensions_mut().insert(RequestId(id));
+                Ok(r)
+            }
+            Err(e) => Err(format!("{} (request {})", e, id)),
+        }
+    }
+}
+
+/// " (request <id>)" for error messages, or nothing.
+fn request_tag(r: &reqwest::blocking::Response) -> String {
+    r.extensions().get::<RequestId>().map_or(String::new(), |id| format!(" (request {})", id.0))
+}
+
+/// Error for a failed response: status, body and request id.
+fn http_error(r: reqwest::blocking::Response) -> String {
+    let tag = request_tag(&r);
+    let status = r.status();
+    let text = r.text().unwrap_or_default();
+    format!("{} {}{}", status, text, tag)
+}
+
+/// Error for a failed response: status and request id.
+fn status_error(r: &reqwest::blocking::Response) -> String {
+    format!("{}{}", r.status(), request_tag(r))
+}
+
+impl ApiClient {
+    pub fn new(base_url: String) -> Self {
+        ApiClient { base_url, access_token: None, http: Default::default() }
+    }
+
+    pub fn set_access_token(&mut self, token: Option<String>) {
+        self.access_token = token;
+    }
+
+    fn client(&self) -> reqwest::blocking::Client {
+        self.http
+            .get_or_init(|| client_builder(Duration::from_secs(30)).build().expect("http client"))
+            .clone()
+    }
+
+    /// Client for uploads: no total timeout, `send_upload` watches for stalls instead.
+    fn upload_client(&self) -> reqwest::blocking::Client {
+        client_builder(None)
+            .tcp_keepalive(Duration::from_secs(60))
+            .build()
+            .expect("http client")
+    }
+
+    /// Client for binary download, no gzip/deflate so response body is raw bytes (avoids "error
+    /// decoding response body" when server or proxy sends compressed binary). reqwest applies
+    /// `timeout` to the wait for the response and to each read of the body, not to the whole
+    /// transfer.
+    fn download_client(&self, timeout: Duration) -> reqwest::blocking::Client {
+        client_builder(timeout)
+            .no_gzip()
+            .no_deflate()
+            .build()
+            .expect("http client")
+    }
+
+    fn headers(&self) -> reqwest::header::HeaderMap {
+        let mut h = reqwest::header::HeaderMap::new();
+        h.insert(reqwest::header::ACCEPT, "application/json".parse().unwrap());
+        if let Some(t) = &self.access_token {
+            let v = format!("Bearer {}", t);
+            h.insert(reqwest::header::AUTHORIZATION, v.parse().unwrap());
+        }
+        h
+    }
+
+    pub fn login(&self, email: &str, password: &str) -> Result<LoginResponse, String> {
+        let url = format!("{}/api/auth/login", self.base_url.trim_end_matches('/'));
+        let body = LoginBody { email: email.to_string(), password: password.to_string() };
+        let r = self
+            .client()
+            .post(&url)
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    pub fn refresh(&self, refresh_token: &str) -> Result<LoginResponse, String> {
+        let url = format!("{}/api/auth/refresh", self.base_url.trim_end_matches('/'));
+        let body = RefreshBody { refresh_token: refresh_token.to_string() };
+        let r = self
+            .client()
+            .post(&url)
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Exchange a long-lived app token (headless / kiosk installs) for an access token.
+    pub fn login_with_token(&self, app_token: &str) -> Result<LoginResponse, String> {
+        let url = format!("{}/api/auth/token", self.base_url.trim_end_matches('/'));
+        let body = AppTokenBody { token: app_token.to_string() };
+        let r = self
+            .client()
+            .post(&url)
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Redeem the one-time exchange id from the browser (SSO) login redirect for a token pair.
+    /// Desktop logins pass the PKCE verifier whose challenge was sent to the start endpoint.
+    pub fn oauth_complete(&self, exchange: &str, code_verifier: Option<&str>) -> Result<LoginResponse, String> {
+        let url = format!("{}/api/auth/oauth/complete", self.base_url.trim_end_matches('/'));
+        let body = OAuthCompleteBody {
+            exchange: exchange.to_string(),
+            code_verifier: code_verifier.map(str::to_string),
+        };
+        let r = self
+            .client()
+            .post(&url)
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Server API version and client compatibility hints (no auth required).
+    pub fn get_version(&self) -> Result<ServerVersion, String> {
+        let url = format!("{}/api/meta/version", self.base_url.trim_end_matches('/'));
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Optional features of the server. Older servers answer 404: SHA-256 only.
+    pub fn get_capabilities(&self) -> Result<Capabilities, String> {
+        let url = format!("{}/api/meta/capabilities", self.base_url.trim_end_matches('/'));
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if r.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(Capabilities { hash_algorithms: vec!["sha256".to_string()] });
+        }
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Server clock minus local clock in seconds, read from the `Date` header of the version
+    /// endpoint. Measured against the midpoint of the request; the header has 1 s resolution.
+    pub fn server_time_offset(&self) -> Result<f64, String> {
+        let url = format!("{}/api/meta/version", self.base_url.trim_end_matches('/'));
+        let sent = chrono::Utc::now().timestamp_millis();
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        let received = chrono::Utc::now().timestamp_millis();
+        let date = r
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|v| v.to_str().ok())
+            .ok_or("Server sent no Date header")?;
+        let server = chrono::DateTime::parse_from_rfc2822(date).map_err(|e| e.to_string())?;
+        let midpoint = (sent + received) as f64 / 2000.0;
+        Ok(server.timestamp() as f64 - midpoint)
+    }
+
+    pub fn me(&self) -> Result<User, String> {
+        let url = format!("{}/api/users/me", self.base_url.trim_end_matches('/'));
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Forgot password: ask the server to email a single-use reset code (no auth required), to be
+    /// redeemed with `confirm_password_reset`. The current password stays valid until then; the
+    /// server answers the same whether or not the account exists.
+    pub fn request_password_reset(&self, email: &str) -> Result<(), String> {
+        let url = format!("{}/api/auth/password-reset", self.base_url.trim_end_matches('/'));
+        let body = PasswordResetBody { email: email.to_string() };
+        let r = self
+            .client()
+            .post(&url)
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        Ok(())
+    }
+
+    /// Set a new password with the code from the reset email (no auth required).
+    pub fn confirm_password_reset(&self, token: &str, new_pass: &str) -> Result<(), String> {
+        let url = format!("{}/api/auth/password-reset/confirm", self.base_url.trim_end_matches('/'));
+        let body = PasswordResetConfirmBody { token: token.to_string(), new_password: new_pass.to_string() };
+        let r = self
+            .client()
+            .post(&url)
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        Ok(())
+    }
+
+    pub fn change_password(&self, current: &str, new_pass: &str) -> Result<(), String> {
+        let url = format!("{}/api/auth/change-password", self.base_url.trim_end_matches('/'));
+        let body = ChangePasswordBody { current_password: current.to_string(), new_password: new_pass.to_string() };
+        let r = self
+            .client()
+            .post(&url)
+            .headers(self.headers())
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        Ok(())
+    }
+
+    pub fn get_storage(&self) -> Result<StorageInfo, String> {
+        let url = format!("{}/api/files/storage", self.base_url.trim_end_matches('/'));
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    pub fn list_files(&self) -> Result<Vec<FileItem>, String> {
+        let url = format!("{}/api/files/list", self.base_url.trim_end_matches('/'));
+        let client = client_builder(Duration::from_secs(60))
+            .build()
+            .expect("client");
+        // Listings of large boxes are several MB of JSON; servers that support it send them gzipped
+        let r = client
+            .get(&url)
+            .headers(self.headers())
+            .header(reqwest::header::ACCEPT_ENCODING, "gzip")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        let total: Option<usize> = r
+            .headers()
+            .get("x-total-count")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok());
+        let gzipped = r
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"gzip"));
+        let items: Vec<FileItem> = if gzipped {
+            serde_json::from_reader(std::io::BufReader::new(flate2::read::GzDecoder::new(r)))
+                .map_err(|e| format!("Invalid compressed file listing: {}", e))?
+        } else {
+            r.json().map_err(|e| e.to_string())?
+        };
+        // A listing cut short must fail the cycle: missing files would look deleted on the server
+        check_listing_complete(items.len(), total)?;
+        Ok(items)
+    }
+
+    /// JPEG preview of an image, longest edge `size` pixels. Ok(None) when the server has no
+    /// preview for this file (not an image, 415) or no thumbnail endpoint (404 from older servers).
+    pub fn get_thumbnail(&self, path: &str, size: u32) -> Result<Option<Vec<u8>>, String> {
+        let url = format!(
+            "{}/api/files/thumbnail?path={}&size={}",
+            self.base_url.trim_end_matches('/'),
+            urlencoding::encode(path),
+            size
+        );
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if r.status() == reqwest::StatusCode::NOT_FOUND || r.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+            return Ok(None);
+        }
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.bytes().map(|b| Some(b.to_vec())).map_err(|e| e.to_string())
+    }
+
+    /// Search the server by file name and, with `content`, inside text files. Ok(None) when the
+    /// server has no search endpoint (older versions), so callers can fall back to the listing.
+    pub fn search(&self, query: &str, content: bool) -> Result<Option<Vec<SearchHit>>, String> {
+        let url = format!(
+            "{}/api/files/search?q={}&content={}",
+            self.base_url.trim_end_matches('/'),
+            urlencoding::encode(query),
+            content
+        );
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if r.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map(Some).map_err(|e| e.to_string())
+    }
+
+    /// Upload file from disk with retries. For files > 50MB, uses chunked upload to bypass
+    /// proxy body limits (e.g. Cloudflare 100MB). `mtime` (Unix seconds, server clock) becomes
+    /// the file's mtime on the server; older servers ignore it and keep the upload time.
+    pub fn upload_file_from_path(&self, path: &str, local_path: &Path, mtime: Option<f64>) -> Result<(), String> {
+        let file_size = std::fs::metadata(local_path).map_err(|e| e.to_string())?.len();
+
+        if file_size > 50 * 1024 * 1024 {
+            return self.upload_file_chunked(path, local_path, file_size, mtime);
+        }
+
+        let url = format!("{}/api/files/upload", self.base_url.trim_end_matches('/'));
+        let url = format!("{}?path={}{}", url, urlencoding::encode(path), mtime_param(mtime));
+        let client = self.upload_client();
+
+        let mut last_err = String::new();
+        for attempt in 0..3 {
+            let file = File::open(local_path).map_err(|e| e.to_string())?;
+            let mut headers = self.headers();
+            headers.insert(
+                reqwest::header::CONTENT_TYPE,
+                "application/octet-stream".parse().unwrap(),
+            );
+            match send_upload(client.post(&url).headers(headers), file, file_size, IDLE_TIMEOUT) {
+                Ok(r) => {
+                    if !r.status().is_success() {
+                        let tag = request_tag(&r);
+                        let status = r.status();
+                        let body_text = r.text().unwrap_or_default();
+                        last_err = if body_text.trim().is_empty() {
+                            format!("{}{}", status, tag)
+                        } else {
+                            format!("{}: {}{}", status, body_text.trim(), tag)
+                        };
+                        // Quota reached / file too large: retrying cannot help
+                        if status == reqwest::StatusCode::INSUFFICIENT_STORAGE
+                            || status == reqwest::StatusCode::PAYLOAD_TOO_LARGE
+                        {
+                            return Err(last_err);
+                        }
+                    } else {
+                        return Ok(());
+                    }
+                }
+                Err(e) => {
+                    last_err = e.to_string();
+                }
+            }
+            if attempt < 2 {
+                std::thread::sleep(Duration::from_secs(3 + attempt as u64 * 4));
+            }
+        }
+        Err(last_err)
+    }
+
+    fn upload_file_chunked(&self, path: &str, local_path: &Path, file_size: u64, mtime: Option<f64>) -> Result<(), String> {
+        let base = self.base_url.trim_end_matches('/');
+        let init_url = format!("{}/api/files/upload/init?path={}{}", base, urlencoding::encode(path), mtime_param(mtime));
+
+        let resp = self.client()
+            .post(&init_url)
+            .headers(self.headers())
+            .send_traced()
+            .map_err(|e| format!("init failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("init failed: {}", status_error(&resp)));
+        }
+
+        let init_data: serde_json::Value = resp.json().map_err(|e| e.to_string())?;
+        let upload_id = init_data["upload_id"].as_str().ok_or("no upload_id")?;
+
+        let chunk_size = 20 * 1024 * 1024; // 20MB chunks
+        let mut file = File::open(local_path).map_err(|e| e.to_string())?;
+        use std::io::{Read, Seek, SeekFrom};
+
+        let client = self.upload_client();
+        let mut index = 0;
+        let mut offset = 0;
+
+        while offset < file_size {
+            let current_chunk_size = std::cmp::min(chunk_size, file_size - offset);
+            let mut buffer = vec![0; current_chunk_size as usize];
+            file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+            file.read_exact(&mut buffer).map_err(|e| e.to_string())?;
+
+            let chunk_url = format!("{}/api/files/upload/chunk?upload_id={}&index={}", base, upload_id, index);
+
+            let mut last_err = String::new();
+            let mut success = false;
+            for attempt in 0..3 {
+                let mut headers = self.headers();
+                headers.insert(reqwest::header::CONTENT_TYPE, "application/octet-stream".parse().unwrap());
+
+                let request = client.post(&chunk_url).headers(headers);
+                match send_upload(request, std::io::Cursor::new(buffer.clone()), current_chunk_size, IDLE_TIMEOUT) {
+                    Ok(r) if r.status().is_success() => {
+                        success = true;
+                        break;
+                    }
+                    Ok(r) => last_err = format!("chunk {} failed: {}", index, status_error(&r)),
+                    Err(e) => last_err = format!("chunk {} failed: {}", index, e),
+                }
+                if attempt < 2 {
+                    std::thread::sleep(Duration::from_secs(2 * (attempt + 1) as u64));
+                }
+            }
+
+            if !success {
+                return Err(last_err);
+            }
+
+            offset += current_chunk_size;
+            index += 1;
+        }
+
+        let finalize_url = format!("{}/api/files/upload/finalize?upload_id={}", base, upload_id);
+        let resp = self.client()
+            .post(&finalize_url)
+            .headers(self.headers())
+            .send_traced()
+            .map_err(|e| format!("finalize failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("finalize failed: {}", status_error(&resp)));
+        }
+
+        Ok(())
+    }
+
+    /// Upload in-memory body (used when caller already has bytes). For large files prefer upload_file_from_path.
+    #[allow(dead_code)]
+    pub fn upload_file(&self, path: &str, body: &[u8]) -> Result<(), String> {
+        let url = format!("{}/api/files/upload", self.base_url.trim_end_matches('/'));
+        let url = format!("{}?path={}", url, urlencoding::encode(path));
+        let mut headers = self.headers();
+        headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            "application/octet-stream".parse().unwrap(),
+        );
+        let request = self.upload_client().post(&url).headers(headers);
+        let r = send_upload(request, std::io::Cursor::new(body.to_vec()), body.len() as u64, IDLE_TIMEOUT)?;
+        if !r.status().is_success() {
+            let tag = request_tag(&r);
+            let status = r.status();
+            let body_text = r.text().unwrap_or_default();
+            return Err(if body_text.trim().is_empty() {
+                format!("{}{}", status, tag)
+            } else {
+                format!("{}: {}{}", status, body_text.trim(), tag)
+            });
+        }
+        Ok(())
+    }
+
+    /// Download file with retries, streaming directly to a temporary file to save memory.
+    /// Returns the bytes of the file for compatibility with existing sync logic.
+    pub fn download_file(&self, path: &str) -> Result<Vec<u8>, String> {
+        let base = self.base_url.trim_end_matches('/');
+        let url = format!("{}/api/files/download?path={}", base, urlencoding::encode(path));
+        let mut last_err = String::new();
+
+        for attempt in 0..3 {
+            match self.download_client(IDLE_TIMEOUT).get(&url).headers(self.headers()).send_traced() {
+                Ok(mut r) => {
+                    if !r.status().is_success() {
+                        let tag = request_tag(&r);
+                        let status = r.status();
+                        let resp_body = r.text().unwrap_or_default();
+                        last_err = if resp_body.trim().is_empty() {
+                            format!("{}{}", status, tag)
+                        } else {
+                            format!("{}: {}{}", status, resp_body.trim(), tag)
+                        };
+                    } else {
+                        // Create a temporary file to stream the response into
+                        let tmp_file_path = std::env::temp_dir().join(format!("bb_dl_{}", uuid::Uuid::new_v4()));
+                        let mut tmp_file = File::create(&tmp_file_path).map_err(|e| e.to_string())?;
+
+                        if let Err(e) = r.copy_to(&mut tmp_file) {
+                            let _ = std::fs::remove_file(&tmp_file_path);
+                            record_failure(Some(RequestFailure::Network));
+                            last_err = format!("failed to read response body: {}", e);
+                        } else {
+                            // Read from temp file into Vec<u8> (still memory-intensive but respects streaming from network)
+                            // In a full refactor, sync.rs should handle the file path directly.
+                            let mut read_buf = Vec::new();
+                            let mut read_file = File::open(&tmp_file_path).map_err(|e| e.to_string())?;
+                            use std::io::Read;
+                            read_file.read_to_end(&mut read_buf).map_err(|e| e.to_string())?;
+                            let _ = std::fs::remove_file(&tmp_file_path);
+                            return Ok(read_buf);
+                        }
+                    }
+                }
+                Err(e) => {
+                    last_err = e.to_string();
+                }
+            }
+            if attempt < 2 {
+                std::thread::sleep(Duration::from_secs(2 * (attempt + 1)));
+            }
+        }
+        Err(last_err)
+    }
+
+    pub fn delete_file(&self, path: &str) -> Result<(), String> {
+        let base = self.base_url.trim_end_matches('/');
+        let url = format!("{}/api/files/delete?path={}", base, urlencoding::encode(path));
+        let r = self.client().delete(&url).headers(self.headers()).send_traced()?;
+        if r.status().as_u16() == 404 {
+            return Ok(());
+        }
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        Ok(())
+    }
+
+    /// Delete many files with one request per `DELETE_BATCH_SIZE` paths. Paths already gone
+    /// count as deleted. Servers without the batch endpoint (404/405) get one request per file.
+    pub fn delete_files(&self, paths: &[String]) -> Result<(), String> {
+        let url = format!("{}/api/files/delete-batch", self.base_url.trim_end_matches('/'));
+        for chunk in paths.chunks(DELETE_BATCH_SIZE) {
+            let r = self
+                .client()
+                .post(&url)
+                .headers(self.headers())
+                .json(&DeleteBatchBody { paths: chunk })
+                .send_traced()?;
+            if matches!(r.status().as_u16(), 404 | 405) {
+                log::debug!("No batch delete endpoint on the server; deleting one file at a time");
+                return paths.iter().try_for_each(|p| self.delete_file(p).map_err(|e| format!("{}: {}", p, e)));
+            }
+            if !r.status().is_success() {
+                return Err(http_error(r));
+            }
+            let res: DeleteBatchResponse = r.json().map_err(|e| e.to_string())?;
+            if let Some(f) = res.failed.first() {
+                return Err(format!("{}: {} ({} failed)", f.path, f.error, res.failed.len()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Move or rename a file on the server (also case-only renames). Servers without the move
+    /// endpoint answer 404 or 405; callers fall back to upload + delete.
+    pub fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let base = self.base_url.trim_end_matches('/');
+        let url = format!(
+            "{}/api/files/move?from={}&to={}",
+            base,
+            urlencoding::encode(from),
+            urlencoding::encode(to)
+        );
+        let r = self.client().post(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        Ok(())
+    }
+
+    /// Copy a file on the server (counts toward the storage limit). 409 when `to` exists.
+    pub fn copy_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let base = self.base_url.trim_end_matches('/');
+        let url = format!(
+            "{}/api/files/copy?from={}&to={}",
+            base,
+            urlencoding::encode(from),
+            urlencoding::encode(to)
+        );
+        let r = self.client().post(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        Ok(())
+    }
+
+    pub fn list_users(&self) -> Result<Vec<User>, String> {
+        let url = format!("{}/api/users", self.base_url.trim_end_matches('/'));
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    pub fn create_user(&self, email: &str, first_name: &str, last_name: &str) -> Result<serde_json::Value, String> {
+        let url = format!("{}/api/users", self.base_url.trim_end_matches('/'));
+        let body = CreateUserBody {
+            email: email.to_string(),
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+        };
+        let r = self
+            .client()
+            .post(&url)
+            .headers(self.headers())
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    pub fn update_user_storage_limit(&self, email: &str, limit_bytes: Option<i64>) -> Result<serde_json::Value, String> {
+        let encoded = urlencoding::encode(email);
+        let url = format!("{}/api/users/{}", self.base_url.trim_end_matches('/'), encoded);
+        let body = UpdateUserBody { storage_limit_bytes: limit_bytes };
+        let r = self
+            .client()
+            .patch(&url)
+            .headers(self.headers())
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Report client version and last sync outcome to the server (best-effort).
+    pub fn client_ping(&self, last_sync_ok: Option<bool>, last_sync_at_rfc3339: Option<String>) -> Result<(), String> {
+        let url = format!("{}/api/clients/ping", self.base_url.trim_end_matches('/'));
+        let body = serde_json::json!({
+            "client_type": "tauri",
+            "client_version": env!("CARGO_PKG_VERSION"),
+            "last_sync_ok": last_sync_ok,
+            "last_sync_at": last_sync_at_rfc3339,
+        });
+        let r = self
+            .client()
+            .post(&url)
+            .headers(self.headers())
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if r.status() == reqwest::StatusCode::NO_CONTENT || r.status().is_success() {
+            return Ok(());
+        }
+        Err(status_error(&r))
+    }
+
+    /// Register this install (first call) or refresh its last-seen / last-sync on the server.
+    /// A revoked device gets "410 Gone".
+    pub fn register_device(
+        &self,
+        device_id: &str,
+        name: &str,
+        last_sync_ok: Option<bool>,
+        last_sync_at_rfc3339: Option<String>,
+    ) -> Result<Device, String> {
+        let url = format!(
+            "{}/api/devices/{}",
+            self.base_url.trim_end_matches('/'),
+            urlencoding::encode(device_id)
+        );
+        let body = serde_json::json!({
+            "name": name,
+            "platform": std::env::consts::OS,
+            "client_version": env!("CARGO_PKG_VERSION"),
+            "last_sync_ok": last_sync_ok,
+            "last_sync_at": last_sync_at_rfc3339,
+        });
+        let r = self
+            .client()
+            .put(&url)
+            .headers(self.headers())
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// The current user's devices, most recently seen first.
+    pub fn list_devices(&self) -> Result<Vec<Device>, String> {
+        let url = format!("{}/api/devices", self.base_url.trim_end_matches('/'));
+        let r = self.client().get(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Revoke one of the current user's devices; it is refused on its next registration.
+    pub fn revoke_device(&self, device_id: &str) -> Result<Device, String> {
+        let url = format!(
+            "{}/api/devices/{}/revoke",
+            self.base_url.trim_end_matches('/'),
+            urlencoding::encode(device_id)
+        );
+        let r = self.client().post(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Upload a crash report from a previous run (stored as a server event for admins).
+    pub fn report_crash(&self, report: &crate::crash::CrashReport) -> Result<(), String> {
+        let url = format!("{}/api/clients/crash", self.base_url.trim_end_matches('/'));
+        let body = serde_json::json!({
+            "client_type": "tauri",
+            "client_version": report.client_version,
+            "os": report.os,
+            "occurred_at": report.occurred_at,
+            "message": report.message.chars().take(2000).collect::<String>(),
+            "backtrace": report.backtrace.chars().take(32000).collect::<String>(),
+        });
+        let r = self.client().post(&url).headers(self.headers()).json(&body).send_traced()?;
+        if r.status().is_success() {
+            return Ok(());
+        }
+        Err(http_error(r))
+    }
+
+    /// Download a zip of the current user's profile and all files to `dest`. Returns its size.
+    pub fn export_account(&self, dest: &Path) -> Result<u64, String> {
+        let url = format!("{}/api/users/me/export", self.base_url.trim_end_matches('/'));
+        let r = self
+            .download_client(ZIP_TIMEOUT)
+            .get(&url)
+            .headers(self.headers())
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        save_response(r, dest)
+    }
+
+    /// Download the server folder `prefix` ("" for all files) as a zip to `dest`, paths relative
+    /// to the folder. Returns its size. Servers before the zip endpoint answer a plain 404.
+    pub fn download_folder_zip(&self, prefix: &str, dest: &Path) -> Result<u64, String> {
+        let url = format!(
+            "{}/api/files/download-zip?path={}",
+            self.base_url.trim_end_matches('/'),
+            urlencoding::encode(prefix)
+        );
+        let r = self
+            .download_client(ZIP_TIMEOUT)
+            .get(&url)
+            .headers(self.headers())
+            .send_traced()?;
+        if !r.status().is_success() {
+            let tag = request_tag(&r);
+            let status = r.status();
+            let text = r.text().unwrap_or_default();
+            if status == reqwest::StatusCode::NOT_FOUND && !text.contains("Folder not found") {
+                return Err("This server cannot download folders as zip; update the server".to_string());
+            }
+            return Err(format!("{} {}{}", status, text, tag));
+        }
+        save_response(r, dest)
+    }
+
+    /// Step 1 of deleting the own account: confirm the password, get a short-lived token.
+    pub fn request_account_deletion(&self, password: &str) -> Result<String, String> {
+        let url = format!("{}/api/users/me/delete-request", self.base_url.trim_end_matches('/'));
+        let r = self
+            .client()
+            .post(&url)
+            .headers(self.headers())
+            .json(&serde_json::json!({ "password": password }))
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        let body: serde_json::Value = r.json().map_err(|e| e.to_string())?;
+        body["confirmation_token"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| "Missing confirmation_token".to_string())
+    }
+
+    /// Step 2: permanently delete the own account and all files on the server.
+    pub fn confirm_account_deletion(&self, confirmation_token: &str) -> Result<(), String> {
+        let url = format!("{}/api/users/me/delete", self.base_url.trim_end_matches('/'));
+        let r = self
+            .client()
+            .post(&url)
+            .headers(self.headers())
+            .json(&serde_json::json!({ "confirmation_token": confirmation_token }))
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        Ok(())
+    }
+
+    /// Admin: change a user's names and/or admin flag. `None` leaves the field unchanged.
+    pub fn update_user(
+        &self,
+        email: &str,
+        first_name: Option<&str>,
+        last_name: Option<&str>,
+        is_admin: Option<bool>,
+    ) -> Result<serde_json::Value, String> {
+        let encoded = urlencoding::encode(email);
+        let url = format!("{}/api/users/{}", self.base_url.trim_end_matches('/'), encoded);
+        let body = UpdateUserProfileBody { first_name, last_name, is_admin };
+        let r = self
+            .client()
+            .patch(&url)
+            .headers(self.headers())
+            .json(&body)
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(http_error(r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Admin: suspend (`enabled = false`) or re-enable an account. Suspended users cannot log in;
+    /// their files stay on the server.
+    pub fn set_user_enabled(&self, email: &str, enabled: bool) -> Result<serde_json::Value, String> {
+        let encoded = urlencoding::encode(email);
+        let url = format!("{}/api/users/{}/enabled", self.base_url.trim_end_matches('/'), encoded);
+        let r = self
+            .client()
+            .put(&url)
+            .headers(self.headers())
+            .json(&UserEnabledBody { enabled })
+            .header("Content-Type", "application/json")
+            .send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    /// Admin: give a user a new temporary password. The server mails it to the user, or returns
+    /// it as `temp_password` when SMTP is not configured.
+    pub fn admin_reset_password(&self, email: &str) -> Result<serde_json::Value, String> {
+        let encoded = urlencoding::encode(email);
+        let url = format!("{}/api/users/{}/reset-password", self.base_url.trim_end_matches('/'), encoded);
+        let r = self.client().post(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        r.json().map_err(|e| e.to_string())
+    }
+
+    pub fn delete_user(&self, email: &str) -> Result<(), String> {
+        let encoded = urlencoding::encode(email);
+        let url = format!("{}/api/users/{}", self.base_url.trim_end_matches('/'), encoded);
+        let r = self.client().delete(&url).headers(self.headers()).send_traced()?;
+        if !r.status().is_success() {
+            return Err(status_error(&r));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_log_redacts_credentials_in_urls() {
+        let url = reqwest::Url::parse("https://box.example/api/auth/oauth/complete?exchange=s3cret&path=a%2Bb.txt").unwrap();
+        let logged = redact_url(&url);
+        assert!(!logged.contains("s3cret"));
+        assert!(logged.contains("exchange=REDACTED") && logged.contains("path=a%2Bb.txt"));
+        let plain = reqwest::Url::parse("https://box.example/api/files/list").unwrap();
+        assert_eq!(redact_url(&plain), "https://box.example/api/files/list");
+    }
+
+    #[test]
+    fn upload_fails_when_nothing_moves_for_the_idle_timeout() {
+        // Accepts connections but never answers: the small body is taken, then nothing moves
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/files/upload", listener.local_addr().unwrap());
+        let client = client_builder(None).build().unwrap();
+        let started = std::time::Instant::now();
+        let err = send_upload(client.post(&url), std::io::Cursor::new(vec![0u8; 1024]), 1024, Duration::from_secs(1))
+            .unwrap_err();
+        assert!(err.contains("no progress for 1 s"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,81 @@
+//! Optional app lock for shared computers: after `app_lock_minutes` without activity in the
+//! settings window (or on demand), account and admin actions need the account password again.
+//! With the lock on, the app also starts locked. Sync keeps running while locked.
+
+use crate::config;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Last activity while unlocked; None until the first unlock.
+static LAST_ACTIVITY: Mutex<Option<Instant>> = Mutex::new(None);
+static LOCKED_ON_DEMAND: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize)]
+pub struct AppLockStatus {
+    /// Idle minutes before locking; None when the lock is off.
+    pub minutes: Option<u32>,
+    pub locked: bool,
+}
+
+/// Locked when asked to, or when the lock is on and there was no activity for `minutes`.
+fn is_locked_at(now: Instant, last_activity: Option<Instant>, minutes: Option<u32>, on_demand: bool) -> bool {
+    let Some(minutes) = minutes else {
+        return on_demand;
+    };
+    on_demand
+        || match last_activity {
+            Some(at) => now.duration_since(at) >= Duration::from_secs(u64::from(minutes) * 60),
+            None => true,
+        }
+}
+
+pub fn is_locked() -> bool {
+    let last = LAST_ACTIVITY.lock().map(|g| *g).unwrap_or(None);
+    is_locked_at(Instant::now(), last, config::get_app_lock_minutes(), LOCKED_ON_DEMAND.load(Ordering::SeqCst))
+}
+
+pub fn status() -> AppLockStatus {
+    AppLockStatus { minutes: config::get_app_lock_minutes(), locked: is_locked() }
+}
+
+/// The user did something in the settings window. Does not unlock.
+pub fn touch() {
+    if !is_locked() {
+        let _ = LAST_ACTIVITY.lock().map(|mut g| *g = Some(Instant::now()));
+    }
+}
+
+/// Lock now, e.g. before leaving the computer.
+pub fn lock() {
+    LOCKED_ON_DEMAND.store(true, Ordering::SeqCst);
+    log::info!("App locked");
+}
+
+/// Unlock after the caller verified the password.
+pub fn unlock() {
+    let _ = LAST_ACTIVITY.lock().map(|mut g| *g = Some(Instant::now()));
+    LOCKED_ON_DEMAND.store(false, Ordering::SeqCst);
+    log::info!("App unlocked");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_after_idle_minutes_at_start_and_on_demand() {
+        let now = Instant::now();
+        let recent = Some(now - Duration::from_secs(60));
+        let idle = Some(now - Duration::from_secs(10 * 60));
+        // Off: only an explicit lock counts
+        assert!(!is_locked_at(now, None, None, false));
+        assert!(is_locked_at(now, None, None, true));
+        // On: locked at start, after idling and on demand
+        assert!(is_locked_at(now, None, Some(5), false));
+        assert!(!is_locked_at(now, recent, Some(5), false));
+        assert!(is_locked_at(now, idle, Some(5), false));
+        assert!(is_locked_at(now, recent, Some(5), true));
+    }
+}
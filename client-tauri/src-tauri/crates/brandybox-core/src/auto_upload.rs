@@ -0,0 +1,163 @@
+//! Auto-upload: copy new photos, videos and screenshots from user-chosen folders (Screenshots,
+//! phone imports) into a subfolder of the sync folder, from where the normal sync uploads them.
+//!
+//! Files are deduplicated by SHA-256: media already imported once, or already present in the
+//! target subfolder, is not copied again, even under another name or after being deleted from
+//! the box. Sources are never modified. Seen files are remembered by size and mtime in
+//! auto_upload_state.json so unchanged files are not hashed again.
+
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const MEDIA_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "heic", "heif", "gif", "webp", "tif", "tiff", "bmp", "dng", "cr2", "cr3",
+    "nef", "arw", "raf", "orf", "rw2", "mp4", "mov", "m4v", "avi", "mkv", "3gp", "webm",
+];
+
+/// Files modified more recently than this may still be written by the camera import / screenshot tool.
+const MIN_AGE_SECS: u64 = 10;
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct AutoUploadState {
+    /// Source path → (size, mtime secs) when it was last looked at.
+    seen: HashMap<String, (u64, u64)>,
+    /// Hashes of every file imported so far.
+    imported_hashes: HashSet<String>,
+}
+
+fn load_state() -> AutoUploadState {
+    std::fs::read_to_string(config::get_auto_upload_state_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &AutoUploadState) {
+    let content = serde_json::to_string(state).unwrap_or_default();
+    if let Err(e) = config::write_atomic(&config::get_auto_upload_state_path(), content.as_bytes()) {
+        log::warn!("Could not save auto-upload state: {}", e);
+    }
+}
+
+pub fn is_media(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| MEDIA_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// `dir/name`, or `dir/name (1).ext`, `dir/name (2).ext`… when the name is taken.
+fn free_target(dir: &Path, file_name: &str) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let p = Path::new(file_name);
+    let stem = p.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = p.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|i| dir.join(format!("{} ({}){}", stem, i, ext)))
+        .find(|c| !c.exists())
+        .unwrap_or(candidate)
+}
+
+/// Copy new media from `sources` into `target` (created when needed), recording what was seen
+/// in `state`. Returns the number of files copied.
+fn import_from(sources: &[PathBuf], target: &Path, state: &mut AutoUploadState) -> Result<usize, String> {
+    let now = std::time::SystemTime::now();
+    let mut known: HashSet<String> = state.imported_hashes.clone();
+    if target.is_dir() {
+        for e in walkdir::WalkDir::new(target).into_iter().filter_map(|e| e.ok()) {
+            if e.file_type().is_file() {
+                if let Some(h) = crate::sync::compute_file_hash(e.path()) {
+                    known.insert(h);
+                }
+            }
+        }
+    }
+    let mut copied = 0usize;
+    for source in sources {
+        if !source.is_dir() || source.starts_with(target) || target.starts_with(source) {
+            log::warn!("Auto-upload: skipping source {}", source.display());
+            continue;
+        }
+        for e in walkdir::WalkDir::new(source).into_iter().filter_map(|e| e.ok()) {
+            if !e.file_type().is_file() || !is_media(e.path()) {
+                continue;
+            }
+            let Ok(meta) = e.metadata() else { continue };
+            let Ok(mtime) = meta.modified() else { continue };
+            if now.duration_since(mtime).map(|d| d.as_secs() < MIN_AGE_SECS).unwrap_or(true) {
+                continue;
+            }
+            let key = e.path().to_string_lossy().to_string();
+            let stamp = (meta.len(), mtime.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs());
+            if state.seen.get(&key) == Some(&stamp) {
+                continue;
+            }
+            let Some(hash) = crate::sync::compute_file_hash(e.path()) else { continue };
+            if !known.contains(&hash) {
+                std::fs::create_dir_all(target).map_err(|e| e.to_string())?;
+                let name = e.file_name().to_string_lossy().to_string();
+                let dest = free_target(target, &name);
+                if let Err(err) = std::fs::copy(e.path(), &dest) {
+                    log::warn!("Auto-upload {}: {}", e.path().display(), err);
+                    continue;
+                }
+                log::info!("Auto-upload: {} -> {}", e.path().display(), dest.display());
+                copied += 1;
+                known.insert(hash.clone());
+            }
+            state.imported_hashes.insert(hash);
+            state.seen.insert(key, stamp);
+        }
+    }
+    Ok(copied)
+}
+
+/// Run the configured auto-upload into `sync_root`; does nothing when no folders are set.
+pub fn run(sync_root: &Path) -> Result<usize, String> {
+    let sources = config::get_auto_upload_folders();
+    if sources.is_empty() {
+        return Ok(0);
+    }
+    let subfolder = config::get_auto_upload_subfolder();
+    let target = sync_root.join(subfolder.replace('/', std::path::MAIN_SEPARATOR_STR));
+    let mut state = load_state();
+    let result = import_from(&sources, &target, &mut state);
+    save_state(&state);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_new_media_once_and_dedupes_by_hash() {
+        let base = std::env::temp_dir().join(format!("brandybox-test-autoupload-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let (source, target) = (base.join("Screenshots"), base.join("box").join("Camera Uploads"));
+        std::fs::create_dir_all(source.join("2026")).unwrap();
+        let mut state = AutoUploadState::default();
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+        for (name, content) in [("a.png", "one"), ("2026/a.png", "two"), ("copy.PNG", "one"), ("notes.txt", "x")] {
+            let p = source.join(name);
+            std::fs::write(&p, content).unwrap();
+            std::fs::File::options().write(true).open(&p).unwrap().set_modified(old).unwrap();
+        }
+
+        assert_eq!(import_from(std::slice::from_ref(&source), &target, &mut state).unwrap(), 2);
+        assert!(target.join("a.png").exists());
+        assert!(target.join("a (1).png").exists());
+        assert!(!target.join("notes.txt").exists());
+
+        // Deleted from the box: not imported again
+        std::fs::remove_file(target.join("a.png")).unwrap();
+        assert_eq!(import_from(std::slice::from_ref(&source), &target, &mut state).unwrap(), 0);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
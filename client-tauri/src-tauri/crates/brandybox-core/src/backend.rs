@@ -0,0 +1,220 @@
+//! Storage backend used by the sync engine: the real server (`ApiClient`) or an in-memory mock.
+
+use crate::api::{ApiClient, FileItem, HashAlgorithm};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Remote file operations needed by `sync::run_sync`. Paths are relative, with forward slashes.
+pub trait Backend {
+    fn list_files(&self) -> Result<Vec<FileItem>, String>;
+    fn download_file(&self, path: &str) -> Result<Vec<u8>, String>;
+    /// `mtime` (Unix seconds, server clock) is kept as the file's server mtime when supported.
+    fn upload_file_from_path(&self, path: &str, local_path: &Path, mtime: Option<f64>) -> Result<(), String>;
+    fn delete_file(&self, path: &str) -> Result<(), String>;
+    /// Delete several files; an error names the path that failed. Defaults to one call per file.
+    fn delete_files(&self, paths: &[String]) -> Result<(), String> {
+        paths.iter().try_for_each(|p| self.delete_file(p).map_err(|e| format!("{}: {}", p, e)))
+    }
+    /// Metadata for one file, or None when it does not exist.
+    fn stat(&self, path: &str) -> Result<Option<FileItem>, String> {
+        Ok(self.list_files()?.into_iter().find(|f| f.path == path))
+    }
+    /// Move or rename a file on the server. Backends without server-side moves return an error
+    /// and the engine falls back to upload + delete.
+    fn move_file(&self, from: &str, _to: &str) -> Result<(), String> {
+        Err(format!("Move {}: not supported", from))
+    }
+    /// Server clock minus local clock in seconds; None when unknown (treated as in sync).
+    fn clock_offset(&self) -> Option<f64> {
+        None
+    }
+    /// Hash algorithm to compare content with; listings carry its hash where the server has one.
+    fn hash_algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Sha256
+    }
+}
+
+impl Backend for ApiClient {
+    fn list_files(&self) -> Result<Vec<FileItem>, String> {
+        ApiClient::list_files(self)
+    }
+
+    fn download_file(&self, path: &str) -> Result<Vec<u8>, String> {
+        ApiClient::download_file(self, path)
+    }
+
+    fn upload_file_from_path(&self, path: &str, local_path: &Path, mtime: Option<f64>) -> Result<(), String> {
+        ApiClient::upload_file_from_path(self, path, local_path, mtime)
+    }
+
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        ApiClient::delete_file(self, path)
+    }
+
+    fn delete_files(&self, paths: &[String]) -> Result<(), String> {
+        ApiClient::delete_files(self, paths)
+    }
+
+    fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        ApiClient::move_file(self, from, to)
+    }
+
+    fn clock_offset(&self) -> Option<f64> {
+        self.server_time_offset().map_err(|e| log::debug!("Could not read server time: {}", e)).ok()
+    }
+
+    fn hash_algorithm(&self) -> HashAlgorithm {
+        match self.get_capabilities() {
+            Ok(caps) => caps.hash_algorithm(),
+            Err(e) => {
+                log::debug!("Could not read server capabilities: {}", e);
+                HashAlgorithm::Sha256
+            }
+        }
+    }
+}
+
+struct MemoryFile {
+    content: Vec<u8>,
+    mtime: f64,
+}
+
+/// In-memory backend for tests and dry runs. Behaves like the server: uploads keep the given
+/// mtime (else the current time), listings include the SHA-256 hash, missing files yield "404 Not Found" and
+/// uploads beyond the optional quota yield "507 Insufficient Storage". A clock offset simulates
+/// a server whose clock differs from the local one.
+#[derive(Default)]
+pub struct MemoryBackend {
+    files: Mutex<HashMap<String, MemoryFile>>,
+    quota_bytes: Option<u64>,
+    clock_offset: f64,
+    blake3: bool,
+}
+
+fn now_secs() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Backend that rejects uploads once the stored bytes would exceed `quota_bytes`.
+    pub fn with_quota(quota_bytes: u64) -> Self {
+        Self { quota_bytes: Some(quota_bytes), ..Self::default() }
+    }
+
+    /// Backend whose clock runs `offset_secs` ahead of the local clock (negative: behind).
+    pub fn with_clock_offset(offset_secs: f64) -> Self {
+        Self { clock_offset: offset_secs, ..Self::default() }
+    }
+
+    /// Backend that also lists BLAKE3 hashes, like a server with the `blake3` capability.
+    pub fn with_blake3() -> Self {
+        Self { blake3: true, ..Self::default() }
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        self.files
+            .lock()
+            .map(|f| f.values().map(|m| m.content.len() as u64).sum())
+            .unwrap_or(0)
+    }
+
+    /// Put a file on the "server" directly (e.g. uploaded by another device).
+    pub fn insert(&self, path: &str, content: &[u8], mtime: f64) {
+        if let Ok(mut files) = self.files.lock() {
+            files.insert(path.to_string(), MemoryFile { content: content.to_vec(), mtime });
+        }
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.lock().map(|f| f.contains_key(path)).unwrap_or(false)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.lock().map(|f| f.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn list_files(&self) -> Result<Vec<FileItem>, String> {
+        let files = self.files.lock().map_err(|e| e.to_string())?;
+        let mut out: Vec<FileItem> = files
+            .iter()
+            .map(|(path, f)| FileItem {
+                path: path.clone(),
+                mtime: f.mtime,
+                hash: Some(format!("{:x}", Sha256::digest(&f.content))),
+                size: Some(f.content.len() as u64),
+                blake3: self.blake3.then(|| blake3::hash(&f.content).to_hex().to_string()),
+            })
+            .collect();
+        out.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(out)
+    }
+
+    fn download_file(&self, path: &str) -> Result<Vec<u8>, String> {
+        let files = self.files.lock().map_err(|e| e.to_string())?;
+        files
+            .get(path)
+            .map(|f| f.content.clone())
+            .ok_or_else(|| "404 Not Found".to_string())
+    }
+
+    fn upload_file_from_path(&self, path: &str, local_path: &Path, mtime: Option<f64>) -> Result<(), String> {
+        let content = std::fs::read(local_path).map_err(|e| e.to_string())?;
+        if let Some(quota) = self.quota_bytes {
+            let old = self
+                .files
+                .lock()
+                .map(|f| f.get(path).map(|m| m.content.len() as u64).unwrap_or(0))
+                .unwrap_or(0);
+            if self.used_bytes() - old + content.len() as u64 > quota {
+                return Err("507 Insufficient Storage: Your storage limit has been reached".to_string());
+            }
+        }
+        self.insert(path, &content, mtime.unwrap_or_else(|| now_secs() + self.clock_offset));
+        Ok(())
+    }
+
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        let mut files = self.files.lock().map_err(|e| e.to_string())?;
+        files
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| "404 Not Found".to_string())
+    }
+
+    fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let mut files = self.files.lock().map_err(|e| e.to_string())?;
+        if from != to && files.contains_key(to) {
+            return Err("409 Conflict".to_string());
+        }
+        let file = files.remove(from).ok_or_else(|| "404 Not Found".to_string())?;
+        files.insert(to.to_string(), file);
+        Ok(())
+    }
+
+    fn clock_offset(&self) -> Option<f64> {
+        Some(self.clock_offset)
+    }
+
+    fn hash_algorithm(&self) -> HashAlgorithm {
+        if self.blake3 {
+            HashAlgorithm::Blake3
+        } else {
+            HashAlgorithm::Sha256
+        }
+    }
+}
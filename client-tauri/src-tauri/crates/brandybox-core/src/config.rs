@@ -0,0 +1,1200 @@
+//! Client configuration: config dir, sync folder, base URL, autostart.
+//! Matches Python client paths and config.json layout (or `data/` beside the binary in portable mode).
+
+use crate::{metadata_crypto, secure_delete};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_REMOTE_BASE_URL: &str = "https://brandybox.brandstaetter.rocks";
+const CONFIG_FILENAME: &str = "config.json";
+const CONFIG_BACKUP_FILENAME: &str = "config.json.bak";
+const SYNC_STATE_FILENAME: &str = "sync_state.json";
+const SYNC_STATE_BACKUP_FILENAME: &str = "sync_state.json.bak";
+const ACTIVITY_FILENAME: &str = "recent_activity.json";
+const STATS_FILENAME: &str = "sync_stats.json";
+const THUMBNAIL_CACHE_DIRNAME: &str = "thumbnails";
+const AUTO_UPLOAD_STATE_FILENAME: &str = "auto_upload_state.json";
+const LOCAL_INDEX_FILENAME: &str = "local_index.json";
+const DELETE_SNAPSHOTS_DIRNAME: &str = "delete_snapshots";
+/// Default size cap for all delete snapshots together (2 GB).
+const DEFAULT_DELETE_SNAPSHOT_LIMIT_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+const DEFAULT_AUTO_UPLOAD_SUBFOLDER: &str = "Camera Uploads";
+const INSTANCE_LOCK_FILENAME: &str = "instance.lock";
+const PORTABLE_FLAG_FILENAME: &str = "portable.flag";
+const PORTABLE_DATA_DIRNAME: &str = "data";
+const PORTABLE_ARG: &str = "--portable";
+/// Launch argument for "start in tray"; `--background` is accepted as an alias.
+pub const MINIMIZED_ARG: &str = "--minimized";
+pub const BACKGROUND_ARG: &str = "--background";
+/// Launch argument asking the (running) instance to start a sync; meant for OS keyboard shortcuts.
+pub const SYNC_NOW_ARG: &str = "--sync-now";
+/// Launch argument for download-only (kiosk) mode, regardless of the `download_only` setting.
+pub const DOWNLOAD_ONLY_ARG: &str = "--download-only";
+/// Global hotkeys registered at startup unless the settings turn them off.
+pub const DEFAULT_SHORTCUT_SHOW_WINDOW: &str = "CommandOrControl+Alt+B";
+pub const DEFAULT_SHORTCUT_SYNC_NOW: &str = "CommandOrControl+Alt+S";
+
+pub fn expand_tilde(path: &str) -> PathBuf {
+    let s = path.trim();
+    if s.starts_with('~') {
+        let rest = s.trim_start_matches('~').trim_start_matches('/');
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(s)
+}
+
+/// Portable install: config, sync state and logs live in `data/` beside the executable.
+/// Enabled by a `portable.flag` file next to the binary or the `--portable` argument.
+fn portable_data_dir() -> Option<&'static PathBuf> {
+    static PORTABLE_DIR: std::sync::OnceLock<Option<PathBuf>> = std::sync::OnceLock::new();
+    PORTABLE_DIR
+        .get_or_init(|| {
+            let exe = std::env::current_exe().ok()?;
+            let exe_dir = exe.parent()?;
+            let by_arg = std::env::args().any(|a| a == PORTABLE_ARG);
+            if by_arg || exe_dir.join(PORTABLE_FLAG_FILENAME).exists() {
+                Some(exe_dir.join(PORTABLE_DATA_DIRNAME))
+            } else {
+                None
+            }
+        })
+        .as_ref()
+}
+
+pub fn is_portable() -> bool {
+    portable_data_dir().is_some()
+}
+
+fn config_dir() -> PathBuf {
+    if let Ok(override_dir) = std::env::var("BRANDYBOX_CONFIG_DIR") {
+        let s = override_dir.trim();
+        if !s.is_empty() {
+            return expand_tilde(s);
+        }
+    }
+    if let Some(dir) = portable_data_dir() {
+        return dir.clone();
+    }
+    #[cfg(windows)]
+    {
+        let appdata = std::env::var("APPDATA").unwrap_or_else(|_| std::env::var("USERPROFILE").unwrap_or_default());
+        PathBuf::from(appdata).join("BrandyBox")
+    }
+    #[cfg(not(windows))]
+    {
+        if let Some(xdg) = std::env::var_os("XDG_CONFIG_HOME") {
+            PathBuf::from(xdg).join("brandybox")
+        } else {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".config")
+                .join("brandybox")
+        }
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct ConfigFile {
+    sync_folder: Option<String>,
+    autostart: Option<bool>,
+    base_url_mode: Option<String>,
+    manual_base_url: Option<String>,
+    settings_window_geometry: Option<String>,
+    /// Plaintext app token written by older versions; moved to the keyring on first read.
+    app_token: Option<String>,
+    /// Set once the one-time import from the Python client has run.
+    legacy_import_done: Option<bool>,
+    /// Autostart launches stay in the tray instead of opening the settings window.
+    start_minimized: Option<bool>,
+    /// Desktop notifications from the Rust side (default on).
+    notifications_enabled: Option<bool>,
+    /// When notifications are held back: quiet hours and fullscreen apps.
+    notification_schedule: Option<NotificationSchedule>,
+    /// Quiet hours and fullscreen muting as written by older versions; read until the schedule
+    /// is saved, then left out of the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quiet_hours_start: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quiet_hours_end: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mute_notifications_fullscreen: Option<bool>,
+    /// File log level: off, error, warn, info, debug, trace.
+    log_level: Option<String>,
+    /// Log every API request (method, URL, status, duration, sizes; no tokens or bodies).
+    http_debug: Option<bool>,
+    /// Random id identifying this install to the server's device list.
+    device_id: Option<String>,
+    /// Storage usage percentages that trigger a warning (default 80 and 95).
+    quota_warning_thresholds: Option<Vec<u32>>,
+    /// Linux: show sync status as file manager emblems (gio metadata). Off by default.
+    file_manager_emblems: Option<bool>,
+    /// Folders (Screenshots, phone imports) whose new media is copied into the sync folder.
+    auto_upload_folders: Option<Vec<String>>,
+    /// Subfolder of the sync folder that receives auto-uploaded media.
+    auto_upload_subfolder: Option<String>,
+    /// Local time windows during which background sync does not run.
+    sync_pause_windows: Option<Vec<SyncWindow>>,
+    /// Sync hidden files and folders (names starting with "."; `.git` never). Default on.
+    sync_hidden_files: Option<bool>,
+    /// Files larger than this are not synced (None: no limit).
+    max_file_size_bytes: Option<u64>,
+    /// Paths (relative to the sync folder) synced regardless of `max_file_size_bytes`.
+    large_file_allowlist: Option<Vec<String>>,
+    /// Files and folders (relative to the sync folder) excluded from sync by the user.
+    ignore_paths: Option<Vec<String>>,
+    /// Copy files into a dated snapshot folder before sync deletes them locally. Off by default.
+    delete_snapshots: Option<bool>,
+    /// Size cap for all snapshots together; the oldest are removed beyond it.
+    delete_snapshot_limit_bytes: Option<u64>,
+    /// Overwrite files this many times before sync deletes them locally (0 or unset: off).
+    secure_delete_passes: Option<u32>,
+    /// Set by `start_restore`: sync download-only until one cycle has completed.
+    restore_pending: Option<bool>,
+    /// Kiosk mode: mirror the box into the sync folder, never change anything on the server.
+    download_only: Option<bool>,
+    /// Tray icon variant: "system" (follow the taskbar theme), "light" or "dark".
+    tray_icon_theme: Option<String>,
+    /// Language of notifications and messages: "system", or one of `i18n::LANGUAGES`.
+    language: Option<String>,
+    /// Local port of the metrics HTTP endpoint (127.0.0.1 only). Off when unset.
+    metrics_port: Option<u16>,
+    /// Lock account and admin settings after this many idle minutes. Off when unset.
+    app_lock_minutes: Option<u32>,
+    /// Global hotkey that shows the settings window, e.g. "CommandOrControl+Alt+B". Empty: off.
+    shortcut_show_window: Option<String>,
+    /// Global hotkey that starts a sync. Empty: off.
+    shortcut_sync_now: Option<String>,
+}
+
+/// A recurring local time window, e.g. 09:00–17:00 on weekdays. `end` before `start` wraps
+/// past midnight. `days` are ISO weekdays (1 = Monday … 7 = Sunday); empty means every day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncWindow {
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub days: Vec<u32>,
+}
+
+/// When notifications are muted. Quiet hours use the `SyncWindow` format of the sync pause
+/// windows (and the same check); muting only silences notifications, sync keeps running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationSchedule {
+    #[serde(default)]
+    pub quiet_hours: Option<SyncWindow>,
+    /// Hold back notifications while a fullscreen app is in front.
+    #[serde(default = "default_mute_fullscreen")]
+    pub mute_fullscreen: bool,
+}
+
+fn default_mute_fullscreen() -> bool {
+    true
+}
+
+impl Default for NotificationSchedule {
+    fn default() -> Self {
+        Self { quiet_hours: None, mute_fullscreen: default_mute_fullscreen() }
+    }
+}
+
+fn ensure_config_dir() -> PathBuf {
+    let d = config_dir();
+    let _ = std::fs::create_dir_all(&d);
+    d
+}
+
+/// Write `content` to `path` atomically: temp file in the same dir, fsync, then rename over.
+/// A crash mid-write leaves either the old or the new file, never a truncated one.
+pub fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = path.with_file_name(format!("{}.tmp", file_name));
+    {
+        let mut f = std::fs::File::create(&tmp)?;
+        f.write_all(content)?;
+        f.sync_all()?;
+    }
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+fn config_backup_path() -> PathBuf {
+    config_dir().join(CONFIG_BACKUP_FILENAME)
+}
+
+fn parse_config_file(path: &Path) -> Option<ConfigFile> {
+    let s = metadata_crypto::read(path)?;
+    serde_json::from_str(&s).ok()
+}
+
+/// config.json as last read or written, shared by all threads. `write_config` holds the write
+/// lock for its whole read-modify-write, so concurrent updates of different settings (background
+/// sync, window events, UI commands) cannot drop each other. Reloaded when the file changes on
+/// disk (edited by hand) or the config directory changes.
+static CONFIG: std::sync::RwLock<Option<CachedConfig>> = std::sync::RwLock::new(None);
+
+struct CachedConfig {
+    path: PathBuf,
+    /// Modification time and size of the file it was read from or written to; None: no file.
+    stamp: Option<(std::time::SystemTime, u64)>,
+    config: ConfigFile,
+}
+
+fn file_stamp(path: &Path) -> Option<(std::time::SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// The cached config when it is still current for `path`.
+fn cached(cache: &Option<CachedConfig>, path: &Path) -> Option<ConfigFile> {
+    cache
+        .as_ref()
+        .filter(|c| c.path == path && c.stamp == file_stamp(path))
+        .map(|c| c.config.clone())
+}
+
+fn read_config() -> ConfigFile {
+    let path = config_dir().join(CONFIG_FILENAME);
+    if let Some(cfg) = CONFIG.read().ok().and_then(|c| cached(&c, &path)) {
+        return cfg;
+    }
+    let mut cache = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(cfg) = cached(&cache, &path) {
+        return cfg;
+    }
+    let config = load_config(&path);
+    *cache = Some(CachedConfig { stamp: file_stamp(&path), path, config: config.clone() });
+    config
+}
+
+fn load_config(path: &Path) -> ConfigFile {
+    if !path.exists() {
+        return ConfigFile::default();
+    }
+    if let Some(cfg) = parse_config_file(path) {
+        return cfg;
+    }
+    // Corrupt config.json: fall back to the last good copy and restore it
+    match parse_config_file(&config_backup_path()) {
+        Some(cfg) => {
+            log::warn!("config.json unreadable; restored settings from {}", CONFIG_BACKUP_FILENAME);
+            if let Ok(json) = serde_json::to_string_pretty(&cfg) {
+                let _ = metadata_crypto::write(path, &json);
+            }
+            cfg
+        }
+        None => {
+            log::warn!("config.json unreadable and no usable backup; using defaults");
+            ConfigFile::default()
+        }
+    }
+}
+
+/// Apply `update` to the current config and save it. `update` must not call other config
+/// functions (the config lock is held).
+fn write_config(update: impl FnOnce(&mut ConfigFile)) {
+    let path = ensure_config_dir().join(CONFIG_FILENAME);
+    let mut cache = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = cached(&cache, &path).unwrap_or_else(|| load_config(&path));
+    update(&mut cfg);
+    // Keep the current (known-good) file as backup before replacing it
+    if parse_config_file(&path).is_some() {
+        let _ = std::fs::copy(&path, config_backup_path());
+    }
+    let json = serde_json::to_string_pretty(&cfg).unwrap_or_else(|_| "{}".to_string());
+    match metadata_crypto::write(&path, &json) {
+        Ok(()) => *cache = Some(CachedConfig { stamp: file_stamp(&path), path, config: cfg }),
+        Err(e) => {
+            log::warn!("Failed to write config.json: {}", e);
+            *cache = None;
+        }
+    }
+}
+
+/// Self-test: config.json parses (when present) and the config directory takes a file written
+/// the same way as config.json. Returns a short description of what was checked.
+pub fn check_config() -> Result<String, String> {
+    let path = config_dir().join(CONFIG_FILENAME);
+    if path.exists() && parse_config_file(&path).is_none() {
+        return Err(format!("{} is not valid JSON", path.display()));
+    }
+    let probe = ensure_config_dir().join("self_test.tmp");
+    let content = chrono::Utc::now().to_rfc3339();
+    write_atomic(&probe, content.as_bytes()).map_err(|e| format!("Cannot write to {}: {}", config_dir().display(), e))?;
+    let read = std::fs::read_to_string(&probe);
+    let _ = std::fs::remove_file(&probe);
+    match read {
+        Ok(r) if r == content => Ok(format!("{} readable and writable", config_dir().display())),
+        Ok(_) => Err(format!("{} returned different content than written", probe.display())),
+        Err(e) => Err(format!("Cannot read back {}: {}", probe.display(), e)),
+    }
+}
+
+/// Config directory path (for E2E credential file, etc.). Does not create the dir.
+pub fn get_config_dir() -> PathBuf {
+    config_dir()
+}
+
+#[allow(dead_code)]
+pub fn get_config_path() -> PathBuf {
+    ensure_config_dir();
+    config_dir().join(CONFIG_FILENAME)
+}
+
+pub fn get_instance_lock_path() -> PathBuf {
+    config_dir().join(INSTANCE_LOCK_FILENAME)
+}
+
+pub fn get_sync_state_path() -> PathBuf {
+    ensure_config_dir();
+    config_dir().join(SYNC_STATE_FILENAME)
+}
+
+/// Files that `metadata_crypto` encrypts when metadata encryption is on.
+pub fn metadata_files() -> Vec<PathBuf> {
+    let dir = config_dir();
+    [
+        CONFIG_FILENAME,
+        CONFIG_BACKUP_FILENAME,
+        SYNC_STATE_FILENAME,
+        SYNC_STATE_BACKUP_FILENAME,
+        LOCAL_INDEX_FILENAME,
+        ACTIVITY_FILENAME,
+    ]
+    .iter()
+    .map(|name| dir.join(name))
+    .collect()
+}
+
+/// Last sync state that parsed, loaded when sync_state.json is corrupt.
+pub fn get_sync_state_backup_path() -> PathBuf {
+    ensure_config_dir();
+    config_dir().join(SYNC_STATE_BACKUP_FILENAME)
+}
+
+pub fn get_activity_path() -> PathBuf {
+    ensure_config_dir();
+    config_dir().join(ACTIVITY_FILENAME)
+}
+
+pub fn get_stats_path() -> PathBuf {
+    ensure_config_dir();
+    config_dir().join(STATS_FILENAME)
+}
+
+pub fn get_thumbnail_cache_dir() -> PathBuf {
+    let d = ensure_config_dir().join(THUMBNAIL_CACHE_DIRNAME);
+    let _ = std::fs::create_dir_all(&d);
+    d
+}
+
+pub fn get_delete_snapshots_dir() -> PathBuf {
+    ensure_config_dir().join(DELETE_SNAPSHOTS_DIRNAME)
+}
+
+pub fn get_auto_upload_state_path() -> PathBuf {
+    ensure_config_dir();
+    config_dir().join(AUTO_UPLOAD_STATE_FILENAME)
+}
+
+pub fn get_local_index_path() -> PathBuf {
+    ensure_config_dir();
+    config_dir().join(LOCAL_INDEX_FILENAME)
+}
+
+pub fn get_default_sync_folder() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join("brandyBox")
+}
+
+pub fn user_has_set_sync_folder() -> bool {
+    read_config().sync_folder.map(|s| !s.is_empty()).unwrap_or(false)
+}
+
+pub fn get_sync_folder_path() -> PathBuf {
+    let raw = read_config().sync_folder.filter(|s| !s.is_empty());
+    match raw {
+        Some(s) => expand_tilde(&s),
+        None => get_default_sync_folder(),
+    }
+}
+
+pub fn set_sync_folder_path(folder: PathBuf) {
+    let s = folder.to_string_lossy().to_string();
+    write_config(|c| c.sync_folder = Some(s));
+}
+
+pub fn get_autostart() -> bool {
+    read_config().autostart.unwrap_or(false)
+}
+
+pub fn set_autostart(enabled: bool) {
+    write_config(|c| c.autostart = Some(enabled));
+    apply_autostart_platform(enabled);
+}
+
+pub fn get_start_minimized() -> bool {
+    read_config().start_minimized.unwrap_or(true)
+}
+
+pub fn set_start_minimized(enabled: bool) {
+    write_config(|c| c.start_minimized = Some(enabled));
+    if get_autostart() {
+        apply_autostart_platform(true);
+    }
+}
+
+pub fn get_notifications_enabled() -> bool {
+    read_config().notifications_enabled.unwrap_or(true)
+}
+
+pub fn set_notifications_enabled(enabled: bool) {
+    write_config(|c| c.notifications_enabled = Some(enabled));
+}
+
+/// Notification schedule; older configs' whole-hour quiet hours and fullscreen flag are mapped
+/// onto it.
+pub fn get_notification_schedule() -> NotificationSchedule {
+    let c = read_config();
+    if let Some(schedule) = c.notification_schedule {
+        return schedule;
+    }
+    let quiet_hours = match (c.quiet_hours_start, c.quiet_hours_end) {
+        (Some(start), Some(end)) if start < 24 && end < 24 && start != end => Some(SyncWindow {
+            start: format!("{:02}:00", start),
+            end: format!("{:02}:00", end),
+            days: Vec::new(),
+        }),
+        _ => None,
+    };
+    NotificationSchedule {
+        quiet_hours,
+        mute_fullscreen: c.mute_notifications_fullscreen.unwrap_or_else(default_mute_fullscreen),
+    }
+}
+
+pub fn set_notification_schedule(schedule: NotificationSchedule) {
+    write_config(|c| {
+        c.notification_schedule = Some(schedule);
+        c.quiet_hours_start = None;
+        c.quiet_hours_end = None;
+        c.mute_notifications_fullscreen = None;
+    });
+}
+
+/// Auto-upload source folders (`~` expanded); empty when the feature is off.
+pub fn get_auto_upload_folders() -> Vec<PathBuf> {
+    read_config()
+        .auto_upload_folders
+        .unwrap_or_default()
+        .iter()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| expand_tilde(s))
+        .collect()
+}
+
+pub fn get_auto_upload_subfolder() -> String {
+    read_config()
+        .auto_upload_subfolder
+        .map(|s| s.trim().trim_matches('/').to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_AUTO_UPLOAD_SUBFOLDER.to_string())
+}
+
+pub fn set_auto_upload(folders: Vec<String>, subfolder: Option<String>) {
+    write_config(|c| {
+        c.auto_upload_folders = Some(folders);
+        c.auto_upload_subfolder = subfolder;
+    });
+}
+
+pub fn get_file_manager_emblems() -> bool {
+    read_config().file_manager_emblems.unwrap_or(false)
+}
+
+pub fn set_file_manager_emblems(enabled: bool) {
+    write_config(|c| c.file_manager_emblems = Some(enabled));
+}
+
+pub fn get_sync_hidden_files() -> bool {
+    read_config().sync_hidden_files.unwrap_or(true)
+}
+
+pub fn set_sync_hidden_files(enabled: bool) {
+    write_config(|c| c.sync_hidden_files = Some(enabled));
+}
+
+/// Size limit for synced files; None (or 0 in config.json) means no limit.
+pub fn get_max_file_size_bytes() -> Option<u64> {
+    read_config().max_file_size_bytes.filter(|b| *b > 0)
+}
+
+pub fn set_max_file_size_bytes(limit: Option<u64>) {
+    write_config(|c| c.max_file_size_bytes = limit);
+}
+
+pub fn get_large_file_allowlist() -> Vec<String> {
+    read_config().large_file_allowlist.unwrap_or_default()
+}
+
+/// Add or remove one path from the files exempt from the size limit.
+pub fn set_large_file_allowed(path: &str, allowed: bool) {
+    write_config(|c| {
+        let list = c.large_file_allowlist.get_or_insert_with(Vec::new);
+        list.retain(|p| p != path);
+        if allowed {
+            list.push(path.to_string());
+            list.sort();
+        }
+    });
+}
+
+pub fn get_delete_snapshots_enabled() -> bool {
+    read_config().delete_snapshots.unwrap_or(false)
+}
+
+pub fn get_delete_snapshot_limit_bytes() -> u64 {
+    read_config().delete_snapshot_limit_bytes.filter(|b| *b > 0).unwrap_or(DEFAULT_DELETE_SNAPSHOT_LIMIT_BYTES)
+}
+
+/// Turn delete snapshots on or off; `limit_bytes` None keeps the current cap.
+pub fn set_delete_snapshots(enabled: bool, limit_bytes: Option<u64>) {
+    write_config(|c| {
+        c.delete_snapshots = Some(enabled);
+        if let Some(limit) = limit_bytes.filter(|b| *b > 0) {
+            c.delete_snapshot_limit_bytes = Some(limit);
+        }
+    });
+}
+
+/// Overwrite passes for local deletes; 0 when secure delete is off.
+pub fn get_secure_delete_passes() -> u32 {
+    read_config().secure_delete_passes.unwrap_or(0).min(secure_delete::MAX_PASSES)
+}
+
+pub fn set_secure_delete_passes(passes: u32) {
+    write_config(|c| c.secure_delete_passes = Some(passes.min(secure_delete::MAX_PASSES)).filter(|p| *p > 0));
+}
+
+pub fn get_restore_pending() -> bool {
+    read_config().restore_pending.unwrap_or(false)
+}
+
+pub fn set_restore_pending(pending: bool) {
+    write_config(|c| c.restore_pending = Some(pending));
+}
+
+/// Started with `--download-only`: kiosk mode cannot be turned off in Settings.
+pub fn download_only_forced() -> bool {
+    std::env::args().any(|a| a == DOWNLOAD_ONLY_ARG)
+}
+
+pub fn get_download_only() -> bool {
+    download_only_forced() || read_config().download_only.unwrap_or(false)
+}
+
+pub fn set_download_only(enabled: bool) {
+    write_config(|c| c.download_only = Some(enabled));
+}
+
+pub fn get_ignore_paths() -> Vec<String> {
+    read_config().ignore_paths.unwrap_or_default()
+}
+
+/// Add or remove one file or folder from the paths excluded from sync.
+pub fn set_path_ignored(path: &str, ignored: bool) {
+    write_config(|c| {
+        let list = c.ignore_paths.get_or_insert_with(Vec::new);
+        list.retain(|p| p != path);
+        if ignored {
+            list.push(path.to_string());
+            list.sort();
+        }
+    });
+}
+
+/// Windows during which background sync is paused (manual "Sync now" still runs).
+pub fn get_sync_pause_windows() -> Vec<SyncWindow> {
+    read_config().sync_pause_windows.unwrap_or_default()
+}
+
+pub fn set_sync_pause_windows(windows: Vec<SyncWindow>) {
+    write_config(|c| c.sync_pause_windows = Some(windows));
+}
+
+const DEFAULT_QUOTA_WARNING_THRESHOLDS: [u32; 2] = [80, 95];
+
+/// Quota warning thresholds in percent, ascending, each between 1 and 100.
+pub fn get_quota_warning_thresholds() -> Vec<u32> {
+    let mut t: Vec<u32> = read_config()
+        .quota_warning_thresholds
+        .unwrap_or_else(|| DEFAULT_QUOTA_WARNING_THRESHOLDS.to_vec())
+        .into_iter()
+        .filter(|p| (1..=100).contains(p))
+        .collect();
+    t.sort_unstable();
+    t.dedup();
+    t
+}
+
+pub fn set_quota_warning_thresholds(thresholds: Vec<u32>) {
+    write_config(|c| c.quota_warning_thresholds = Some(thresholds));
+}
+
+pub fn get_log_level() -> String {
+    read_config()
+        .log_level
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "info".to_string())
+}
+
+pub fn set_log_level(level: String) {
+    write_config(|c| c.log_level = Some(level));
+}
+
+/// HTTP trace logging, on via the `http_debug` setting or the `BRANDYBOX_HTTP_DEBUG` variable.
+pub fn get_http_debug() -> bool {
+    let by_env = std::env::var("BRANDYBOX_HTTP_DEBUG").is_ok_and(|v| !matches!(v.trim(), "" | "0" | "false"));
+    by_env || read_config().http_debug.unwrap_or(false)
+}
+
+pub fn set_http_debug(enabled: bool) {
+    write_config(|c| c.http_debug = Some(enabled));
+}
+
+/// Port of the local metrics endpoint; None when it is off.
+pub fn get_metrics_port() -> Option<u16> {
+    read_config().metrics_port.filter(|p| *p != 0)
+}
+
+pub fn set_metrics_port(port: Option<u16>) {
+    write_config(|c| c.metrics_port = port);
+}
+
+/// Idle minutes before the settings window locks; None when the app lock is off.
+pub fn get_app_lock_minutes() -> Option<u32> {
+    read_config().app_lock_minutes.filter(|m| *m > 0)
+}
+
+pub fn set_app_lock_minutes(minutes: Option<u32>) {
+    write_config(|c| c.app_lock_minutes = minutes);
+}
+
+/// Global hotkey that shows the settings window; None when it is turned off.
+pub fn get_shortcut_show_window() -> Option<String> {
+    shortcut_setting(read_config().shortcut_show_window, DEFAULT_SHORTCUT_SHOW_WINDOW)
+}
+
+/// Global hotkey that starts a sync; None when it is turned off.
+pub fn get_shortcut_sync_now() -> Option<String> {
+    shortcut_setting(read_config().shortcut_sync_now, DEFAULT_SHORTCUT_SYNC_NOW)
+}
+
+/// Save both hotkeys; None or an empty string turns one off.
+pub fn set_shortcuts(show_window: Option<String>, sync_now: Option<String>) {
+    write_config(|c| {
+        c.shortcut_show_window = Some(show_window.map(|s| s.trim().to_string()).unwrap_or_default());
+        c.shortcut_sync_now = Some(sync_now.map(|s| s.trim().to_string()).unwrap_or_default());
+    });
+}
+
+/// Unset falls back to `default`; saved empty means off.
+fn shortcut_setting(saved: Option<String>, default: &str) -> Option<String> {
+    match saved {
+        None => Some(default.to_string()),
+        Some(s) if s.trim().is_empty() => None,
+        Some(s) => Some(s.trim().to_string()),
+    }
+}
+
+pub fn get_language() -> String {
+    read_config()
+        .language
+        .filter(|l| !l.trim().is_empty())
+        .unwrap_or_else(|| "system".to_string())
+}
+
+pub fn set_language(language: String) {
+    write_config(|c| c.language = Some(language));
+}
+
+pub const TRAY_ICON_THEMES: &[&str] = &["system", "light", "dark"];
+
+pub fn get_tray_icon_theme() -> String {
+    read_config()
+        .tray_icon_theme
+        .filter(|t| TRAY_ICON_THEMES.contains(&t.as_str()))
+        .unwrap_or_else(|| "system".to_string())
+}
+
+pub fn set_tray_icon_theme(theme: String) {
+    write_config(|c| c.tray_icon_theme = Some(theme));
+}
+
+/// Windows: "light" or "dark" for the taskbar, which can differ from the app theme (dark taskbar
+/// with light apps is the default). None elsewhere; there the system theme decides.
+pub fn taskbar_theme() -> Option<&'static str> {
+    #[cfg(windows)]
+    {
+        use winreg::enums::HKEY_CURRENT_USER;
+        let key = winreg::RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey(r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize")
+            .ok()?;
+        let light: u32 = key.get_value("SystemUsesLightTheme").ok()?;
+        Some(if light == 0 { "dark" } else { "light" })
+    }
+    #[cfg(not(windows))]
+    None
+}
+
+pub fn get_base_url_mode() -> String {
+    read_config()
+        .base_url_mode
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "automatic".to_string())
+}
+
+pub fn set_base_url_mode(mode: String) {
+    write_config(|c| c.base_url_mode = Some(mode));
+}
+
+pub fn get_manual_base_url() -> String {
+    read_config()
+        .manual_base_url
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| DEFAULT_REMOTE_BASE_URL.to_string())
+}
+
+pub fn set_manual_base_url(url: String) {
+    write_config(|c| c.manual_base_url = Some(url.trim().to_string()));
+}
+
+/// Gets saved settings window geometry as "x,y,width,height" (physical pixels), or None.
+#[allow(dead_code)]
+pub fn get_settings_window_geometry() -> Option<String> {
+    read_config()
+        .settings_window_geometry
+        .filter(|s| !s.trim().is_empty())
+}
+
+/// Saves settings window geometry string "x,y,width,height" (physical pixels).
+#[allow(dead_code)]
+pub fn set_settings_window_geometry(geometry: String) {
+    let s = geometry.trim().to_string();
+    write_config(|c| c.settings_window_geometry = if s.is_empty() { None } else { Some(s) });
+}
+
+/// Remove and return an app token that older versions stored in plaintext here. New tokens only
+/// go to the keyring.
+pub fn take_legacy_app_token() -> Option<String> {
+    read_config().app_token?;
+    let mut token = None;
+    write_config(|c| token = c.app_token.take());
+    token.filter(|s| !s.trim().is_empty())
+}
+
+/// This install's device id, created and saved on first use.
+pub fn get_device_id() -> String {
+    if let Some(id) = read_config().device_id.filter(|s| !s.trim().is_empty()) {
+        return id;
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    write_config(|c| c.device_id = Some(id.clone()));
+    id
+}
+
+pub fn get_legacy_import_done() -> bool {
+    read_config().legacy_import_done.unwrap_or(false)
+}
+
+pub fn set_legacy_import_done() {
+    write_config(|c| c.legacy_import_done = Some(true));
+}
+
+/// Config directories used by the Python client where they differ from ours.
+/// Only macOS differs: Python used Application Support, we use XDG-style ~/.config/brandybox.
+pub fn legacy_config_dirs() -> Vec<PathBuf> {
+    let mut dirs_out = Vec::new();
+    if cfg!(target_os = "macos") {
+        if let Some(home) = dirs::home_dir() {
+            let legacy = home.join("Library/Application Support/BrandyBox");
+            if legacy != config_dir() {
+                dirs_out.push(legacy);
+            }
+        }
+    }
+    dirs_out
+}
+
+/// True if the autostart entry content launches the Python client (venv or `python -m brandybox.main`).
+fn is_python_autostart(content: &str) -> bool {
+    content.contains("brandybox.main") || content.contains("python")
+}
+
+/// Replace an autostart entry written by the Python client: rewrite it for this binary when
+/// autostart is enabled, otherwise remove it. Returns true if a legacy entry was found.
+pub fn cleanup_legacy_autostart() -> bool {
+    let entry = if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|h| h.join("Library/LaunchAgents/rocks.brandstaetter.brandybox.plist"))
+    } else if cfg!(windows) {
+        // Python used a Startup-folder shortcut; apply_autostart_windows migrates it to the Run key
+        #[cfg(windows)]
+        if legacy_startup_shortcut().is_some() {
+            apply_autostart_platform(get_autostart());
+            return true;
+        }
+        None
+    } else {
+        dirs::config_dir().map(|d| d.join("autostart").join("brandybox.desktop"))
+    };
+    let legacy = entry
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .map(|c| is_python_autostart(&c))
+        .unwrap_or(false);
+    if legacy {
+        log::info!("Replacing Python client autostart entry");
+        apply_autostart_platform(get_autostart());
+    }
+    legacy
+}
+
+const SETTINGS_BUNDLE_VERSION: u32 = 1;
+
+/// Portable settings bundle written by `export_settings`. Contains no secrets.
+#[derive(Serialize, Deserialize)]
+struct SettingsBundle {
+    version: u32,
+    exported_at: String,
+    settings: ConfigFile,
+}
+
+/// Current settings without secrets and machine-specific state.
+fn shareable_settings() -> ConfigFile {
+    let mut settings = read_config();
+    settings.app_token = None;
+    settings.settings_window_geometry = None;
+    settings.legacy_import_done = None;
+    settings.device_id = None;
+    settings
+}
+
+/// Settings as JSON for diagnostics (no secrets).
+pub fn sanitized_config_json() -> String {
+    serde_json::to_string_pretty(&shareable_settings()).unwrap_or_default()
+}
+
+/// Write all settings except secrets and machine-specific state to a single JSON file.
+pub fn export_settings(path: &Path) -> Result<(), String> {
+    let settings = shareable_settings();
+    let bundle = SettingsBundle {
+        version: SETTINGS_BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        settings,
+    };
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Apply a bundle from `export_settings`. Local secrets and machine state are kept; switching the
+/// sync folder resets sync state so the new folder is never diffed against the old one.
+pub fn import_settings(path: &Path) -> Result<(), String> {
+    let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let bundle: SettingsBundle = serde_json::from_str(&s).map_err(|e| format!("Invalid settings file: {}", e))?;
+    if bundle.version > SETTINGS_BUNDLE_VERSION {
+        return Err(format!("Settings file version {} is newer than this client supports", bundle.version));
+    }
+    let (mut folder_changed, mut autostart_changed) = (false, false);
+    write_config(|c| {
+        folder_changed = bundle.settings.sync_folder.is_some() && bundle.settings.sync_folder != c.sync_folder;
+        autostart_changed = bundle.settings.autostart.is_some() && bundle.settings.autostart != c.autostart;
+        let mut imported = bundle.settings;
+        imported.app_token = c.app_token.take();
+        imported.settings_window_geometry = c.settings_window_geometry.take();
+        imported.legacy_import_done = c.legacy_import_done.take();
+        *c = imported;
+    });
+    if folder_changed {
+        clear_sync_state();
+    }
+    if autostart_changed {
+        apply_autostart_platform(get_autostart());
+    }
+    Ok(())
+}
+
+/// Command line written into autostart entries.
+fn executable_command() -> Vec<String> {
+    let mut cmd = if is_portable() {
+        // Portable installs are not on PATH; always launch the binary we run from
+        let exe = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("BrandyBox"));
+        vec![exe.to_string_lossy().to_string(), PORTABLE_ARG.to_string()]
+    } else if cfg!(windows) {
+        vec![std::env::current_exe().unwrap_or_else(|_| PathBuf::from("BrandyBox.exe")).to_string_lossy().to_string()]
+    } else {
+        vec!["BrandyBox".to_string()]
+    };
+    if get_start_minimized() {
+        cmd.push(MINIMIZED_ARG.to_string());
+    }
+    cmd
+}
+
+fn apply_autostart_platform(enabled: bool) {
+    let cmd = executable_command();
+    #[cfg(windows)]
+    apply_autostart_windows(enabled, &cmd);
+    #[cfg(target_os = "macos")]
+    apply_autostart_macos(enabled, &cmd);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    apply_autostart_linux(enabled, &cmd);
+}
+
+/// Entry content we would write for `cmd` (Run value, plist or .desktop file).
+fn autostart_entry_content(cmd: &[String]) -> String {
+    #[cfg(windows)]
+    return windows_run_value(cmd);
+    #[cfg(target_os = "macos")]
+    return macos_plist_content(cmd);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    return linux_desktop_content(cmd);
+}
+
+/// Currently registered autostart entry, if any.
+fn read_autostart_entry() -> Option<String> {
+    #[cfg(windows)]
+    {
+        use winreg::enums::HKEY_CURRENT_USER;
+        let run = winreg::RegKey::predef(HKEY_CURRENT_USER).open_subkey(WINDOWS_RUN_KEY).ok()?;
+        run.get_value::<String, _>(WINDOWS_RUN_VALUE).ok()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::fs::read_to_string(macos_plist_path()).ok()
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        std::fs::read_to_string(linux_desktop_path()).ok()
+    }
+}
+
+/// When autostart is enabled but the registered entry is missing or stale (app moved or updated
+/// to a new install path), rewrite it for the current executable. Returns true if repaired.
+pub fn repair_autostart() -> bool {
+    if !get_autostart() {
+        return false;
+    }
+    let expected = autostart_entry_content(&executable_command());
+    if read_autostart_entry().as_deref() == Some(expected.as_str()) {
+        return false;
+    }
+    log::info!("Autostart entry missing or stale; rewriting for {:?}", std::env::current_exe().ok());
+    apply_autostart_platform(true);
+    true
+}
+
+#[cfg(windows)]
+const WINDOWS_RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+#[cfg(windows)]
+const WINDOWS_RUN_VALUE: &str = "BrandyBox";
+
+/// Startup-folder shortcut created by the Python client and earlier Tauri versions (via PowerShell).
+#[cfg(windows)]
+fn legacy_startup_shortcut() -> Option<PathBuf> {
+    let appdata = std::env::var("APPDATA").ok()?;
+    let lnk = PathBuf::from(appdata).join("Microsoft/Windows/Start Menu/Programs/Startup/BrandyBox.lnk");
+    lnk.exists().then_some(lnk)
+}
+
+#[cfg(windows)]
+fn windows_run_value(cmd: &[String]) -> String {
+    cmd.iter()
+        .enumerate()
+        .map(|(i, a)| if i == 0 || a.contains(' ') { format!("\"{}\"", a) } else { a.clone() })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Autostart via the per-user `Run` registry key (no PowerShell, works under restricted policies).
+#[cfg(windows)]
+fn apply_autostart_windows(enabled: bool, cmd: &[String]) {
+    use winreg::enums::HKEY_CURRENT_USER;
+    if let Some(lnk) = legacy_startup_shortcut() {
+        log::info!("Removing legacy startup shortcut {}", lnk.display());
+        let _ = std::fs::remove_file(lnk);
+    }
+    let hkcu = winreg::RegKey::predef(HKEY_CURRENT_USER);
+    let run = match hkcu.create_subkey(WINDOWS_RUN_KEY) {
+        Ok((key, _)) => key,
+        Err(e) => {
+            log::warn!("Autostart: cannot open Run key: {}", e);
+            return;
+        }
+    };
+    if enabled {
+        if let Err(e) = run.set_value(WINDOWS_RUN_VALUE, &windows_run_value(cmd)) {
+            log::warn!("Autostart: cannot write Run value: {}", e);
+        }
+    } else {
+        let _ = run.delete_value(WINDOWS_RUN_VALUE);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn macos_plist_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Library/LaunchAgents/rocks.brandstaetter.brandybox.plist")
+}
+
+#[cfg(target_os = "macos")]
+fn macos_plist_content(cmd: &[String]) -> String {
+    let args_xml: String = cmd.iter().map(|a| format!("    <string>{}</string>", a)).collect::<Vec<_>>().join("\n");
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>rocks.brandstaetter.brandybox</string>
+  <key>ProgramArguments</key>
+  <array>
+{}
+  </array>
+  <key>RunAtLoad</key>
+  <true/>
+</dict>
+</plist>
+"#,
+        args_xml
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn apply_autostart_macos(enabled: bool, cmd: &[String]) {
+    let plist = macos_plist_path();
+    if let Some(parent) = plist.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if enabled {
+        let _ = std::fs::write(plist, macos_plist_content(cmd));
+    } else if plist.exists() {
+        let _ = std::fs::remove_file(plist);
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn linux_desktop_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("autostart")
+        .join("brandybox.desktop")
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn linux_desktop_content(cmd: &[String]) -> String {
+    format!(
+        "[Desktop Entry]\nType=Application\nName=Brandy Box\nExec={}\nX-GNOME-Autostart-enabled=true\n",
+        cmd.join(" ")
+    )
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn apply_autostart_linux(enabled: bool, cmd: &[String]) {
+    let desktop = linux_desktop_path();
+    if let Some(parent) = desktop.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if enabled {
+        let _ = std::fs::write(desktop, linux_desktop_content(cmd));
+    } else if desktop.exists() {
+        let _ = std::fs::remove_file(desktop);
+    }
+}
+
+/// Remove config, its backup and sync state, and drop the autostart entry. With no sync state
+/// the next run behaves like a fresh install: nothing is deleted on either side.
+pub fn reset_local_data() {
+    apply_autostart_platform(false);
+    let dir = config_dir();
+    for name in [CONFIG_FILENAME, CONFIG_BACKUP_FILENAME, SYNC_STATE_FILENAME, ACTIVITY_FILENAME, STATS_FILENAME] {
+        let path = dir.join(name);
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!("Reset: could not remove {}: {}", path.display(), e);
+            }
+        }
+    }
+    // Do not re-import Python client data the user just chose to discard
+    set_legacy_import_done();
+}
+
+pub fn clear_sync_state() {
+    let content = r#"{"paths": [], "downloaded_paths": [], "file_hashes": {}}"#;
+    let _ = metadata_crypto::write(&get_sync_state_path(), content);
+    let _ = std::fs::remove_file(get_sync_state_backup_path());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::tests::engine_env;
+
+    #[test]
+    fn concurrent_config_updates_keep_every_setting() {
+        let (_guard, _root) = engine_env("config-threads");
+        let writers: Vec<_> = (0..4u32)
+            .map(|t| {
+                std::thread::spawn(move || {
+                    for i in 1..=25u32 {
+                        match t {
+                            0 => set_metrics_port(Some(9000 + i as u16)),
+                            1 => set_app_lock_minutes(Some(i)),
+                            2 => set_max_file_size_bytes(Some(u64::from(i) * 1024)),
+                            _ => set_large_file_allowed(&format!("big{}.iso", i), true),
+                        }
+                        let _ = get_sync_folder_path();
+                    }
+                })
+            })
+            .collect();
+        for w in writers {
+            w.join().unwrap();
+        }
+        assert_eq!(get_metrics_port(), Some(9025));
+        assert_eq!(get_app_lock_minutes(), Some(25));
+        assert_eq!(get_max_file_size_bytes(), Some(25 * 1024));
+        assert_eq!(get_large_file_allowlist().len(), 25);
+
+        // A hand edit of config.json is picked up
+        let path = get_config_dir().join("config.json");
+        let text = std::fs::read_to_string(&path).unwrap().replace("9025", "9123");
+        std::fs::write(&path, text).unwrap();
+        assert_eq!(get_metrics_port(), Some(9123));
+    }
+
+    #[test]
+    fn legacy_quiet_hours_become_the_notification_schedule() {
+        let (_guard, _root) = engine_env("quiet-hours");
+        let path = get_config_dir().join("config.json");
+        std::fs::write(&path, r#"{"quiet_hours_start": 22, "quiet_hours_end": 7, "mute_notifications_fullscreen": false}"#)
+            .unwrap();
+        let schedule = get_notification_schedule();
+        let quiet = schedule.quiet_hours.clone().unwrap();
+        assert_eq!((quiet.start.as_str(), quiet.end.as_str()), ("22:00", "07:00"));
+        assert!(!schedule.mute_fullscreen);
+        assert!(crate::sync::in_sync_window(&quiet, 5, 23 * 60));
+
+        set_notification_schedule(NotificationSchedule { quiet_hours: None, ..schedule });
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        for key in ["quiet_hours_start", "quiet_hours_end", "mute_notifications_fullscreen"] {
+            assert!(saved.get(key).is_none(), "{} left in {}", key, saved);
+        }
+        assert_eq!(get_notification_schedule().quiet_hours, None);
+        assert!(!get_notification_schedule().mute_fullscreen);
+    }
+}
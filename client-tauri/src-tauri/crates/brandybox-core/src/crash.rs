@@ -0,0 +1,113 @@
+//! Panic hook that writes crash reports to `<config dir>/crashes/`. Reports stay on disk until
+//! the user sends them (`ApiClient::report_crash`) or discards them on a later start.
+
+use crate::api::ApiClient;
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const CRASH_DIRNAME: &str = "crashes";
+/// Older reports are dropped so a crash loop cannot fill the disk.
+const CRASH_REPORTS_KEPT: usize = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub occurred_at: String,
+    pub client_version: String,
+    pub os: String,
+    pub thread: String,
+    pub message: String,
+    pub backtrace: String,
+}
+
+fn crash_dir() -> PathBuf {
+    config::get_config_dir().join(CRASH_DIRNAME)
+}
+
+fn report_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(crash_dir())
+        .map(|rd| {
+            rd.filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|x| x == "json").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Install the panic hook. The default hook still runs afterwards (stderr output).
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = info.payload().downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+        let message = match info.location() {
+            Some(loc) => format!("panicked at {}:{}:{}: {}", loc.file(), loc.line(), loc.column(), payload),
+            None => format!("panicked: {}", payload),
+        };
+        let report = CrashReport {
+            occurred_at: chrono::Utc::now().to_rfc3339(),
+            client_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+            message,
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        };
+        log::error!("{} (thread {})", report.message, report.thread);
+        write_report(&report);
+        default_hook(info);
+    }));
+}
+
+fn write_report(report: &CrashReport) {
+    let dir = crash_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    let name = format!("crash-{}.json", chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f"));
+    if let Ok(json) = serde_json::to_string_pretty(report) {
+        let _ = std::fs::write(dir.join(name), json);
+    }
+    let files = report_files();
+    for old in files.iter().take(files.len().saturating_sub(CRASH_REPORTS_KEPT)) {
+        let _ = std::fs::remove_file(old);
+    }
+}
+
+/// Crash reports from previous runs that were neither sent nor discarded, oldest first.
+pub fn pending_reports() -> Vec<CrashReport> {
+    report_files()
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect()
+}
+
+/// Upload all pending reports; each one is deleted once the server accepted it.
+pub fn send_pending(client: &ApiClient) -> Result<usize, String> {
+    let mut sent = 0;
+    for path in report_files() {
+        let report: CrashReport = match std::fs::read_to_string(&path).ok().and_then(|s| serde_json::from_str(&s).ok()) {
+            Some(r) => r,
+            None => {
+                let _ = std::fs::remove_file(&path);
+                continue;
+            }
+        };
+        client.report_crash(&report)?;
+        let _ = std::fs::remove_file(&path);
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+pub fn discard_pending() {
+    for path in report_files() {
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,46 @@
+//! This install's identity in the server's device list (id from config, name from the hostname).
+
+use crate::api::ApiClient;
+use crate::config;
+use std::sync::Mutex;
+
+/// Outcome of the last sync cycle (ok, RFC 3339 time), reported with the next check-in.
+static LAST_OUTCOME: Mutex<Option<(bool, String)>> = Mutex::new(None);
+
+/// Human-readable name shown in the device list: the machine's hostname when available.
+pub fn device_name() -> String {
+    let from_env = ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .filter_map(|k| std::env::var(k).ok())
+        .find(|s| !s.trim().is_empty());
+    let from_file = || {
+        std::fs::read_to_string("/etc/hostname")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    from_env
+        .or_else(from_file)
+        .unwrap_or_else(|| format!("Brandy Box ({})", std::env::consts::OS))
+}
+
+pub fn record_sync_outcome(ok: bool) {
+    if let Ok(mut g) = LAST_OUTCOME.lock() {
+        *g = Some((ok, chrono::Utc::now().to_rfc3339()));
+    }
+}
+
+/// Register this device (or refresh last-seen) before a sync cycle, reporting the previous
+/// cycle's outcome. Returns Ok(false) when the user revoked this device on the server.
+pub fn check_in(client: &ApiClient) -> Result<bool, String> {
+    let last = LAST_OUTCOME.lock().ok().and_then(|g| g.clone());
+    let (ok, at) = match last {
+        Some((ok, at)) => (Some(ok), Some(at)),
+        None => (None, None),
+    };
+    match client.register_device(&config::get_device_id(), &device_name(), ok, at) {
+        Ok(device) => Ok(!device.revoked),
+        Err(e) if e.starts_with("410") => Ok(false),
+        Err(e) => Err(e),
+    }
+}
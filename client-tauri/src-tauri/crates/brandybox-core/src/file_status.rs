@@ -0,0 +1,193 @@
+//! Per-file sync status for file manager integrations (Explorer overlay icons, Linux emblems,
+//! Finder badges). The engine reports what it is doing; integrations ask `status_of` through
+//! the IPC `file-status` command.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileSyncStatus {
+    /// Same content locally and on the server.
+    Synced,
+    /// Being uploaded or downloaded right now (folders: something inside is).
+    Syncing,
+    /// Changed; will be transferred in this or the next cycle.
+    Pending,
+    /// The last attempt failed (e.g. storage full).
+    Error,
+    /// Not known to the engine (ignored, new since the last listing, or outside the folder).
+    Unknown,
+}
+
+#[derive(Default)]
+struct Store {
+    loaded: bool,
+    synced: HashSet<String>,
+    pending: HashSet<String>,
+    active: Option<String>,
+    errors: HashMap<String, String>,
+}
+
+static STORE: Mutex<Option<Store>> = Mutex::new(None);
+
+fn with_store<T>(f: impl FnOnce(&mut Store) -> T) -> Option<T> {
+    let mut guard = STORE.lock().ok()?;
+    let store = guard.get_or_insert_with(Store::default);
+    if !store.loaded {
+        store.synced = crate::sync::synced_paths().into_iter().collect();
+        store.loaded = true;
+    }
+    Some(f(store))
+}
+
+impl Store {
+    fn status_of(&self, path: &str) -> FileSyncStatus {
+        if self.active.as_deref() == Some(path) {
+            return FileSyncStatus::Syncing;
+        }
+        if self.errors.contains_key(path) {
+            return FileSyncStatus::Error;
+        }
+        if self.pending.contains(path) {
+            return FileSyncStatus::Pending;
+        }
+        if self.synced.contains(path) {
+            return FileSyncStatus::Synced;
+        }
+        let prefix = if path.is_empty() { String::new() } else { format!("{}/", path) };
+        let inside = |p: &String| p.starts_with(&prefix);
+        if self.errors.keys().any(inside) {
+            FileSyncStatus::Error
+        } else if self.active.as_ref().is_some_and(inside) {
+            FileSyncStatus::Syncing
+        } else if self.pending.iter().any(inside) {
+            FileSyncStatus::Pending
+        } else if self.synced.iter().any(inside) {
+            FileSyncStatus::Synced
+        } else {
+            FileSyncStatus::Unknown
+        }
+    }
+
+    fn mark_done(&mut self, path: &str) {
+        self.pending.remove(path);
+        self.errors.remove(path);
+        self.synced.insert(path.to_string());
+        self.active = None;
+    }
+
+    fn mark_failed(&mut self, path: &str, error: &str) {
+        self.pending.remove(path);
+        self.errors.insert(path.to_string(), error.to_string());
+        self.active = None;
+    }
+
+    /// Drop `path` and everything inside it (it was excluded from sync).
+    fn forget(&mut self, path: &str) {
+        let prefix = format!("{}/", path);
+        let keep = |p: &String| p != path && !p.starts_with(&prefix);
+        self.synced.retain(keep);
+        self.pending.retain(keep);
+        self.errors.retain(|p, _| keep(p));
+        if self.active.as_ref().is_some_and(|p| !keep(p)) {
+            self.active = None;
+        }
+    }
+
+    fn end_cycle(&mut self, synced: Option<&[String]>) {
+        self.active = None;
+        if let Some(paths) = synced {
+            self.synced = paths.iter().cloned().collect();
+            self.pending.clear();
+            let synced = &self.synced;
+            self.errors.retain(|p, _| !synced.contains(p));
+        }
+    }
+}
+
+/// A cycle starts: `pending` are the paths it will transfer. Errors of the last cycle are kept
+/// until the path is retried.
+pub fn begin_cycle<'a>(pending: impl IntoIterator<Item = &'a String>) {
+    with_store(|s| {
+        s.pending = pending.into_iter().cloned().collect();
+        s.active = None;
+    });
+}
+
+pub fn set_active(path: &str) {
+    with_store(|s| s.active = Some(path.to_string()));
+}
+
+pub fn mark_done(path: &str) {
+    with_store(|s| s.mark_done(path));
+}
+
+pub fn mark_failed(path: &str, error: &str) {
+    with_store(|s| s.mark_failed(path, error));
+}
+
+/// `path` (file or folder) was excluded from sync: no longer pending, failed or synced.
+pub fn forget(path: &str) {
+    with_store(|s| s.forget(path));
+}
+
+/// The cycle ended. On success `synced` is the new set of synced paths; on failure whatever
+/// was not transferred stays pending.
+pub fn end_cycle(synced: Option<&[String]>) {
+    with_store(|s| s.end_cycle(synced));
+}
+
+/// Last error for `path`, if its last transfer failed.
+pub fn error_of(path: &str) -> Option<String> {
+    with_store(|s| s.errors.get(path.trim_matches('/')).cloned()).flatten()
+}
+
+/// Status of every file the engine knows about (synced, pending or failed).
+pub fn snapshot() -> Vec<(String, FileSyncStatus)> {
+    with_store(|s| {
+        let paths: HashSet<&String> = s.synced.iter().chain(&s.pending).chain(s.errors.keys()).collect();
+        paths.into_iter().map(|p| (p.clone(), s.status_of(p))).collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Files waiting for transfer (including the active one) and files whose last attempt failed.
+pub fn queue_depths() -> (usize, usize) {
+    with_store(|s| (s.pending.len(), s.errors.len())).unwrap_or_default()
+}
+
+/// Status of a file or folder given relative to the sync folder ("" is the folder itself).
+/// A folder reports the most urgent status of its contents: error, syncing, pending, synced.
+pub fn status_of(path: &str) -> FileSyncStatus {
+    with_store(|s| s.status_of(path.trim_matches('/'))).unwrap_or(FileSyncStatus::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_and_folders_report_most_urgent_status() {
+        let mut s = Store { loaded: true, ..Store::default() };
+        s.end_cycle(Some(&["docs/a.txt".to_string(), "b.txt".to_string()]));
+        s.pending = ["docs/new.txt".to_string(), "photos/p.jpg".to_string()].into_iter().collect();
+        assert_eq!(s.status_of("b.txt"), FileSyncStatus::Synced);
+        assert_eq!(s.status_of("docs"), FileSyncStatus::Pending);
+        s.active = Some("docs/new.txt".to_string());
+        assert_eq!(s.status_of("docs/new.txt"), FileSyncStatus::Syncing);
+        assert_eq!(s.status_of("docs"), FileSyncStatus::Syncing);
+        s.mark_done("docs/new.txt");
+        assert_eq!(s.status_of("docs"), FileSyncStatus::Synced);
+        s.mark_failed("photos/p.jpg", "507 Insufficient Storage");
+        assert_eq!(s.status_of("photos"), FileSyncStatus::Error);
+        assert_eq!(s.status_of(""), FileSyncStatus::Error);
+        assert_eq!(s.status_of("elsewhere.txt"), FileSyncStatus::Unknown);
+        s.end_cycle(Some(&["b.txt".to_string()]));
+        assert!(s.errors.contains_key("photos/p.jpg"));
+        s.forget("photos");
+        assert_eq!(s.status_of("photos/p.jpg"), FileSyncStatus::Unknown);
+        assert_eq!(s.status_of("b.txt"), FileSyncStatus::Synced);
+    }
+}
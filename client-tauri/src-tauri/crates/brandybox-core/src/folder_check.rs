@@ -0,0 +1,396 @@
+//! Checks for a candidate sync folder, used by the setup wizard before the first sync.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Folder names of other sync clients; syncing inside them makes two clients fight over files.
+const CLOUD_FOLDER_NAMES: &[&str] = &[
+    "dropbox",
+    "onedrive",
+    "google drive",
+    "my drive",
+    "icloud drive",
+    "mobile documents",
+    "cloudstorage",
+    "nextcloud",
+    "owncloud",
+    "pcloud drive",
+    "mega",
+    "seafile",
+    "syncthing",
+];
+
+#[cfg(windows)]
+const SYSTEM_DIRS: &[&str] = &[r"C:\Windows", r"C:\Program Files", r"C:\Program Files (x86)", r"C:\ProgramData"];
+#[cfg(not(windows))]
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/opt", "/proc", "/run", "/sbin", "/snap", "/sys", "/usr",
+    "/var", "/Applications", "/Library", "/System",
+];
+
+#[cfg(unix)]
+/// Filesystem types of network mounts (Linux `/proc/mounts` without the `fuse.` prefix, macOS `mount`).
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "webdav", "davfs", "sshfs", "rclone", "gvfsd-fuse", "9p", "afs",
+    "ceph", "glusterfs",
+];
+
+/// What kind of drive a folder is on. Network and removable drives can disappear while the app
+/// runs; an unmounted drive looks like an empty sync folder.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeKind {
+    #[default]
+    Local,
+    Network,
+    Removable,
+}
+
+impl VolumeKind {
+    /// Network or removable: sync uses stricter deletion safeguards.
+    pub fn is_fragile(self) -> bool {
+        self != VolumeKind::Local
+    }
+
+    /// Warning shown while the sync folder is on such a drive.
+    pub fn warning(self) -> Option<&'static str> {
+        match self {
+            VolumeKind::Local => None,
+            VolumeKind::Network => Some(
+                "The sync folder is on a network drive. If the connection drops, sync pauses instead of treating files as deleted, but a local folder is more reliable",
+            ),
+            VolumeKind::Removable => Some(
+                "The sync folder is on a removable drive. Keep it connected while Brandy Box runs; when it is unplugged, sync pauses instead of treating files as deleted",
+            ),
+        }
+    }
+}
+
+#[cfg(unix)]
+/// Classify one mount by filesystem type, device and mount point.
+fn classify_mount(device: &str, mount_point: &str, fs_type: &str) -> VolumeKind {
+    let fs = fs_type.strip_prefix("fuse.").unwrap_or(fs_type).to_lowercase();
+    if NETWORK_FS_TYPES.contains(&fs.as_str()) || device.starts_with("//") {
+        return VolumeKind::Network;
+    }
+    // udisks and macOS mount USB sticks and SD cards here
+    if ["/media/", "/run/media/", "/Volumes/"].iter().any(|p| mount_point.starts_with(p)) {
+        return VolumeKind::Removable;
+    }
+    if removable_block_device(device) {
+        return VolumeKind::Removable;
+    }
+    VolumeKind::Local
+}
+
+/// Linux: `/sys/class/block/<dev>/removable` (of the partition's parent disk) is 1.
+#[cfg(target_os = "linux")]
+fn removable_block_device(device: &str) -> bool {
+    let Some(name) = device.strip_prefix("/dev/") else {
+        return false;
+    };
+    let Ok(sys) = std::fs::canonicalize(Path::new("/sys/class/block").join(name)) else {
+        return false;
+    };
+    [sys.join("removable"), sys.join("../removable")]
+        .iter()
+        .any(|p| std::fs::read_to_string(p).map(|s| s.trim() == "1").unwrap_or(false))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn removable_block_device(_device: &str) -> bool {
+    false
+}
+
+/// Undo the octal escapes (`\040` for a space) in /proc/mounts fields.
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    let mut out = Vec::with_capacity(field.len());
+    let bytes = field.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 4 <= bytes.len() && bytes[i + 1..i + 4].iter().all(|b| (b'0'..=b'7').contains(b)) {
+            out.push(u8::from_str_radix(&field[i + 1..i + 4], 8).unwrap_or(b'?'));
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// (device, mount point, filesystem type) of every mount.
+#[cfg(target_os = "linux")]
+fn list_mounts() -> Vec<(String, String, String)> {
+    let text = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split(' ');
+            let device = unescape_mount_field(fields.next()?);
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fs_type = fields.next()?.to_string();
+            Some((device, mount_point, fs_type))
+        })
+        .collect()
+}
+
+/// macOS/BSD: parse `mount` output, "//user@nas/share on /Volumes/share (smbfs, nodev, …)".
+#[cfg(all(unix, not(target_os = "linux")))]
+fn list_mounts() -> Vec<(String, String, String)> {
+    let Ok(out) = std::process::Command::new("mount").output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (device, rest) = line.split_once(" on ")?;
+            let (mount_point, opts) = rest.rsplit_once(" (")?;
+            let fs_type = opts.split([',', ')']).next()?.trim();
+            Some((device.to_string(), mount_point.to_string(), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// The drive `path` (or its nearest existing parent) is on.
+#[cfg(unix)]
+pub fn volume_kind(path: &Path) -> VolumeKind {
+    let Some(existing) = nearest_existing(path) else {
+        return VolumeKind::Local;
+    };
+    let existing = std::fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+    list_mounts()
+        .into_iter()
+        .filter(|(_, mount_point, _)| existing.starts_with(mount_point))
+        .max_by_key(|(_, mount_point, _)| mount_point.len())
+        .map(|(device, mount_point, fs_type)| classify_mount(&device, &mount_point, &fs_type))
+        .unwrap_or_default()
+}
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetDriveTypeW(root_path: *const u16) -> u32;
+}
+
+/// The drive `path` is on: UNC paths and mapped drives are network drives, USB sticks and
+/// SD cards removable.
+#[cfg(windows)]
+pub fn volume_kind(path: &Path) -> VolumeKind {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Component, Prefix};
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_REMOTE: u32 = 4;
+    let root = match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::UNC(..) | Prefix::VerbatimUNC(..) => return VolumeKind::Network,
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => format!("{}:\\", letter as char),
+            _ => return VolumeKind::Local,
+        },
+        _ => return VolumeKind::Local,
+    };
+    let wide: Vec<u16> = std::ffi::OsStr::new(&root).encode_wide().chain(std::iter::once(0)).collect();
+    // SAFETY: `wide` is a NUL-terminated UTF-16 string that outlives the call
+    match unsafe { GetDriveTypeW(wide.as_ptr()) } {
+        DRIVE_REMOTE => VolumeKind::Network,
+        DRIVE_REMOVABLE => VolumeKind::Removable,
+        _ => VolumeKind::Local,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncFolderCheck {
+    /// Absolute path that was checked.
+    pub path: PathBuf,
+    pub exists: bool,
+    /// Exists, or could be created (nearest existing parent is writable).
+    pub creatable: bool,
+    pub writable: bool,
+    pub system_dir: bool,
+    /// Name of the other sync client's folder this one is inside, e.g. "OneDrive".
+    pub inside_cloud_folder: Option<String>,
+    /// Free space on the folder's drive.
+    pub free_bytes: Option<u64>,
+    /// Network or removable drive: allowed, but shown as a warning.
+    pub volume: VolumeKind,
+    /// Reasons the folder cannot be used; empty when it can.
+    pub problems: Vec<String>,
+}
+
+impl SyncFolderCheck {
+    pub fn ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn nearest_existing(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.is_dir())
+}
+
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".brandybox-write-test-{}", std::process::id()));
+    let ok = std::fs::write(&probe, b"").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    ok
+}
+
+fn is_system_dir(path: &Path) -> bool {
+    SYSTEM_DIRS.iter().any(|d| {
+        let d = Path::new(d);
+        path == d || path.starts_with(d)
+    })
+}
+
+/// Name of the first ancestor (or the folder itself) that belongs to another sync client.
+pub fn cloud_folder_of(path: &Path) -> Option<String> {
+    path.ancestors().filter_map(|p| p.file_name()).find_map(|name| {
+        let name = name.to_string_lossy();
+        let lower = name.to_lowercase();
+        // "OneDrive - Company" is OneDrive for Business
+        let base = lower.split(" - ").next().unwrap_or("").trim();
+        CLOUD_FOLDER_NAMES.contains(&base).then(|| name.to_string())
+    })
+}
+
+pub fn check_sync_folder(path: &Path) -> SyncFolderCheck {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    let exists = path.is_dir();
+    let existing = nearest_existing(&path);
+    let writable = existing.map(is_writable).unwrap_or(false);
+    let mut check = SyncFolderCheck {
+        exists,
+        creatable: exists || writable,
+        writable,
+        system_dir: is_system_dir(&path),
+        inside_cloud_folder: cloud_folder_of(&path),
+        free_bytes: existing.and_then(|p| fs2::available_space(p).ok()),
+        volume: volume_kind(&path),
+        problems: Vec::new(),
+        path,
+    };
+    if check.path.exists() && !exists {
+        check.problems.push("A file with this name already exists".to_string());
+    } else if !check.creatable {
+        check.problems.push("The folder does not exist and cannot be created here".to_string());
+    } else if !check.writable {
+        check.problems.push("The folder is not writable".to_string());
+    }
+    if check.system_dir {
+        check.problems.push("This is a system folder".to_string());
+    }
+    if let Some(name) = &check.inside_cloud_folder {
+        check.problems.push(format!("The folder is inside \"{}\", which another sync app manages", name));
+    }
+    check
+}
+
+/// Why a new sync folder must be refused, or needs the user's explicit confirmation.
+#[derive(Debug, Default, Serialize)]
+pub struct SyncFolderRisks {
+    pub rejected: Vec<String>,
+    pub needs_confirmation: Vec<String>,
+}
+
+/// Check `path` before saving it as the sync folder. `current` is the folder in use (if any),
+/// `home` the user's home directory.
+pub fn sync_folder_risks(path: &Path, current: Option<&Path>, home: Option<&Path>) -> SyncFolderRisks {
+    let check = check_sync_folder(path);
+    let path = check.path.as_path();
+    let mut risks = SyncFolderRisks::default();
+    if path.parent().is_none() {
+        risks.rejected.push("A drive or filesystem root cannot be the sync folder".to_string());
+    }
+    if let Some(home) = home {
+        if path == home {
+            risks.needs_confirmation.push(
+                "This is your home folder: everything in it, including settings and other apps' data, would be uploaded"
+                    .to_string(),
+            );
+        } else if home.starts_with(path) && path.parent().is_some() {
+            risks.rejected.push("This folder contains your home folder".to_string());
+        }
+    }
+    if let Some(current) = current.filter(|c| *c != path) {
+        if path.starts_with(current) {
+            risks.rejected.push("The folder is inside the current sync folder".to_string());
+        } else if current.starts_with(path) {
+            risks
+                .needs_confirmation
+                .push("The folder contains the current sync folder, whose files would be uploaded again".to_string());
+        }
+    }
+    if check.path.exists() && !check.exists {
+        risks.rejected.push("A file with this name already exists".to_string());
+    } else if !check.creatable || !check.writable {
+        risks.rejected.push("The folder is not writable or cannot be created".to_string());
+    }
+    if check.system_dir {
+        risks.rejected.push("This is a system folder".to_string());
+    }
+    if let Some(name) = check.inside_cloud_folder {
+        risks
+            .needs_confirmation
+            .push(format!("The folder is inside \"{}\", which another sync app manages", name));
+    }
+    if let Some(warning) = check.volume.warning() {
+        risks.needs_confirmation.push(warning.to_string());
+    }
+    risks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_cloud_system_and_writable_folders() {
+        assert_eq!(cloud_folder_of(Path::new("/home/anna/OneDrive - Contoso/Box")).as_deref(), Some("OneDrive - Contoso"));
+        assert_eq!(cloud_folder_of(Path::new("/home/anna/Dropbox")).as_deref(), Some("Dropbox"));
+        assert_eq!(cloud_folder_of(Path::new("/home/anna/brandyBox")), None);
+
+        let base = std::env::temp_dir().join(format!("brandybox-test-foldercheck-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let new_folder = check_sync_folder(&base.join("brandyBox"));
+        assert!(new_folder.ok(), "{:?}", new_folder.problems);
+        assert!(!new_folder.exists && new_folder.creatable);
+        std::fs::write(base.join("file"), b"x").unwrap();
+        assert!(!check_sync_folder(&base.join("file")).ok());
+        #[cfg(not(windows))]
+        assert!(check_sync_folder(Path::new("/etc/brandybox")).system_dir);
+
+        let current = base.join("brandyBox");
+        let risks = sync_folder_risks(&current.join("sub"), Some(&current), Some(&base));
+        assert!(risks.rejected.iter().any(|r| r.contains("inside the current")));
+        let risks = sync_folder_risks(&base, Some(&current), Some(&base));
+        assert!(risks.rejected.is_empty(), "{:?}", risks.rejected);
+        assert_eq!(risks.needs_confirmation.len(), 2);
+        let root = base.ancestors().last().unwrap();
+        assert!(!sync_folder_risks(root, None, Some(&base)).rejected.is_empty());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn classifies_network_and_removable_mounts() {
+        assert_eq!(classify_mount("nas:/export/home", "/mnt/nas", "nfs4"), VolumeKind::Network);
+        assert_eq!(classify_mount("//nas/share", "/mnt/share", "cifs"), VolumeKind::Network);
+        assert_eq!(classify_mount("anna@host:", "/home/anna/remote", "fuse.sshfs"), VolumeKind::Network);
+        assert_eq!(classify_mount("//anna@nas/box", "/Volumes/box", "smbfs"), VolumeKind::Network);
+        assert_eq!(classify_mount("/dev/sdb1", "/media/anna/USB", "vfat"), VolumeKind::Removable);
+        assert_eq!(classify_mount("/dev/disk4s1", "/Volumes/SD", "msdos"), VolumeKind::Removable);
+        assert_eq!(classify_mount("/dev/nvme0n1p2", "/", "ext4"), VolumeKind::Local);
+        assert!(VolumeKind::Network.is_fragile() && VolumeKind::Local.warning().is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn unescapes_mount_points() {
+        assert_eq!(unescape_mount_field("/media/anna/My\\040Stick"), "/media/anna/My Stick");
+        assert_eq!(unescape_mount_field("/mnt/plain"), "/mnt/plain");
+    }
+}
@@ -0,0 +1,202 @@
+//! Translations for texts the Rust side shows to the user: notifications, sync warnings, login
+//! errors. Each message has a key and `{name}` placeholders; English is the fallback for missing
+//! translations. The language comes from `config::get_language`, "system" meaning the OS locale.
+
+use crate::config;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Languages with a catalog; `config::get_language` may also be "system".
+pub const LANGUAGES: &[&str] = &["en", "de"];
+
+const EN: &[(&str, &str)] = &[
+    ("login.invalid_credentials", "Invalid email or password."),
+    ("login.invalid_app_token", "Invalid or revoked app token."),
+    ("login.not_logged_in", "Not logged in"),
+    ("app_lock.locked", "Account settings are locked. Enter your password to unlock them."),
+    ("app_lock.wrong_password", "Wrong password."),
+    ("login.reset_email_missing", "Enter your email address first."),
+    ("login.reset_sent", "If {email} has an account, a reset code is on its way. Enter it below with your new password. Your current password stays valid until then."),
+    ("login.reset_done", "Password changed. Sign in with your new password."),
+    ("login.reset_code_invalid", "This reset code is invalid, already used or expired. Request a new one."),
+    ("login.reset_unavailable", "This server cannot send password emails. Ask the admin to reset your password."),
+    ("login.reset_rate_limited", "Too many reset requests. Try again in an hour."),
+    ("sync.busy", "Sync in progress; try again when it has finished."),
+    ("sync.folder_not_set", "Sync folder not set"),
+    ("sync.download_only", "Download-only mode: server files are not changed from this device"),
+    ("notify.sync_failed", "Brandy Box – Sync failed"),
+    ("notify.sync_finished", "Sync finished successfully."),
+    ("notify.quota_title", "Brandy Box – Storage almost full"),
+    ("notify.auth_expired_title", "Brandy Box – Login required"),
+    ("notify.auth_expired", "Your session has expired. Open Brandy Box and log in again to resume syncing."),
+    ("notify.device_revoked_title", "Brandy Box – Device removed"),
+    ("notify.device_revoked", "This device was removed from your account and has been signed out. Syncing has stopped."),
+    ("notify.autostart_repaired", "Autostart entry pointed to an old install location and was updated."),
+    ("quota.warning", "Storage {percent} % full. Free up space or ask for more to keep syncing."),
+    ("sync.unsaved_deletes", "{count} file(s) deleted on the server were kept here: they could not be copied into the delete snapshot"),
+    ("sync.failed_deletes", "{count} file(s) deleted on the server could not be deleted here; trying again on the next sync"),
+    ("sync.held_deletes", "{count} file(s) are missing from the server listing; local copies kept until you confirm the deletes"),
+    ("sync.quota_skipped", "Storage full — {count} file(s) not uploaded"),
+    ("sync.too_large", "{count} file(s) too large for the server, not uploaded"),
+    ("sync.skipped_downloads", "{count} download(s) skipped (permission denied or file gone on server)"),
+    ("sync.unreadable", "Cannot read (permission denied), not synced: {paths}"),
+    ("sync.oversized", "Larger than the {limit} limit, not synced: {paths}"),
+    ("sync.unsafe_paths", "{count} server path(s) ignored because they point outside the sync folder"),
+    ("sync.skipped_uploads", "{count} upload(s) skipped (files removed during sync)"),
+    ("status.syncing_files", "Syncing {current}/{total} files"),
+    ("status.syncing_detail", "Syncing… ({detail})"),
+    ("status.checking", "Syncing… (checking for changes)"),
+    ("status.up_to_date", "Up to date"),
+    ("status.up_to_date_at", "Up to date, last sync {time}"),
+    ("status.warning", "Warning: {message}"),
+    ("status.error", "Error: {message}"),
+    ("status.overdue", "{status} (no successful sync for {hours} h)"),
+    ("status.paused", "{status} (paused)"),
+    ("status.paused_until", "{status} (paused until {time})"),
+    ("password.too_short", "Use at least {min} characters."),
+    ("password.mismatch", "The two new passwords do not match."),
+    ("password.same_as_current", "The new password must differ from the current one."),
+    ("password.common", "This password is too common; choose one that is harder to guess."),
+    ("password.contains_email", "Do not use your email address in the password."),
+    ("password.too_weak", "Too easy to guess. Make it longer or mix words, digits and symbols; avoid sequences like 1234 or aaaa."),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("login.invalid_credentials", "E-Mail-Adresse oder Passwort ist falsch."),
+    ("login.invalid_app_token", "App-Token ist ungültig oder wurde widerrufen."),
+    ("login.not_logged_in", "Nicht angemeldet"),
+    ("app_lock.locked", "Die Kontoeinstellungen sind gesperrt. Gib dein Passwort ein, um sie zu entsperren."),
+    ("app_lock.wrong_password", "Falsches Passwort."),
+    ("login.reset_email_missing", "Gib zuerst deine E-Mail-Adresse ein."),
+    ("login.reset_sent", "Falls {email} ein Konto hat, ist ein Code zum Zurücksetzen unterwegs. Gib ihn unten mit deinem neuen Passwort ein. Bis dahin bleibt dein aktuelles Passwort gültig."),
+    ("login.reset_done", "Passwort geändert. Melde dich mit deinem neuen Passwort an."),
+    ("login.reset_code_invalid", "Dieser Code ist ungültig, schon benutzt oder abgelaufen. Fordere einen neuen an."),
+    ("login.reset_unavailable", "Dieser Server kann keine Passwort-E-Mails senden. Bitte den Admin, dein Passwort zurückzusetzen."),
+    ("login.reset_rate_limited", "Zu viele Anfragen zum Zurücksetzen. Versuche es in einer Stunde erneut."),
+    ("sync.busy", "Die Synchronisierung läuft; bitte versuche es danach noch einmal."),
+    ("sync.folder_not_set", "Kein Sync-Ordner festgelegt"),
+    ("sync.download_only", "Nur-Download-Modus: Von diesem Gerät aus werden keine Serverdateien geändert"),
+    ("notify.sync_failed", "Brandy Box – Synchronisierung fehlgeschlagen"),
+    ("notify.sync_finished", "Synchronisierung erfolgreich abgeschlossen."),
+    ("notify.quota_title", "Brandy Box – Speicher fast voll"),
+    ("notify.auth_expired_title", "Brandy Box – Anmeldung nötig"),
+    ("notify.auth_expired", "Deine Sitzung ist abgelaufen. Öffne Brandy Box und melde dich neu an, damit wieder synchronisiert wird."),
+    ("notify.device_revoked_title", "Brandy Box – Gerät entfernt"),
+    ("notify.device_revoked", "Dieses Gerät wurde aus deinem Konto entfernt und abgemeldet. Die Synchronisierung ist gestoppt."),
+    ("notify.autostart_repaired", "Der Autostart-Eintrag zeigte auf einen alten Installationsort und wurde aktualisiert."),
+    ("quota.warning", "Speicher zu {percent} % belegt. Gib Platz frei oder bitte um mehr, damit weiter synchronisiert wird."),
+    ("sync.unsaved_deletes", "{count} auf dem Server gelöschte Datei(en) wurden hier behalten: Sie konnten nicht in den Lösch-Schnappschuss kopiert werden"),
+    ("sync.failed_deletes", "{count} auf dem Server gelöschte Datei(en) konnten hier nicht gelöscht werden; nächste Synchronisierung versucht es erneut"),
+    ("sync.held_deletes", "{count} Datei(en) fehlen in der Serverliste; die lokalen Kopien bleiben, bis du das Löschen bestätigst"),
+    ("sync.quota_skipped", "Speicher voll — {count} Datei(en) nicht hochgeladen"),
+    ("sync.too_large", "{count} Datei(en) zu groß für den Server, nicht hochgeladen"),
+    ("sync.skipped_downloads", "{count} Download(s) übersprungen (keine Berechtigung oder Datei auf dem Server nicht mehr vorhanden)"),
+    ("sync.unreadable", "Nicht lesbar (keine Berechtigung), nicht synchronisiert: {paths}"),
+    ("sync.oversized", "Größer als das Limit von {limit}, nicht synchronisiert: {paths}"),
+    ("sync.unsafe_paths", "{count} Serverpfad(e) ignoriert, weil sie aus dem Sync-Ordner hinaus zeigen"),
+    ("sync.skipped_uploads", "{count} Upload(s) übersprungen (Dateien während der Synchronisierung entfernt)"),
+    ("status.syncing_files", "Synchronisiere {current}/{total} Dateien"),
+    ("status.syncing_detail", "Synchronisiere… ({detail})"),
+    ("status.checking", "Synchronisiere… (suche nach Änderungen)"),
+    ("status.up_to_date", "Aktuell"),
+    ("status.up_to_date_at", "Aktuell, zuletzt synchronisiert um {time}"),
+    ("status.warning", "Warnung: {message}"),
+    ("status.error", "Fehler: {message}"),
+    ("status.overdue", "{status} (seit {hours} h keine erfolgreiche Synchronisierung)"),
+    ("status.paused", "{status} (pausiert)"),
+    ("status.paused_until", "{status} (pausiert bis {time})"),
+    ("password.too_short", "Verwende mindestens {min} Zeichen."),
+    ("password.mismatch", "Die beiden neuen Passwörter stimmen nicht überein."),
+    ("password.same_as_current", "Das neue Passwort muss sich vom aktuellen unterscheiden."),
+    ("password.common", "Dieses Passwort ist zu verbreitet; wähle eines, das schwerer zu erraten ist."),
+    ("password.contains_email", "Verwende deine E-Mail-Adresse nicht im Passwort."),
+    ("password.too_weak", "Zu leicht zu erraten. Mach es länger oder kombiniere Wörter, Ziffern und Sonderzeichen; vermeide Folgen wie 1234 oder aaaa."),
+];
+
+fn catalog(language: &str) -> &'static [(&'static str, &'static str)] {
+    match language {
+        "de" => DE,
+        _ => EN,
+    }
+}
+
+/// Two-letter language of a locale such as "de_AT.UTF-8", "de-DE" or "en".
+fn language_of_locale(locale: &str) -> Option<&'static str> {
+    let code = locale.trim().split(['_', '-', '.', '@']).next()?.to_ascii_lowercase();
+    LANGUAGES.iter().copied().find(|l| *l == code)
+}
+
+fn os_locale() -> Option<String> {
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(v) = std::env::var(var) {
+            if !v.trim().is_empty() && v != "C" && v != "POSIX" {
+                return Some(v);
+            }
+        }
+    }
+    #[cfg(windows)]
+    {
+        use winreg::enums::HKEY_CURRENT_USER;
+        let key = winreg::RegKey::predef(HKEY_CURRENT_USER).open_subkey(r"Control Panel\International").ok()?;
+        key.get_value::<String, _>("LocaleName").ok()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // GUI apps get no LANG; the user's locale is in the global defaults
+        let out = std::process::Command::new("defaults").args(["read", "-g", "AppleLocale"]).output().ok()?;
+        Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    None
+}
+
+/// Language of the OS locale when there is a catalog for it, else English. Read once.
+fn system_language() -> &'static str {
+    static SYSTEM: OnceLock<&'static str> = OnceLock::new();
+    SYSTEM.get_or_init(|| os_locale().as_deref().and_then(language_of_locale).unwrap_or("en"))
+}
+
+/// The language messages are shown in.
+pub fn language() -> &'static str {
+    let setting = config::get_language();
+    LANGUAGES.iter().copied().find(|l| *l == setting).unwrap_or_else(system_language)
+}
+
+/// Message `key` in `language` with `{name}` placeholders filled from `args`.
+fn translate(language: &str, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let lookup = |table: &[(&str, &'static str)]| table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+    let mut text = lookup(catalog(language)).or_else(|| lookup(EN)).unwrap_or(key).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// Message `key` in the user's language, e.g. `t("sync.too_large", &[("count", &3)])`.
+pub fn t(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate(language(), key, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_with_placeholders_and_falls_back_to_english() {
+        assert_eq!(translate("de", "sync.too_large", &[("count", &3)]), "3 Datei(en) zu groß für den Server, nicht hochgeladen");
+        assert_eq!(translate("en", "sync.too_large", &[("count", &3)]), "3 file(s) too large for the server, not uploaded");
+        assert_eq!(translate("fr", "login.invalid_credentials", &[]), "Invalid email or password.");
+        assert_eq!(translate("de", "no.such.key", &[]), "no.such.key");
+        assert_eq!(language_of_locale("de_AT.UTF-8"), Some("de"));
+        assert_eq!(language_of_locale("de-DE"), Some("de"));
+        assert_eq!(language_of_locale("fr_FR"), None);
+    }
+
+    #[test]
+    fn every_german_message_has_an_english_original() {
+        for (key, _) in DE {
+            assert!(EN.iter().any(|(k, _)| k == key), "{} missing in English", key);
+        }
+        assert_eq!(DE.len(), EN.len());
+    }
+}
@@ -0,0 +1,28 @@
+//! Brandy Box core: API client, configuration, base URL resolution and the sync engine.
+//! Has no Tauri dependencies so it can be used by the desktop shell, a CLI and tests.
+
+pub mod activity;
+pub mod api;
+pub mod app_lock;
+pub mod auto_upload;
+pub mod backend;
+pub mod config;
+pub mod crash;
+pub mod device;
+pub mod file_status;
+pub mod folder_check;
+pub mod i18n;
+pub mod local_index;
+pub mod metadata_crypto;
+pub mod metrics;
+pub mod network;
+pub mod password;
+pub mod search;
+pub mod secure_delete;
+pub mod self_test;
+pub mod snapshots;
+pub mod stats;
+pub mod sync;
+pub mod thumbnails;
+pub mod usage;
+pub mod user_import;
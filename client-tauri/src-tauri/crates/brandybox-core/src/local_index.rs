@@ -0,0 +1,220 @@
+//! Persisted index of the sync folder's directories, so a cycle only lists the directories
+//! whose contents changed. Creating, deleting or renaming an entry updates its directory's
+//! mtime; a directory whose mtime matches the index is not listed again, only the files
+//! recorded for it are stat'ed (edits change file mtimes, not directory mtimes).
+//!
+//! A full listing runs when there is no index, the sync folder changed, the last full listing
+//! is older than `FULL_SCAN_INTERVAL_SECS` or the caller asks for one (network drives, whose
+//! directory mtimes are not reliable).
+
+use crate::{config, metadata_crypto};
+use crate::sync::system_time_secs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Safety net: list every directory at least this often.
+const FULL_SCAN_INTERVAL_SECS: f64 = 3600.0;
+/// A directory modified this close to the previous scan may have changed again within the same
+/// mtime tick (FAT stores 2 s steps), so it is listed again.
+const MTIME_GRANULARITY_SECS: f64 = 2.0;
+
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct DirEntry {
+    mtime: f64,
+    files: Vec<String>,
+    subdirs: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct LocalIndex {
+    root: String,
+    /// When the index was last updated and last rebuilt from a full listing (Unix seconds).
+    scanned_at: f64,
+    full_scan_at: f64,
+    /// Relative directory path ("" for the root) → its entries.
+    dirs: HashMap<String, DirEntry>,
+}
+
+/// Result of `scan`: files with mtimes and directories that could not be read.
+#[derive(Default)]
+pub(crate) struct Scan {
+    pub files: Vec<(String, f64)>,
+    pub unreadable_dirs: Vec<String>,
+    /// Directories that were listed (not taken from the index).
+    pub dirs_listed: usize,
+}
+
+fn load() -> LocalIndex {
+    metadata_crypto::read(&config::get_local_index_path())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(index: &LocalIndex) {
+    let content = serde_json::to_string(index).unwrap_or_default();
+    if let Err(e) = metadata_crypto::write(&config::get_local_index_path(), &content) {
+        log::warn!("Could not save local index: {}", e);
+    }
+}
+
+fn join_rel(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn modified_secs(meta: &std::fs::Metadata) -> Option<f64> {
+    meta.modified().ok().map(system_time_secs)
+}
+
+/// List `dir` (relative to `root`) from disk. Err(true) when permission was denied.
+fn list_dir(root: &Path, dir: &str, mtime: f64) -> Result<DirEntry, bool> {
+    let abs = root.join(dir.replace('/', std::path::MAIN_SEPARATOR_STR));
+    let entries = std::fs::read_dir(abs).map_err(|e| e.kind() == std::io::ErrorKind::PermissionDenied)?;
+    let mut listed = DirEntry { mtime, ..DirEntry::default() };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        // Symlinks are neither followed nor synced
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if file_type.is_dir() {
+            listed.subdirs.push(name);
+        } else if file_type.is_file() {
+            listed.files.push(name);
+        }
+    }
+    listed.files.sort();
+    listed.subdirs.sort();
+    Ok(listed)
+}
+
+/// Walk the sync folder, listing only directories that changed since `index` (all of them with
+/// `full`), and update `index`. `on_file` gets the number of files found so far.
+pub(crate) fn scan_with(index: &mut LocalIndex, root: &Path, full: bool, mut on_file: impl FnMut(usize)) -> Scan {
+    let now = system_time_secs(std::time::SystemTime::now());
+    let root_str = root.to_string_lossy().into_owned();
+    let full = full || index.root != root_str || now - index.full_scan_at > FULL_SCAN_INTERVAL_SECS || index.full_scan_at > now;
+    let trusted_before = index.scanned_at - MTIME_GRANULARITY_SECS;
+    let old_dirs = std::mem::take(&mut index.dirs);
+
+    let mut scan = Scan::default();
+    let mut stack = vec![String::new()];
+    while let Some(dir) = stack.pop() {
+        let abs = root.join(dir.replace('/', std::path::MAIN_SEPARATOR_STR));
+        let Some(mtime) = std::fs::metadata(&abs).ok().and_then(|m| modified_secs(&m)) else {
+            continue;
+        };
+        let known = old_dirs.get(&dir).filter(|e| !full && e.mtime == mtime && mtime < trusted_before);
+        let entry = match known {
+            Some(entry) => entry.clone(),
+            None => match list_dir(root, &dir, mtime) {
+                Ok(entry) => {
+                    scan.dirs_listed += 1;
+                    entry
+                }
+                Err(permission_denied) => {
+                    if permission_denied {
+                        scan.unreadable_dirs.push(dir);
+                    }
+                    continue;
+                }
+            },
+        };
+        for name in &entry.files {
+            let rel = join_rel(&dir, name);
+            let path = abs.join(name);
+            if let Some(file_mtime) = std::fs::symlink_metadata(&path).ok().filter(|m| m.is_file()).and_then(|m| modified_secs(&m)) {
+                scan.files.push((rel, file_mtime));
+                on_file(scan.files.len());
+            }
+        }
+        stack.extend(entry.subdirs.iter().rev().map(|name| join_rel(&dir, name)));
+        index.dirs.insert(dir, entry);
+    }
+
+    index.root = root_str;
+    index.scanned_at = now;
+    if full {
+        index.full_scan_at = now;
+    }
+    scan
+}
+
+/// `scan_with` on the persisted index.
+pub(crate) fn scan(root: &Path, full: bool, on_file: impl FnMut(usize)) -> Scan {
+    let mut index = load();
+    let scan = scan_with(&mut index, root, full, on_file);
+    save(&index);
+    if scan.dirs_listed < index.dirs.len() {
+        log::debug!("Local scan: listed {} of {} directories", scan.dirs_listed, index.dirs.len());
+    }
+    scan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_dir_mtime(path: &Path, secs: f64) {
+        let t = std::time::UNIX_EPOCH + std::time::Duration::from_secs_f64(secs);
+        std::fs::File::open(path).unwrap().set_modified(t).unwrap();
+    }
+
+    fn paths(scan: &Scan) -> Vec<&str> {
+        let mut p: Vec<&str> = scan.files.iter().map(|(p, _)| p.as_str()).collect();
+        p.sort();
+        p
+    }
+
+    #[test]
+    fn lists_only_changed_directories_and_stats_known_files() {
+        let root = std::env::temp_dir().join(format!("brandybox-local-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("a/deep")).unwrap();
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        std::fs::write(root.join("top.txt"), b"1").unwrap();
+        std::fs::write(root.join("a/deep/x.txt"), b"1").unwrap();
+        std::fs::write(root.join("b/y.txt"), b"1").unwrap();
+        for dir in ["", "a", "a/deep", "b"] {
+            set_dir_mtime(&root.join(dir), 1_600_000_000.0);
+        }
+
+        let mut index = LocalIndex::default();
+        let first = scan_with(&mut index, &root, false, |_| {});
+        assert_eq!(paths(&first), ["a/deep/x.txt", "b/y.txt", "top.txt"]);
+        assert_eq!(first.dirs_listed, 4);
+
+        // Nothing changed: no directory is listed, files are still found
+        let again = scan_with(&mut index, &root, false, |_| {});
+        assert_eq!(again.dirs_listed, 0);
+        assert_eq!(paths(&again), paths(&first));
+
+        // An edit is seen through the file's mtime; a new file through its directory's mtime
+        std::fs::write(root.join("b/y.txt"), b"22").unwrap();
+        std::fs::write(root.join("a/deep/new.txt"), b"1").unwrap();
+        let changed = scan_with(&mut index, &root, false, |_| {});
+        assert_eq!(changed.dirs_listed, 1);
+        assert_eq!(paths(&changed), ["a/deep/new.txt", "a/deep/x.txt", "b/y.txt", "top.txt"]);
+        let y = |s: &Scan| s.files.iter().find(|(p, _)| p == "b/y.txt").unwrap().1;
+        assert!(y(&changed) > y(&first));
+
+        // A change hidden from the directory mtime is only caught by a full scan
+        std::fs::write(root.join("b/hidden.txt"), b"1").unwrap();
+        set_dir_mtime(&root.join("b"), 1_600_000_000.0);
+        assert!(!paths(&scan_with(&mut index, &root, false, |_| {})).contains(&"b/hidden.txt"));
+        let full = scan_with(&mut index, &root, true, |_| {});
+        assert!(paths(&full).contains(&"b/hidden.txt"));
+
+        // Another sync folder starts over
+        let other = root.join("a");
+        let moved = scan_with(&mut index, &other, false, |_| {});
+        assert_eq!(paths(&moved), ["deep/new.txt", "deep/x.txt"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
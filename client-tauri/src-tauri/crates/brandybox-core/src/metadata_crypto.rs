@@ -0,0 +1,163 @@
+//! Optional encryption at rest for the files in the config directory that reveal what is in the
+//! box: config.json, sync_state.json, local_index.json and recent_activity.json (and their
+//! backups). ChaCha20-Poly1305 with a random 256-bit key that the desktop shell keeps in the OS
+//! keyring and hands over with `set_key` at startup. Encrypted files start with `MAGIC`; plain JSON
+//! is still read, so switching encryption on or off only rewrites the files (`rewrite_all`).
+
+use crate::config;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+pub type Key = [u8; 32];
+
+const MAGIC: &[u8] = b"BRANDYBOX-ENC1\n";
+
+static KEY: RwLock<Option<Key>> = RwLock::new(None);
+/// An encrypted file was found but there is no key: nothing may be written over it.
+static KEY_MISSING: AtomicBool = AtomicBool::new(false);
+
+/// Use `key` for reading and writing from now on; None writes plain JSON.
+pub fn set_key(key: Option<Key>) {
+    let _ = KEY.write().map(|mut g| *g = key);
+    if key.is_some() {
+        KEY_MISSING.store(false, Ordering::SeqCst);
+    }
+}
+
+fn key() -> Option<Key> {
+    KEY.read().map(|g| *g).unwrap_or(None)
+}
+
+pub fn is_enabled() -> bool {
+    key().is_some()
+}
+
+/// True when encrypted files could not be read because the keyring has no key for them.
+pub fn key_missing() -> bool {
+    KEY_MISSING.load(Ordering::SeqCst)
+}
+
+pub fn generate_key() -> Result<Key, String> {
+    let mut key = [0u8; 32];
+    SystemRandom::new().fill(&mut key).map_err(|_| "No secure random source")?;
+    Ok(key)
+}
+
+fn seal(key: &Key, plain: &[u8]) -> Result<Vec<u8>, String> {
+    let aead = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).map_err(|_| "Invalid key")?);
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce).map_err(|_| "No secure random source")?;
+    let mut sealed = plain.to_vec();
+    aead.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(MAGIC), &mut sealed)
+        .map_err(|_| "Encryption failed")?;
+    Ok([MAGIC, &nonce, &sealed].concat())
+}
+
+#[derive(Debug, PartialEq)]
+enum Decoded {
+    Text(String),
+    NoKey,
+    Invalid,
+}
+
+/// File content to text: plain JSON as is, encrypted content with `key`.
+fn decode(data: &[u8], key: Option<&Key>) -> Decoded {
+    let Some(rest) = data.strip_prefix(MAGIC) else {
+        return String::from_utf8(data.to_vec()).map_or(Decoded::Invalid, Decoded::Text);
+    };
+    let Some(key) = key else {
+        return Decoded::NoKey;
+    };
+    if rest.len() < NONCE_LEN {
+        return Decoded::Invalid;
+    }
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let Ok(unbound) = UnboundKey::new(&CHACHA20_POLY1305, key) else {
+        return Decoded::Invalid;
+    };
+    let nonce = Nonce::try_assume_unique_for_key(nonce).expect("nonce length checked");
+    let mut buf = sealed.to_vec();
+    match LessSafeKey::new(unbound).open_in_place(nonce, Aad::from(MAGIC), &mut buf) {
+        Ok(plain) => String::from_utf8(plain.to_vec()).map_or(Decoded::Invalid, Decoded::Text),
+        Err(_) => Decoded::Invalid,
+    }
+}
+
+/// Read a metadata file, decrypting it when needed. None when it is missing, corrupt or
+/// encrypted with a key that is not available.
+pub fn read(path: &Path) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    match decode(&data, key().as_ref()) {
+        Decoded::Text(text) => Some(text),
+        Decoded::NoKey => {
+            if !KEY_MISSING.swap(true, Ordering::SeqCst) {
+                log::error!("{} is encrypted but the keyring has no key for it", path.display());
+            }
+            None
+        }
+        Decoded::Invalid => None,
+    }
+}
+
+/// Write a metadata file atomically, encrypted when a key is set. Refused while encrypted files
+/// lack their key, so defaults never replace them.
+pub fn write(path: &Path, text: &str) -> std::io::Result<()> {
+    if key_missing() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "encrypted settings cannot be read without their key",
+        ));
+    }
+    let content = match key() {
+        Some(key) => seal(&key, text.as_bytes()).map_err(std::io::Error::other)?,
+        None => text.as_bytes().to_vec(),
+    };
+    config::write_atomic(path, &content)
+}
+
+/// Switch to `new_key`: read every metadata file with the current key and write it back with the
+/// new one (None: plain JSON). Returns how many files were rewritten.
+pub fn rewrite_all(new_key: Option<Key>) -> Result<usize, String> {
+    if key_missing() {
+        return Err("Encrypted settings cannot be read without their key".to_string());
+    }
+    let files: Vec<_> = config::metadata_files()
+        .into_iter()
+        .filter(|p| p.exists())
+        .map(|p| read(&p).map(|text| (p.clone(), text)).ok_or(format!("Cannot read {}", p.display())))
+        .collect::<Result<_, _>>()?;
+    set_key(new_key);
+    for (path, text) in &files {
+        write(path, text).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    }
+    log::info!(
+        "Metadata encryption {}; {} files rewritten",
+        if new_key.is_some() { "on" } else { "off" },
+        files.len()
+    );
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_files_need_the_right_key_and_plain_json_still_reads() {
+        let key = generate_key().unwrap();
+        let sealed = seal(&key, br#"{"paths":["Photos/a.jpg"]}"#).unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert!(!String::from_utf8_lossy(&sealed).contains("Photos"));
+        assert_eq!(decode(&sealed, Some(&key)), Decoded::Text(r#"{"paths":["Photos/a.jpg"]}"#.to_string()));
+        assert_eq!(decode(&sealed, None), Decoded::NoKey);
+        assert_eq!(decode(&sealed, Some(&generate_key().unwrap())), Decoded::Invalid);
+        // Tampered content fails authentication
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(decode(&tampered, Some(&key)), Decoded::Invalid);
+        assert_eq!(decode(b"{}", Some(&key)), Decoded::Text("{}".to_string()));
+    }
+}
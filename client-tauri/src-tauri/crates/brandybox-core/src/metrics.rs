@@ -0,0 +1,116 @@
+//! Sync metrics for monitoring: lifetime counters from `stats`, the current status and queue
+//! depths, and the time since the last successful cycle. Served as JSON over IPC (`metrics`) and
+//! in the Prometheus text format over the optional local HTTP endpoint, so a homelab Prometheus
+//! can alert when a machine has not synced for hours.
+
+use crate::{file_status, stats, sync};
+use serde::Serialize;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Metrics {
+    pub cycles_total: u64,
+    pub failed_cycles_total: u64,
+    pub bytes_uploaded_total: u64,
+    pub bytes_downloaded_total: u64,
+    pub files_uploaded_total: u64,
+    pub files_downloaded_total: u64,
+    /// Unix time of the last cycle without error; None before the first one.
+    pub last_success_timestamp: Option<i64>,
+    /// Seconds since then.
+    pub last_success_age_seconds: Option<i64>,
+    pub last_attempt_timestamp: Option<i64>,
+    /// idle, syncing, synced, warning, error, auth_expired or device_revoked.
+    pub status: String,
+    pub paused: bool,
+    /// Files this cycle still has to transfer.
+    pub pending_files: usize,
+    /// Files whose last transfer failed.
+    pub failed_files: usize,
+    pub held_local_deletes: usize,
+}
+
+fn unix_time(rfc3339: Option<&str>) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(rfc3339?).ok().map(|t| t.timestamp())
+}
+
+/// Current metrics.
+pub fn collect() -> Metrics {
+    let totals = stats::get(0).lifetime;
+    let times = stats::last_sync_times();
+    let last_success = unix_time(times.last_success.as_deref());
+    let (pending_files, failed_files) = file_status::queue_depths();
+    Metrics {
+        cycles_total: totals.cycles,
+        failed_cycles_total: totals.errors,
+        bytes_uploaded_total: totals.bytes_uploaded,
+        bytes_downloaded_total: totals.bytes_downloaded,
+        files_uploaded_total: totals.files_uploaded,
+        files_downloaded_total: totals.files_downloaded,
+        last_success_timestamp: last_success,
+        last_success_age_seconds: last_success.map(|t| (chrono::Utc::now().timestamp() - t).max(0)),
+        last_attempt_timestamp: unix_time(times.last_attempt.as_deref()),
+        status: sync::get_sync_status().0,
+        paused: sync::paused_until().is_some() || sync::active_pause_window().is_some(),
+        pending_files,
+        failed_files,
+        held_local_deletes: sync::held_local_deletes(),
+    }
+}
+
+const STATUSES: &[&str] = &["idle", "syncing", "synced", "warning", "error", "auth_expired", "device_revoked"];
+
+/// `m` in the Prometheus text exposition format (metric names prefixed `brandybox_`).
+pub fn to_prometheus(m: &Metrics) -> String {
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+        out.push_str(&format!("# HELP brandybox_{name} {help}\n# TYPE brandybox_{name} {kind}\n"));
+        out.push_str(&format!("brandybox_{name}{value}\n"));
+    };
+    let plain = |v: u64| format!(" {}", v);
+    metric("sync_cycles_total", "counter", "Sync cycles run.", plain(m.cycles_total));
+    metric("sync_failures_total", "counter", "Sync cycles that ended with an error.", plain(m.failed_cycles_total));
+    metric("uploaded_bytes_total", "counter", "Bytes uploaded.", plain(m.bytes_uploaded_total));
+    metric("downloaded_bytes_total", "counter", "Bytes downloaded.", plain(m.bytes_downloaded_total));
+    metric("uploaded_files_total", "counter", "Files uploaded.", plain(m.files_uploaded_total));
+    metric("downloaded_files_total", "counter", "Files downloaded.", plain(m.files_downloaded_total));
+    if let (Some(at), Some(age)) = (m.last_success_timestamp, m.last_success_age_seconds) {
+        metric("last_success_timestamp_seconds", "gauge", "Unix time of the last successful sync.", format!(" {}", at));
+        metric("last_success_age_seconds", "gauge", "Seconds since the last successful sync.", format!(" {}", age));
+    }
+    if let Some(at) = m.last_attempt_timestamp {
+        metric("last_attempt_timestamp_seconds", "gauge", "Unix time of the last sync attempt.", format!(" {}", at));
+    }
+    metric("paused", "gauge", "1 while sync is paused.", plain(m.paused as u64));
+    metric("pending_files", "gauge", "Files the running cycle still has to transfer.", plain(m.pending_files as u64));
+    metric("failed_files", "gauge", "Files whose last transfer failed.", plain(m.failed_files as u64));
+    metric("held_local_deletes", "gauge", "Local deletes waiting for confirmation.", plain(m.held_local_deletes as u64));
+    out.push_str("# HELP brandybox_status Current sync status (1 for the active one).\n# TYPE brandybox_status gauge\n");
+    for status in STATUSES {
+        out.push_str(&format!("brandybox_status{{status=\"{}\"}} {}\n", status, (m.status == *status) as u8));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prometheus_text_has_counters_gauges_and_status_labels() {
+        let m = Metrics {
+            cycles_total: 12,
+            failed_cycles_total: 2,
+            last_success_timestamp: Some(1_700_000_000),
+            last_success_age_seconds: Some(21_600),
+            status: "error".to_string(),
+            ..Metrics::default()
+        };
+        let text = to_prometheus(&m);
+        assert!(text.contains("# TYPE brandybox_sync_cycles_total counter\nbrandybox_sync_cycles_total 12\n"));
+        assert!(text.contains("brandybox_sync_failures_total 2\n"));
+        assert!(text.contains("brandybox_last_success_age_seconds 21600\n"));
+        assert!(text.contains("brandybox_status{status=\"error\"} 1\n"));
+        assert!(text.contains("brandybox_status{status=\"synced\"} 0\n"));
+        assert!(!text.contains("last_attempt_timestamp_seconds"));
+    }
+}
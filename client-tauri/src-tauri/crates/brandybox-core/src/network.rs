@@ -0,0 +1,125 @@
+//! Resolve backend base URL: LAN vs Cloudflare (matches Python client logic).
+
+use serde::Serialize;
+
+const LAN_HOST: &str = "192.168.0.150";
+#[allow(dead_code)]
+const LAN_NETWORK_NAME: &str = "brandstaetter";
+const BACKEND_PORT: &str = "8081";
+const CLOUDFLARE_URL: &str = "https://brandybox.brandstaetter.rocks";
+
+fn is_local_network() -> bool {
+    if let Ok(override_url) = std::env::var("BRANDYBOX_BASE_URL") {
+        if !override_url.trim().is_empty() {
+            return false; // caller will use override
+        }
+    }
+    // Try LAN reachability (short timeout)
+    let url = format!("http://{}:{}/api/users/me", LAN_HOST, BACKEND_PORT);
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(2))
+        .timeout(std::time::Duration::from_secs(2))
+        .build();
+    if let Ok(c) = client {
+        if let Ok(r) = c.get(&url).send() {
+            if r.status().as_u16() == 200 || r.status().as_u16() == 401 {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Local address the OS would use to reach the Internet, or None when offline. Connecting a UDP
+/// socket only picks the route; nothing is sent.
+pub fn local_route_addr() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("1.1.1.1:53").ok()?;
+    socket.local_addr().ok().map(|a| a.ip())
+}
+
+pub fn get_base_url() -> String {
+    if let Ok(override_url) = std::env::var("BRANDYBOX_BASE_URL") {
+        let s = override_url.trim();
+        if !s.is_empty() {
+            return s.trim_end_matches('/').to_string();
+        }
+    }
+    let mode = crate::config::get_base_url_mode();
+    if mode == "manual" {
+        return crate::config::get_manual_base_url().trim_end_matches('/').to_string();
+    }
+    if is_local_network() {
+        format!("http://{}:{}", LAN_HOST, BACKEND_PORT)
+    } else {
+        CLOUDFLARE_URL.to_string()
+    }
+}
+
+/// Result of probing a manually entered base URL, for the settings UI.
+#[derive(Serialize)]
+pub struct BaseUrlCheck {
+    pub ok: bool,
+    /// URL as it would be saved (trimmed, no trailing slash).
+    pub normalized_url: String,
+    /// Step that failed: "syntax", "reachability" or "backend"; "ok" when all passed.
+    pub stage: String,
+    pub message: Option<String>,
+    pub api_version: Option<String>,
+    pub min_supported_client_version: Option<String>,
+    /// Server offers browser (SSO) sign-in.
+    pub google_signin_available: bool,
+}
+
+impl BaseUrlCheck {
+    fn failed(url: &str, stage: &str, message: String) -> Self {
+        BaseUrlCheck {
+            ok: false,
+            normalized_url: url.to_string(),
+            stage: stage.to_string(),
+            message: Some(message),
+            api_version: None,
+            min_supported_client_version: None,
+            google_signin_available: false,
+        }
+    }
+}
+
+/// Check URL syntax, that the host answers, and that it is a Brandy Box backend (`/api/meta/version`).
+pub fn validate_base_url(url: &str) -> BaseUrlCheck {
+    let normalized = url.trim().trim_end_matches('/').to_string();
+    let parsed = match reqwest::Url::parse(&normalized) {
+        Ok(u) => u,
+        Err(e) => return BaseUrlCheck::failed(&normalized, "syntax", format!("Invalid URL: {}", e)),
+    };
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return BaseUrlCheck::failed(&normalized, "syntax", "URL must start with http:// or https://".to_string());
+    }
+    if parsed.host_str().map(|h| h.is_empty()).unwrap_or(true) {
+        return BaseUrlCheck::failed(&normalized, "syntax", "URL has no host".to_string());
+    }
+    let client = crate::api::ApiClient::new(normalized.clone());
+    match client.get_version() {
+        Ok(v) => BaseUrlCheck {
+            ok: true,
+            normalized_url: normalized,
+            stage: "ok".to_string(),
+            message: None,
+            api_version: Some(v.api_version),
+            min_supported_client_version: v.min_supported_client_version,
+            google_signin_available: v.google_signin_available,
+        },
+        // An HTTP status means the host answered, but not with our version endpoint
+        Err(e) if e.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false) => BaseUrlCheck::failed(
+            &normalized,
+            "backend",
+            format!("Server answered but is not a Brandy Box backend ({})", e),
+        ),
+        Err(e) if e.contains("decod") => BaseUrlCheck::failed(
+            &normalized,
+            "backend",
+            "Server answered but is not a Brandy Box backend".to_string(),
+        ),
+        Err(e) => BaseUrlCheck::failed(&normalized, "reachability", format!("Server not reachable: {}", e)),
+    }
+}
@@ -0,0 +1,150 @@
+//! Checks for a new password before it is sent to the server: minimum length, confirmation,
+//! different from the current one, not a common password or the email, and a rough strength
+//! score (0–4, in the spirit of zxcvbn) from length, character classes and patterns.
+
+use crate::i18n;
+use serde::Serialize;
+
+pub const MIN_LENGTH: usize = 8;
+/// Lowest score accepted for a new password.
+pub const MIN_SCORE: u8 = 2;
+
+/// Lowercased passwords rejected outright (and anything made of one of them plus digits).
+const COMMON: &[&str] = &[
+    "password", "passwort", "12345678", "123456789", "1234567890", "qwertyui", "qwertz", "qwerty", "asdfghjk",
+    "iloveyou", "letmein", "welcome", "willkommen", "admin", "abc12345", "monkey", "dragon", "sunshine",
+    "football", "baseball", "princess", "trustno1", "hallo123", "brandybox", "changeme",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordIssue {
+    TooShort,
+    Mismatch,
+    SameAsCurrent,
+    Common,
+    ContainsEmail,
+    TooWeak,
+}
+
+impl PasswordIssue {
+    pub fn message(self) -> String {
+        match self {
+            PasswordIssue::TooShort => i18n::t("password.too_short", &[("min", &MIN_LENGTH)]),
+            PasswordIssue::Mismatch => i18n::t("password.mismatch", &[]),
+            PasswordIssue::SameAsCurrent => i18n::t("password.same_as_current", &[]),
+            PasswordIssue::Common => i18n::t("password.common", &[]),
+            PasswordIssue::ContainsEmail => i18n::t("password.contains_email", &[]),
+            PasswordIssue::TooWeak => i18n::t("password.too_weak", &[]),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct IssueReport {
+    pub code: PasswordIssue,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PasswordCheck {
+    /// True when there are no issues.
+    pub ok: bool,
+    /// 0 (very weak) to 4 (very strong).
+    pub score: u8,
+    pub issues: Vec<IssueReport>,
+}
+
+fn is_common(lower: &str) -> bool {
+    let stem = lower.trim_end_matches(|c: char| c.is_ascii_digit() || "!.?".contains(c));
+    COMMON.iter().any(|c| *c == lower || (!stem.is_empty() && *c == stem))
+}
+
+/// Runs of repeated or consecutive characters ("aaaa", "1234", "dcba") count as one character.
+fn effective_length(password: &str) -> usize {
+    let chars: Vec<i64> = password.chars().map(|c| c as i64).collect();
+    let continues_run = |w: &[i64]| w[2] - w[1] == w[1] - w[0] && (w[2] - w[1]).abs() <= 1;
+    chars.len() - chars.windows(3).filter(|w| continues_run(w)).count()
+}
+
+/// Rough strength: bits from the effective length and character classes used, mapped to 0–4.
+pub fn score(password: &str) -> u8 {
+    if is_common(&password.to_lowercase()) {
+        return 0;
+    }
+    let pool: u32 = [
+        (password.chars().any(|c| c.is_ascii_lowercase()), 26),
+        (password.chars().any(|c| c.is_ascii_uppercase()), 26),
+        (password.chars().any(|c| c.is_ascii_digit()), 10),
+        (password.chars().any(|c| c.is_ascii_punctuation() || c == ' '), 33),
+        (!password.is_ascii(), 100),
+    ]
+    .iter()
+    .filter(|(used, _)| *used)
+    .map(|(_, size)| size)
+    .sum();
+    let bits = effective_length(password) as f64 * f64::from(pool.max(1)).log2();
+    match bits {
+        b if b < 28.0 => 0,
+        b if b < 40.0 => 1,
+        b if b < 60.0 => 2,
+        b if b < 80.0 => 3,
+        _ => 4,
+    }
+}
+
+/// Check `new` against the rules. `confirmation` is the repeated entry when the form has one;
+/// `email` the account's address, if known.
+pub fn check(current: &str, new: &str, confirmation: Option<&str>, email: Option<&str>) -> PasswordCheck {
+    let mut issues = Vec::new();
+    let lower = new.to_lowercase();
+    if new.trim().chars().count() < MIN_LENGTH {
+        issues.push(PasswordIssue::TooShort);
+    }
+    if confirmation.is_some_and(|c| c != new) {
+        issues.push(PasswordIssue::Mismatch);
+    }
+    if !current.is_empty() && new == current {
+        issues.push(PasswordIssue::SameAsCurrent);
+    }
+    if is_common(&lower) {
+        issues.push(PasswordIssue::Common);
+    }
+    let local_part = email.and_then(|e| e.split('@').next()).map(str::to_lowercase).unwrap_or_default();
+    if local_part.len() >= 3 && lower.contains(&local_part) {
+        issues.push(PasswordIssue::ContainsEmail);
+    }
+    let score = score(new);
+    if score < MIN_SCORE && !issues.contains(&PasswordIssue::Common) && !issues.contains(&PasswordIssue::TooShort) {
+        issues.push(PasswordIssue::TooWeak);
+    }
+    PasswordCheck {
+        ok: issues.is_empty(),
+        score,
+        issues: issues.into_iter().map(|code| IssueReport { code, message: code.message() }).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(c: &PasswordCheck) -> Vec<PasswordIssue> {
+        c.issues.iter().map(|i| i.code).collect()
+    }
+
+    #[test]
+    fn rejects_weak_reused_and_mismatched_passwords() {
+        use PasswordIssue::*;
+        assert_eq!(codes(&check("old", "short", None, None)), [TooShort]);
+        assert_eq!(codes(&check("old", "Password123!", None, None)), [Common]);
+        assert_eq!(codes(&check("old", "aaaaaaaaaaaa", None, None)), [TooWeak]);
+        assert_eq!(codes(&check("old", "abcdefgh1234", None, None)), [TooWeak]);
+        assert_eq!(codes(&check("Tr4vel-Mug-Rain", "Tr4vel-Mug-Rain", None, None)), [SameAsCurrent]);
+        assert_eq!(codes(&check("old", "Tr4vel-Mug-Rain", Some("Tr4vel-Mug-Rian"), None)), [Mismatch]);
+        assert_eq!(codes(&check("old", "markus-Tr4vel-Mug", None, Some("markus@example.com"))), [ContainsEmail]);
+        let good = check("old", "Tr4vel-Mug-Rain", Some("Tr4vel-Mug-Rain"), Some("markus@example.com"));
+        assert!(good.ok && good.score >= 3, "{:?}", good);
+        assert!(score("correct horse battery staple") >= 3);
+    }
+}
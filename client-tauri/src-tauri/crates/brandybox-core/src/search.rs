@@ -0,0 +1,103 @@
+//! Find synced files by name across the local folder and the server listing (ignore rules
+//! apply). Every word of the query must occur in the path; matches in the file name rank higher
+//! than matches in folder names.
+
+use crate::sync;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchResult {
+    pub path: String,
+    /// Present in the local sync folder.
+    pub local: bool,
+    /// Present on the server.
+    pub remote: bool,
+    pub score: u32,
+}
+
+/// Rank `path` for the lowercase query `terms`; None when a term does not occur.
+fn score(path: &str, terms: &[String]) -> Option<u32> {
+    let lower = path.to_lowercase();
+    let name = lower.rsplit('/').next().unwrap_or(&lower);
+    let stem = name.rsplit_once('.').map(|(s, _)| s).unwrap_or(name);
+    let mut total = 0;
+    for term in terms {
+        total += if stem == term || name == term {
+            100
+        } else if name.starts_with(term.as_str()) {
+            60
+        } else if name.contains(term.as_str()) {
+            40
+        } else if lower.contains(term.as_str()) {
+            10
+        } else {
+            return None;
+        };
+    }
+    Some(total)
+}
+
+/// Search `local` and `remote` paths; best matches first, then shorter paths, at most `limit`.
+pub fn search_paths<'a>(
+    query: &str,
+    local: impl IntoIterator<Item = &'a str>,
+    remote: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+) -> Vec<SearchResult> {
+    let terms: Vec<String> = query.split_whitespace().map(|t| t.to_lowercase()).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut found: BTreeMap<&str, SearchResult> = BTreeMap::new();
+    let sides = local.into_iter().map(|p| (p, true)).chain(remote.into_iter().map(|p| (p, false)));
+    for (path, is_local) in sides {
+        if sync::is_ignored(path) {
+            continue;
+        }
+        if let Some(entry) = found.get_mut(path) {
+            entry.local |= is_local;
+            entry.remote |= !is_local;
+            continue;
+        }
+        if let Some(score) = score(path, &terms) {
+            found.insert(path, SearchResult { path: path.to_string(), local: is_local, remote: !is_local, score });
+        }
+    }
+    let mut results: Vec<SearchResult> = found.into_values().collect();
+    results.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.len().cmp(&b.path.len())));
+    results.truncate(limit);
+    results
+}
+
+/// Relative paths of the files in the local sync folder.
+pub fn local_paths(root: &Path) -> Vec<String> {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| Some(e.path().strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_name_matches_and_merges_sides() {
+        let local = ["Taxes/2024/receipt.pdf", "notes/receipts.txt", "Photos/Thumbs.db"];
+        let remote = ["Taxes/2024/receipt.pdf", "receipt.pdf", "Receipts old/scan.png", "other.txt"];
+        let r = search_paths("receipt", local, remote, 10);
+        let paths: Vec<&str> = r.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["receipt.pdf", "Taxes/2024/receipt.pdf", "notes/receipts.txt", "Receipts old/scan.png"]);
+        assert!(r[1].local && r[1].remote);
+        assert!(!r[0].local && r[0].remote);
+        assert_eq!(search_paths("2024 receipt", local, remote, 10).len(), 1);
+        assert!(search_paths("thumbs", local, remote, 10).is_empty());
+        assert!(search_paths("  ", local, remote, 10).is_empty());
+    }
+}
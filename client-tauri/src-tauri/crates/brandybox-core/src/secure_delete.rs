@@ -0,0 +1,131 @@
+//! Secure delete (opt-in): files that sync deletes locally, and delete snapshots pruned beyond
+//! their size cap, are overwritten `passes` times before they are unlinked (0x00 and 0xFF
+//! alternating, random data last), then truncated and renamed so the directory entry does not
+//! keep the name either. Only meaningful on plain, non-encrypted disks: SSD wear levelling and
+//! copy-on-write file systems (btrfs, APFS, ZFS) can keep old blocks regardless.
+
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io::{Seek, Write};
+use std::path::Path;
+
+/// Upper bound for `secure_delete_passes`; more passes add time, not safety.
+pub const MAX_PASSES: u32 = 7;
+
+const CHUNK: usize = 64 * 1024;
+
+/// Overwrite the content of `file` in place, `passes` times, syncing each pass to disk.
+fn overwrite(file: &mut std::fs::File, passes: u32) -> std::io::Result<()> {
+    let len = file.metadata()?.len();
+    let rng = SystemRandom::new();
+    let mut buf = vec![0u8; CHUNK];
+    for pass in 0..passes {
+        let random = pass + 1 == passes;
+        if !random {
+            buf.fill(if pass % 2 == 0 { 0x00 } else { 0xFF });
+        }
+        file.rewind()?;
+        let mut left = len;
+        while left > 0 {
+            let n = left.min(CHUNK as u64) as usize;
+            if random {
+                rng.fill(&mut buf[..n]).map_err(|_| std::io::Error::other("no secure random source"))?;
+            }
+            file.write_all(&buf[..n])?;
+            left -= n as u64;
+        }
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Make a read-only file writable again (owner write permission on Unix).
+fn clear_readonly(path: &Path) -> std::io::Result<()> {
+    let mut perms = std::fs::metadata(path)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        perms.set_mode(perms.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    perms.set_readonly(false);
+    std::fs::set_permissions(path, perms)
+}
+
+/// Delete one file, overwriting it first when `passes` > 0. A file that cannot be opened for
+/// writing even after clearing its read-only flag is deleted without overwriting.
+pub fn remove_file(path: &Path, passes: u32) -> std::io::Result<()> {
+    if passes == 0 {
+        return std::fs::remove_file(path);
+    }
+    let open = || std::fs::OpenOptions::new().write(true).open(path);
+    let mut file = match open().or_else(|_| clear_readonly(path).and_then(|()| open())) {
+        Ok(file) => file,
+        Err(e) => {
+            log::warn!("Cannot overwrite {}: {}; deleting without overwriting", path.display(), e);
+            return std::fs::remove_file(path);
+        }
+    };
+    overwrite(&mut file, passes.min(MAX_PASSES))?;
+    file.set_len(0)?;
+    file.sync_all()?;
+    drop(file);
+    let anonymous = path.with_file_name(format!(".brandybox-deleted-{}", uuid::Uuid::new_v4().simple()));
+    match std::fs::rename(path, &anonymous) {
+        Ok(()) => std::fs::remove_file(&anonymous),
+        Err(_) => std::fs::remove_file(path),
+    }
+}
+
+/// Delete a folder with all its contents, overwriting each file first when `passes` > 0.
+pub fn remove_dir_all(dir: &Path, passes: u32) -> std::io::Result<()> {
+    if passes > 0 {
+        for entry in walkdir::WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+            if entry.file_type().is_file() {
+                if let Err(e) = remove_file(entry.path(), passes) {
+                    log::warn!("Secure delete of {} failed: {}", entry.path().display(), e);
+                }
+            }
+        }
+    }
+    std::fs::remove_dir_all(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwrites_content_before_removing() {
+        let dir = std::env::temp_dir().join(format!("brandybox-secure-delete-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let secret = b"account number 1234".repeat(5000);
+        let file = dir.join("secret.txt");
+        std::fs::write(&file, &secret).unwrap();
+
+        let mut handle = std::fs::OpenOptions::new().write(true).open(&file).unwrap();
+        overwrite(&mut handle, 3).unwrap();
+        let after = std::fs::read(&file).unwrap();
+        assert_eq!(after.len(), secret.len());
+        assert!(!after.windows(7).any(|w| w == b"account"));
+        // Last pass is random, not a fill pattern
+        assert!(after.iter().any(|b| *b != 0x00 && *b != 0xFF));
+
+        std::fs::write(&file, &secret).unwrap();
+        remove_file(&file, 2).unwrap();
+        assert!(!file.exists());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1, "no renamed leftovers");
+
+        std::fs::write(&file, &secret).unwrap();
+        let mut perms = std::fs::metadata(&file).unwrap().permissions();
+        perms.set_readonly(true);
+        std::fs::set_permissions(&file, perms).unwrap();
+        remove_file(&file, 2).unwrap();
+        assert!(!file.exists(), "read-only files are deleted too");
+
+        std::fs::write(dir.join("sub/a.txt"), b"a").unwrap();
+        remove_dir_all(&dir, 1).unwrap();
+        assert!(!dir.exists());
+    }
+}
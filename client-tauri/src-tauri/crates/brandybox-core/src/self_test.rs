@@ -0,0 +1,187 @@
+//! Self-test for support conversations: config directory, keyring, server, login and a round
+//! trip of a small probe file (upload, download, hash comparison, delete). Each step is reported
+//! as passed, failed or skipped (when an earlier step it needs failed), with a short detail.
+
+use crate::api::ApiClient;
+use crate::backend::Backend;
+use crate::{config, network, sync};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestReport {
+    /// True when no check failed.
+    pub passed: bool,
+    /// RFC 3339.
+    pub started: String,
+    pub client_version: String,
+    pub base_url: String,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Default)]
+struct Checks(Vec<Check>);
+
+impl Checks {
+    /// Run `check`, record how it went and hand back its value when it passed.
+    fn run<T>(&mut self, name: &'static str, check: impl FnOnce() -> Result<(T, String), String>) -> Option<T> {
+        let start = Instant::now();
+        let result = check();
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let (outcome, detail, value) = match result {
+            Ok((value, detail)) => (Outcome::Passed, detail, Some(value)),
+            Err(e) => (Outcome::Failed, e, None),
+        };
+        self.0.push(Check { name, outcome, detail, duration_ms });
+        value
+    }
+
+    fn skip(&mut self, name: &'static str, reason: &str) {
+        self.0.push(Check { name, outcome: Outcome::Skipped, detail: reason.to_string(), duration_ms: 0 });
+    }
+}
+
+/// The probe came back unchanged, and the server lists it with the hashes computed here.
+fn verify_hashes<B: Backend + ?Sized>(backend: &B, local: &Path, uploaded: &[u8], downloaded: &[u8]) -> Result<((), String), String> {
+    if uploaded != downloaded {
+        return Err(format!("Downloaded {} bytes differ from the {} uploaded", downloaded.len(), uploaded.len()));
+    }
+    let sha256 = sync::compute_file_hash(local).ok_or("Cannot hash the local probe file")?;
+    let item = backend
+        .stat(sync::SELF_TEST_PROBE_NAME)?
+        .ok_or("Probe file missing from the server listing")?;
+    if let Some(server) = item.hash.as_deref().filter(|h| *h != sha256) {
+        return Err(format!("Server SHA-256 {} differs from the local {}", server, sha256));
+    }
+    let mut detail = format!("Content and SHA-256 match ({}…)", &sha256[..12]);
+    if let Some(server) = item.blake3.as_deref() {
+        let blake3 = blake3::hash(uploaded).to_hex().to_string();
+        if server != blake3 {
+            return Err(format!("Server BLAKE3 {} differs from the local {}", server, blake3));
+        }
+        detail.push_str(", BLAKE3 matches");
+    }
+    Ok(((), detail))
+}
+
+/// Upload, download, compare and delete the probe file, using `scratch` for the local copy.
+fn probe_transfer<B: Backend + ?Sized>(backend: &B, scratch: &Path, checks: &mut Checks) {
+    let probe = sync::SELF_TEST_PROBE_NAME;
+    let local = scratch.join(probe);
+    let uploaded = checks.run("upload", || {
+        let content = format!("Brandy Box self-test {}\n", chrono::Utc::now().to_rfc3339()).into_bytes();
+        std::fs::create_dir_all(scratch).map_err(|e| format!("Cannot create {}: {}", scratch.display(), e))?;
+        std::fs::write(&local, &content).map_err(|e| format!("Cannot write {}: {}", local.display(), e))?;
+        backend.upload_file_from_path(probe, &local, None)?;
+        let detail = format!("{} bytes uploaded as {}", content.len(), probe);
+        Ok((content, detail))
+    });
+    match uploaded {
+        Some(content) => {
+            let downloaded = checks.run("download", || {
+                let body = backend.download_file(probe)?;
+                let detail = format!("{} bytes downloaded", body.len());
+                Ok((body, detail))
+            });
+            match downloaded {
+                Some(body) => {
+                    checks.run("hash", || verify_hashes(backend, &local, &content, &body));
+                }
+                None => checks.skip("hash", "Download failed"),
+            }
+            checks.run("cleanup", || {
+                backend.delete_file(probe)?;
+                Ok(((), "Probe file deleted from the server".to_string()))
+            });
+        }
+        None => {
+            for name in ["download", "hash", "cleanup"] {
+                checks.skip(name, "Upload failed");
+            }
+        }
+    }
+    let _ = std::fs::remove_dir_all(scratch);
+}
+
+/// Run all checks. `keyring` checks credential storage (it lives in the desktop shell), `login`
+/// returns an authenticated client.
+pub fn run(
+    keyring: impl FnOnce() -> Result<String, String>,
+    login: impl FnOnce() -> Result<ApiClient, String>,
+) -> SelfTestReport {
+    let started = chrono::Utc::now().to_rfc3339();
+    let base_url = network::get_base_url();
+    let mut checks = Checks::default();
+    checks.run("config", || config::check_config().map(|d| ((), d)));
+    checks.run("keyring", || keyring().map(|d| ((), d)));
+    let reachable = checks.run("server", || {
+        let version = ApiClient::new(base_url.clone()).get_version()?;
+        Ok(((), format!("{} reachable, API {}", base_url, version.api_version)))
+    });
+    let client = match reachable {
+        Some(()) => checks.run("login", || login().map(|c| (c, "Access token obtained".to_string()))),
+        None => {
+            checks.skip("login", "Server not reachable");
+            None
+        }
+    };
+    match client {
+        Some(client) => probe_transfer(&client, &config::get_config_dir().join("self_test"), &mut checks),
+        None => {
+            for name in ["upload", "download", "hash", "cleanup"] {
+                checks.skip(name, "Not logged in");
+            }
+        }
+    }
+    let passed = checks.0.iter().all(|c| c.outcome != Outcome::Failed);
+    log::info!("Self-test {}", if passed { "passed" } else { "failed" });
+    for c in checks.0.iter().filter(|c| c.outcome != Outcome::Passed) {
+        log::info!("Self-test {}: {:?} – {}", c.name, c.outcome, c.detail);
+    }
+    SelfTestReport { passed, started, client_version: env!("CARGO_PKG_VERSION").to_string(), base_url, checks: checks.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+
+    #[test]
+    fn probe_round_trip_passes_and_leaves_nothing_behind() {
+        let scratch = std::env::temp_dir().join(format!("brandybox-self-test-{}", std::process::id()));
+        let backend = MemoryBackend::new();
+        let mut checks = Checks::default();
+        probe_transfer(&backend, &scratch, &mut checks);
+        let names: Vec<_> = checks.0.iter().map(|c| (c.name, c.outcome)).collect();
+        assert_eq!(
+            names,
+            [("upload", Outcome::Passed), ("download", Outcome::Passed), ("hash", Outcome::Passed), ("cleanup", Outcome::Passed)]
+        );
+        assert!(backend.list_files().unwrap().is_empty());
+        assert!(!scratch.exists());
+
+        // Full box: the upload fails and the dependent steps are skipped
+        let full = MemoryBackend::with_quota(1);
+        let mut checks = Checks::default();
+        probe_transfer(&full, &scratch, &mut checks);
+        assert_eq!(checks.0[0].outcome, Outcome::Failed);
+        assert!(checks.0[1..].iter().all(|c| c.outcome == Outcome::Skipped));
+    }
+}
@@ -0,0 +1,146 @@
+//! Delete snapshots (opt-in): right before sync deletes local files because they were deleted
+//! on the server, they are copied into `delete_snapshots/<date>_<time>/` in the config directory,
+//! keeping their paths. A wrong delete can be undone by copying them back. The oldest snapshots
+//! are removed once all of them together exceed `config::get_delete_snapshot_limit_bytes`.
+
+use crate::{config, secure_delete};
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    /// Folder name, e.g. "2026-03-01_142501"; sorts by time.
+    pub name: String,
+    pub path: String,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+fn dir_usage(dir: &Path) -> (u64, u64) {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .fold((0, 0), |(files, bytes), e| (files + 1, bytes + e.metadata().map(|m| m.len()).unwrap_or(0)))
+}
+
+fn list_in(base: &Path) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = std::fs::read_dir(base)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| {
+            let (files, bytes) = dir_usage(&e.path());
+            Snapshot { name: e.file_name().to_string_lossy().into_owned(), path: e.path().to_string_lossy().into_owned(), files, bytes }
+        })
+        .collect();
+    snapshots.sort_by(|a, b| b.name.cmp(&a.name));
+    snapshots
+}
+
+/// Snapshots, newest first.
+pub fn list() -> Vec<Snapshot> {
+    list_in(&config::get_delete_snapshots_dir())
+}
+
+/// Remove the oldest snapshots until all fit in `limit_bytes`. The newest is always kept, even
+/// when it alone is larger.
+fn prune(base: &Path, limit_bytes: u64) {
+    let snapshots = list_in(base);
+    let mut total: u64 = snapshots.iter().map(|s| s.bytes).sum();
+    for old in snapshots.iter().skip(1).rev() {
+        if total <= limit_bytes {
+            break;
+        }
+        match secure_delete::remove_dir_all(Path::new(&old.path), config::get_secure_delete_passes()) {
+            Ok(()) => {
+                log::info!("Removed delete snapshot {} ({} bytes) to stay under the size cap", old.name, old.bytes);
+                total -= old.bytes;
+            }
+            Err(e) => log::warn!("Could not remove delete snapshot {}: {}", old.name, e),
+        }
+    }
+}
+
+/// Copy `paths` (relative to `local_root`) into a new snapshot under `base`. Returns the paths
+/// that could not be copied; they must not be deleted.
+fn preserve_in(base: &Path, local_root: &Path, paths: &[String], limit_bytes: u64) -> Vec<String> {
+    let stamp = chrono::Local::now().format("%Y-%m-%d_%H%M%S").to_string();
+    let mut dir = base.join(&stamp);
+    let mut n = 2;
+    while dir.exists() {
+        dir = base.join(format!("{}-{}", stamp, n));
+        n += 1;
+    }
+    let mut failed = Vec::new();
+    let mut copied_count = 0;
+    for rel in paths {
+        let native = rel.replace('/', std::path::MAIN_SEPARATOR_STR);
+        let src = local_root.join(&native);
+        if !src.is_file() {
+            continue;
+        }
+        let dst = dir.join(&native);
+        let copied = dst
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::copy(&src, &dst))
+            .and_then(|_| {
+                let modified = std::fs::metadata(&src)?.modified()?;
+                std::fs::File::options().write(true).open(&dst)?.set_modified(modified)
+            });
+        match copied {
+            Ok(()) => copied_count += 1,
+            Err(e) => {
+                log::warn!("Delete snapshot: could not copy {}: {}; keeping the file", rel, e);
+                failed.push(rel.clone());
+            }
+        }
+    }
+    if copied_count > 0 {
+        log::info!("Delete snapshot {}: copied {} file(s) before deleting them", dir.display(), copied_count);
+        prune(base, limit_bytes);
+    }
+    failed
+}
+
+/// `preserve_in` on the configured snapshot folder and size cap.
+pub(crate) fn preserve(local_root: &Path, paths: &[String]) -> Vec<String> {
+    preserve_in(&config::get_delete_snapshots_dir(), local_root, paths, config::get_delete_snapshot_limit_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_files_with_paths_and_prunes_oldest_beyond_cap() {
+        let base = std::env::temp_dir().join(format!("brandybox-snapshots-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let root = base.join("root");
+        let snaps = base.join("snapshots");
+        std::fs::create_dir_all(root.join("photos")).unwrap();
+        std::fs::write(root.join("photos/a.jpg"), vec![1u8; 600]).unwrap();
+        std::fs::write(root.join("b.txt"), b"b").unwrap();
+
+        let paths = vec!["photos/a.jpg".to_string(), "b.txt".to_string(), "gone.txt".to_string()];
+        assert!(preserve_in(&snaps, &root, &paths, 10_000).is_empty());
+        let first = list_in(&snaps);
+        assert_eq!((first.len(), first[0].files, first[0].bytes), (1, 2, 601));
+        let copy = Path::new(&first[0].path).join("photos").join("a.jpg");
+        assert_eq!(std::fs::read(&copy).unwrap().len(), 600);
+        assert_eq!(
+            std::fs::metadata(&copy).unwrap().modified().unwrap(),
+            std::fs::metadata(root.join("photos/a.jpg")).unwrap().modified().unwrap()
+        );
+
+        // A later snapshot gets its own folder (also within the same second); over the cap,
+        // the older one is removed
+        preserve_in(&snaps, &root, &paths[..1], 1_000);
+        let after = list_in(&snaps);
+        assert_eq!(after.len(), 1);
+        assert_ne!(after[0].name, first[0].name);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
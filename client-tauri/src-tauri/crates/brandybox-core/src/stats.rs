@@ -0,0 +1,155 @@
+//! Lifetime and per-day sync statistics, persisted to sync_stats.json in the config dir.
+
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Per-day entries older than this are dropped when saving.
+const STATS_DAYS_KEPT: usize = 90;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncTotals {
+    pub bytes_uploaded: u64,
+    pub bytes_downloaded: u64,
+    pub files_uploaded: u64,
+    pub files_downloaded: u64,
+    pub cycles: u64,
+    pub errors: u64,
+}
+
+impl SyncTotals {
+    fn add(&mut self, other: &SyncTotals) {
+        self.bytes_uploaded += other.bytes_uploaded;
+        self.bytes_downloaded += other.bytes_downloaded;
+        self.files_uploaded += other.files_uploaded;
+        self.files_downloaded += other.files_downloaded;
+        self.cycles += other.cycles;
+        self.errors += other.errors;
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncStats {
+    /// RFC 3339 time of the first recorded cycle.
+    pub since: Option<String>,
+    pub lifetime: SyncTotals,
+    /// Keyed by local date (YYYY-MM-DD).
+    pub days: BTreeMap<String, SyncTotals>,
+    /// RFC 3339 time of the last cycle, successful or not.
+    pub last_attempt: Option<String>,
+    /// RFC 3339 time of the last cycle that completed without error.
+    pub last_success: Option<String>,
+}
+
+static STATS: Mutex<Option<SyncStats>> = Mutex::new(None);
+
+fn load() -> SyncStats {
+    std::fs::read_to_string(config::get_stats_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(stats: &SyncStats) {
+    let content = serde_json::to_string_pretty(stats).unwrap_or_default();
+    if let Err(e) = config::write_atomic(&config::get_stats_path(), content.as_bytes()) {
+        log::warn!("Could not save sync statistics: {}", e);
+    }
+}
+
+fn record(cycle: SyncTotals) {
+    let mut guard = match STATS.lock() {
+        Ok(g) => g,
+        Err(_) => return,
+    };
+    let stats = guard.get_or_insert_with(load);
+    let now = chrono::Local::now();
+    if stats.since.is_none() {
+        stats.since = Some(now.to_rfc3339());
+    }
+    stats.last_attempt = Some(now.to_rfc3339());
+    if cycle.errors == 0 {
+        stats.last_success = Some(now.to_rfc3339());
+    }
+    stats.lifetime.add(&cycle);
+    stats
+        .days
+        .entry(now.format("%Y-%m-%d").to_string())
+        .or_default()
+        .add(&cycle);
+    while stats.days.len() > STATS_DAYS_KEPT {
+        stats.days.pop_first();
+    }
+    save(stats);
+}
+
+/// Record a completed sync cycle.
+pub fn record_cycle(bytes_downloaded: u64, bytes_uploaded: u64, files_downloaded: u64, files_uploaded: u64) {
+    record(SyncTotals {
+        bytes_uploaded,
+        bytes_downloaded,
+        files_uploaded,
+        files_downloaded,
+        cycles: 1,
+        errors: 0,
+    });
+}
+
+/// Record a sync cycle that ended with an error.
+pub fn record_failed_cycle() {
+    record(SyncTotals {
+        cycles: 1,
+        errors: 1,
+        ..Default::default()
+    });
+}
+
+/// Lifetime totals plus the most recent `days` per-day entries.
+pub fn get(days: usize) -> SyncStats {
+    let mut guard = match STATS.lock() {
+        Ok(g) => g,
+        Err(_) => return SyncStats::default(),
+    };
+    let stats = guard.get_or_insert_with(load);
+    let skip = stats.days.len().saturating_sub(days);
+    SyncStats {
+        since: stats.since.clone(),
+        lifetime: stats.lifetime.clone(),
+        days: stats.days.iter().skip(skip).map(|(k, v)| (k.clone(), v.clone())).collect(),
+        last_attempt: stats.last_attempt.clone(),
+        last_success: stats.last_success.clone(),
+    }
+}
+
+/// When sync last ran and last succeeded (RFC 3339); kept across restarts.
+#[derive(Clone, Default, Serialize)]
+pub struct SyncTimes {
+    pub last_attempt: Option<String>,
+    pub last_success: Option<String>,
+    /// First recorded cycle.
+    pub since: Option<String>,
+}
+
+pub fn last_sync_times() -> SyncTimes {
+    match STATS.lock() {
+        Ok(mut g) => {
+            let stats = g.get_or_insert_with(load);
+            SyncTimes {
+                last_attempt: stats.last_attempt.clone(),
+                last_success: stats.last_success.clone(),
+                since: stats.since.clone(),
+            }
+        }
+        Err(_) => SyncTimes::default(),
+    }
+}
+
+/// Forget all statistics (e.g. after a reset).
+pub fn clear() {
+    if let Ok(mut g) = STATS.lock() {
+        *g = Some(SyncStats::default());
+    }
+}
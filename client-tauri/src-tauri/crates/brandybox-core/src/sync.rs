@@ -0,0 +1,2850 @@
+//! Sync engine: list local/remote, diff, propagate deletes, download, upload.
+//! Matches Python client logic (robust sync v2) and sync_state.json layout.
+//!
+//! Robustness: only mark paths as "in sync" when verified on both sides.
+//! Skipped downloads/uploads are excluded from state and trigger warning status.
+
+use crate::activity::{self, ActivityKind};
+use crate::api::{self, FileItem, HashAlgorithm, RequestFailure};
+use crate::backend::Backend;
+use crate::config;
+use crate::file_status;
+use crate::folder_check::{self, VolumeKind};
+use crate::i18n;
+use crate::local_index;
+use crate::metadata_crypto;
+use crate::secure_delete;
+use crate::snapshots;
+use crate::stats;
+use sha2::{Digest, Sha256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Server file name of the self-test probe (`run_self_test`); never synced, so other devices
+/// do not pick it up while it exists.
+pub const SELF_TEST_PROBE_NAME: &str = ".brandybox-self-test";
+const SYNC_IGNORE: &[&str] = &[".directory", "Thumbs.db", "Desktop.ini", ".DS_Store", SELF_TEST_PROBE_NAME];
+#[allow(dead_code)]
+const SYNC_MAX_WORKERS: usize = 8;
+/// Local and server mtimes this close (after clock correction) count as equal: the Date header
+/// has 1 s resolution and FAT/exFAT store mtimes in 2 s steps.
+const MTIME_TOLERANCE_SECS: f64 = 2.0;
+/// Prefix of BLAKE3 content hashes in listings and `file_hashes`; bare hex digests are SHA-256,
+/// the only kind before servers offered BLAKE3, so existing state files stay valid.
+const BLAKE3_PREFIX: &str = "blake3:";
+/// Mtimes this close are the same timestamp (rounding when the server stores an uploaded mtime).
+const MTIME_SAME_SECS: f64 = 0.001;
+/// A clock offset above this is logged; the user's clock is probably wrong.
+const CLOCK_SKEW_WARN_SECS: f64 = 60.0;
+/// Local deletes above this many that cover half or more of the synced files suggest a broken
+/// server listing rather than deletes elsewhere; they wait for confirmation.
+const LISTING_SHRINK_MIN: usize = 50;
+/// Tombstones are dropped after this long (30 days).
+const TOMBSTONE_TTL_SECS: f64 = 30.0 * 86400.0;
+/// Server deletes are skipped when more than this many are planned and they exceed the local
+/// file count (new device, wrong sync folder).
+const MASS_DELETE_MIN: usize = 50;
+/// On network and removable drives, skip server deletes above this many when they cover half or
+/// more of the synced files: a drive that drops out looks like a folder whose files were deleted.
+const FRAGILE_MASS_DELETE_MIN: usize = 10;
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct SyncStateFile {
+    paths: Vec<String>,
+    downloaded_paths: Vec<String>,
+    file_hashes: HashMap<String, String>,
+    /// Drive the sync folder was on at the last completed cycle.
+    volume: VolumeKind,
+    /// Paths this client deleted on the server because they were deleted locally, with when
+    /// (local Unix seconds). A server copy that is not newer than its tombstone and has the
+    /// deleted content was deleted here, not created elsewhere (e.g. another device re-uploading
+    /// from a stale state).
+    tombstones: HashMap<String, f64>,
+    /// Content hash of each tombstoned path at the time it was deleted. Server mtimes are the
+    /// uploader's, so an old mtime alone does not mean an old copy (restores, `cp -p`).
+    tombstone_hashes: HashMap<String, String>,
+}
+
+/// Cached `sync_hidden_files` setting: `is_ignored` runs for every path, so it must not read
+/// config.json each time. Cleared by `reload_ignore_settings` and at the start of every cycle.
+static SYNC_HIDDEN_FILES: std::sync::Mutex<Option<bool>> = std::sync::Mutex::new(None);
+
+fn sync_hidden_files() -> bool {
+    match SYNC_HIDDEN_FILES.lock() {
+        Ok(mut g) => *g.get_or_insert_with(config::get_sync_hidden_files),
+        Err(_) => true,
+    }
+}
+
+/// Cached user-excluded paths (`config::get_ignore_paths`), like `SYNC_HIDDEN_FILES`.
+static IGNORE_PATHS: std::sync::Mutex<Option<Vec<String>>> = std::sync::Mutex::new(None);
+
+fn is_user_ignored(normalized: &str) -> bool {
+    let Ok(mut g) = IGNORE_PATHS.lock() else {
+        return false;
+    };
+    g.get_or_insert_with(config::get_ignore_paths).iter().any(|p| {
+        normalized == p || normalized.strip_prefix(p.as_str()).is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Re-read the ignore settings from config.json (after they were changed).
+pub fn reload_ignore_settings() {
+    let _ = SYNC_HIDDEN_FILES.lock().map(|mut g| *g = None);
+    let _ = IGNORE_PATHS.lock().map(|mut g| *g = None);
+}
+
+/// Exclude a file or folder (relative to the sync folder) from sync, or include it again.
+/// Takes effect right away, also for a running cycle: its pending transfers of the path are
+/// skipped. Neither copy is deleted; an excluded path just stops being synced. Returns the
+/// normalized path.
+pub fn set_path_ignored(path: &str, ignored: bool) -> Result<String, String> {
+    let path = path.replace('\\', "/").trim_matches('/').to_string();
+    if !is_safe_relative_path(&path) {
+        return Err("Invalid path".to_string());
+    }
+    config::set_path_ignored(&path, ignored);
+    reload_ignore_settings();
+    if ignored {
+        file_status::forget(&path);
+    }
+    Ok(path)
+}
+
+/// Drive of the sync folder, from the last completed cycle (cached; `get_sync_status_payload`
+/// runs often).
+static SYNC_ROOT_VOLUME: std::sync::Mutex<Option<VolumeKind>> = std::sync::Mutex::new(None);
+
+fn sync_root_volume() -> VolumeKind {
+    match SYNC_ROOT_VOLUME.lock() {
+        Ok(mut g) => *g.get_or_insert_with(|| load_sync_state().volume),
+        Err(_) => VolumeKind::Local,
+    }
+}
+
+/// Create the sync folder if it is missing, unless it was on a network or removable drive: then
+/// the drive is probably not connected, and a new empty folder on the local disk would be
+/// filled with downloads in its place.
+pub fn ensure_sync_root(root: &Path) -> Result<(), SyncError> {
+    if root.is_dir() {
+        return Ok(());
+    }
+    if sync_root_volume().is_fragile() {
+        return Err(SyncError::new(
+            SyncErrorCode::SyncFolderUnavailable,
+            format!("Sync folder {} not found. Is the drive connected? Sync is paused until it is back", root.display()),
+        ));
+    }
+    std::fs::create_dir_all(root).map_err(|e| {
+        SyncError::new(SyncErrorCode::SyncFolderUnavailable, format!("Cannot create sync folder {}: {}", root.display(), e))
+    })
+}
+
+/// True when any component of the path is a dotfile or dotfolder.
+fn is_hidden_path(normalized: &str) -> bool {
+    normalized.split('/').any(|c| c.starts_with('.'))
+}
+
+pub(crate) fn is_ignored(path_str: &str) -> bool {
+    let normalized = path_str.replace('\\', "/");
+    if normalized.contains("/.git/") || normalized.starts_with(".git/") {
+        return true;
+    }
+    if is_hidden_path(&normalized) && !sync_hidden_files() {
+        return true;
+    }
+    if is_user_ignored(&normalized) {
+        return true;
+    }
+    let name = Path::new(&normalized).file_name().and_then(|n| n.to_str()).unwrap_or("");
+    SYNC_IGNORE.contains(&name)
+}
+
+/// True when a server path can be joined onto the sync folder: relative with forward slashes,
+/// no empty, `.` or `..` components, no backslashes or control characters (and no colons on
+/// Windows, where they would name a drive or an alternate data stream).
+pub(crate) fn is_safe_relative_path(path: &str) -> bool {
+    if path.is_empty() || path.starts_with('/') || path.contains('\\') || path.chars().any(|c| c.is_control()) {
+        return false;
+    }
+    if cfg!(windows) && path.contains(':') {
+        return false;
+    }
+    path.split('/').all(|c| !c.is_empty() && c != "." && c != "..")
+}
+
+/// Local path for a server path, or None when it is unsafe or would end up outside the sync
+/// folder (e.g. through a symlinked directory). Checks the deepest existing ancestor.
+fn resolve_in_root(local_root: &Path, path: &str) -> Option<std::path::PathBuf> {
+    if !is_safe_relative_path(path) {
+        return None;
+    }
+    let full = local_root.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+    let root = local_root.canonicalize().ok()?;
+    let existing = full.ancestors().find(|a| a.exists())?;
+    existing.canonicalize().ok()?.starts_with(&root).then_some(full)
+}
+
+/// Drop server entries with unsafe paths; returns the rejected paths.
+fn reject_unsafe_remote(remote_list: &mut Vec<crate::api::FileItem>) -> Vec<String> {
+    let mut rejected = Vec::new();
+    remote_list.retain(|i| {
+        let safe = is_safe_relative_path(&i.path);
+        if !safe {
+            rejected.push(i.path.clone());
+        }
+        safe
+    });
+    if !rejected.is_empty() {
+        log::warn!("Ignoring {} server path(s) outside the sync folder: {:?}", rejected.len(), rejected);
+    }
+    rejected
+}
+
+pub(crate) fn system_time_secs(t: std::time::SystemTime) -> f64 {
+    t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs_f64()
+}
+
+fn set_file_mtime(path: &Path, secs: f64) -> std::io::Result<()> {
+    let t = std::time::UNIX_EPOCH + std::time::Duration::from_secs_f64(secs.max(0.0));
+    std::fs::File::options().write(true).open(path)?.set_modified(t)
+}
+
+/// Local files with their mtimes, plus the directories that could not be read (permission
+/// denied; "" for the root). Their contents are unknown, so they must not count as deleted.
+/// Unchanged directories come from `local_index`; on network drives every directory is listed.
+/// With `report`, the number of files scanned so far goes to the sync progress.
+fn list_local(root: &Path, report: bool) -> (Vec<(String, f64)>, Vec<String>) {
+    let full = folder_check::volume_kind(root) == VolumeKind::Network;
+    let scan = local_index::scan(root, full, |n| {
+        let n = n as u64;
+        if report && n.is_multiple_of(SCAN_PROGRESS_EVERY) {
+            set_progress_detail("scanning", n, 0, Some(format!("Scanned {} files", format_count(n))));
+        }
+    });
+    let files = scan.files.into_iter().filter(|(path, _)| !is_ignored(path)).collect();
+    (files, scan.unreadable_dirs)
+}
+
+/// True when `path` lies inside one of the unreadable directories from `list_local`.
+fn in_unreadable_dir(path: &str, unreadable_dirs: &[String]) -> bool {
+    unreadable_dirs
+        .iter()
+        .any(|d| d.is_empty() || path.strip_prefix(d.as_str()).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// Feed the file's content to `update` piece by piece.
+fn read_file_in_pieces(path: &Path, mut update: impl FnMut(&[u8])) -> Option<()> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut buffer = [0u8; 65536]; // 64KB buffer
+    use std::io::Read;
+    loop {
+        let n = file.read(&mut buffer).ok()?;
+        if n == 0 {
+            return Some(());
+        }
+        update(&buffer[..n]);
+    }
+}
+
+/// SHA-256 hex digest of a file.
+pub(crate) fn compute_file_hash(path: &Path) -> Option<String> {
+    let mut hasher = Sha256::new();
+    read_file_in_pieces(path, |piece| hasher.update(piece))?;
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Hash a local file with the algorithm of `like` (a server hash from `select_hashes`), in
+/// the same tagged format.
+fn compute_file_hash_like(path: &Path, like: &str) -> Option<String> {
+    if !like.starts_with(BLAKE3_PREFIX) {
+        return compute_file_hash(path);
+    }
+    let mut hasher = blake3::Hasher::new();
+    read_file_in_pieces(path, |piece| {
+        hasher.update(piece);
+    })?;
+    Some(format!("{}{}", BLAKE3_PREFIX, hasher.finalize().to_hex()))
+}
+
+/// Upper bound for hashing threads; beyond this the disk, not the CPU, is the limit.
+const HASH_WORKERS_MAX: usize = 8;
+
+/// Hash `paths` (relative, each with the server hash whose algorithm to use) on a rayon pool of
+/// up to `HASH_WORKERS_MAX` threads. Files that vanished or cannot be read are left out.
+fn hash_local_files<'a>(local_root: &Path, paths: &[(&'a str, &str)], report: bool) -> HashMap<&'a str, String> {
+    use rayon::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get()).min(HASH_WORKERS_MAX).min(paths.len());
+    let done = AtomicUsize::new(0);
+    let hash_one = |&(path, like): &(&'a str, &str)| -> Option<(&'a str, String)> {
+        let local_path = local_root.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+        let hash = if local_path.is_file() { compute_file_hash_like(&local_path, like) } else { None };
+        let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+        if report {
+            let detail = format!("Hashing {}/{} changed files", finished, paths.len());
+            set_progress_detail("hashing", finished as u64, paths.len() as u64, Some(detail));
+        }
+        hash.map(|hash| (path, hash))
+    };
+    if workers <= 1 {
+        return paths.iter().filter_map(hash_one).collect();
+    }
+    let pool = match rayon::ThreadPoolBuilder::new().num_threads(workers).build() {
+        Ok(pool) => pool,
+        Err(e) => {
+            log::warn!("Cannot start hashing threads ({}); hashing on one thread", e);
+            return paths.iter().filter_map(hash_one).collect();
+        }
+    };
+    // One file per task so a few large ones do not leave threads idle
+    let hashes = pool.install(|| paths.par_iter().with_max_len(1).filter_map(hash_one).collect());
+    if report {
+        // Threads may report out of order; end on the final count
+        let detail = format!("Hashing {}/{} changed files", paths.len(), paths.len());
+        set_progress_detail("hashing", paths.len() as u64, paths.len() as u64, Some(detail));
+    }
+    hashes
+}
+
+/// With BLAKE3 negotiated, make each listed file's `hash` its BLAKE3 hash where the server has
+/// one (files stored before keep SHA-256), and move cached SHA-256 hashes of those files over.
+fn select_hashes(remote_list: &mut [FileItem], algorithm: HashAlgorithm, cache: &mut HashMap<String, String>) {
+    if algorithm != HashAlgorithm::Blake3 {
+        return;
+    }
+    for item in remote_list.iter_mut() {
+        let Some(blake3) = &item.blake3 else {
+            continue;
+        };
+        let tagged = format!("{}{}", BLAKE3_PREFIX, blake3);
+        if let (Some(cached), Some(sha256)) = (cache.get_mut(&item.path), &item.hash) {
+            if cached == sha256 {
+                cached.clone_from(&tagged);
+            }
+        }
+        item.hash = Some(tagged);
+    }
+}
+
+/// Paths recorded as synced after the last completed cycle.
+pub(crate) fn synced_paths() -> Vec<String> {
+    load_sync_state().paths
+}
+
+fn parse_sync_state(path: &Path) -> Option<SyncStateFile> {
+    let s = metadata_crypto::read(path)?;
+    serde_json::from_str(&s).ok()
+}
+
+/// Load sync_state.json; when it is corrupt (e.g. truncated by a crash) use the backup, because
+/// an empty state would make the next cycle treat the whole box as new.
+fn load_sync_state() -> SyncStateFile {
+    let path = config::get_sync_state_path();
+    if !path.exists() {
+        return SyncStateFile::default();
+    }
+    if let Some(state) = parse_sync_state(&path) {
+        return state;
+    }
+    match parse_sync_state(&config::get_sync_state_backup_path()) {
+        Some(state) => {
+            log::warn!("sync_state.json unreadable; using the backup from the previous cycle");
+            state
+        }
+        None => {
+            log::error!("sync_state.json unreadable and no usable backup; starting from an empty state");
+            SyncStateFile::default()
+        }
+    }
+}
+
+/// Write the state atomically (temp file, fsync, rename), keeping the previous good file as backup.
+fn save_sync_state(state: &SyncStateFile) {
+    let path = config::get_sync_state_path();
+    let _ = std::fs::create_dir_all(path.parent().unwrap_or(Path::new(".")));
+    if parse_sync_state(&path).is_some() {
+        let _ = std::fs::copy(&path, config::get_sync_state_backup_path());
+    }
+    let json = serde_json::to_string_pretty(state).unwrap_or_default();
+    if let Err(e) = metadata_crypto::write(&path, &json) {
+        log::error!("Failed to write sync_state.json: {}", e);
+    }
+}
+
+/// Counts from sync_state.json (no paths), for diagnostics.
+pub fn state_summary() -> serde_json::Value {
+    let state = load_sync_state();
+    serde_json::json!({
+        "synced_paths": state.paths.len(),
+        "pending_downloaded_paths": state.downloaded_paths.len(),
+        "file_hashes": state.file_hashes.len(),
+    })
+}
+
+/// Import a Python client sync_state.json when we have no state yet, so a client switch does
+/// not treat the whole box as new. Paths are normalized to forward slashes. Returns the number
+/// of synced paths imported.
+pub fn import_legacy_state(legacy_path: &Path) -> Option<usize> {
+    let current = load_sync_state();
+    if !current.paths.is_empty() {
+        return None;
+    }
+    let s = std::fs::read_to_string(legacy_path).ok()?;
+    let mut legacy: SyncStateFile = serde_json::from_str(&s).ok()?;
+    legacy.paths = legacy.paths.iter().map(|p| p.replace('\\', "/")).collect();
+    legacy.downloaded_paths = legacy.downloaded_paths.iter().map(|p| p.replace('\\', "/")).collect();
+    legacy.file_hashes = legacy
+        .file_hashes
+        .into_iter()
+        .map(|(p, h)| (p.replace('\\', "/"), h))
+        .collect();
+    if legacy.paths.is_empty() {
+        return None;
+    }
+    let n = legacy.paths.len();
+    save_sync_state(&legacy);
+    Some(n)
+}
+
+#[derive(Clone)]
+pub struct SyncProgress {
+    pub phase: String,
+    pub current: u64,
+    pub total: u64,
+    /// Human-readable step, e.g. "Scanned 12,400 files" or "Hashing 3/58 changed files".
+    pub detail: Option<String>,
+}
+
+static SYNC_PROGRESS: std::sync::Mutex<Option<SyncProgress>> = std::sync::Mutex::new(None);
+
+type ProgressListener = Box<dyn Fn(&SyncProgress) + Send>;
+
+/// Called on progress changes (the desktop app emits a `sync-progress` event).
+static PROGRESS_LISTENER: std::sync::Mutex<Option<ProgressListener>> = std::sync::Mutex::new(None);
+/// Phase and time of the last listener call, to throttle updates within a phase.
+static PROGRESS_NOTIFIED: std::sync::Mutex<Option<(String, std::time::Instant)>> = std::sync::Mutex::new(None);
+const PROGRESS_NOTIFY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+/// The local scan reports progress every this many files.
+const SCAN_PROGRESS_EVERY: u64 = 500;
+
+pub fn set_progress_listener(listener: impl Fn(&SyncProgress) + Send + 'static) {
+    let _ = PROGRESS_LISTENER.lock().map(|mut g| *g = Some(Box::new(listener)));
+}
+
+#[derive(Clone)]
+pub enum SyncStatus {
+    Idle,
+    Syncing,
+    Synced,
+    Warning(String),
+    Error(SyncError),
+    /// Refresh token expired or was revoked; background sync stays stopped until the user logs in again.
+    AuthExpired,
+    /// The user revoked this device on the server; credentials were wiped.
+    DeviceRevoked,
+}
+
+static SYNC_STATUS: std::sync::Mutex<SyncStatus> = std::sync::Mutex::new(SyncStatus::Idle);
+
+/// Machine-readable cause of a sync error or warning, so the UI can tell quota, login and network
+/// problems apart without parsing (translated) messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncErrorCode {
+    AuthExpired,
+    DeviceRevoked,
+    /// Server unreachable, connection dropped or timed out.
+    Network,
+    /// Server answered with a 5xx or an incomplete listing.
+    Server,
+    /// Storage full: the cycle failed or uploads were skipped.
+    QuotaExceeded,
+    /// Storage usage crossed a warning threshold.
+    QuotaWarning,
+    /// Sync folder missing or emptied, e.g. a drive that is not connected.
+    SyncFolderUnavailable,
+    FileTooLarge,
+    FileSizeLimit,
+    PermissionDenied,
+    HeldDeletes,
+    UnsavedDeletes,
+    /// Files deleted on the server that could not be removed here.
+    FailedDeletes,
+    UnsafePaths,
+    SkippedDownloads,
+    SkippedUploads,
+    Unknown,
+}
+
+/// One cause of the last cycle's warning: how many files it affected and a few of them.
+#[derive(Clone, Debug, Serialize)]
+pub struct SyncIssue {
+    pub code: SyncErrorCode,
+    pub count: usize,
+    pub sample_paths: Vec<String>,
+}
+
+/// Paths listed per issue in the status payload.
+const ISSUE_SAMPLE_PATHS: usize = 5;
+
+impl SyncIssue {
+    pub fn new<'a>(code: SyncErrorCode, paths: impl IntoIterator<Item = &'a String>) -> Self {
+        let mut paths: Vec<String> = paths.into_iter().cloned().collect();
+        paths.sort();
+        SyncIssue { code, count: paths.len(), sample_paths: paths.into_iter().take(ISSUE_SAMPLE_PATHS).collect() }
+    }
+}
+
+/// Causes of the last cycle's warning; replaced by every cycle.
+static SYNC_ISSUES: std::sync::Mutex<Vec<SyncIssue>> = std::sync::Mutex::new(Vec::new());
+
+/// Add a cause found after the cycle (e.g. the quota check).
+pub fn add_sync_issue(issue: SyncIssue) {
+    let _ = SYNC_ISSUES.lock().map(|mut g| g.push(issue));
+}
+
+impl From<Option<RequestFailure>> for SyncErrorCode {
+    /// Code for a cycle that failed right after `failure` (None: the last request succeeded).
+    fn from(failure: Option<RequestFailure>) -> Self {
+        match failure {
+            Some(RequestFailure::Status(401 | 403)) => SyncErrorCode::AuthExpired,
+            Some(RequestFailure::Status(507)) => SyncErrorCode::QuotaExceeded,
+            Some(RequestFailure::Status(500..=599) | RequestFailure::IncompleteListing) => SyncErrorCode::Server,
+            Some(RequestFailure::Network) => SyncErrorCode::Network,
+            Some(RequestFailure::Status(_)) | None => SyncErrorCode::Unknown,
+        }
+    }
+}
+
+/// Why a cycle failed: the message for the user and its cause.
+#[derive(Clone, Debug)]
+pub struct SyncError {
+    pub code: SyncErrorCode,
+    pub message: String,
+}
+
+impl SyncError {
+    pub fn new(code: SyncErrorCode, message: impl Into<String>) -> Self {
+        SyncError { code, message: message.into() }
+    }
+}
+
+/// An error passed up from the backend gets the code of the request that failed, if any.
+impl From<String> for SyncError {
+    fn from(message: String) -> Self {
+        SyncError::new(SyncErrorCode::from(api::take_last_failure()), message)
+    }
+}
+
+impl From<SyncError> for String {
+    fn from(e: SyncError) -> Self {
+        e.message
+    }
+}
+
+impl std::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+pub fn get_sync_status() -> (String, Option<String>) {
+    let guard = match SYNC_STATUS.lock() {
+        Ok(g) => g,
+        Err(_) => return ("idle".to_string(), None),
+    };
+    match &*guard {
+        SyncStatus::Idle => ("idle".to_string(), None),
+        SyncStatus::Syncing => ("syncing".to_string(), None),
+        SyncStatus::Synced => ("synced".to_string(), None),
+        SyncStatus::Warning(msg) => ("warning".to_string(), Some(msg.clone())),
+        SyncStatus::Error(e) => ("error".to_string(), Some(e.message.clone())),
+        SyncStatus::AuthExpired => ("auth_expired".to_string(), Some(i18n::t("notify.auth_expired", &[]))),
+        SyncStatus::DeviceRevoked => ("device_revoked".to_string(), Some(i18n::t("notify.device_revoked", &[]))),
+    }
+}
+
+/// Without a successful cycle for this long (while cycles are attempted), sync counts as overdue.
+pub const SYNC_OVERDUE_AFTER_HOURS: i64 = 6;
+
+/// Hours since the last successful sync when it is overdue: cycles have been attempted but none
+/// succeeded for `SYNC_OVERDUE_AFTER_HOURS`. None when syncing works or was never attempted.
+pub fn overdue_hours(times: &stats::SyncTimes, now: chrono::DateTime<chrono::Utc>) -> Option<i64> {
+    let parse = |s: &Option<String>| {
+        s.as_deref()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&chrono::Utc))
+    };
+    let attempt = parse(&times.last_attempt)?;
+    // Never succeeded: count from the first recorded cycle
+    let success = parse(&times.last_success).or(parse(&times.since))?;
+    if success >= attempt && times.last_success.is_some() {
+        return None;
+    }
+    let hours = (now - success).num_hours();
+    (hours >= SYNC_OVERDUE_AFTER_HOURS).then_some(hours)
+}
+
+/// Payload for the sync-status Tauri event (status + optional message, last attempt/success).
+pub fn get_sync_status_payload() -> serde_json::Value {
+    let (status, message) = get_sync_status();
+    let error_code = match SYNC_STATUS.lock().map(|g| g.clone()) {
+        Ok(SyncStatus::AuthExpired) => Some(SyncErrorCode::AuthExpired),
+        Ok(SyncStatus::DeviceRevoked) => Some(SyncErrorCode::DeviceRevoked),
+        Ok(SyncStatus::Error(e)) => Some(e.code),
+        Ok(SyncStatus::Warning(_)) => {
+            Some(SYNC_ISSUES.lock().ok().and_then(|g| g.first().map(|i| i.code)).unwrap_or(SyncErrorCode::Unknown))
+        }
+        _ => None,
+    };
+    let details = match status.as_str() {
+        "warning" => SYNC_ISSUES.lock().map(|g| g.clone()).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let window = active_pause_window();
+    let times = stats::last_sync_times();
+    let overdue = overdue_hours(&times, chrono::Utc::now());
+    serde_json::json!({
+        "status": status,
+        "message": message,
+        "error_code": error_code,
+        "details": details,
+        "paused_until": paused_until(),
+        "paused_by_schedule_until": window.map(|w| w.end),
+        "last_attempt": times.last_attempt,
+        "last_successful_sync": times.last_success,
+        "overdue_hours": overdue,
+        "sync_folder_volume": sync_root_volume(),
+        "volume_warning": sync_root_volume().warning(),
+        "held_local_deletes": held_local_deletes(),
+        "restore_pending": config::get_restore_pending(),
+        "download_only": config::get_download_only(),
+    })
+}
+
+pub fn set_sync_status(status: SyncStatus) {
+    let _ = SYNC_STATUS.lock().map(|mut g| *g = status);
+}
+
+/// Back to idle after a successful login, so the background loop resumes.
+pub fn clear_auth_expired() {
+    let _ = SYNC_STATUS.lock().map(|mut g| {
+        if matches!(*g, SyncStatus::AuthExpired | SyncStatus::DeviceRevoked) {
+            *g = SyncStatus::Idle;
+        }
+    });
+}
+
+pub fn get_sync_progress() -> Option<SyncProgress> {
+    SYNC_PROGRESS.lock().ok().and_then(|g| g.clone())
+}
+
+/// Outcome of the last completed sync cycle (for the tray header).
+#[derive(Clone, Serialize)]
+pub struct LastSync {
+    pub at: String,
+    pub bytes_downloaded: u64,
+    pub bytes_uploaded: u64,
+}
+
+static LAST_SYNC: std::sync::Mutex<Option<LastSync>> = std::sync::Mutex::new(None);
+
+const RECENT_FILES_MAX: usize = 10;
+
+/// Background sync is paused until this time (manual "Sync now" still runs).
+static PAUSED_UNTIL: std::sync::Mutex<Option<chrono::DateTime<chrono::Utc>>> = std::sync::Mutex::new(None);
+
+pub fn get_last_sync() -> Option<LastSync> {
+    LAST_SYNC.lock().ok().and_then(|g| g.clone())
+}
+
+/// Most recently transferred paths, newest first.
+pub fn get_recent_files() -> Vec<String> {
+    activity::recent_transferred_paths(RECENT_FILES_MAX)
+}
+
+pub fn pause_for(minutes: u64) {
+    let until = chrono::Utc::now() + chrono::Duration::minutes(minutes as i64);
+    let _ = PAUSED_UNTIL.lock().map(|mut g| *g = Some(until));
+}
+
+pub fn resume() {
+    let _ = PAUSED_UNTIL.lock().map(|mut g| *g = None);
+}
+
+/// End of the current pause (RFC 3339), or None when not paused. Expired pauses are cleared.
+pub fn paused_until() -> Option<String> {
+    let mut guard = PAUSED_UNTIL.lock().ok()?;
+    match *guard {
+        Some(until) if until > chrono::Utc::now() => Some(until.to_rfc3339()),
+        Some(_) => {
+            *guard = None;
+            None
+        }
+        None => None,
+    }
+}
+
+/// Parse "HH:MM" (24 h) into minutes since midnight.
+pub fn parse_hhmm(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// True when `minute` (since local midnight) on ISO `weekday` (1 = Monday) falls into `window`.
+/// For windows wrapping midnight the day is the one the window started on.
+pub fn in_sync_window(window: &config::SyncWindow, weekday: u32, minute: u32) -> bool {
+    let (Some(start), Some(end)) = (parse_hhmm(&window.start), parse_hhmm(&window.end)) else {
+        return false;
+    };
+    let day_matches = |d: u32| window.days.is_empty() || window.days.contains(&d);
+    let previous_day = if weekday == 1 { 7 } else { weekday - 1 };
+    match start.cmp(&end) {
+        std::cmp::Ordering::Equal => false,
+        std::cmp::Ordering::Less => day_matches(weekday) && minute >= start && minute < end,
+        std::cmp::Ordering::Greater => {
+            (day_matches(weekday) && minute >= start) || (day_matches(previous_day) && minute < end)
+        }
+    }
+}
+
+/// The configured pause window active right now, if any. Background sync skips while one is.
+pub fn active_pause_window() -> Option<config::SyncWindow> {
+    use chrono::{Datelike, Timelike};
+    let now = chrono::Local::now();
+    let (weekday, minute) = (now.weekday().number_from_monday(), now.hour() * 60 + now.minute());
+    config::get_sync_pause_windows()
+        .into_iter()
+        .find(|w| in_sync_window(w, weekday, minute))
+}
+
+/// Start time and bytes transferred so far in the running cycle (for the transfer rate).
+static CYCLE_TRANSFER: std::sync::Mutex<Option<(std::time::Instant, u64)>> = std::sync::Mutex::new(None);
+
+fn add_transferred(bytes: u64) {
+    let _ = CYCLE_TRANSFER.lock().map(|mut g| {
+        if let Some((_, total)) = g.as_mut() {
+            *total += bytes;
+        }
+    });
+}
+
+/// Average transfer rate of the running cycle in bytes per second.
+pub fn transfer_rate() -> Option<f64> {
+    let guard = CYCLE_TRANSFER.lock().ok()?;
+    let (start, bytes) = (*guard)?;
+    let secs = start.elapsed().as_secs_f64();
+    if secs < 1.0 || bytes == 0 {
+        return None;
+    }
+    Some(bytes as f64 / secs)
+}
+
+fn format_rate(bytes_per_sec: f64) -> String {
+    if bytes_per_sec >= 1024.0 * 1024.0 {
+        format!("{:.1} MB/s", bytes_per_sec / (1024.0 * 1024.0))
+    } else {
+        format!("{:.0} KB/s", bytes_per_sec / 1024.0)
+    }
+}
+
+/// One-line status for the tray tooltip, e.g. "Syncing 45/120 files — 13.0 MB/s".
+pub fn status_summary() -> String {
+    let (status, message) = get_sync_status();
+    let text = match status.as_str() {
+        "syncing" => match get_sync_progress() {
+            Some(p) if p.total > 0 => {
+                let mut t = i18n::t("status.syncing_files", &[("current", &p.current), ("total", &p.total)]);
+                if let Some(rate) = transfer_rate() {
+                    t.push_str(&format!(" — {}", format_rate(rate)));
+                }
+                t
+            }
+            Some(SyncProgress { detail: Some(detail), .. }) => i18n::t("status.syncing_detail", &[("detail", &detail)]),
+            _ => i18n::t("status.checking", &[]),
+        },
+        "error" | "warning" | "auth_expired" | "device_revoked" => {
+            let key = if status == "warning" { "status.warning" } else { "status.error" };
+            let text = i18n::t(key, &[("message", &message.unwrap_or_default())]);
+            match overdue_hours(&stats::last_sync_times(), chrono::Utc::now()) {
+                Some(h) => i18n::t("status.overdue", &[("status", &text), ("hours", &h)]),
+                None => text,
+            }
+        }
+        _ => {
+            let last = stats::last_sync_times()
+                .last_success
+                .and_then(|at| chrono::DateTime::parse_from_rfc3339(&at).ok())
+                .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string());
+            match last {
+                Some(t) => i18n::t("status.up_to_date_at", &[("time", &t)]),
+                None => i18n::t("status.up_to_date", &[]),
+            }
+        }
+    };
+    if status == "syncing" {
+        return text;
+    }
+    if paused_until().is_some() {
+        return i18n::t("status.paused", &[("status", &text)]);
+    }
+    match active_pause_window() {
+        Some(w) => i18n::t("status.paused_until", &[("status", &text), ("time", &w.end)]),
+        None => text,
+    }
+}
+
+fn set_progress(phase: &str, current: u64, total: u64) {
+    set_progress_detail(phase, current, total, None);
+}
+
+fn set_progress_detail(phase: &str, current: u64, total: u64, detail: Option<String>) {
+    let progress = SyncProgress { phase: phase.to_string(), current, total, detail };
+    let _ = SYNC_PROGRESS.lock().map(|mut g| *g = Some(progress.clone()));
+    let due = match PROGRESS_NOTIFIED.lock() {
+        Ok(mut last) => {
+            let due = match &*last {
+                Some((p, at)) => p != phase || at.elapsed() >= PROGRESS_NOTIFY_INTERVAL || (total > 0 && current >= total),
+                None => true,
+            };
+            if due {
+                *last = Some((phase.to_string(), std::time::Instant::now()));
+            }
+            due
+        }
+        Err(_) => false,
+    };
+    if due {
+        if let Ok(listener) = PROGRESS_LISTENER.lock() {
+            if let Some(f) = listener.as_ref() {
+                f(&progress);
+            }
+        }
+    }
+}
+
+/// 12400 -> "12,400".
+fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// What one sync cycle will do. Built from the local and remote listings and the last synced paths.
+struct SyncPlan {
+    /// Deepest paths first so directories empty out bottom-up.
+    to_del_remote: Vec<String>,
+    to_del_local: Vec<String>,
+    to_download: Vec<String>,
+    to_upload: Vec<String>,
+    /// Paths present on both sides after deletes (already in sync unless transferred below).
+    base_synced: HashSet<String>,
+    /// Files whose local content already matches the server hash (no transfer needed).
+    verified_hashes: Vec<(String, String)>,
+    /// Case-only renames made locally, applied on the server: (old, new).
+    rename_remote: Vec<(String, String)>,
+    /// Case-only renames made on the server, applied locally: (old, new).
+    rename_local: Vec<(String, String)>,
+    total_work: u64,
+}
+
+/// Server clock minus local clock for this cycle. Offsets within the tolerance are measurement
+/// noise and ignored; an unknown offset counts as zero.
+fn measure_clock_offset<B: Backend + ?Sized>(client: &B) -> f64 {
+    let offset = client.clock_offset().unwrap_or(0.0);
+    if offset.abs() >= CLOCK_SKEW_WARN_SECS {
+        log::warn!("Local clock differs from server by {:.0} s; correcting mtime comparisons", offset);
+    }
+    if offset.abs() <= MTIME_TOLERANCE_SECS {
+        0.0
+    } else {
+        offset
+    }
+}
+
+/// Compare a local mtime with a server mtime, converting the latter to local clock time first.
+/// Differences within `MTIME_TOLERANCE_SECS` yield `Equal`.
+fn compare_mtimes(local: f64, remote: f64, clock_offset: f64) -> std::cmp::Ordering {
+    let remote_local = remote - clock_offset;
+    if (local - remote_local).abs() <= MTIME_TOLERANCE_SECS {
+        std::cmp::Ordering::Equal
+    } else {
+        local.total_cmp(&remote_local)
+    }
+}
+
+impl SyncPlan {
+    /// Files inside unreadable directories look deleted locally but are not: keep them on the
+    /// server and do not download over whatever is there.
+    fn skip_unreadable_dirs(&mut self, unreadable_dirs: &[String]) {
+        if unreadable_dirs.is_empty() {
+            return;
+        }
+        log::warn!("Cannot read {} local folder(s): {:?}", unreadable_dirs.len(), unreadable_dirs);
+        self.to_del_remote.retain(|p| !in_unreadable_dir(p, unreadable_dirs));
+        self.to_download.retain(|p| !in_unreadable_dir(p, unreadable_dirs));
+    }
+}
+
+impl SyncPlan {
+    /// A listing that is empty, or lacks more than `LISTING_SHRINK_MIN` files and half or more
+    /// of the synced ones, more likely comes from a server fault than from deletes on other
+    /// devices. Unless `confirmed`, keep the local files and return the paths held back.
+    fn hold_implausible_local_deletes(&mut self, remote_count: usize, synced_count: usize, confirmed: bool) -> Vec<String> {
+        let n = self.to_del_local.len();
+        let implausible = n > 0 && (remote_count == 0 || (n > LISTING_SHRINK_MIN && n * 2 >= synced_count));
+        if !implausible || confirmed {
+            return Vec::new();
+        }
+        log::warn!(
+            "Server listing has {} files but {} of {} synced files are missing from it; keeping the local copies until confirmed",
+            remote_count,
+            n,
+            synced_count
+        );
+        self.total_work = self.total_work.saturating_sub(n as u64);
+        std::mem::take(&mut self.to_del_local)
+    }
+
+    /// Download-only cycle (restore, kiosk mode): nothing is uploaded, deleted or renamed on the
+    /// server. Files deleted locally come back from the server; case-only renames made here stay
+    /// synced under the old name. With `mirror`, files edited locally are replaced by the server
+    /// copy (returned), otherwise they are kept until two-way sync resumes.
+    fn download_only(&mut self, mirror: bool) -> Vec<String> {
+        for (old, new) in std::mem::take(&mut self.rename_remote) {
+            self.base_synced.remove(&new);
+            self.base_synced.insert(old);
+        }
+        let mut uploads = std::mem::take(&mut self.to_upload);
+        // Edited files are on both sides; new local files are left alone
+        uploads.retain(|p| mirror && self.base_synced.contains(p));
+        self.to_download.extend(uploads.iter().cloned());
+        // Paths also in to_del_local are gone on both sides
+        let gone: HashSet<&String> = self.to_del_local.iter().collect();
+        let restored: Vec<String> = std::mem::take(&mut self.to_del_remote).into_iter().filter(|p| !gone.contains(p)).collect();
+        self.to_download.extend(restored);
+        self.to_download.sort_unstable();
+        self.total_work = (self.to_del_local.len() + self.to_download.len() + self.rename_local.len()) as u64;
+        uploads
+    }
+
+    /// Leave out files over the size limit that are not allowlisted: uploads by local size,
+    /// downloads by the size the server reports. Returns the skipped paths with their sizes.
+    fn skip_oversized(
+        &mut self,
+        local_root: &Path,
+        remote_list: &[crate::api::FileItem],
+        limit: Option<u64>,
+        allowlist: &[String],
+    ) -> Vec<(String, u64)> {
+        let Some(limit) = limit else { return Vec::new() };
+        let remote_sizes: HashMap<&str, u64> = remote_list.iter().filter_map(|i| Some((i.path.as_str(), i.size?))).collect();
+        let mut skipped: Vec<(String, u64)> = Vec::new();
+        let mut over = |path: &String, size: Option<u64>| match size {
+            Some(size) if size > limit && !allowlist.contains(path) => {
+                skipped.push((path.clone(), size));
+                false
+            }
+            _ => true,
+        };
+        self.to_upload.retain(|p| {
+            let full = local_root.join(p.replace('/', std::path::MAIN_SEPARATOR_STR));
+            over(p, std::fs::metadata(full).ok().map(|m| m.len()))
+        });
+        self.to_download.retain(|p| over(p, remote_sizes.get(p.as_str()).copied()));
+        for (path, size) in &skipped {
+            log::warn!("{}: {} bytes, over the {} byte size limit; not synced", path, size, limit);
+            self.base_synced.remove(path);
+        }
+        skipped.sort();
+        skipped
+    }
+}
+
+/// Byte count for messages, e.g. "1.5 GB".
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Server hashes by path, borrowed from the listing.
+fn remote_hashes(remote_list: &[FileItem]) -> HashMap<&str, &str> {
+    remote_list.iter().filter_map(|i| Some((i.path.as_str(), i.hash.as_deref()?))).collect()
+}
+
+/// One row of the diff: a path seen locally, on the server or at the last completed cycle.
+/// Paths are borrowed from the inputs; only those that end up in the plan are copied.
+#[derive(Default)]
+struct PathRow<'a> {
+    local_mtime: Option<f64>,
+    remote: Option<&'a FileItem>,
+    synced: bool,
+    ignored: bool,
+    /// Only on the server, in the content this client deleted and no newer than its tombstone.
+    stale_copy: bool,
+}
+
+impl PathRow<'_> {
+    /// Synced before (or tombstoned) and now missing locally, so deleted here.
+    fn deleted_locally(&self) -> bool {
+        (self.synced || self.stale_copy) && !self.ignored && self.local_mtime.is_none()
+    }
+
+    fn deleted_remotely(&self) -> bool {
+        self.synced && !self.ignored && self.remote.is_none()
+    }
+}
+
+/// Owned paths, deepest first so directories empty out bottom-up.
+fn deepest_first(mut paths: Vec<&str>) -> Vec<String> {
+    paths.sort_by_cached_key(|p| (std::cmp::Reverse(p.matches('/').count()), *p));
+    paths.into_iter().map(str::to_string).collect()
+}
+
+fn build_plan(
+    local_root: &Path,
+    local_list: &[(String, f64)],
+    remote_list: &[FileItem],
+    state: &SyncStateFile,
+    clock_offset: f64,
+    strict_deletes: bool,
+    report: bool,
+) -> SyncPlan {
+    let mut rows: HashMap<&str, PathRow> = HashMap::with_capacity(local_list.len().max(remote_list.len()));
+    for (path, mtime) in local_list {
+        rows.entry(path).or_default().local_mtime = Some(*mtime);
+    }
+    for item in remote_list {
+        rows.entry(&item.path).or_default().remote = Some(item);
+    }
+    for path in &state.paths {
+        rows.entry(path).or_default().synced = true;
+    }
+    for (path, row) in rows.iter_mut() {
+        row.ignored = is_ignored(path);
+        if let (Some(deleted_at), Some(r), None) = (state.tombstones.get(*path), row.remote, row.local_mtime) {
+            let same_content = state.tombstone_hashes.get(*path).is_some_and(|h| r.hash.as_deref() == Some(h.as_str()));
+            row.stale_copy = !row.synced && same_content && r.mtime - clock_offset <= deleted_at + MTIME_TOLERANCE_SECS;
+            if row.stale_copy {
+                log::info!("{}: deleted here before; the server copy is the same, deleting it again", path);
+            }
+        }
+    }
+    let remote_hashes = remote_hashes(remote_list);
+
+    // Safety: never delete more files on server than we have locally when the number is large
+    let deletes_remote = rows.values().filter(|r| r.deleted_locally()).count();
+    let mut allow_remote_deletes = true;
+    if deletes_remote > MASS_DELETE_MIN && deletes_remote > local_list.len() {
+        log::warn!(
+            "Skipping server deletes: would delete {} on server but only {} files locally; likely new device or wrong sync folder",
+            deletes_remote,
+            local_list.len()
+        );
+        allow_remote_deletes = false;
+    } else if strict_deletes && deletes_remote > FRAGILE_MASS_DELETE_MIN && deletes_remote * 2 >= state.paths.len() {
+        log::warn!(
+            "Skipping server deletes: would delete {} of {} synced files and the sync folder is on a network or removable drive",
+            deletes_remote,
+            state.paths.len()
+        );
+        allow_remote_deletes = false;
+    }
+
+    let mut to_del_remote: Vec<&str> = Vec::new();
+    let mut to_del_local: Vec<&str> = Vec::new();
+    let mut to_download: Vec<&str> = Vec::new();
+    let mut base_synced: HashSet<String> = HashSet::new();
+    let mut total_work = 0u64;
+    for (path, row) in &rows {
+        let del_remote = allow_remote_deletes && row.deleted_locally();
+        if del_remote {
+            to_del_remote.push(path);
+        }
+        if row.deleted_remotely() {
+            to_del_local.push(path);
+        }
+        if row.ignored {
+            continue;
+        }
+        match (row.local_mtime.is_some(), row.remote.is_some()) {
+            (false, true) => {
+                total_work += 1;
+                if !del_remote {
+                    to_download.push(path);
+                }
+            }
+            (true, false) => total_work += 1,
+            // Present on both sides after deletes: in sync unless transferred below
+            (true, true) => {
+                base_synced.insert(path.to_string());
+            }
+            (false, false) => {}
+        }
+    }
+    total_work += (to_del_remote.len() + to_del_local.len()) as u64;
+
+    // Files on both sides whose mtimes say one side changed: (path, remote is newer).
+    // Within the tolerance only a known server hash (compared below) can justify a transfer.
+    let changed: Vec<(&str, bool)> = local_list
+        .iter()
+        .filter_map(|(path, local_mtime)| {
+            let row = &rows[path.as_str()];
+            let r = row.remote.filter(|_| !row.ignored)?;
+            let strict_remote_newer = r.mtime - clock_offset > *local_mtime;
+            match compare_mtimes(*local_mtime, r.mtime, clock_offset) {
+                std::cmp::Ordering::Less => Some((path.as_str(), true)),
+                std::cmp::Ordering::Greater => Some((path.as_str(), false)),
+                std::cmp::Ordering::Equal if r.hash.is_some() && (*local_mtime - (r.mtime - clock_offset)).abs() > MTIME_SAME_SECS => {
+                    Some((path.as_str(), strict_remote_newer))
+                }
+                std::cmp::Ordering::Equal => None,
+            }
+        })
+        .collect();
+
+    // Hash only the changed files the server has a hash for: equal content needs no transfer
+    let to_hash: Vec<(&str, &str)> = changed.iter().filter_map(|(p, _)| Some((*p, *remote_hashes.get(p)?))).collect();
+    let local_hashes = hash_local_files(local_root, &to_hash, report);
+
+    let mut verified_hashes: Vec<(String, String)> = Vec::new();
+    let mut upload_changed: HashSet<&str> = HashSet::new();
+    for &(path, remote_newer) in &changed {
+        match (local_hashes.get(path), remote_hashes.get(path)) {
+            (Some(local), Some(server)) if local == server => {
+                if remote_newer {
+                    verified_hashes.push((path.to_string(), server.to_string()));
+                }
+            }
+            _ if remote_newer => to_download.push(path),
+            _ => {
+                upload_changed.insert(path);
+            }
+        }
+    }
+    to_download.sort_unstable();
+
+    // New local files plus changed ones whose content differs from the server
+    let to_upload: Vec<String> = local_list
+        .iter()
+        .filter(|(path, _)| {
+            let row = &rows[path.as_str()];
+            !row.ignored && ((row.remote.is_none() && !row.synced) || upload_changed.contains(path.as_str()))
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    let mut plan = SyncPlan {
+        to_del_remote: deepest_first(to_del_remote),
+        to_del_local: deepest_first(to_del_local),
+        to_download: to_download.into_iter().map(str::to_string).collect(),
+        to_upload,
+        base_synced,
+        verified_hashes,
+        rename_remote: Vec::new(),
+        rename_local: Vec::new(),
+        total_work,
+    };
+    detect_case_renames(&mut plan, local_root, &remote_hashes);
+
+    log::info!(
+        "Sync plan: {} to_download, {} to_upload, {} delete_server, {} delete_local, {} case renames",
+        plan.to_download.len(),
+        plan.to_upload.len(),
+        plan.to_del_remote.len(),
+        plan.to_del_local.len(),
+        plan.rename_remote.len() + plan.rename_local.len()
+    );
+    plan
+}
+
+/// Pairs of (deleted path, new path) that differ only in letter case.
+fn case_pairs(deleted: &[String], added: &[String]) -> Vec<(String, String)> {
+    let added_by_lower: HashMap<String, &String> = added.iter().map(|p| (p.to_lowercase(), p)).collect();
+    deleted
+        .iter()
+        .filter_map(|old| {
+            let new = added_by_lower.get(&old.to_lowercase())?;
+            (*new != old).then(|| (old.clone(), (*new).clone()))
+        })
+        .collect()
+}
+
+/// Turn "delete old + transfer new" into a rename when a file was renamed by case only and its
+/// content is unchanged. Plain deletes and transfers would re-send the file and, on
+/// case-insensitive filesystems, could delete the freshly written new name along with the old.
+fn detect_case_renames(plan: &mut SyncPlan, local_root: &Path, remote_hashes: &HashMap<&str, &str>) {
+    let unchanged = |path: &str, hash: &str| {
+        compute_file_hash_like(&local_root.join(path.replace('/', std::path::MAIN_SEPARATOR_STR)), hash).as_deref() == Some(hash)
+    };
+    // Renamed locally: the old name is deleted on the server, the new one is a new upload
+    let new_uploads: Vec<String> = plan.to_upload.iter().filter(|p| !remote_hashes.contains_key(p.as_str())).cloned().collect();
+    plan.rename_remote = case_pairs(&plan.to_del_remote, &new_uploads)
+        .into_iter()
+        .filter(|(old, new)| remote_hashes.get(old.as_str()).is_some_and(|h| unchanged(new, h)))
+        .collect();
+    apply_renames(&plan.rename_remote, &mut plan.to_del_remote, &mut plan.to_upload, &mut plan.base_synced);
+    // Renamed on the server: the old name is deleted locally, the new one is a new download
+    plan.rename_local = case_pairs(&plan.to_del_local, &plan.to_download)
+        .into_iter()
+        .filter(|(old, new)| remote_hashes.get(new.as_str()).is_some_and(|h| unchanged(old, h)))
+        .collect();
+    apply_renames(&plan.rename_local, &mut plan.to_del_local, &mut plan.to_download, &mut plan.base_synced);
+    let renames = (plan.rename_remote.len() + plan.rename_local.len()) as u64;
+    plan.total_work = plan.total_work.saturating_sub(renames);
+}
+
+/// Drop renamed paths from the planned deletes and transfers (one pass each, not per rename).
+fn apply_renames(renames: &[(String, String)], deletes: &mut Vec<String>, transfers: &mut Vec<String>, base_synced: &mut HashSet<String>) {
+    if renames.is_empty() {
+        return;
+    }
+    let old: HashSet<&str> = renames.iter().map(|(o, _)| o.as_str()).collect();
+    let new: HashSet<&str> = renames.iter().map(|(_, n)| n.as_str()).collect();
+    deletes.retain(|p| !old.contains(p.as_str()));
+    transfers.retain(|p| !new.contains(p.as_str()));
+    base_synced.extend(new.into_iter().map(str::to_string));
+}
+
+/// Rename a local file whose new name differs only in case, via a temporary name so it also
+/// works on case-insensitive filesystems (Windows, macOS).
+fn rename_case_only(local_root: &Path, old: &str, new: &str) -> Result<(), String> {
+    let src = resolve_in_root(local_root, old).ok_or("path outside the sync folder")?;
+    let dst = resolve_in_root(local_root, new).ok_or("path outside the sync folder")?;
+    let name = src.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = src.with_file_name(format!(".{}.brandybox-rename", name));
+    std::fs::rename(&src, &tmp).map_err(|e| e.to_string())?;
+    if let Some(parent) = dst.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    std::fs::rename(&tmp, &dst).map_err(|e| {
+        let _ = std::fs::rename(&tmp, &src);
+        e.to_string()
+    })
+}
+
+/// A planned download is skipped when an interrupted cycle already fetched it, or when the
+/// local file still has the content we last recorded for the server hash.
+fn download_already_done(
+    path: &str,
+    local_path: &Path,
+    prev_downloaded: &HashSet<String>,
+    state_hashes: &HashMap<String, String>,
+    remote_hashes: &HashMap<&str, &str>,
+) -> bool {
+    if !(local_path.exists() && local_path.is_file()) {
+        return false;
+    }
+    if prev_downloaded.contains(path) {
+        return true;
+    }
+    matches!(remote_hashes.get(path), Some(hash) if state_hashes.get(path).map(String::as_str) == Some(*hash))
+}
+
+/// Counts and byte totals of what the next sync would do, per phase.
+#[derive(Serialize, Default)]
+pub struct SyncEstimate {
+    pub upload_files: u64,
+    pub upload_bytes: u64,
+    pub download_files: u64,
+    pub download_bytes: u64,
+    /// Downloads whose size the server did not report (older servers); not in `download_bytes`.
+    pub download_unknown_size_files: u64,
+    pub delete_remote_files: u64,
+    pub delete_local_files: u64,
+}
+
+/// Compute the sync plan without changing anything, for "what will this cost" in the UI.
+pub fn estimate_sync<B: Backend + ?Sized>(client: &B, local_root: &Path) -> Result<SyncEstimate, String> {
+    estimate_with_state(client, local_root, &load_sync_state())
+}
+
+/// Like `estimate_sync` for a folder that has never been synced (setup wizard): nothing is
+/// deleted on either side, everything missing is transferred.
+pub fn estimate_initial_sync<B: Backend + ?Sized>(client: &B, local_root: &Path) -> Result<SyncEstimate, String> {
+    estimate_with_state(client, local_root, &SyncStateFile::default())
+}
+
+fn estimate_with_state<B: Backend + ?Sized>(client: &B, local_root: &Path, state: &SyncStateFile) -> Result<SyncEstimate, String> {
+    reload_ignore_settings();
+    let prev_downloaded: HashSet<String> = state.downloaded_paths.iter().cloned().collect();
+    let (local_list, unreadable_dirs) = list_local(local_root, false);
+    let mut remote_list = client.list_files()?;
+    reject_unsafe_remote(&mut remote_list);
+    select_hashes(&mut remote_list, client.hash_algorithm(), &mut state.file_hashes.clone());
+    let strict_deletes = state.volume.is_fragile() || folder_check::volume_kind(local_root).is_fragile();
+    let mut plan = build_plan(local_root, &local_list, &remote_list, state, measure_clock_offset(client), strict_deletes, false);
+    plan.skip_unreadable_dirs(&unreadable_dirs);
+    let confirmed = HELD_DELETES_CONFIRMED.load(std::sync::atomic::Ordering::SeqCst);
+    plan.hold_implausible_local_deletes(remote_list.len(), state.paths.len(), confirmed);
+    plan.skip_oversized(local_root, &remote_list, config::get_max_file_size_bytes(), &config::get_large_file_allowlist());
+    if config::get_restore_pending() || config::get_download_only() {
+        plan.download_only(config::get_download_only());
+    }
+
+    let remote_hashes = remote_hashes(&remote_list);
+    let remote_sizes: HashMap<&str, Option<u64>> = remote_list.iter().map(|i| (i.path.as_str(), i.size)).collect();
+    let mut estimate = SyncEstimate {
+        delete_remote_files: plan.to_del_remote.len() as u64,
+        delete_local_files: plan.to_del_local.len() as u64,
+        ..Default::default()
+    };
+    for path in &plan.to_download {
+        let local_path = local_root.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+        if download_already_done(path, &local_path, &prev_downloaded, &state.file_hashes, &remote_hashes) {
+            continue;
+        }
+        estimate.download_files += 1;
+        match remote_sizes.get(path.as_str()).copied().flatten() {
+            Some(size) => estimate.download_bytes += size,
+            None => estimate.download_unknown_size_files += 1,
+        }
+    }
+    for path in &plan.to_upload {
+        let full = local_root.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+        estimate.upload_files += 1;
+        estimate.upload_bytes += std::fs::metadata(&full).map(|m| m.len()).unwrap_or(0);
+    }
+    Ok(estimate)
+}
+
+/// Up to 5 paths joined for a warning, with the number of further paths.
+fn format_path_list(paths: &[String]) -> String {
+    const SHOWN: usize = 5;
+    let mut out = paths.iter().take(SHOWN).cloned().collect::<Vec<_>>().join(", ");
+    if paths.len() > SHOWN {
+        out.push_str(&format!(" and {} more", paths.len() - SHOWN));
+    }
+    out
+}
+
+/// Upload failures the engine handles without aborting the cycle.
+#[derive(Debug, PartialEq, Eq)]
+enum UploadFailure {
+    /// 507: the user's or the server's storage limit is reached.
+    StorageFull,
+    /// 413: the server refuses files of this size.
+    TooLarge,
+    Other,
+}
+
+fn classify_upload_error(e: &str) -> UploadFailure {
+    if e.contains("507") || e.contains("storage limit") {
+        UploadFailure::StorageFull
+    } else if e.contains("413") {
+        UploadFailure::TooLarge
+    } else {
+        UploadFailure::Other
+    }
+}
+
+/// Set while a sync cycle owns the sync state; see `try_lock_sync`.
+static SYNC_RUNNING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// A sync was requested while a cycle ran; see `lock_or_queue_sync`.
+static RERUN_REQUESTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Local deletes held back by the last cycle because the server listing looked implausible.
+static HELD_LOCAL_DELETES: std::sync::Mutex<usize> = std::sync::Mutex::new(0);
+/// Set by `confirm_held_deletes`; the next cycle carries out the held deletes.
+static HELD_DELETES_CONFIRMED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Number of local deletes waiting for confirmation (0 when none).
+pub fn held_local_deletes() -> usize {
+    HELD_LOCAL_DELETES.lock().map(|g| *g).unwrap_or(0)
+}
+
+/// Let the next cycle delete the local files it held back (the user checked the server).
+pub fn confirm_held_deletes() {
+    HELD_DELETES_CONFIRMED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Proof of exclusive ownership of the sync state. At most one exists at a time; dropping it lets
+/// the next cycle start.
+pub struct SyncGuard(());
+
+impl Drop for SyncGuard {
+    fn drop(&mut self) {
+        SYNC_RUNNING.store(false, std::sync::atomic::Ordering::Release);
+    }
+}
+
+/// Set when the app quits; see `request_shutdown`.
+static SHUTDOWN_REQUESTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Stop syncing for app exit: no new cycle starts, and a running one stops transferring after
+/// the file in progress, saves the state of what it finished and releases the lock.
+pub fn request_shutdown() {
+    SHUTDOWN_REQUESTED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// Wait until no cycle holds the sync lock. False when one still does after `timeout`.
+pub fn wait_until_idle(timeout: std::time::Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    while SYNC_RUNNING.load(std::sync::atomic::Ordering::Acquire) {
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    true
+}
+
+/// Claim the right to run a sync cycle, or None when one is already running (manual and
+/// background runs race for this, not for the status) or the app is quitting.
+pub fn try_lock_sync() -> Option<SyncGuard> {
+    if shutdown_requested() {
+        return None;
+    }
+    SYNC_RUNNING
+        .compare_exchange(false, true, std::sync::atomic::Ordering::AcqRel, std::sync::atomic::Ordering::Acquire)
+        .ok()
+        .map(|_| SyncGuard(()))
+}
+
+/// Claim the sync lock for a requested run. When a cycle is running, ask it to run once more
+/// when it ends (see `finish_cycle`) and return None: any number of requests during one cycle
+/// ("Sync now" pressed repeatedly, a burst of wake-ups) add up to one extra cycle.
+pub fn lock_or_queue_sync() -> Option<SyncGuard> {
+    use std::sync::atomic::Ordering::SeqCst;
+    if let Some(guard) = try_lock_sync() {
+        return Some(guard);
+    }
+    RERUN_REQUESTED.store(true, SeqCst);
+    // The cycle may have ended in between without seeing the request
+    let guard = try_lock_sync()?;
+    RERUN_REQUESTED.store(false, SeqCst);
+    Some(guard)
+}
+
+/// End of a cycle: the guard back when a sync was requested during it (run again), else None
+/// after releasing the lock.
+pub fn finish_cycle(guard: SyncGuard) -> Option<SyncGuard> {
+    use std::sync::atomic::Ordering::SeqCst;
+    if RERUN_REQUESTED.swap(false, SeqCst) {
+        return Some(guard);
+    }
+    drop(guard);
+    // A request between the check and the release found the lock taken and left only the flag
+    if RERUN_REQUESTED.load(SeqCst) {
+        let guard = try_lock_sync()?;
+        if RERUN_REQUESTED.swap(false, SeqCst) {
+            return Some(guard);
+        }
+    }
+    None
+}
+
+/// Run one cycle, failing with "Sync already running" when another one holds the lock.
+pub fn run_sync<B: Backend + ?Sized>(client: &B, local_root: &Path) -> Result<(u64, u64, Option<String>), SyncError> {
+    let guard = try_lock_sync().ok_or_else(|| SyncError::new(SyncErrorCode::Unknown, "Sync already running"))?;
+    run_sync_locked(&guard, client, local_root)
+}
+
+/// Run one cycle as the holder of `_guard` (callers that take the lock before starting a thread).
+pub fn run_sync_locked<B: Backend + ?Sized>(
+    _guard: &SyncGuard,
+    client: &B,
+    local_root: &Path,
+) -> Result<(u64, u64, Option<String>), SyncError> {
+    // Only failures of this cycle's requests may decide its error code
+    api::take_last_failure();
+    let result = run_sync_cycle(client, local_root);
+    if result.is_err() {
+        file_status::end_cycle(None);
+    }
+    result
+}
+
+fn run_sync_cycle<B: Backend + ?Sized>(client: &B, local_root: &Path) -> Result<(u64, u64, Option<String>), SyncError> {
+    reload_ignore_settings();
+    let mut state = load_sync_state();
+    if metadata_crypto::key_missing() {
+        // Syncing against an empty state and default settings could pick the wrong folder
+        return Err(SyncError::new(
+            SyncErrorCode::Unknown,
+            "Settings and sync state are encrypted, but the keyring has no key for them",
+        ));
+    }
+    let last_synced: HashSet<String> = state.paths.iter().cloned().collect();
+    let prev_downloaded: HashSet<String> = state.downloaded_paths.iter().cloned().collect();
+
+    set_progress_detail("scanning", 0, 0, Some("Scanning the sync folder".to_string()));
+    let _ = CYCLE_TRANSFER.lock().map(|mut g| *g = Some((std::time::Instant::now(), 0)));
+    let volume = folder_check::volume_kind(local_root);
+    let fragile = volume.is_fragile() || state.volume.is_fragile();
+    let (local_list, unreadable_dirs) = list_local(local_root, true);
+    if fragile && local_list.is_empty() && !last_synced.is_empty() {
+        set_progress("idle", 0, 0);
+        return Err(SyncError::new(
+            SyncErrorCode::SyncFolderUnavailable,
+            format!(
+                "Sync folder is empty but {} files were synced before. Is the drive connected? Sync is paused until the files are back",
+                last_synced.len()
+            ),
+        ));
+    }
+    let detail = format!("Scanned {} files; listing server files", format_count(local_list.len() as u64));
+    set_progress_detail("listing", local_list.len() as u64, 0, Some(detail));
+    let mut remote_list = client.list_files()?;
+    let unsafe_paths = reject_unsafe_remote(&mut remote_list);
+    select_hashes(&mut remote_list, client.hash_algorithm(), &mut state.file_hashes);
+
+    log::info!(
+        "Sync: {} remote, {} local (sync_folder={})",
+        remote_list.len(),
+        local_list.len(),
+        local_root.display()
+    );
+
+    let clock_offset = measure_clock_offset(client);
+    let mut plan = build_plan(local_root, &local_list, &remote_list, &state, clock_offset, fragile, true);
+    plan.skip_unreadable_dirs(&unreadable_dirs);
+    let confirmed = HELD_DELETES_CONFIRMED.swap(false, std::sync::atomic::Ordering::SeqCst);
+    let held_deletes = plan.hold_implausible_local_deletes(remote_list.len(), state.paths.len(), confirmed);
+    let _ = HELD_LOCAL_DELETES.lock().map(|mut g| *g = held_deletes.len());
+    let max_file_size = config::get_max_file_size_bytes();
+    let oversized = plan.skip_oversized(local_root, &remote_list, max_file_size, &config::get_large_file_allowlist());
+    let restoring = config::get_restore_pending();
+    let kiosk = config::get_download_only();
+    if restoring || kiosk {
+        log::info!("Download-only cycle ({}): nothing is uploaded or deleted on the server", if kiosk { "kiosk mode" } else { "restore" });
+        // The stored hash is the server's: without dropping it the download would count as done
+        for path in plan.download_only(kiosk) {
+            state.file_hashes.remove(&path);
+        }
+    }
+    for (path, hash) in &plan.verified_hashes {
+        state.file_hashes.insert(path.clone(), hash.clone());
+    }
+    let SyncPlan {
+        mut to_del_remote,
+        mut to_del_local,
+        mut to_download,
+        mut to_upload,
+        mut base_synced,
+        rename_remote,
+        rename_local,
+        total_work,
+        ..
+    } = plan;
+    let remote_hashes = remote_hashes(&remote_list);
+    let remote_mtimes: HashMap<&str, f64> = remote_list.iter().map(|i| (i.path.as_str(), i.mtime)).collect();
+    let mut done = 0u64;
+    file_status::begin_cycle(to_download.iter().chain(&to_upload));
+
+    // Case-only renames; when one fails, fall back to delete + transfer
+    for (old, new) in &rename_remote {
+        set_progress("rename_server", done, total_work);
+        match client.move_file(old, new) {
+            Ok(()) => {
+                if let Some(hash) = state.file_hashes.remove(old) {
+                    state.file_hashes.insert(new.clone(), hash);
+                }
+                activity::record(ActivityKind::Renamed, new);
+            }
+            Err(e) => {
+                log::info!("Rename {} -> {} on server failed ({}); uploading instead", old, new, e);
+                base_synced.remove(new);
+                to_del_remote.push(old.clone());
+                to_upload.push(new.clone());
+            }
+        }
+        done += 1;
+    }
+    for (old, new) in &rename_local {
+        set_progress("rename_local", done, total_work);
+        match rename_case_only(local_root, old, new) {
+            Ok(()) => {
+                if let Some(hash) = state.file_hashes.remove(old) {
+                    state.file_hashes.insert(new.clone(), hash);
+                }
+                activity::record(ActivityKind::Renamed, new);
+            }
+            Err(e) => {
+                log::warn!("Rename {} -> {} locally failed ({}); downloading instead", old, new, e);
+                base_synced.remove(new);
+                to_del_local.push(old.clone());
+                to_download.push(new.clone());
+            }
+        }
+        done += 1;
+    }
+
+    // Batched: one request per chunk instead of one per file
+    for chunk in to_del_remote.chunks(crate::api::DELETE_BATCH_SIZE) {
+        set_progress("delete_server", done, total_work);
+        // Paths excluded by the user while this cycle was running are kept
+        let batch: Vec<String> = chunk.iter().filter(|p| !is_ignored(p)).cloned().collect();
+        client.delete_files(&batch).map_err(|e| format!("Delete server {}", e))?;
+        let now = system_time_secs(std::time::SystemTime::now());
+        for path in &batch {
+            state.tombstones.insert(path.clone(), now);
+            match remote_hashes.get(path.as_str()) {
+                Some(hash) => state.tombstone_hashes.insert(path.clone(), hash.to_string()),
+                None => state.tombstone_hashes.remove(path),
+            };
+            activity::record(ActivityKind::DeletedRemotely, path);
+        }
+        done += chunk.len() as u64;
+    }
+    // Files that could not be copied into the delete snapshot are kept (and stay synced)
+    let unsaved_deletes = if config::get_delete_snapshots_enabled() && !to_del_local.is_empty() {
+        snapshots::preserve(local_root, &to_del_local)
+    } else {
+        Vec::new()
+    };
+    if !unsaved_deletes.is_empty() {
+        let unsaved: HashSet<&String> = unsaved_deletes.iter().collect();
+        to_del_local.retain(|p| !unsaved.contains(p));
+    }
+    let secure_delete_passes = config::get_secure_delete_passes();
+    let mut failed_deletes: Vec<String> = Vec::new();
+    for path in &to_del_local {
+        set_progress("delete_local", done, total_work);
+        if is_ignored(path) {
+            done += 1;
+            continue;
+        }
+        let Some(full) = resolve_in_root(local_root, path) else {
+            log::warn!("Delete local {}: path outside the sync folder, skipping", path);
+            done += 1;
+            continue;
+        };
+        if full.exists() && full.is_file() {
+            match secure_delete::remove_file(&full, secure_delete_passes) {
+                Ok(()) => activity::record(ActivityKind::DeletedLocally, path),
+                Err(e) => {
+                    log::warn!("Delete local {} failed: {}", path, e);
+                    failed_deletes.push(path.clone());
+                }
+            }
+            let mut parent = full.parent();
+            while let Some(p) = parent {
+                if p != local_root && p.read_dir().map(|mut d| d.next().is_none()).unwrap_or(false) {
+                    let _ = std::fs::remove_dir(p);
+                    parent = p.parent();
+                } else {
+                    break;
+                }
+            }
+        }
+        done += 1;
+    }
+
+    let mut bytes_downloaded = 0u64;
+    let mut completed_downloads: HashSet<String> = HashSet::new();
+    let mut skipped_downloads: HashSet<String> = HashSet::new();
+    // Stopped for app exit before every planned download ran
+    let mut downloads_interrupted = false;
+
+    for path in &to_download {
+        if shutdown_requested() {
+            log::info!("Quitting: downloads stopped, the rest follows in the next cycle");
+            downloads_interrupted = true;
+            break;
+        }
+        set_progress("download", done, total_work);
+        if is_ignored(path) {
+            done += 1;
+            continue;
+        }
+        let Some(local_path) = resolve_in_root(local_root, path) else {
+            log::warn!("Download {}: path outside the sync folder, skipping", path);
+            skipped_downloads.insert(path.clone());
+            file_status::mark_failed(path, "Path outside the sync folder");
+            done += 1;
+            continue;
+        };
+        if download_already_done(path, &local_path, &prev_downloaded, &state.file_hashes, &remote_hashes) {
+            done += 1;
+            continue;
+        }
+        file_status::set_active(path);
+        match client.download_file(path) {
+            Ok(body) => {
+                bytes_downloaded += body.len() as u64;
+                add_transferred(body.len() as u64);
+                let _content_hash = {
+                    let mut hasher = Sha256::new();
+                    hasher.update(&body);
+                    format!("{:x}", hasher.finalize())
+                };
+                if let Some(parent) = local_path.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                let tmp_path = local_path.with_extension("tmp_download");
+                if let Err(e) = std::fs::write(&tmp_path, &body) {
+                    if e.kind() == std::io::ErrorKind::PermissionDenied {
+                        log::warn!("Download {}: permission denied, skipping", path);
+                        skipped_downloads.insert(path.clone());
+                        file_status::mark_failed(path, "Permission denied");
+                        done += 1;
+                        continue;
+                    }
+                    return Err(SyncError::new(SyncErrorCode::Unknown, format!("Download {}: {}", path, e)));
+                }
+                // Same mtime as on the server (in local clock time): the next cycle sees no change
+                if let Some(mtime) = remote_mtimes.get(path.as_str()) {
+                    if let Err(e) = set_file_mtime(&tmp_path, mtime - clock_offset) {
+                        log::debug!("Download {}: cannot set mtime: {}", path, e);
+                    }
+                }
+                if let Err(e) = std::fs::rename(&tmp_path, &local_path) {
+                    let _ = std::fs::remove_file(&tmp_path);
+                    return Err(SyncError::new(
+                        SyncErrorCode::Unknown,
+                        format!("Download {}: failed to rename tmp to final: {}", path, e),
+                    ));
+                }
+                completed_downloads.insert(path.clone());
+                file_status::mark_done(path);
+                activity::record(ActivityKind::Downloaded, path);
+                if let Some(h) = remote_hashes.get(path.as_str()) {
+                    state.file_hashes.insert(path.clone(), h.to_string());
+                }
+            }
+            Err(e) => {
+                if e.contains("404") {
+                    log::debug!("Download {}: 404, file no longer on server", path);
+                    if local_path.exists() && local_path.is_file() {
+                        let _ = std::fs::remove_file(&local_path);
+                    }
+                    skipped_downloads.insert(path.clone());
+                } else {
+                    return Err(format!("Download {}: {}", path, e).into());
+                }
+            }
+        }
+        done += 1;
+    }
+
+    if !skipped_downloads.is_empty() {
+        let sample: Vec<_> = {
+            let mut v: Vec<_> = skipped_downloads.iter().cloned().collect();
+            v.sort();
+            v.into_iter().take(5).collect()
+        };
+        log::warn!(
+            "Skipped {} downloads (permission denied or file gone): sample={:?}",
+            skipped_downloads.len(),
+            sample
+        );
+    }
+
+    let mut bytes_uploaded = 0u64;
+    let mut completed_uploads: HashSet<String> = HashSet::new();
+    let mut skipped_uploads: HashSet<String> = HashSet::new();
+    // Not uploaded because the quota is reached / the server refuses files this large.
+    // They stay out of the state, so the next cycle tries again.
+    let mut quota_skipped: Vec<String> = Vec::new();
+    let mut too_large: Vec<String> = Vec::new();
+    // Local files we may not read: skipped, reported by path and kept out of the state.
+    let mut unreadable: Vec<String> = Vec::new();
+    // Smallest file the server rejected for quota: files at least this big cannot fit either
+    let mut smallest_rejected: Option<u64> = None;
+
+    for path in &to_upload {
+        if shutdown_requested() {
+            log::info!("Quitting: uploads stopped, the rest follows in the next cycle");
+            break;
+        }
+        set_progress("upload", done, total_work);
+        if is_ignored(path) {
+            done += 1;
+            continue;
+        }
+        let full = local_root.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+        if full.exists() && full.is_file() {
+            let meta = std::fs::metadata(&full).ok();
+            let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+            // Keep the modification time on the server (converted to server clock)
+            let mtime = meta.and_then(|m| m.modified().ok()).map(|t| system_time_secs(t) + clock_offset);
+            if smallest_rejected.is_some_and(|s| size >= s) {
+                file_status::mark_failed(path, "Storage full");
+                quota_skipped.push(path.clone());
+                done += 1;
+                continue;
+            }
+            if let Err(e) = std::fs::File::open(&full) {
+                if e.kind() == std::io::ErrorKind::PermissionDenied {
+                    log::warn!("Upload {}: permission denied, skipping", path);
+                    file_status::mark_failed(path, "Permission denied");
+                    unreadable.push(path.clone());
+                    done += 1;
+                    continue;
+                }
+            }
+            file_status::set_active(path);
+            if let Err(e) = client.upload_file_from_path(path, &full, mtime) {
+                file_status::mark_failed(path, &e);
+                match classify_upload_error(&e) {
+                    UploadFailure::StorageFull => {
+                        log::warn!("Upload {}: storage full ({})", path, e);
+                        smallest_rejected = Some(smallest_rejected.map_or(size, |s| s.min(size)));
+                        quota_skipped.push(path.clone());
+                    }
+                    UploadFailure::TooLarge => {
+                        log::warn!("Upload {}: rejected as too large ({})", path, e);
+                        too_large.push(path.clone());
+                    }
+                    UploadFailure::Other => return Err(format!("Upload {}: {}", path, e).into()),
+                }
+                done += 1;
+                continue;
+            }
+            bytes_uploaded += size;
+            add_transferred(size);
+            completed_uploads.insert(path.clone());
+            file_status::mark_done(path);
+            activity::record(ActivityKind::Uploaded, path);
+        } else {
+            log::debug!("Upload {}: file no longer present, skipping", path);
+            skipped_uploads.insert(path.clone());
+        }
+        done += 1;
+    }
+
+    let mut warning_msg = None;
+    let mut warnings: Vec<String> = Vec::new();
+    let mut issues: Vec<SyncIssue> = Vec::new();
+    if !unsaved_deletes.is_empty() {
+        warnings.push(i18n::t("sync.unsaved_deletes", &[("count", &unsaved_deletes.len())]));
+        issues.push(SyncIssue::new(SyncErrorCode::UnsavedDeletes, &unsaved_deletes));
+    }
+    if !failed_deletes.is_empty() {
+        warnings.push(i18n::t("sync.failed_deletes", &[("count", &failed_deletes.len())]));
+        issues.push(SyncIssue::new(SyncErrorCode::FailedDeletes, &failed_deletes));
+    }
+    if !held_deletes.is_empty() {
+        warnings.push(i18n::t("sync.held_deletes", &[("count", &held_deletes.len())]));
+        issues.push(SyncIssue::new(SyncErrorCode::HeldDeletes, &held_deletes));
+    }
+    if !quota_skipped.is_empty() {
+        warnings.push(i18n::t("sync.quota_skipped", &[("count", &quota_skipped.len())]));
+        issues.push(SyncIssue::new(SyncErrorCode::QuotaExceeded, &quota_skipped));
+    }
+    if !too_large.is_empty() {
+        warnings.push(i18n::t("sync.too_large", &[("count", &too_large.len())]));
+        issues.push(SyncIssue::new(SyncErrorCode::FileTooLarge, &too_large));
+    }
+    if !skipped_downloads.is_empty() {
+        warnings.push(i18n::t("sync.skipped_downloads", &[("count", &skipped_downloads.len())]));
+        issues.push(SyncIssue::new(SyncErrorCode::SkippedDownloads, &skipped_downloads));
+    }
+    if !unreadable.is_empty() || !unreadable_dirs.is_empty() {
+        let mut paths: Vec<String> = unreadable.clone();
+        paths.extend(unreadable_dirs.iter().map(|d| format!("{}/", d)));
+        paths.sort();
+        warnings.push(i18n::t("sync.unreadable", &[("paths", &format_path_list(&paths))]));
+        issues.push(SyncIssue::new(SyncErrorCode::PermissionDenied, &paths));
+    }
+    if let (false, Some(limit)) = (oversized.is_empty(), max_file_size) {
+        let files: Vec<String> = oversized.iter().map(|(p, size)| format!("{} ({})", p, format_size(*size))).collect();
+        warnings.push(i18n::t(
+            "sync.oversized",
+            &[("limit", &format_size(limit)), ("paths", &format_path_list(&files))],
+        ));
+        issues.push(SyncIssue::new(SyncErrorCode::FileSizeLimit, oversized.iter().map(|(p, _)| p)));
+    }
+    if !unsafe_paths.is_empty() {
+        warnings.push(i18n::t("sync.unsafe_paths", &[("count", &unsafe_paths.len())]));
+        issues.push(SyncIssue::new(SyncErrorCode::UnsafePaths, &unsafe_paths));
+    }
+    if !skipped_uploads.is_empty() {
+        issues.push(SyncIssue::new(SyncErrorCode::SkippedUploads, &skipped_uploads));
+        let sample: Vec<_> = {
+            let mut v: Vec<_> = skipped_uploads.iter().cloned().collect();
+            v.sort();
+            v.into_iter().take(5).collect()
+        };
+        log::warn!(
+            "Skipped {} uploads (file no longer present during sync): sample={:?}",
+            skipped_uploads.len(),
+            sample
+        );
+        warnings.push(i18n::t("sync.skipped_uploads", &[("count", &skipped_uploads.len())]));
+    }
+    if !warnings.is_empty() {
+        warning_msg = Some(warnings.join("; "));
+    }
+    let _ = SYNC_ISSUES.lock().map(|mut g| *g = issues);
+
+    // Persist ONLY verified paths: base_synced | completed_downloads | completed_uploads
+    let unreadable: HashSet<&String> = unreadable.iter().collect();
+    let mut new_synced = base_synced;
+    // Kept deletes stay synced: otherwise the next cycle would upload them as new files
+    new_synced.extend(held_deletes);
+    new_synced.extend(unsaved_deletes);
+    // Files that could not be removed too, so the next cycle deletes them again
+    new_synced.extend(failed_deletes);
+    new_synced.extend(completed_downloads.iter().cloned());
+    new_synced.extend(completed_uploads.iter().cloned());
+    new_synced.retain(|p| !is_ignored(p) && !unreadable.contains(p));
+    let now = system_time_secs(std::time::SystemTime::now());
+    state.tombstones.retain(|p, deleted_at| now - *deleted_at < TOMBSTONE_TTL_SECS && !new_synced.contains(p));
+    let tombstones = &state.tombstones;
+    state.tombstone_hashes.retain(|p, _| tombstones.contains_key(p));
+    let mut new_synced: Vec<String> = new_synced.into_iter().collect();
+    new_synced.sort_unstable();
+    state.paths = new_synced;
+    state.downloaded_paths.clear();
+    state.volume = volume;
+    let _ = SYNC_ROOT_VOLUME.lock().map(|mut g| *g = Some(volume));
+    save_sync_state(&state);
+    file_status::end_cycle(Some(&state.paths));
+
+    set_progress("idle", 0, 0);
+    let _ = CYCLE_TRANSFER.lock().map(|mut g| *g = None);
+
+    log::info!(
+        "Sync cycle complete: {} downloaded ({} bytes), {} skipped, {} uploaded ({} bytes), {} synced paths{}",
+        completed_downloads.len(),
+        bytes_downloaded,
+        skipped_downloads.len(),
+        completed_uploads.len(),
+        bytes_uploaded,
+        state.paths.len(),
+        if warning_msg.is_some() { " [WARNING]" } else { "" }
+    );
+
+    // Downloads that failed or were cut short by quitting are retried download-only before
+    // two-way sync starts
+    if restoring && skipped_downloads.is_empty() && !downloads_interrupted {
+        log::info!("Restore complete; two-way sync from the next cycle");
+        config::set_restore_pending(false);
+    }
+
+    activity::persist();
+    stats::record_cycle(
+        bytes_downloaded,
+        bytes_uploaded,
+        completed_downloads.len() as u64,
+        completed_uploads.len() as u64,
+    );
+    let _ = LAST_SYNC.lock().map(|mut g| {
+        *g = Some(LastSync {
+            at: chrono::Utc::now().to_rfc3339(),
+            bytes_downloaded,
+            bytes_uploaded,
+        })
+    });
+
+    Ok((bytes_downloaded, bytes_uploaded, warning_msg))
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    /// run_sync keeps state in the config dir (BRANDYBOX_CONFIG_DIR), so engine tests run one at a time.
+    static ENGINE_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Fresh config dir and sync root under the system temp dir. Also used by the config tests,
+    /// which share the process-wide config dir.
+    pub(crate) fn engine_env(name: &str) -> (std::sync::MutexGuard<'static, ()>, PathBuf) {
+        let guard = ENGINE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let base = std::env::temp_dir().join(format!("brandybox-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(base.join("config")).unwrap();
+        std::fs::create_dir_all(base.join("root")).unwrap();
+        std::env::set_var("BRANDYBOX_CONFIG_DIR", base.join("config"));
+        (guard, base.join("root"))
+    }
+
+    #[test]
+    fn engine_first_sync_uploads_and_downloads() {
+        let (_guard, root) = engine_env("first");
+        let backend = MemoryBackend::new();
+        backend.insert("remote/a.txt", b"from server", 1000.0);
+        std::fs::write(root.join("local.txt"), b"from disk").unwrap();
+
+        run_sync(&backend, &root).unwrap();
+
+        assert_eq!(std::fs::read(root.join("remote").join("a.txt")).unwrap(), b"from server");
+        assert!(backend.contains("local.txt"));
+        let mut synced = load_sync_state().paths;
+        synced.sort();
+        assert_eq!(synced, vec!["local.txt".to_string(), "remote/a.txt".to_string()]);
+    }
+
+    #[test]
+    fn engine_estimate_matches_first_sync_plan() {
+        let (_guard, root) = engine_env("estimate");
+        let backend = MemoryBackend::new();
+        backend.insert("a.bin", &[0u8; 300], 1000.0);
+        backend.insert("b.bin", &[0u8; 200], 1000.0);
+        std::fs::write(root.join("c.bin"), [0u8; 50]).unwrap();
+
+        let estimate = estimate_sync(&backend, &root).unwrap();
+
+        assert_eq!((estimate.download_files, estimate.download_bytes), (2, 500));
+        assert_eq!((estimate.upload_files, estimate.upload_bytes), (1, 50));
+        assert_eq!(estimate.delete_remote_files + estimate.delete_local_files, 0);
+        assert!(!root.join("a.bin").exists(), "estimate must not transfer anything");
+    }
+
+    #[test]
+    fn engine_local_delete_propagates_to_server() {
+        let (_guard, root) = engine_env("delete");
+        let backend = MemoryBackend::new();
+        backend.insert("doc.txt", b"x", 1000.0);
+        run_sync(&backend, &root).unwrap();
+
+        std::fs::remove_file(root.join("doc.txt")).unwrap();
+        run_sync(&backend, &root).unwrap();
+
+        assert!(!backend.contains("doc.txt"), "locally deleted file must be removed on the server");
+        assert!(!root.join("doc.txt").exists(), "and must not be downloaded again");
+    }
+
+    #[test]
+    fn engine_skips_mass_server_delete_when_local_folder_is_empty() {
+        let (_guard, root) = engine_env("safety");
+        let backend = MemoryBackend::new();
+        for i in 0..60 {
+            backend.insert(&format!("f{}.txt", i), b"x", 1000.0);
+        }
+        run_sync(&backend, &root).unwrap();
+
+        // Sync folder wiped (e.g. unmounted drive): must not delete everything on the server
+        std::fs::remove_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&root).unwrap();
+        run_sync(&backend, &root).unwrap();
+
+        assert_eq!(backend.len(), 60);
+        assert!(root.join("f0.txt").exists());
+    }
+
+    #[test]
+    fn engine_leaves_ignored_paths_alone() {
+        let (_guard, root) = engine_env("ignorepath");
+        let backend = MemoryBackend::new();
+        std::fs::create_dir_all(root.join("build")).unwrap();
+        std::fs::write(root.join("build").join("out.bin"), b"v1").unwrap();
+        std::fs::write(root.join("notes.txt"), b"n").unwrap();
+        run_sync(&backend, &root).unwrap();
+        assert!(backend.contains("build/out.bin"));
+
+        assert_eq!(set_path_ignored("/build/", true).unwrap(), "build");
+        assert!(set_path_ignored("../etc", true).is_err());
+        std::fs::write(root.join("build").join("new.bin"), b"x").unwrap();
+        backend.delete_file("build/out.bin").unwrap();
+        run_sync(&backend, &root).unwrap();
+        assert!(!backend.contains("build/new.bin"), "ignored folder must not be uploaded");
+        assert!(root.join("build").join("out.bin").exists(), "server delete must not remove the ignored local copy");
+        assert!(!load_sync_state().paths.iter().any(|p| p.starts_with("build/")));
+
+        set_path_ignored("build", false).unwrap();
+        run_sync(&backend, &root).unwrap();
+        assert!(backend.contains("build/new.bin"));
+        assert!(backend.contains("build/out.bin"));
+    }
+
+    #[test]
+    fn engine_tombstones_tell_stale_copies_from_new_files() {
+        let (_guard, root) = engine_env("tombstones");
+        let backend = MemoryBackend::new();
+        std::fs::write(root.join("old.txt"), b"v1").unwrap();
+        set_file_mtime(&root.join("old.txt"), 1_600_000_000.0).unwrap();
+        std::fs::write(root.join("reused.txt"), b"v1").unwrap();
+        run_sync(&backend, &root).unwrap();
+
+        std::fs::remove_file(root.join("old.txt")).unwrap();
+        std::fs::remove_file(root.join("reused.txt")).unwrap();
+        run_sync(&backend, &root).unwrap();
+        assert!(!backend.contains("old.txt") && !backend.contains("reused.txt"));
+        assert!(load_sync_state().tombstones.contains_key("old.txt"));
+
+        // Another device with a stale state uploads the old copy again; a third creates a new
+        // file under the other name
+        backend.insert("old.txt", b"v1", 1_600_000_000.0);
+        let later = system_time_secs(std::time::SystemTime::now()) + 600.0;
+        backend.insert("reused.txt", b"new", later);
+        run_sync(&backend, &root).unwrap();
+        assert!(!backend.contains("old.txt"), "stale copy must be deleted again");
+        assert!(!root.join("old.txt").exists());
+        assert_eq!(std::fs::read(root.join("reused.txt")).unwrap(), b"new");
+        let state = load_sync_state();
+        assert!(!state.tombstones.contains_key("reused.txt"));
+    }
+
+    #[test]
+    fn engine_keeps_files_recreated_elsewhere_with_an_older_mtime() {
+        let (_guard, root) = engine_env("tombstone_recreated");
+        let backend = MemoryBackend::new();
+        std::fs::write(root.join("report.txt"), b"draft").unwrap();
+        set_file_mtime(&root.join("report.txt"), 1_600_000_000.0).unwrap();
+        run_sync(&backend, &root).unwrap();
+        std::fs::remove_file(root.join("report.txt")).unwrap();
+        run_sync(&backend, &root).unwrap();
+        assert!(!backend.contains("report.txt"));
+
+        // Another device restores a different version from a backup, keeping its old mtime
+        backend.insert("report.txt", b"final from backup", 1_500_000_000.0);
+        run_sync(&backend, &root).unwrap();
+        assert!(backend.contains("report.txt"), "re-created file must not be deleted from the server");
+        assert_eq!(std::fs::read(root.join("report.txt")).unwrap(), b"final from backup");
+        assert!(!load_sync_state().tombstones.contains_key("report.txt"));
+    }
+
+    #[test]
+    fn engine_holds_local_deletes_when_server_listing_looks_broken() {
+        let (_guard, root) = engine_env("held_deletes");
+        let backend = MemoryBackend::new();
+        for i in 0..80 {
+            std::fs::write(root.join(format!("f{i}.txt")), b"x").unwrap();
+        }
+        run_sync(&backend, &root).unwrap();
+
+        // Most files vanish from the listing at once: keep them and ask
+        for i in 0..70 {
+            backend.delete_file(&format!("f{i}.txt")).unwrap();
+        }
+        let (_, _, warning) = run_sync(&backend, &root).unwrap();
+        assert!(warning.unwrap().contains("missing from the server listing"));
+        assert_eq!(held_local_deletes(), 70);
+        assert!(root.join("f0.txt").exists());
+        assert!(!backend.contains("f0.txt"), "held files must not be uploaded again");
+        run_sync(&backend, &root).unwrap();
+        assert!(root.join("f0.txt").exists() && !backend.contains("f0.txt"));
+
+        confirm_held_deletes();
+        run_sync(&backend, &root).unwrap();
+        assert!(!root.join("f0.txt").exists());
+        assert!(root.join("f75.txt").exists());
+        assert_eq!(held_local_deletes(), 0);
+
+        // An empty listing never deletes without confirmation, however few files
+        for i in 70..80 {
+            backend.delete_file(&format!("f{i}.txt")).unwrap();
+        }
+        run_sync(&backend, &root).unwrap();
+        assert!(root.join("f75.txt").exists());
+        assert_eq!(held_local_deletes(), 10);
+    }
+
+    #[test]
+    fn engine_copies_files_into_snapshot_before_deleting_them() {
+        let (_guard, root) = engine_env("snapshot");
+        let backend = MemoryBackend::new();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/thesis.txt"), b"final").unwrap();
+        std::fs::write(root.join("kept.txt"), b"kept").unwrap();
+        run_sync(&backend, &root).unwrap();
+        config::set_delete_snapshots(true, None);
+
+        backend.delete_file("docs/thesis.txt").unwrap();
+        run_sync(&backend, &root).unwrap();
+        assert!(!root.join("docs/thesis.txt").exists());
+        let snapshots = snapshots::list();
+        assert_eq!(snapshots.len(), 1);
+        let copy = Path::new(&snapshots[0].path).join("docs").join("thesis.txt");
+        assert_eq!(std::fs::read(copy).unwrap(), b"final");
+    }
+
+    #[test]
+    fn engine_restore_downloads_first_then_syncs_both_ways() {
+        let (_guard, root) = engine_env("restore");
+        let backend = MemoryBackend::new();
+        backend.insert("photos/a.jpg", b"server", 1_600_000_000.0);
+        backend.insert("notes.txt", b"server notes", 1_600_000_000.0);
+        std::fs::write(root.join("notes.txt"), b"edited here").unwrap();
+        std::fs::write(root.join("leftover.txt"), b"old machine").unwrap();
+        config::set_restore_pending(true);
+
+        run_sync(&backend, &root).unwrap();
+        assert_eq!(std::fs::read(root.join("photos/a.jpg")).unwrap(), b"server");
+        assert_eq!(backend.len(), 2);
+        assert!(!backend.contains("leftover.txt"));
+        assert_eq!(backend.download_file("notes.txt").unwrap(), b"server notes");
+        assert!(!config::get_restore_pending());
+
+        run_sync(&backend, &root).unwrap();
+        assert!(backend.contains("leftover.txt"));
+    }
+
+    #[test]
+    fn engine_restore_stays_pending_when_quitting_interrupts_downloads() {
+        let (_guard, root) = engine_env("restore_interrupted");
+        let backend = MemoryBackend::new();
+        backend.insert("photos/a.jpg", b"server", 1_600_000_000.0);
+        std::fs::write(root.join("leftover.txt"), b"old machine").unwrap();
+        config::set_restore_pending(true);
+
+        let running = try_lock_sync().unwrap();
+        request_shutdown();
+        let result = run_sync_locked(&running, &backend, &root);
+        SHUTDOWN_REQUESTED.store(false, std::sync::atomic::Ordering::SeqCst);
+        drop(running);
+        result.unwrap();
+        assert!(!root.join("photos/a.jpg").exists());
+        assert!(config::get_restore_pending(), "restore must resume after an interrupted cycle");
+
+        run_sync(&backend, &root).unwrap();
+        assert_eq!(std::fs::read(root.join("photos/a.jpg")).unwrap(), b"server");
+        assert!(!backend.contains("leftover.txt"));
+        assert!(!config::get_restore_pending());
+    }
+
+    #[test]
+    fn engine_kiosk_mode_mirrors_the_server_without_changing_it() {
+        let (_guard, root) = engine_env("kiosk");
+        let backend = MemoryBackend::new();
+        backend.insert("slides/a.jpg", b"a", 1_600_000_000.0);
+        backend.insert("slides/b.jpg", b"b", 1_600_000_000.0);
+        run_sync(&backend, &root).unwrap();
+        config::set_download_only(true);
+
+        std::fs::write(root.join("slides/a.jpg"), b"edited").unwrap();
+        std::fs::remove_file(root.join("slides/b.jpg")).unwrap();
+        std::fs::write(root.join("local.txt"), b"x").unwrap();
+        backend.insert("slides/c.jpg", b"c", 1_600_000_000.0);
+        run_sync(&backend, &root).unwrap();
+        assert_eq!(backend.len(), 3);
+        assert!(!backend.contains("local.txt"));
+        assert_eq!(std::fs::read(root.join("slides/a.jpg")).unwrap(), b"a");
+        assert_eq!(std::fs::read(root.join("slides/b.jpg")).unwrap(), b"b");
+        assert_eq!(std::fs::read(root.join("slides/c.jpg")).unwrap(), b"c");
+        assert!(root.join("local.txt").exists());
+    }
+
+    #[test]
+    fn engine_guards_deletes_on_removable_drive() {
+        let (_guard, root) = engine_env("removable");
+        let backend = MemoryBackend::new();
+        for i in 0..20 {
+            std::fs::write(root.join(format!("f{}.txt", i)), b"x").unwrap();
+        }
+        // The temp dir is on a local disk; each completed cycle records the detected drive
+        let mark_removable = || {
+            let mut state = load_sync_state();
+            state.volume = VolumeKind::Removable;
+            save_sync_state(&state);
+            let _ = SYNC_ROOT_VOLUME.lock().map(|mut g| *g = None);
+        };
+        run_sync(&backend, &root).unwrap();
+        mark_removable();
+
+        // Half the folder gone at once: kept on the server and downloaded again
+        for i in 0..12 {
+            std::fs::remove_file(root.join(format!("f{}.txt", i))).unwrap();
+        }
+        run_sync(&backend, &root).unwrap();
+        assert_eq!(backend.len(), 20);
+        assert!(root.join("f0.txt").exists());
+
+        // Drive unplugged, empty mount point left behind: no cycle at all
+        mark_removable();
+        for i in 0..20 {
+            std::fs::remove_file(root.join(format!("f{}.txt", i))).unwrap();
+        }
+        let err = run_sync(&backend, &root).unwrap_err();
+        assert!(err.message.contains("drive connected"), "{}", err);
+        assert_eq!(err.code, SyncErrorCode::SyncFolderUnavailable);
+        assert_eq!(backend.len(), 20);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(ensure_sync_root(&root).unwrap_err().code, SyncErrorCode::SyncFolderUnavailable);
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn blake3_file_hashes_match_official_test_vectors() {
+        let hex = |input: &[u8]| blake3::hash(input).to_hex().to_string();
+        assert_eq!(hex(b""), "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262");
+        assert_eq!(hex(b"abc"), "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85");
+
+        // Official test vectors use the input bytes 0, 1, …, 250, 0, 1, …
+        let data: Vec<u8> = (0..5 * 1024 + 300).map(|i| (i % 251) as u8).collect();
+        assert_eq!(hex(&data[..1024]), "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7");
+        assert_eq!(hex(&data[..1025]), "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444");
+
+        // Files are hashed piece by piece; the result must equal the one-shot hash
+        let file = std::env::temp_dir().join(format!("brandybox-test-blake3-{}", std::process::id()));
+        std::fs::write(&file, &data).unwrap();
+        let hashed = compute_file_hash_like(&file, "blake3:");
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(hashed, Some(format!("{}{}", BLAKE3_PREFIX, hex(&data))));
+    }
+
+    #[test]
+    fn engine_compares_blake3_and_migrates_cached_sha256() {
+        let (_guard, root) = engine_env("blake3");
+        let backend = MemoryBackend::with_blake3();
+        backend.insert("a.txt", b"same", 1000.0);
+        std::fs::write(root.join("a.txt"), b"same").unwrap();
+        set_file_mtime(&root.join("a.txt"), 5000.0).unwrap();
+
+        // Cached SHA-256 from before the server offered BLAKE3 becomes the BLAKE3 hash
+        let mut listed = backend.list_files().unwrap();
+        let sha256 = listed[0].hash.clone().unwrap();
+        let mut cache: HashMap<String, String> = [("a.txt".to_string(), sha256)].into_iter().collect();
+        select_hashes(&mut listed, HashAlgorithm::Blake3, &mut cache);
+        assert!(cache["a.txt"].starts_with(BLAKE3_PREFIX));
+        assert_eq!(listed[0].hash.as_ref(), Some(&cache["a.txt"]));
+
+        // Same content, different mtimes: verified by BLAKE3, nothing transferred
+        let (local_list, _) = list_local(&root, false);
+        let plan = build_plan(&root, &local_list, &listed, &SyncStateFile::default(), 0.0, false, false);
+        assert!(plan.to_upload.is_empty() && plan.to_download.is_empty());
+        assert_eq!(compute_file_hash_like(&root.join("a.txt"), &cache["a.txt"]).as_ref(), Some(&cache["a.txt"]));
+
+        run_sync(&backend, &root).unwrap();
+        assert_eq!(std::fs::read(root.join("a.txt")).unwrap(), b"same");
+    }
+
+    #[test]
+    fn engine_hashes_many_changed_files_in_parallel() {
+        let (_guard, root) = engine_env("parallel_hash");
+        let backend = MemoryBackend::new();
+        for i in 0..40 {
+            let name = format!("f{i}.txt");
+            let content = if i % 4 == 0 { format!("changed {i}") } else { format!("file {i}") };
+            backend.insert(&name, format!("file {i}").as_bytes(), 1000.0);
+            std::fs::write(root.join(&name), content).unwrap();
+            set_file_mtime(&root.join(&name), 5000.0).unwrap();
+        }
+        let listed = backend.list_files().unwrap();
+        let (local_list, _) = list_local(&root, false);
+        let plan = build_plan(&root, &local_list, &listed, &SyncStateFile::default(), 0.0, false, true);
+        let mut uploads = plan.to_upload.clone();
+        uploads.sort();
+        let mut expected: Vec<String> = (0..40).step_by(4).map(|i| format!("f{i}.txt")).collect();
+        expected.sort();
+        assert_eq!(uploads, expected);
+        assert!(plan.to_download.is_empty());
+        assert_eq!(get_sync_progress().map(|p| (p.current, p.total)), Some((40, 40)));
+    }
+
+    /// A box of `n` files spread over nested folders. By `i % 10`: 1 new locally, 2 new on the
+    /// server, 3 deleted locally, 4 deleted on the server, the rest unchanged on both sides.
+    fn synthetic_box(n: usize) -> (Vec<(String, f64)>, Vec<FileItem>, SyncStateFile) {
+        let (mut local, mut remote, mut synced) = (Vec::new(), Vec::new(), SyncStateFile::default());
+        for i in 0..n {
+            let path = format!("d{}/s{}/file{}.txt", i / 500, i % 7, i);
+            let kind = i % 10;
+            if kind != 2 && kind != 3 {
+                local.push((path.clone(), 1000.0));
+            }
+            if kind != 1 && kind != 4 {
+                remote.push(FileItem { path: path.clone(), mtime: 1000.0, hash: Some(format!("{:064x}", i)), size: Some(10), blake3: None });
+            }
+            if kind != 1 && kind != 2 {
+                synced.paths.push(path);
+            }
+        }
+        (local, remote, synced)
+    }
+
+    #[test]
+    fn build_plan_handles_a_100k_file_box() {
+        let (_guard, root) = engine_env("large_plan");
+        let (local, remote, synced) = synthetic_box(100_000);
+        let plan = build_plan(&root, &local, &remote, &synced, 0.0, false, false);
+        assert_eq!(plan.to_upload.len(), 10_000);
+        assert_eq!(plan.to_download.len(), 10_000);
+        assert_eq!(plan.to_del_remote.len(), 10_000);
+        assert_eq!(plan.to_del_local.len(), 10_000);
+        assert_eq!(plan.base_synced.len(), 60_000);
+        assert_eq!(plan.total_work, 40_000 + 20_000);
+        assert!(plan.to_download.windows(2).all(|w| w[0] < w[1]));
+        let depth = |p: &String| p.matches('/').count();
+        assert!(plan.to_del_remote.windows(2).all(|w| depth(&w[0]) >= depth(&w[1])));
+    }
+
+    /// Timings for large boxes, not run by default:
+    /// `cargo test --release -p brandybox-core bench_ -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_large_box() {
+        let (_guard, root) = engine_env("bench");
+        for n in [100_000, 300_000] {
+            let (local, remote, synced) = synthetic_box(n);
+            let started = std::time::Instant::now();
+            let plan = build_plan(&root, &local, &remote, &synced, 0.0, false, false);
+            println!("build_plan, {} files: {:?} ({} transfers)", n, started.elapsed(), plan.total_work);
+        }
+        let files = 20_000;
+        for i in 0..files {
+            let dir = root.join(format!("d{}", i / 200));
+            if i % 200 == 0 {
+                std::fs::create_dir_all(&dir).unwrap();
+            }
+            std::fs::write(dir.join(format!("f{}.txt", i)), b"x").unwrap();
+        }
+        // Let directory mtimes age past the granularity window so the index may reuse them
+        std::thread::sleep(std::time::Duration::from_secs(3));
+        for label in ["full", "incremental"] {
+            let started = std::time::Instant::now();
+            let (listed, _) = list_local(&root, false);
+            assert_eq!(listed.len(), files);
+            println!("list_local ({}), {} files: {:?}", label, files, started.elapsed());
+        }
+    }
+
+    #[test]
+    fn engine_keeps_mtimes_across_devices() {
+        let (_guard, root) = engine_env("mtimes");
+        let backend = MemoryBackend::new();
+        std::fs::write(root.join("report.pdf"), b"pdf").unwrap();
+        set_file_mtime(&root.join("report.pdf"), 1_600_000_000.0).unwrap();
+        backend.insert("photo.jpg", b"jpg", 1_500_000_000.0);
+        run_sync(&backend, &root).unwrap();
+
+        let listed = backend.list_files().unwrap();
+        let uploaded = listed.iter().find(|i| i.path == "report.pdf").unwrap();
+        assert!((uploaded.mtime - 1_600_000_000.0).abs() < 0.01, "upload must keep the local mtime");
+        let downloaded = std::fs::metadata(root.join("photo.jpg")).unwrap().modified().unwrap();
+        assert!((system_time_secs(downloaded) - 1_500_000_000.0).abs() < 0.01, "download must take the server mtime");
+
+        // Neither side looks changed afterwards
+        let (local_list, _) = list_local(&root, false);
+        let plan = build_plan(&root, &local_list, &listed, &load_sync_state(), 0.0, false, false);
+        assert!(plan.to_upload.is_empty() && plan.to_download.is_empty() && plan.verified_hashes.is_empty());
+    }
+
+    #[test]
+    fn engine_corrects_for_server_clock_behind() {
+        let (_guard, root) = engine_env("skew");
+        let backend = MemoryBackend::with_clock_offset(-600.0);
+        std::fs::write(root.join("notes.txt"), b"original").unwrap();
+        run_sync(&backend, &root).unwrap();
+
+        // Edited on another device shortly after; stamped with the (slow) server clock
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs_f64();
+        backend.insert("notes.txt", b"edited elsewhere", now - 600.0 + 30.0);
+        run_sync(&backend, &root).unwrap();
+
+        assert_eq!(std::fs::read(root.join("notes.txt")).unwrap(), b"edited elsewhere");
+        assert_eq!(backend.download_file("notes.txt").unwrap(), b"edited elsewhere");
+    }
+
+    #[test]
+    fn engine_never_writes_outside_sync_folder() {
+        let (_guard, root) = engine_env("traversal");
+        let backend = MemoryBackend::new();
+        backend.insert("../escape.txt", b"evil", 1000.0);
+        backend.insert("a/../../escape2.txt", b"evil", 1000.0);
+        backend.insert("ok.txt", b"fine", 1000.0);
+        #[cfg(unix)]
+        {
+            let outside = root.parent().unwrap().join("outside");
+            std::fs::create_dir_all(&outside).unwrap();
+            std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+            backend.insert("link/escape3.txt", b"evil", 1000.0);
+        }
+
+        let (_, _, warning) = run_sync(&backend, &root).unwrap();
+
+        assert!(root.join("ok.txt").exists());
+        assert!(!root.parent().unwrap().join("escape.txt").exists());
+        assert!(!root.parent().unwrap().join("escape2.txt").exists());
+        assert!(!root.parent().unwrap().join("outside").join("escape3.txt").exists());
+        assert!(warning.unwrap().contains("outside the sync folder"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn engine_reports_local_deletes_that_fail() {
+        use std::os::unix::fs::PermissionsExt;
+        let (_guard, root) = engine_env("failed-delete");
+        let backend = MemoryBackend::new();
+        std::fs::create_dir_all(root.join("locked")).unwrap();
+        std::fs::write(root.join("locked").join("a.txt"), b"x").unwrap();
+        std::fs::write(root.join("kept.txt"), b"x").unwrap();
+        run_sync(&backend, &root).unwrap();
+
+        backend.delete_file("locked/a.txt").unwrap();
+        std::fs::set_permissions(root.join("locked"), std::fs::Permissions::from_mode(0o555)).unwrap();
+        let deletable = std::fs::File::create(root.join("locked").join("probe")).is_ok();
+        let result = if deletable { None } else { Some(run_sync(&backend, &root)) };
+        std::fs::set_permissions(root.join("locked"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        // Running with privileges that ignore permissions (e.g. as root): nothing to test
+        let Some(result) = result else { return };
+
+        let warning = result.unwrap().2.unwrap();
+        assert!(warning.contains("could not be deleted"), "{}", warning);
+        assert_eq!(SYNC_ISSUES.lock().unwrap()[0].code, SyncErrorCode::FailedDeletes);
+        // Still synced: the next cycle deletes it instead of uploading it again
+        run_sync(&backend, &root).unwrap();
+        assert!(!root.join("locked").join("a.txt").exists());
+        assert!(!backend.contains("locked/a.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn engine_skips_unreadable_files_and_folders() {
+        use std::os::unix::fs::PermissionsExt;
+        let (_guard, root) = engine_env("unreadable");
+        let backend = MemoryBackend::new();
+        std::fs::create_dir_all(root.join("private")).unwrap();
+        std::fs::write(root.join("private").join("kept.txt"), b"x").unwrap();
+        std::fs::write(root.join("secret.txt"), b"x").unwrap();
+        std::fs::write(root.join("ok.txt"), b"x").unwrap();
+        run_sync(&backend, &root).unwrap();
+
+        std::fs::write(root.join("secret.txt"), b"changed").unwrap();
+        std::fs::set_permissions(root.join("secret.txt"), std::fs::Permissions::from_mode(0o000)).unwrap();
+        std::fs::set_permissions(root.join("private"), std::fs::Permissions::from_mode(0o000)).unwrap();
+        let readable = std::fs::File::open(root.join("secret.txt")).is_ok();
+        let result = if readable { None } else { Some(run_sync(&backend, &root)) };
+        std::fs::set_permissions(root.join("private"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::set_permissions(root.join("secret.txt"), std::fs::Permissions::from_mode(0o644)).unwrap();
+        // Running with privileges that ignore permissions (e.g. as root): nothing to test
+        let Some(result) = result else { return };
+
+        let warning = result.unwrap().2.unwrap();
+        assert!(warning.contains("secret.txt") && warning.contains("private/"), "{}", warning);
+        assert!(backend.contains("private/kept.txt"), "unreadable folder must not count as deleted");
+        assert!(!load_sync_state().paths.contains(&"secret.txt".to_string()));
+    }
+
+    #[test]
+    fn engine_hidden_files_setting_applies_both_ways() {
+        let (_guard, root) = engine_env("hidden");
+        let backend = MemoryBackend::new();
+        config::set_sync_hidden_files(false);
+        std::fs::create_dir_all(root.join(".obsidian")).unwrap();
+        std::fs::write(root.join(".obsidian").join("app.json"), b"{}").unwrap();
+        std::fs::write(root.join("note.md"), b"x").unwrap();
+        backend.insert(".config/tool.conf", b"x", 1000.0);
+
+        run_sync(&backend, &root).unwrap();
+        assert!(backend.contains("note.md"));
+        assert!(!backend.contains(".obsidian/app.json"));
+        assert!(!root.join(".config").exists());
+
+        config::set_sync_hidden_files(true);
+        run_sync(&backend, &root).unwrap();
+        assert!(backend.contains(".obsidian/app.json"));
+        assert!(root.join(".config").join("tool.conf").exists());
+    }
+
+    #[test]
+    fn engine_case_only_renames_move_instead_of_reupload() {
+        let (_guard, root) = engine_env("case");
+        let backend = MemoryBackend::new();
+        backend.insert("readme.md", b"hello", 1000.0);
+        backend.insert("notes.txt", b"notes", 1000.0);
+        run_sync(&backend, &root).unwrap();
+
+        std::fs::rename(root.join("readme.md"), root.join("README.md")).unwrap();
+        backend.move_file("notes.txt", "Notes.txt").unwrap();
+        run_sync(&backend, &root).unwrap();
+
+        let remote = backend.list_files().unwrap();
+        let readme = remote.iter().find(|f| f.path == "README.md").expect("renamed on server");
+        assert_eq!(readme.mtime, 1000.0, "moved, not uploaded again");
+        assert!(!backend.contains("readme.md"));
+        assert!(root.join("Notes.txt").exists());
+        assert!(!std::fs::read_dir(&root).unwrap().any(|e| e.unwrap().file_name() == "notes.txt"));
+        let mut synced = load_sync_state().paths;
+        synced.sort();
+        assert_eq!(synced, vec!["Notes.txt".to_string(), "README.md".to_string()]);
+    }
+
+    #[test]
+    fn engine_skips_files_over_size_limit_unless_allowed() {
+        let (_guard, root) = engine_env("maxsize");
+        let backend = MemoryBackend::new();
+        config::set_max_file_size_bytes(Some(100));
+        std::fs::write(root.join("disk.img"), [0u8; 200]).unwrap();
+        std::fs::write(root.join("small.txt"), [0u8; 10]).unwrap();
+        backend.insert("movie.mkv", &[0u8; 500], 1000.0);
+
+        let (_, _, warning) = run_sync(&backend, &root).unwrap();
+        let warning = warning.unwrap();
+        assert!(warning.contains("disk.img (200 B)") && warning.contains("movie.mkv (500 B)"), "{}", warning);
+        assert!(backend.contains("small.txt"));
+        assert!(!backend.contains("disk.img"));
+        assert!(!root.join("movie.mkv").exists());
+
+        config::set_large_file_allowed("disk.img", true);
+        run_sync(&backend, &root).unwrap();
+        assert!(backend.contains("disk.img"));
+        assert!(!root.join("movie.mkv").exists());
+    }
+
+    #[test]
+    fn overdue_only_after_hours_of_failed_attempts() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let times = |attempt: Option<u32>, success: Option<u32>, since: u32| stats::SyncTimes {
+            last_attempt: attempt.map(|h| format!("2026-03-01T{:02}:00:00Z", h)),
+            last_success: success.map(|h| format!("2026-03-01T{:02}:00:00Z", h)),
+            since: Some(format!("2026-03-01T{:02}:00:00Z", since)),
+        };
+        assert_eq!(overdue_hours(&times(None, None, 1), now), None);
+        assert_eq!(overdue_hours(&times(Some(11), Some(11), 1), now), None);
+        assert_eq!(overdue_hours(&times(Some(11), Some(9), 1), now), None);
+        assert_eq!(overdue_hours(&times(Some(11), Some(5), 1), now), Some(7));
+        // Failing since the first cycle
+        assert_eq!(overdue_hours(&times(Some(11), None, 4), now), Some(8));
+        assert_eq!(overdue_hours(&times(Some(11), None, 10), now), None);
+    }
+
+    #[test]
+    fn format_size_picks_unit() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(200 * 1024 * 1024 * 1024), "200.0 GB");
+    }
+
+    #[test]
+    fn format_count_groups_thousands() {
+        assert_eq!(format_count(7), "7");
+        assert_eq!(format_count(12400), "12,400");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn case_pairs_match_only_case_differences() {
+        let deleted = vec!["a/readme.md".to_string(), "b.txt".to_string()];
+        let added = vec!["a/README.md".to_string(), "c.txt".to_string()];
+        assert_eq!(case_pairs(&deleted, &added), vec![("a/readme.md".to_string(), "a/README.md".to_string())]);
+    }
+
+    #[test]
+    fn unreadable_dir_matching_and_path_lists() {
+        let dirs = vec!["a/b".to_string()];
+        assert!(in_unreadable_dir("a/b/c.txt", &dirs));
+        assert!(!in_unreadable_dir("a/bc.txt", &dirs));
+        assert!(in_unreadable_dir("anything", &[String::new()]));
+        let paths: Vec<String> = (1..=7).map(|i| format!("f{}", i)).collect();
+        assert_eq!(format_path_list(&paths[..2]), "f1, f2");
+        assert_eq!(format_path_list(&paths), "f1, f2, f3, f4, f5 and 2 more");
+    }
+
+    #[test]
+    fn safe_relative_paths() {
+        assert!(is_safe_relative_path("a/b.txt"));
+        assert!(is_safe_relative_path("..hidden/x"));
+        for bad in ["", "/etc/passwd", "../x", "a/../../x", "a//b", "./a", "a\\..\\b", "a\u{0}b"] {
+            assert!(!is_safe_relative_path(bad), "{:?} must be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn mtime_comparison_applies_offset_and_tolerance() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_mtimes(1000.0, 1001.5, 0.0), Ordering::Equal);
+        assert_eq!(compare_mtimes(1000.0, 1005.0, 0.0), Ordering::Less);
+        // Server clock 5 min ahead: a server mtime 300 s "later" is the same moment
+        assert_eq!(compare_mtimes(1000.0, 1300.0, 300.0), Ordering::Equal);
+        assert_eq!(compare_mtimes(1000.0, 1200.0, 300.0), Ordering::Greater);
+    }
+
+    #[test]
+    fn sync_windows_same_day_overnight_and_weekdays() {
+        let w = |start: &str, end: &str, days: Vec<u32>| config::SyncWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+            days,
+        };
+        let office = w("09:00", "17:00", vec![1, 2, 3, 4, 5]);
+        assert!(in_sync_window(&office, 1, 9 * 60));
+        assert!(!in_sync_window(&office, 1, 17 * 60));
+        assert!(!in_sync_window(&office, 6, 12 * 60));
+        let night = w("22:30", "06:00", vec![5]);
+        assert!(in_sync_window(&night, 5, 23 * 60));
+        assert!(in_sync_window(&night, 6, 5 * 60));
+        assert!(!in_sync_window(&night, 5, 5 * 60));
+        assert!(!in_sync_window(&w("10:00", "10:00", vec![]), 3, 10 * 60));
+        assert!(!in_sync_window(&w("25:00", "10:00", vec![]), 3, 9 * 60));
+        assert_eq!(parse_hhmm("07:05"), Some(425));
+        assert_eq!(parse_hhmm("7"), None);
+    }
+
+    #[test]
+    fn corrupt_sync_state_falls_back_to_backup() {
+        let (_guard, root) = engine_env("statebackup");
+        let backend = MemoryBackend::new();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        run_sync(&backend, &root).unwrap();
+        std::fs::write(root.join("b.txt"), b"b").unwrap();
+        run_sync(&backend, &root).unwrap();
+        // Crash mid-write of a non-atomic writer: truncated file
+        std::fs::write(config::get_sync_state_path(), b"{\"paths\": [\"a.t").unwrap();
+        assert_eq!(load_sync_state().paths, vec!["a.txt".to_string()]);
+    }
+
+    #[test]
+    fn sync_lock_is_exclusive_until_dropped() {
+        let (_guard, root) = engine_env("lock");
+        let backend = MemoryBackend::new();
+        let held = try_lock_sync().unwrap();
+        assert!(try_lock_sync().is_none());
+        assert_eq!(run_sync(&backend, &root).unwrap_err().message, "Sync already running");
+        run_sync_locked(&held, &backend, &root).unwrap();
+        drop(held);
+        assert!(run_sync(&backend, &root).is_ok());
+    }
+
+    #[test]
+    fn sync_requests_during_a_cycle_coalesce_into_one_rerun() {
+        let (_guard, _root) = engine_env("coalesce");
+        let running = lock_or_queue_sync().unwrap();
+        // Three requests while the cycle runs: none starts, one rerun is queued
+        assert!(lock_or_queue_sync().is_none());
+        assert!(lock_or_queue_sync().is_none());
+        assert!(lock_or_queue_sync().is_none());
+        let rerun = finish_cycle(running).expect("runs once more");
+        assert!(try_lock_sync().is_none());
+        assert!(finish_cycle(rerun).is_none());
+        // Lock released, nothing left queued
+        let next = try_lock_sync().unwrap();
+        assert!(finish_cycle(next).is_none());
+    }
+
+    #[test]
+    fn quitting_stops_transfers_and_keeps_them_for_the_next_cycle() {
+        let (_guard, root) = engine_env("shutdown");
+        let backend = MemoryBackend::new();
+        backend.insert("remote.txt", b"remote", 1000.0);
+        std::fs::write(root.join("local.txt"), b"local").unwrap();
+
+        let running = try_lock_sync().unwrap();
+        request_shutdown();
+        let result = run_sync_locked(&running, &backend, &root);
+        assert!(try_lock_sync().is_none());
+        SHUTDOWN_REQUESTED.store(false, std::sync::atomic::Ordering::SeqCst);
+        drop(running);
+        result.unwrap();
+        assert!(wait_until_idle(std::time::Duration::ZERO));
+        assert!(!root.join("remote.txt").exists());
+        assert!(!backend.contains("local.txt"));
+        assert!(load_sync_state().paths.is_empty());
+
+        run_sync(&backend, &root).unwrap();
+        assert!(root.join("remote.txt").exists());
+        assert!(backend.contains("local.txt"));
+    }
+
+    #[test]
+    fn engine_storage_full_skips_uploads_but_keeps_downloading() {
+        let (_guard, root) = engine_env("quota");
+        config::set_language("en".to_string());
+        let backend = MemoryBackend::with_quota(100);
+        backend.insert("remote.txt", &[1u8; 10], 1000.0);
+        std::fs::write(root.join("big.bin"), [0u8; 80]).unwrap();
+        std::fs::write(root.join("bigger.bin"), [0u8; 85]).unwrap();
+        std::fs::write(root.join("small.bin"), [0u8; 5]).unwrap();
+
+        let (_, _, warning) = run_sync(&backend, &root).unwrap();
+
+        assert!(root.join("remote.txt").exists());
+        assert!(backend.used_bytes() <= 100);
+        assert!(backend.contains("small.bin"));
+        assert!(warning.unwrap().contains("Storage full — 1 file(s) not uploaded"));
+        assert_eq!(classify_upload_error("finalize failed: 507 Insufficient Storage"), UploadFailure::StorageFull);
+        assert_eq!(classify_upload_error("413 Payload Too Large: File too large"), UploadFailure::TooLarge);
+        let issues = SYNC_ISSUES.lock().unwrap().clone();
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].code, issues[0].count), (SyncErrorCode::QuotaExceeded, 1));
+    }
+
+    /// Answers every request with `status` (e.g. "507 Insufficient Storage"); returns its URL.
+    fn http_stub(status: &'static str) -> String {
+        use std::io::{BufRead, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 0) && line != "\r\n" {
+                    line.clear();
+                }
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn failed_cycles_get_the_code_of_the_failed_request() {
+        let (_guard, root) = engine_env("error-codes");
+        for (status, code) in [
+            ("507 Insufficient Storage", SyncErrorCode::QuotaExceeded),
+            ("401 Unauthorized", SyncErrorCode::AuthExpired),
+            ("502 Bad Gateway", SyncErrorCode::Server),
+            ("400 Bad Request", SyncErrorCode::Unknown),
+        ] {
+            let err = run_sync(&crate::api::ApiClient::new(http_stub(status)), &root).unwrap_err();
+            assert_eq!(err.code, code, "{}", err);
+        }
+        // Nothing listening: no response at all
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        assert_eq!(run_sync(&crate::api::ApiClient::new(url), &root).unwrap_err().code, SyncErrorCode::Network);
+        assert_eq!(SyncErrorCode::from(Some(RequestFailure::IncompleteListing)), SyncErrorCode::Server);
+    }
+
+    #[test]
+    fn revoked_device_tooltip_is_not_up_to_date() {
+        let (_guard, _root) = engine_env("revoked-tooltip");
+        set_sync_status(SyncStatus::DeviceRevoked);
+        let summary = status_summary();
+        set_sync_status(SyncStatus::Idle);
+        assert!(!summary.contains("Up to date"), "{}", summary);
+        assert!(summary.contains("removed from your account"), "{}", summary);
+    }
+
+    /// Scenario: user had file (in last_synced), deletes it locally; sync must delete from server, not re-download.
+    #[test]
+    fn delete_local_then_sync_removes_from_server_not_download() {
+        let last_synced: HashSet<String> = ["DJI_0011.MP4".to_string()].into_iter().collect();
+        let current_local: HashSet<String> = HashSet::new();
+        let current_remote: HashSet<String> = ["DJI_0011.MP4".to_string()].into_iter().collect();
+
+        let to_delete_remote: HashSet<String> = last_synced
+            .difference(&current_local)
+            .filter(|p| !is_ignored(p))
+            .cloned()
+            .collect();
+        assert!(
+            to_delete_remote.contains("DJI_0011.MP4"),
+            "file deleted locally must be in to_delete_remote so it is removed from server"
+        );
+
+        let to_del_remote_set: HashSet<String> = to_delete_remote.iter().cloned().collect();
+        let mut to_download: Vec<String> = current_remote
+            .difference(&current_local)
+            .filter(|p| !is_ignored(p))
+            .cloned()
+            .collect();
+        to_download.retain(|path| !to_del_remote_set.contains(path));
+        assert!(
+            !to_download.contains(&"DJI_0011.MP4".to_string()),
+            "file deleted locally must not be in to_download (must not be re-downloaded)"
+        );
+    }
+}
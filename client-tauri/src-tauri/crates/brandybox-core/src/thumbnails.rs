@@ -0,0 +1,88 @@
+//! Disk cache for server-rendered image previews (config dir `thumbnails/`), so the file browser
+//! and activity feed show previews without downloading whole files. Entries are keyed by path,
+//! size and file version (hash or mtime), so an edited file gets a new preview. The cache is
+//! trimmed to `CACHE_MAX_BYTES`, oldest first.
+
+use crate::api::ApiClient;
+use crate::config;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+const CACHE_MAX_BYTES: u64 = 200 * 1024 * 1024;
+pub const DEFAULT_SIZE: u32 = 256;
+
+fn cache_key(path: &str, size: u32, version: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}\n{}\n{}", path, size, version).as_bytes());
+    format!("{:x}.jpg", hasher.finalize())
+}
+
+/// Remove the oldest files in `dir` until it holds at most `max_bytes`.
+fn trim_cache(dir: &Path, max_bytes: u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut files: Vec<(std::time::SystemTime, u64, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((meta.modified().ok()?, meta.len(), e.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|f| f.1).sum();
+    files.sort();
+    for (_, len, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total = total.saturating_sub(len);
+        }
+    }
+}
+
+/// Cached preview for `path`, without asking the server (no login needed).
+pub fn cached(path: &str, size: u32, version: &str) -> Option<PathBuf> {
+    let cached = config::get_thumbnail_cache_dir().join(cache_key(path, size, version));
+    cached.is_file().then_some(cached)
+}
+
+/// Local path of the preview for `path` (fetched from the server on a cache miss), or None when
+/// the server has no preview for it. `version` is the file's hash or mtime from the listing.
+pub fn get(client: &ApiClient, path: &str, size: u32, version: &str) -> Result<Option<PathBuf>, String> {
+    if let Some(cached) = cached(path, size, version) {
+        return Ok(Some(cached));
+    }
+    let dir = config::get_thumbnail_cache_dir();
+    let cached = dir.join(cache_key(path, size, version));
+    let Some(data) = client.get_thumbnail(path, size)? else {
+        return Ok(None);
+    };
+    config::write_atomic(&cached, &data).map_err(|e| e.to_string())?;
+    trim_cache(&dir, CACHE_MAX_BYTES);
+    Ok(Some(cached))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_changes_with_version_and_cache_trims_oldest() {
+        assert_ne!(cache_key("a.jpg", 256, "h1"), cache_key("a.jpg", 256, "h2"));
+        assert_ne!(cache_key("a.jpg", 256, "h1"), cache_key("a.jpg", 128, "h1"));
+
+        let dir = std::env::temp_dir().join(format!("brandybox-test-thumbs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let now = std::time::SystemTime::now();
+        for (i, name) in ["old.jpg", "mid.jpg", "new.jpg"].iter().enumerate() {
+            let p = dir.join(name);
+            std::fs::write(&p, [0u8; 100]).unwrap();
+            let t = now - std::time::Duration::from_secs(300 - 100 * i as u64);
+            std::fs::File::options().write(true).open(&p).unwrap().set_modified(t).unwrap();
+        }
+        trim_cache(&dir, 250);
+        assert!(!dir.join("old.jpg").exists());
+        assert!(dir.join("mid.jpg").exists() && dir.join("new.jpg").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,190 @@
+//! Space used per folder of the box, locally and on the server, so users can see what takes up
+//! their quota. The last breakdown is cached and refreshed after sync cycles. Also finds
+//! duplicate files on the server by their SHA-256.
+
+use crate::api::FileItem;
+use crate::backend::Backend;
+use crate::sync;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FolderSize {
+    /// Folder relative to the sync folder, up to the requested depth; "" for files at the top level.
+    pub path: String,
+    pub local_bytes: u64,
+    pub local_files: u64,
+    pub remote_bytes: u64,
+    pub remote_files: u64,
+}
+
+/// Last computed breakdown and the depth it was computed for.
+static CACHE: Mutex<Option<(usize, Vec<FolderSize>)>> = Mutex::new(None);
+
+/// Folder of `path` cut to `depth` levels ("a/b/c.txt" at depth 1 → "a").
+fn folder_key(path: &str, depth: usize) -> String {
+    let parts: Vec<&str> = path.split('/').collect();
+    let dirs = &parts[..parts.len().saturating_sub(1)];
+    dirs[..dirs.len().min(depth)].join("/")
+}
+
+/// Group local `(path, size)` and remote `(path, size)` entries per folder, largest first.
+fn group(local: &[(String, u64)], remote: &[(String, u64)], depth: usize) -> Vec<FolderSize> {
+    let mut by_folder: BTreeMap<String, FolderSize> = BTreeMap::new();
+    for (path, size) in local {
+        let key = folder_key(path, depth);
+        let e = by_folder.entry(key.clone()).or_insert_with(|| FolderSize { path: key, ..Default::default() });
+        e.local_bytes += size;
+        e.local_files += 1;
+    }
+    for (path, size) in remote {
+        let key = folder_key(path, depth);
+        let e = by_folder.entry(key.clone()).or_insert_with(|| FolderSize { path: key, ..Default::default() });
+        e.remote_bytes += size;
+        e.remote_files += 1;
+    }
+    let mut out: Vec<FolderSize> = by_folder.into_values().collect();
+    out.sort_by_key(|f| std::cmp::Reverse(f.local_bytes.max(f.remote_bytes)));
+    out
+}
+
+fn local_sizes(root: &Path) -> Vec<(String, u64)> {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+            if sync::is_ignored(&rel) {
+                return None;
+            }
+            Some((rel, e.metadata().ok()?.len()))
+        })
+        .collect()
+}
+
+/// Compute the breakdown `depth` folder levels deep (at least 1) and cache it.
+pub fn refresh<B: Backend + ?Sized>(client: &B, root: &Path, depth: usize) -> Result<Vec<FolderSize>, String> {
+    let depth = depth.max(1);
+    let remote: Vec<(String, u64)> = client
+        .list_files()?
+        .into_iter()
+        .filter(|f| !sync::is_ignored(&f.path))
+        .map(|f| (f.path, f.size.unwrap_or(0)))
+        .collect();
+    let sizes = group(&local_sizes(root), &remote, depth);
+    let _ = CACHE.lock().map(|mut g| *g = Some((depth, sizes.clone())));
+    Ok(sizes)
+}
+
+/// Cached breakdown for `depth`, if one was computed.
+pub fn cached(depth: usize) -> Option<Vec<FolderSize>> {
+    let guard = CACHE.lock().ok()?;
+    guard.as_ref().filter(|(d, _)| *d == depth.max(1)).map(|(_, s)| s.clone())
+}
+
+/// Depth of the cached breakdown; None until someone asked for one (nothing to refresh).
+pub fn cached_depth() -> Option<usize> {
+    CACHE.lock().ok()?.as_ref().map(|(d, _)| *d)
+}
+
+/// Files with identical content.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    /// Size of one copy.
+    pub size: u64,
+    pub paths: Vec<String>,
+    /// Bytes freed by keeping a single copy.
+    pub wasted_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DuplicateReport {
+    /// Most wasted bytes first.
+    pub groups: Vec<DuplicateGroup>,
+    pub wasted_bytes: u64,
+}
+
+/// Group server files with the same hash. Empty files and files without a hash are skipped.
+pub fn find_duplicates(items: &[FileItem]) -> DuplicateReport {
+    let mut by_hash: BTreeMap<&str, Vec<&FileItem>> = BTreeMap::new();
+    for item in items.iter().filter(|i| !sync::is_ignored(&i.path) && i.size != Some(0)) {
+        if let Some(hash) = item.hash.as_deref() {
+            by_hash.entry(hash).or_default().push(item);
+        }
+    }
+    let mut groups: Vec<DuplicateGroup> = by_hash
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(hash, files)| {
+            let size = files.iter().find_map(|f| f.size).unwrap_or(0);
+            let mut paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+            paths.sort();
+            DuplicateGroup {
+                hash: hash.to_string(),
+                size,
+                wasted_bytes: size * (paths.len() as u64 - 1),
+                paths,
+            }
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted_bytes));
+    DuplicateReport {
+        wasted_bytes: groups.iter().map(|g| g.wasted_bytes).sum(),
+        groups,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_local_and_remote_per_folder() {
+        assert_eq!(folder_key("a/b/c.txt", 1), "a");
+        assert_eq!(folder_key("a/b/c.txt", 2), "a/b");
+        assert_eq!(folder_key("top.txt", 1), "");
+        let local = vec![("Photos/1.jpg".to_string(), 300), ("notes.txt".to_string(), 5)];
+        let remote = vec![
+            ("Photos/1.jpg".to_string(), 300),
+            ("Photos/2019/2.jpg".to_string(), 200),
+            ("Videos/v.mp4".to_string(), 400),
+        ];
+        let sizes = group(&local, &remote, 1);
+        assert_eq!(sizes[0].path, "Photos");
+        assert_eq!((sizes[0].local_bytes, sizes[0].remote_bytes, sizes[0].remote_files), (300, 500, 2));
+        assert_eq!(sizes[1].path, "Videos");
+        assert_eq!(sizes[2], FolderSize { path: String::new(), local_bytes: 5, local_files: 1, ..Default::default() });
+    }
+
+    #[test]
+    fn duplicates_grouped_by_hash_with_wasted_bytes() {
+        let item = |path: &str, hash: Option<&str>, size: u64| FileItem {
+            path: path.to_string(),
+            mtime: 0.0,
+            hash: hash.map(String::from),
+            size: Some(size),
+            blake3: None,
+        };
+        let items = vec![
+            item("a/photo.jpg", Some("h1"), 100),
+            item("b/photo copy.jpg", Some("h1"), 100),
+            item("c/photo.jpg", Some("h1"), 100),
+            item("x.bin", Some("h2"), 500),
+            item("y.bin", Some("h2"), 500),
+            item("unique.txt", Some("h3"), 7),
+            item("empty1", Some("e"), 0),
+            item("empty2", Some("e"), 0),
+            item("nohash", None, 100),
+        ];
+        let report = find_duplicates(&items);
+        assert_eq!(report.groups.len(), 2);
+        assert_eq!(report.groups[0].hash, "h2");
+        assert_eq!(report.groups[1].paths.len(), 3);
+        assert_eq!(report.groups[1].wasted_bytes, 200);
+        assert_eq!(report.wasted_bytes, 700);
+    }
+}
@@ -0,0 +1,211 @@
+//! Admin bulk import: create many users from a CSV file.
+//!
+//! Columns: `email,first_name,last_name[,quota]`. A header row starting with "email" is skipped,
+//! as are blank lines and lines starting with `#`. Quota is bytes or a size like `10GB` / `500 MB`;
+//! empty means the server default. Fields may be double-quoted (`"Doe, Jr."`).
+
+use crate::api::ApiClient;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// 1-based line number in the CSV file.
+    pub line: usize,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub storage_limit_bytes: Option<i64>,
+}
+
+/// Outcome of one CSV line, returned to the UI as the import report.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRowResult {
+    pub line: usize,
+    pub email: String,
+    pub ok: bool,
+    pub error: Option<String>,
+    /// Set when the server returned the temporary password (SMTP not configured).
+    pub temp_password: Option<String>,
+}
+
+/// Split one CSV line into fields. Supports double-quoted fields with `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Parse "1073741824", "10GB", "1.5 TB", "500mb" into bytes (binary units).
+pub fn parse_size(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let value: f64 = num.parse().ok()?;
+    let factor: f64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "K" | "KB" | "KIB" => 1024.0,
+        "M" | "MB" | "MIB" => 1024.0 * 1024.0,
+        "G" | "GB" | "GIB" => 1024.0 * 1024.0 * 1024.0,
+        "T" | "TB" | "TIB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    if value < 0.0 {
+        return None;
+    }
+    Some((value * factor).round() as i64)
+}
+
+/// Parse the CSV text. Each entry is a valid row or the per-line error for the report.
+pub fn parse_csv(text: &str) -> Vec<Result<ImportRow, ImportRowResult>> {
+    let mut out = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let line = idx + 1;
+        let raw = raw.trim_start_matches('\u{feff}');
+        if raw.trim().is_empty() || raw.trim_start().starts_with('#') {
+            continue;
+        }
+        let fields = split_csv_line(raw);
+        if line == 1 && fields[0].eq_ignore_ascii_case("email") {
+            continue;
+        }
+        let email = fields[0].clone();
+        let fail = |error: String| ImportRowResult {
+            line,
+            email: email.clone(),
+            ok: false,
+            error: Some(error),
+            temp_password: None,
+        };
+        if fields.len() < 3 {
+            out.push(Err(fail("Expected email, first name, last name".to_string())));
+            continue;
+        }
+        if !email.contains('@') {
+            out.push(Err(fail("Invalid email address".to_string())));
+            continue;
+        }
+        let quota = fields.get(3).map(|s| s.as_str()).unwrap_or("");
+        let storage_limit_bytes = if quota.is_empty() {
+            None
+        } else {
+            match parse_size(quota) {
+                Some(b) => Some(b),
+                None => {
+                    out.push(Err(fail(format!("Invalid quota: {}", quota))));
+                    continue;
+                }
+            }
+        };
+        out.push(Ok(ImportRow {
+            line,
+            email,
+            first_name: fields[1].clone(),
+            last_name: fields[2].clone(),
+            storage_limit_bytes,
+        }));
+    }
+    out
+}
+
+fn import_row(client: &ApiClient, row: &ImportRow) -> ImportRowResult {
+    let mut result = ImportRowResult {
+        line: row.line,
+        email: row.email.clone(),
+        ok: false,
+        error: None,
+        temp_password: None,
+    };
+    match client.create_user(&row.email, &row.first_name, &row.last_name) {
+        Ok(created) => {
+            result.temp_password = created["temp_password"].as_str().map(String::from);
+        }
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    }
+    if let Some(limit) = row.storage_limit_bytes {
+        if let Err(e) = client.update_user_storage_limit(&row.email, Some(limit)) {
+            result.error = Some(format!("User created, but setting the quota failed: {}", e));
+            return result;
+        }
+    }
+    result.ok = true;
+    result
+}
+
+/// Create every user in `text`. `on_progress(done, total, email)` is called after each line.
+/// One failing line does not stop the import; the report has one entry per non-blank line.
+pub fn import_users<F: FnMut(usize, usize, &str)>(
+    client: &ApiClient,
+    text: &str,
+    mut on_progress: F,
+) -> Vec<ImportRowResult> {
+    let rows = parse_csv(text);
+    let total = rows.len();
+    let mut report = Vec::with_capacity(total);
+    for (i, row) in rows.into_iter().enumerate() {
+        let result = match row {
+            Ok(row) => import_row(client, &row),
+            Err(invalid) => invalid,
+        };
+        if result.ok {
+            log::info!("Bulk import: created {}", result.email);
+        } else {
+            log::warn!(
+                "Bulk import line {} ({}): {}",
+                result.line,
+                result.email,
+                result.error.as_deref().unwrap_or("")
+            );
+        }
+        on_progress(i + 1, total, &result.email);
+        report.push(result);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_header_quotes_quota_and_bad_lines() {
+        let csv = "email,first_name,last_name,quota\n\
+                   anna@example.com,Anna,Smith,10GB\n\
+                   \n\
+                   \"bob@example.com\",Bob,\"Doe, Jr.\",\n\
+                   not-an-email,X,Y\n\
+                   carl@example.com,Carl\n\
+                   dora@example.com,Dora,Lee,lots\n";
+        let rows = parse_csv(csv);
+        assert_eq!(rows.len(), 5);
+        let anna = rows[0].as_ref().unwrap();
+        assert_eq!(anna.line, 2);
+        assert_eq!(anna.storage_limit_bytes, Some(10 * 1024 * 1024 * 1024));
+        let bob = rows[1].as_ref().unwrap();
+        assert_eq!(bob.last_name, "Doe, Jr.");
+        assert_eq!(bob.storage_limit_bytes, None);
+        assert_eq!(rows[2].as_ref().unwrap_err().line, 5);
+        assert!(rows[3].is_err());
+        assert!(rows[4].as_ref().unwrap_err().error.as_deref().unwrap().contains("quota"));
+        assert_eq!(parse_size("1.5 MB"), Some(1572864));
+        assert_eq!(parse_size("2048"), Some(2048));
+    }
+}
@@ -0,0 +1,198 @@
+//! COM side: the overlay handlers, their class factory and the DLL exports Explorer and
+//! `regsvr32` call.
+
+use crate::{pipe, Overlay};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use windows::core::{implement, Interface, Ref, BOOL, GUID, HRESULT, PCWSTR, PWSTR};
+use windows::Win32::Foundation::{
+    CLASS_E_CLASSNOTAVAILABLE, CLASS_E_NOAGGREGATION, E_FAIL, E_POINTER, HINSTANCE, HMODULE, S_FALSE, S_OK,
+};
+use windows::Win32::System::Com::{IClassFactory, IClassFactory_Impl};
+use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+use windows::Win32::System::Ole::SELFREG_E_CLASS;
+use windows::Win32::System::SystemServices::DLL_PROCESS_ATTACH;
+use windows::Win32::UI::Shell::{IShellIconOverlayIdentifier, IShellIconOverlayIdentifier_Impl, ISIOI_ICONFILE};
+use winreg::enums::{HKEY_LOCAL_MACHINE, KEY_WRITE};
+use winreg::RegKey;
+
+const ICON_DIR: &str = "overlay-icons";
+const OVERLAY_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Explorer\ShellIconOverlayIdentifiers";
+const APPROVED_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Shell Extensions\Approved";
+
+/// This DLL's module handle, for finding its own path.
+static MODULE: AtomicIsize = AtomicIsize::new(0);
+/// Live objects plus `LockServer` locks; the DLL may unload at zero.
+static REFS: AtomicUsize = AtomicUsize::new(0);
+
+fn dll_path() -> Option<std::path::PathBuf> {
+    let module = HMODULE(MODULE.load(Ordering::SeqCst) as *mut c_void);
+    let mut buf = [0u16; 1024];
+    let len = unsafe { GetModuleFileNameW(Some(module), &mut buf) } as usize;
+    (len > 0 && len < buf.len()).then(|| String::from_utf16_lossy(&buf[..len]).into())
+}
+
+fn overlay_for(clsid: &GUID) -> Option<Overlay> {
+    Overlay::ALL.into_iter().find(|o| GUID::try_from(o.clsid().trim_matches(['{', '}'])).ok().as_ref() == Some(clsid))
+}
+
+#[implement(IShellIconOverlayIdentifier)]
+struct OverlayHandler {
+    overlay: Overlay,
+}
+
+impl OverlayHandler {
+    fn new(overlay: Overlay) -> Self {
+        REFS.fetch_add(1, Ordering::SeqCst);
+        Self { overlay }
+    }
+}
+
+impl Drop for OverlayHandler {
+    fn drop(&mut self) {
+        REFS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl IShellIconOverlayIdentifier_Impl for OverlayHandler_Impl {
+    fn IsMemberOf(&self, pwszpath: &PCWSTR, _dwattrib: u32) -> windows::core::Result<()> {
+        let path = unsafe { pwszpath.to_string() }.map_err(|_| windows::core::Error::from(S_FALSE))?;
+        match pipe::status_of(&path) {
+            Some(status) if self.overlay.shows(&status) => Ok(()),
+            _ => Err(S_FALSE.into()),
+        }
+    }
+
+    fn GetOverlayInfo(&self, pwsziconfile: PWSTR, cchmax: i32, pindex: *mut i32, pdwflags: *mut u32) -> windows::core::Result<()> {
+        if pwsziconfile.is_null() || pindex.is_null() || pdwflags.is_null() {
+            return Err(E_POINTER.into());
+        }
+        let icon = dll_path()
+            .and_then(|p| p.parent().map(|dir| dir.join(ICON_DIR).join(self.overlay.icon_file())))
+            .ok_or_else(|| windows::core::Error::from(E_FAIL))?;
+        let wide: Vec<u16> = icon.to_string_lossy().encode_utf16().chain(std::iter::once(0)).collect();
+        if wide.len() > cchmax.max(0) as usize {
+            return Err(E_FAIL.into());
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(wide.as_ptr(), pwsziconfile.0, wide.len());
+            *pindex = 0;
+            *pdwflags = ISIOI_ICONFILE;
+        }
+        Ok(())
+    }
+
+    fn GetPriority(&self) -> windows::core::Result<i32> {
+        Ok(0)
+    }
+}
+
+#[implement(IClassFactory)]
+struct ClassFactory {
+    overlay: Overlay,
+}
+
+impl IClassFactory_Impl for ClassFactory_Impl {
+    fn CreateInstance(
+        &self,
+        punkouter: Ref<'_, windows::core::IUnknown>,
+        riid: *const GUID,
+        ppvobject: *mut *mut c_void,
+    ) -> windows::core::Result<()> {
+        if ppvobject.is_null() {
+            return Err(E_POINTER.into());
+        }
+        unsafe { *ppvobject = std::ptr::null_mut() };
+        if !punkouter.is_null() {
+            return Err(CLASS_E_NOAGGREGATION.into());
+        }
+        let handler: IShellIconOverlayIdentifier = OverlayHandler::new(self.overlay).into();
+        unsafe { handler.query(riid, ppvobject) }.ok()
+    }
+
+    fn LockServer(&self, flock: BOOL) -> windows::core::Result<()> {
+        if flock.as_bool() {
+            REFS.fetch_add(1, Ordering::SeqCst);
+        } else {
+            REFS.fetch_sub(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
+fn register() -> std::io::Result<()> {
+    let dll = dll_path().ok_or_else(|| std::io::Error::other("DLL path unknown"))?;
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let approved = hklm.open_subkey_with_flags(APPROVED_KEY, KEY_WRITE)?;
+    for overlay in Overlay::ALL {
+        let description = format!("Brandy Box overlay ({})", overlay.registry_name().trim());
+        let (class, _) = hklm.create_subkey(format!(r"Software\Classes\CLSID\{}", overlay.clsid()))?;
+        class.set_value("", &description)?;
+        let (server, _) = class.create_subkey("InprocServer32")?;
+        server.set_value("", &dll.as_os_str())?;
+        server.set_value("ThreadingModel", &"Apartment")?;
+        let (entry, _) = hklm.create_subkey(format!(r"{}\{}", OVERLAY_KEY, overlay.registry_name()))?;
+        entry.set_value("", &overlay.clsid())?;
+        approved.set_value(overlay.clsid(), &description)?;
+    }
+    Ok(())
+}
+
+fn unregister() {
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let approved = hklm.open_subkey_with_flags(APPROVED_KEY, KEY_WRITE).ok();
+    for overlay in Overlay::ALL {
+        let _ = hklm.delete_subkey_all(format!(r"{}\{}", OVERLAY_KEY, overlay.registry_name()));
+        let _ = hklm.delete_subkey_all(format!(r"Software\Classes\CLSID\{}", overlay.clsid()));
+        if let Some(key) = &approved {
+            let _ = key.delete_value(overlay.clsid());
+        }
+    }
+}
+
+#[no_mangle]
+extern "system" fn DllMain(module: HINSTANCE, reason: u32, _reserved: *mut c_void) -> BOOL {
+    if reason == DLL_PROCESS_ATTACH {
+        MODULE.store(module.0 as isize, Ordering::SeqCst);
+    }
+    true.into()
+}
+
+#[no_mangle]
+extern "system" fn DllGetClassObject(rclsid: *const GUID, riid: *const GUID, ppv: *mut *mut c_void) -> HRESULT {
+    if rclsid.is_null() || ppv.is_null() {
+        return E_POINTER;
+    }
+    unsafe { *ppv = std::ptr::null_mut() };
+    let Some(overlay) = overlay_for(unsafe { &*rclsid }) else {
+        return CLASS_E_CLASSNOTAVAILABLE;
+    };
+    let factory: IClassFactory = ClassFactory { overlay }.into();
+    unsafe { factory.query(riid, ppv) }
+}
+
+#[no_mangle]
+extern "system" fn DllCanUnloadNow() -> HRESULT {
+    if REFS.load(Ordering::SeqCst) == 0 {
+        S_OK
+    } else {
+        S_FALSE
+    }
+}
+
+#[no_mangle]
+extern "system" fn DllRegisterServer() -> HRESULT {
+    match register() {
+        Ok(()) => S_OK,
+        Err(_) => {
+            unregister();
+            SELFREG_E_CLASS
+        }
+    }
+}
+
+#[no_mangle]
+extern "system" fn DllUnregisterServer() -> HRESULT {
+    unregister();
+    S_OK
+}
@@ -0,0 +1,96 @@
+//! Windows Explorer overlay icons for files in the Brandy Box folder: a green check when synced,
+//! blue arrows while pending or transferring, a red mark when the last attempt failed.
+//!
+//! Explorer loads this DLL in-process and asks each overlay handler (one COM class per icon)
+//! whether it applies to a path. The answer comes from the running app over the IPC named pipe
+//! (`sync-folder` and `file-status`); when the app is not running no overlays are shown. The
+//! installer registers the DLL with `regsvr32`, which writes the classes and the
+//! `ShellIconOverlayIdentifiers` entries under HKLM, so overlays need the all-users install.
+
+#[cfg(windows)]
+mod com;
+#[cfg(windows)]
+mod pipe;
+
+/// One overlay icon, each registered as its own shell extension class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    Synced,
+    Syncing,
+    Error,
+}
+
+impl Overlay {
+    pub const ALL: [Overlay; 3] = [Overlay::Synced, Overlay::Syncing, Overlay::Error];
+
+    /// Does this overlay apply to a `file-status` answer (`synced`, `syncing`, `pending`, …)?
+    pub fn shows(self, status: &str) -> bool {
+        match self {
+            Overlay::Synced => status == "synced",
+            Overlay::Syncing => status == "syncing" || status == "pending",
+            Overlay::Error => status == "error",
+        }
+    }
+
+    /// Icon file, in the `overlay-icons` folder next to the DLL.
+    pub fn icon_file(self) -> &'static str {
+        match self {
+            Overlay::Synced => "synced.ico",
+            Overlay::Syncing => "syncing.ico",
+            Overlay::Error => "error.ico",
+        }
+    }
+
+    /// Name under `ShellIconOverlayIdentifiers`. Explorer uses only the first 15 entries in
+    /// alphabetical order; the leading spaces sort these ahead of most other sync clients.
+    pub fn registry_name(self) -> &'static str {
+        match self {
+            Overlay::Synced => "   BrandyBox1Synced",
+            Overlay::Syncing => "   BrandyBox2Syncing",
+            Overlay::Error => "   BrandyBox3Error",
+        }
+    }
+
+    /// COM class id, as registered under `CLSID`.
+    pub fn clsid(self) -> &'static str {
+        match self {
+            Overlay::Synced => "{56EC4AF0-E8F6-4855-A557-2A89DC045B6F}",
+            Overlay::Syncing => "{27BADE52-BE1F-468A-8F46-FEFAC88F4A98}",
+            Overlay::Error => "{B4529D5C-85FA-4838-AEAA-D2D2EFE646C6}",
+        }
+    }
+}
+
+/// Is `path` the sync folder or inside it? Windows paths: case-insensitive, `\` or `/`.
+pub fn is_inside(folder: &str, path: &str) -> bool {
+    let norm = |s: &str| s.replace('/', "\\").trim_end_matches('\\').to_lowercase();
+    let (folder, path) = (norm(folder), norm(path));
+    !folder.is_empty() && (path == folder || path.starts_with(&format!("{}\\", folder)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays_cover_each_status_once() {
+        for status in ["synced", "syncing", "pending", "error"] {
+            assert_eq!(Overlay::ALL.iter().filter(|o| o.shows(status)).count(), 1, "{}", status);
+        }
+        assert!(!Overlay::ALL.iter().any(|o| o.shows("unknown")));
+        let mut names: Vec<_> = Overlay::ALL.iter().map(|o| o.registry_name()).collect();
+        names.dedup();
+        assert_eq!(names.len(), 3);
+    }
+
+    #[test]
+    fn matches_paths_inside_the_sync_folder_only() {
+        let folder = r"C:\Users\anna\Brandy Box";
+        assert!(is_inside(folder, r"C:\Users\anna\Brandy Box"));
+        assert!(is_inside(folder, r"c:\users\anna\brandy box\Photos\a.jpg"));
+        assert!(is_inside(&format!("{}\\", folder), r"C:\Users\anna\Brandy Box\a.txt"));
+        assert!(!is_inside(folder, r"C:\Users\anna\Brandy Box 2\a.txt"));
+        assert!(!is_inside(folder, r"C:\Users\anna"));
+        assert!(!is_inside("", r"C:\a.txt"));
+    }
+}
@@ -0,0 +1,70 @@
+//! Status lookups over the app's IPC named pipe, cached briefly: Explorer asks every overlay
+//! handler about every visible item, and asks again on each repaint.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a file's status is reused; short so icons follow a sync closely.
+const STATUS_TTL: Duration = Duration::from_secs(3);
+/// How long the sync folder (or "app not running") is reused.
+const FOLDER_TTL: Duration = Duration::from_secs(30);
+const MAX_CACHED: usize = 4096;
+
+struct Cache {
+    folder: Option<(Instant, Option<String>)>,
+    statuses: HashMap<String, (Instant, String)>,
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+/// Same name as the app's server in `ipc.rs`.
+fn pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!(r"\\.\pipe\brandybox-{}", user)
+}
+
+/// One request to the running app; None when it is not running or answers with an error.
+fn request(request: &serde_json::Value) -> Option<serde_json::Value> {
+    let stream = std::fs::OpenOptions::new().read(true).write(true).open(pipe_name()).ok()?;
+    let mut writer = &stream;
+    writer.write_all(format!("{}\n", request).as_bytes()).ok()?;
+    let mut line = String::new();
+    std::io::BufReader::new(&stream).read_line(&mut line).ok()?;
+    let response: serde_json::Value = serde_json::from_str(&line).ok()?;
+    (response["ok"].as_bool() == Some(true)).then(|| response["result"].clone())
+}
+
+fn sync_folder(cache: &mut Cache) -> Option<String> {
+    match &cache.folder {
+        Some((at, folder)) if at.elapsed() < FOLDER_TTL => folder.clone(),
+        _ => {
+            let folder = request(&serde_json::json!({ "cmd": "sync-folder" }))
+                .and_then(|r| r["path"].as_str().map(str::to_string));
+            cache.folder = Some((Instant::now(), folder.clone()));
+            folder
+        }
+    }
+}
+
+/// `file-status` of an absolute path inside the sync folder (`synced`, `syncing`, …).
+pub fn status_of(path: &str) -> Option<String> {
+    let mut guard = CACHE.lock().ok()?;
+    let cache = guard.get_or_insert_with(|| Cache { folder: None, statuses: HashMap::new() });
+    if !crate::is_inside(&sync_folder(cache)?, path) {
+        return None;
+    }
+    if let Some((at, status)) = cache.statuses.get(path) {
+        if at.elapsed() < STATUS_TTL {
+            return Some(status.clone());
+        }
+    }
+    let status = request(&serde_json::json!({ "cmd": "file-status", "path": path }))
+        .and_then(|r| r["status"].as_str().map(str::to_string))?;
+    if cache.statuses.len() >= MAX_CACHED {
+        cache.statuses.clear();
+    }
+    cache.statuses.insert(path.to_string(), (Instant::now(), status.clone()));
+    Some(status)
+}
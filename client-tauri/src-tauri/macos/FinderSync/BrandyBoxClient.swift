@@ -0,0 +1,74 @@
+// Talks to the running Brandy Box app over its IPC socket: one JSON object per line,
+// answered with {"ok":true,"result":…} or {"ok":false,"error":"…"}.
+
+import Foundation
+
+final class BrandyBoxClient {
+    /// The sandbox moves NSHomeDirectory() into the extension's container; the socket is in the
+    /// real home (see the temporary-exception entitlement).
+    private let socketPath: String = {
+        let home = getpwuid(getuid()).flatMap { String(validatingUTF8: $0.pointee.pw_dir) } ?? NSHomeDirectory()
+        return (home as NSString).appendingPathComponent(".config/brandybox/brandybox.sock")
+    }()
+
+    /// The folder to badge, or nil when the app is not running or has no folder yet.
+    func syncFolder() -> URL? {
+        guard let result = request(["cmd": "sync-folder"]) as? [String: Any],
+              result["set"] as? Bool == true,
+              let path = result["path"] as? String else { return nil }
+        return URL(fileURLWithPath: path, isDirectory: true)
+    }
+
+    /// `file-status` for many items at once: `synced`, `syncing`, `pending`, `error` or `unknown`.
+    func statuses(of urls: [URL]) -> [URL: String] {
+        guard !urls.isEmpty,
+              let entries = request(["cmd": "file-status", "paths": urls.map { $0.path }]) as? [[String: Any]],
+              entries.count == urls.count else { return [:] }
+        var out: [URL: String] = [:]
+        for (url, entry) in zip(urls, entries) {
+            out[url] = entry["status"] as? String ?? "unknown"
+        }
+        return out
+    }
+
+    private func request(_ body: [String: Any]) -> Any? {
+        guard let line = try? JSONSerialization.data(withJSONObject: body) else { return nil }
+        let fd = socket(AF_UNIX, SOCK_STREAM, 0)
+        guard fd >= 0 else { return nil }
+        defer { close(fd) }
+        var timeout = timeval(tv_sec: 2, tv_usec: 0)
+        setsockopt(fd, SOL_SOCKET, SO_RCVTIMEO, &timeout, socklen_t(MemoryLayout<timeval>.size))
+        setsockopt(fd, SOL_SOCKET, SO_SNDTIMEO, &timeout, socklen_t(MemoryLayout<timeval>.size))
+
+        var addr = sockaddr_un()
+        addr.sun_family = sa_family_t(AF_UNIX)
+        let pathBytes = Array(socketPath.utf8CString)
+        guard pathBytes.count <= MemoryLayout.size(ofValue: addr.sun_path) else { return nil }
+        withUnsafeMutableBytes(of: &addr.sun_path) { raw in
+            raw.copyBytes(from: pathBytes.map { UInt8(bitPattern: $0) })
+        }
+        let connected = withUnsafePointer(to: &addr) {
+            $0.withMemoryRebound(to: sockaddr.self, capacity: 1) {
+                connect(fd, $0, socklen_t(MemoryLayout<sockaddr_un>.size))
+            }
+        }
+        guard connected == 0 else { return nil }
+
+        var payload = line
+        payload.append(0x0A)
+        let sent = payload.withUnsafeBytes { write(fd, $0.baseAddress, payload.count) }
+        guard sent == payload.count else { return nil }
+
+        var response = Data()
+        var buffer = [UInt8](repeating: 0, count: 64 * 1024)
+        while !response.contains(0x0A) {
+            let n = read(fd, &buffer, buffer.count)
+            if n <= 0 { break }
+            response.append(buffer, count: n)
+        }
+        guard let end = response.firstIndex(of: 0x0A),
+              let json = try? JSONSerialization.jsonObject(with: response[..<end]) as? [String: Any],
+              json["ok"] as? Bool == true else { return nil }
+        return json["result"]
+    }
+}
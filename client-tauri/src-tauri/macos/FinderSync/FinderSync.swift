@@ -0,0 +1,95 @@
+// Finder Sync extension: badges items in the Brandy Box folder as synced, syncing or failed.
+// Finder asks for each item as it comes into view; the items seen are re-checked every few
+// seconds in one batch request, so badges follow a running sync.
+
+import Cocoa
+import FinderSync
+
+@objc(FinderSync)
+final class FinderSync: FIFinderSync {
+    private let client = BrandyBoxClient()
+    private let queue = DispatchQueue(label: "rocks.brandstaetter.brandybox.findersync")
+    /// Items Finder has shown, by the directory they are in (only touched on `queue`).
+    private var visible: [URL: Set<URL>] = [:]
+    private var timer: DispatchSourceTimer?
+
+    private static let refreshInterval: DispatchTimeInterval = .seconds(3)
+    private static let folderInterval: TimeInterval = 30
+    private var folderCheckedAt = Date.distantPast
+
+    override init() {
+        super.init()
+        let controller = FIFinderSyncController.default()
+        controller.setBadgeImage(Self.badge("checkmark.circle.fill", .systemGreen), label: "Synced", forBadgeIdentifier: "synced")
+        controller.setBadgeImage(Self.badge("arrow.triangle.2.circlepath.circle.fill", .systemBlue), label: "Syncing", forBadgeIdentifier: "syncing")
+        controller.setBadgeImage(Self.badge("exclamationmark.circle.fill", .systemRed), label: "Sync error", forBadgeIdentifier: "error")
+
+        let timer = DispatchSource.makeTimerSource(queue: queue)
+        timer.schedule(deadline: .now(), repeating: Self.refreshInterval)
+        timer.setEventHandler { [weak self] in self?.refresh() }
+        timer.resume()
+        self.timer = timer
+    }
+
+    private static func badge(_ symbol: String, _ color: NSColor) -> NSImage {
+        let config = NSImage.SymbolConfiguration(paletteColors: [.white, color])
+        let image = NSImage(systemSymbolName: symbol, accessibilityDescription: nil)?.withSymbolConfiguration(config)
+        return image ?? NSImage()
+    }
+
+    /// Follow the app's sync folder (it can change in the settings or the app may start later).
+    private func updateDirectoryURLs() {
+        guard Date().timeIntervalSince(folderCheckedAt) > Self.folderInterval else { return }
+        folderCheckedAt = Date()
+        let urls: Set<URL> = client.syncFolder().map { [$0] } ?? []
+        DispatchQueue.main.async {
+            let controller = FIFinderSyncController.default()
+            if controller.directoryURLs != urls {
+                controller.directoryURLs = urls
+            }
+        }
+    }
+
+    private func refresh() {
+        updateDirectoryURLs()
+        let items = Array(visible.values.joined())
+        apply(client.statuses(of: items))
+    }
+
+    private func apply(_ statuses: [URL: String]) {
+        guard !statuses.isEmpty else { return }
+        DispatchQueue.main.async {
+            let controller = FIFinderSyncController.default()
+            for (url, status) in statuses {
+                controller.setBadgeIdentifier(Self.badgeIdentifier(for: status), for: url)
+            }
+        }
+    }
+
+    private static func badgeIdentifier(for status: String) -> String {
+        switch status {
+        case "synced": return "synced"
+        case "syncing", "pending": return "syncing"
+        case "error": return "error"
+        default: return ""
+        }
+    }
+
+    // MARK: - FIFinderSync
+
+    override func beginObservingDirectory(at url: URL) {
+        queue.async { self.visible[url.standardizedFileURL, default: []] = [] }
+    }
+
+    override func endObservingDirectory(at url: URL) {
+        queue.async { self.visible[url.standardizedFileURL] = nil }
+    }
+
+    override func requestBadgeIdentifier(for url: URL) {
+        queue.async {
+            let dir = url.deletingLastPathComponent().standardizedFileURL
+            self.visible[dir, default: []].insert(url)
+            self.apply(self.client.statuses(of: [url]))
+        }
+    }
+}
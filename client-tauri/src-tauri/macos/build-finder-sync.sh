@@ -0,0 +1,38 @@
+#!/usr/bin/env bash
+# Build the Finder Sync extension (BrandyBoxFinderSync.appex) for the macOS bundle.
+# Runs from beforeBuildCommand (tauri.macos.conf.json); needs the Xcode command line tools.
+# Output: target/finder-sync/BrandyBoxFinderSync.appex (universal), copied into
+# Brandy Box.app/Contents/PlugIns by the bundler. Signs with APPLE_SIGNING_IDENTITY, ad hoc otherwise.
+set -euo pipefail
+
+HERE="$(cd "$(dirname "$0")" && pwd)"
+SRC_TAURI="$(dirname "$HERE")"
+SRC="$HERE/FinderSync"
+BUILD="$SRC_TAURI/target/finder-sync"
+APPEX="$BUILD/BrandyBoxFinderSync.appex"
+MIN_MACOS="12.0"
+VERSION="$(sed -n 's/^ *"version": *"\([^"]*\)".*/\1/p' "$SRC_TAURI/tauri.conf.json" | head -1)"
+
+rm -rf "$BUILD"
+mkdir -p "$APPEX/Contents/MacOS"
+for arch in arm64 x86_64; do
+  swiftc "$SRC"/*.swift \
+    -module-name BrandyBoxFinderSync \
+    -target "$arch-apple-macos$MIN_MACOS" \
+    -application-extension \
+    -framework Cocoa -framework FinderSync \
+    -Xlinker -e -Xlinker _NSExtensionMain \
+    -O -o "$BUILD/BrandyBoxFinderSync-$arch"
+done
+lipo -create "$BUILD"/BrandyBoxFinderSync-arm64 "$BUILD"/BrandyBoxFinderSync-x86_64 \
+  -output "$APPEX/Contents/MacOS/BrandyBoxFinderSync"
+
+cp "$SRC/Info.plist" "$APPEX/Contents/Info.plist"
+/usr/libexec/PlistBuddy -c "Set :CFBundleShortVersionString $VERSION" "$APPEX/Contents/Info.plist"
+/usr/libexec/PlistBuddy -c "Set :CFBundleVersion $VERSION" "$APPEX/Contents/Info.plist"
+
+codesign --force --options runtime --timestamp=none \
+  --sign "${APPLE_SIGNING_IDENTITY:--}" \
+  --entitlements "$SRC/FinderSync.entitlements" \
+  "$APPEX"
+echo "Built $APPEX"
@@ -1,14 +1,20 @@
 //! Keyring-backed credential storage (email + refresh_token). Matches Python keyring usage.
 //! When BRANDYBOX_CONFIG_DIR is set (E2E), also supports e2e_credentials.json so CI can seed
 //! credentials without a system keyring.
+//! App tokens (headless use) come from BRANDYBOX_APP_TOKEN or the keyring; they are never written
+//! to config.json.
 
 use crate::config;
+use brandybox_core::metadata_crypto;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 const SERVICE_NAME: &str = "BrandyBox";
 const KEY_EMAIL: &str = "email";
 const KEY_REFRESH_TOKEN: &str = "refresh_token";
+const KEY_APP_TOKEN: &str = "app_token";
+const KEY_METADATA: &str = "metadata_key";
+const APP_TOKEN_ENV: &str = "BRANDYBOX_APP_TOKEN";
 const E2E_CREDENTIALS_FILENAME: &str = "e2e_credentials.json";
 
 fn is_e2e_config() -> bool {
@@ -83,3 +89,112 @@ pub fn clear_stored() {
     let _ = keyring::Entry::new(service, KEY_EMAIL).and_then(|e| e.delete_credential());
     let _ = keyring::Entry::new(service, KEY_REFRESH_TOKEN).and_then(|e| e.delete_credential());
 }
+
+/// Self-test: store, read back and delete a throwaway keyring entry. E2E configs use a file and
+/// skip the keyring.
+pub fn check_keyring() -> Result<String, String> {
+    if is_e2e_config() {
+        return Ok("E2E config: credentials in a file, keyring not used".to_string());
+    }
+    let entry = keyring::Entry::new(service_name(), "self_test").map_err(|e| e.to_string())?;
+    let value = chrono::Utc::now().to_rfc3339();
+    entry.set_password(&value).map_err(|e| format!("Cannot store in the keyring: {}", e))?;
+    let read = entry.get_password();
+    let _ = entry.delete_credential();
+    match read {
+        Ok(r) if r == value => Ok(format!("Keyring ({}) readable and writable", service_name())),
+        Ok(_) => Err("Keyring returned a different value than stored".to_string()),
+        Err(e) => Err(format!("Cannot read from the keyring: {}", e)),
+    }
+}
+
+/// Long-lived app token, if configured: env var first, then keyring. A token that older versions
+/// saved in config.json is moved to the keyring on first read.
+pub fn get_app_token() -> Option<String> {
+    if let Ok(t) = std::env::var(APP_TOKEN_ENV) {
+        let t = t.trim();
+        if !t.is_empty() {
+            return Some(t.to_string());
+        }
+    }
+    let from_keyring = keyring::Entry::new(service_name(), KEY_APP_TOKEN)
+        .ok()
+        .and_then(|e| e.get_password().ok())
+        .filter(|t| !t.is_empty());
+    if from_keyring.is_some() {
+        return from_keyring;
+    }
+    let legacy = config::take_legacy_app_token()?;
+    if let Err(e) = set_app_token(&legacy) {
+        log::warn!("Removed plaintext app token from config.json: {}", e);
+    }
+    Some(legacy)
+}
+
+/// Store the app token in the keyring. Without a keyring the token is not saved; headless
+/// installs set BRANDYBOX_APP_TOKEN instead.
+pub fn set_app_token(token: &str) -> Result<(), String> {
+    keyring::Entry::new(service_name(), KEY_APP_TOKEN)
+        .and_then(|e| e.set_password(token))
+        .map_err(|e| format!("Cannot store the app token in the keyring ({}); set {} instead", e, APP_TOKEN_ENV))
+}
+
+pub fn clear_app_token() {
+    let _ = keyring::Entry::new(service_name(), KEY_APP_TOKEN).and_then(|e| e.delete_credential());
+    let _ = config::take_legacy_app_token();
+}
+
+/// Key for encrypting sync metadata at rest (hex in the keyring); None when encryption is off.
+/// Kept on logout: the encrypted files stay readable for the next login.
+pub fn get_metadata_key() -> Option<metadata_crypto::Key> {
+    let hex = keyring::Entry::new(service_name(), KEY_METADATA).ok()?.get_password().ok()?;
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect::<Option<_>>()?;
+    bytes.try_into().ok()
+}
+
+/// Store the metadata key and read it back, so encryption is only turned on with a working
+/// keyring. None deletes it.
+pub fn set_metadata_key(key: Option<&metadata_crypto::Key>) -> Result<(), String> {
+    let entry = keyring::Entry::new(service_name(), KEY_METADATA).map_err(|e| e.to_string())?;
+    let Some(key) = key else {
+        return match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Cannot delete the key from the keyring: {}", e)),
+        };
+    };
+    let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+    entry.set_password(&hex).map_err(|e| format!("Cannot store the key in the keyring: {}", e))?;
+    if get_metadata_key().as_ref() != Some(key) {
+        return Err("The keyring did not return the stored key".to_string());
+    }
+    Ok(())
+}
+
+/// Keyring entry as written by the Python client. Python `keyring` names Windows credentials
+/// "<user>@<service>" while keyring-rs uses "<user>.<service>"; other platforms match.
+fn legacy_entry(key: &str) -> keyring::Result<keyring::Entry> {
+    if cfg!(windows) {
+        keyring::Entry::new_with_target(&format!("{}@{}", key, SERVICE_NAME), SERVICE_NAME, key)
+    } else {
+        keyring::Entry::new(SERVICE_NAME, key)
+    }
+}
+
+/// Copy Python client credentials into our store when we have none yet. Returns true if imported.
+pub fn import_legacy() -> bool {
+    if is_e2e_config() || get_stored().is_some() {
+        return false;
+    }
+    let email = legacy_entry(KEY_EMAIL).and_then(|e| e.get_password()).ok();
+    let token = legacy_entry(KEY_REFRESH_TOKEN).and_then(|e| e.get_password()).ok();
+    match (email, token) {
+        (Some(email), Some(token)) if !email.is_empty() && !token.is_empty() => {
+            set_stored(&email, &token);
+            true
+        }
+        _ => false,
+    }
+}
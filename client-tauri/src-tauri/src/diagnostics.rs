@@ -0,0 +1,94 @@
+//! Diagnostic bundle for bug reports: logs, sanitized config, sync summary and versions in one zip.
+
+use crate::api::ApiClient;
+use crate::{config, crash, logging, network, stats, sync};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Default location: the user's Downloads folder (home as fallback).
+pub fn default_bundle_path() -> PathBuf {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    dir.join(format!(
+        "brandybox-diagnostics-{}.zip",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+#[derive(Serialize)]
+pub struct AppInfo {
+    pub client_version: String,
+    pub commit: String,
+    pub build_profile: String,
+    pub os: String,
+    pub arch: String,
+    pub portable: bool,
+    pub base_url: String,
+    /// None when the server could not be reached.
+    pub server_api_version: Option<String>,
+    pub server_min_supported_client_version: Option<String>,
+    pub server_error: Option<String>,
+}
+
+/// Client build and platform plus the server version (fetched live; blocking).
+pub fn app_info() -> AppInfo {
+    let base_url = network::get_base_url();
+    let server = ApiClient::new(base_url.clone()).get_version();
+    let (server_version, server_error) = match server {
+        Ok(v) => (Some(v), None),
+        Err(e) => (None, Some(e)),
+    };
+    AppInfo {
+        client_version: env!("CARGO_PKG_VERSION").to_string(),
+        commit: env!("BRANDYBOX_GIT_COMMIT").to_string(),
+        build_profile: env!("BRANDYBOX_BUILD_PROFILE").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        portable: config::is_portable(),
+        base_url,
+        server_api_version: server_version.as_ref().map(|v| v.api_version.clone()),
+        server_min_supported_client_version: server_version.and_then(|v| v.min_supported_client_version),
+        server_error,
+    }
+}
+
+/// App info plus status and counters.
+fn summary() -> serde_json::Value {
+    let (status, message) = sync::get_sync_status();
+    serde_json::json!({
+        "created_at": chrono::Utc::now().to_rfc3339(),
+        "app": app_info(),
+        "sync_status": { "status": status, "message": message },
+        "last_sync": sync::get_last_sync(),
+        "sync_state": sync::state_summary(),
+        "stats": stats::get(7),
+    })
+}
+
+/// Write the bundle to `path`. Contains no credentials or tokens.
+pub fn create_bundle(path: &Path) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut add = |name: &str, data: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(data).map_err(|e| e.to_string())
+    };
+    let summary = serde_json::to_string_pretty(&summary()).unwrap_or_default();
+    add("diagnostics.json", summary.as_bytes())?;
+    add("config.json", config::sanitized_config_json().as_bytes())?;
+    let crashes = crash::pending_reports();
+    if !crashes.is_empty() {
+        add("crashes.json", serde_json::to_string_pretty(&crashes).unwrap_or_default().as_bytes())?;
+    }
+    for log_file in logging::log_files() {
+        if let (Some(name), Ok(data)) = (log_file.file_name(), std::fs::read(&log_file)) {
+            add(&format!("logs/{}", name.to_string_lossy()), &data)?;
+        }
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    log::info!("Diagnostic bundle written to {}", path.display());
+    Ok(())
+}
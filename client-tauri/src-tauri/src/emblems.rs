@@ -0,0 +1,110 @@
+//! Linux: show each file's sync status as an emblem in file managers that read GIO metadata
+//! (Nautilus, Nemo, Caja): synced, syncing/pending or error. Set with `gio set`, only for files
+//! whose status changed since the last update. Dolphin does not read GIO metadata; a Dolphin
+//! overlay plugin can use the IPC `file-status` command instead.
+
+use brandybox_core::config;
+use brandybox_core::file_status::{self, FileSyncStatus};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Status last written as an emblem, per path relative to the sync folder.
+static APPLIED: Mutex<Option<HashMap<String, FileSyncStatus>>> = Mutex::new(None);
+
+fn emblem_for(status: FileSyncStatus) -> Option<&'static str> {
+    match status {
+        FileSyncStatus::Synced => Some("emblem-default"),
+        FileSyncStatus::Syncing | FileSyncStatus::Pending => Some("emblem-synchronizing"),
+        FileSyncStatus::Error => Some("emblem-important"),
+        FileSyncStatus::Unknown => None,
+    }
+}
+
+/// Paths whose emblem has to change, with the new emblem (None: remove it).
+fn changes(
+    applied: &HashMap<String, FileSyncStatus>,
+    current: &HashMap<String, FileSyncStatus>,
+) -> Vec<(String, Option<&'static str>)> {
+    let mut out: Vec<(String, Option<&'static str>)> = current
+        .iter()
+        .filter(|(p, s)| applied.get(*p) != Some(*s))
+        .map(|(p, s)| (p.clone(), emblem_for(*s)))
+        .collect();
+    out.extend(applied.keys().filter(|p| !current.contains_key(*p)).map(|p| (p.clone(), None)));
+    out.sort();
+    out
+}
+
+fn set_emblem(path: &std::path::Path, emblem: Option<&str>) -> bool {
+    let mut cmd = std::process::Command::new("gio");
+    cmd.arg("set");
+    match emblem {
+        Some(e) => cmd.args(["-t", "stringv"]).arg(path).args(["metadata::emblems", e]),
+        None => cmd.args(["-t", "unset"]).arg(path).arg("metadata::emblems"),
+    };
+    cmd.output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+/// Bring emblems in line with the current per-file status. Runs in the background; when
+/// emblems were turned off, the ones set earlier are removed.
+pub fn refresh() {
+    if !cfg!(target_os = "linux") {
+        return;
+    }
+    std::thread::spawn(|| {
+        let Ok(mut guard) = APPLIED.lock() else { return };
+        let applied = guard.get_or_insert_with(HashMap::new);
+        let current: HashMap<String, FileSyncStatus> = if config::get_file_manager_emblems() {
+            file_status::snapshot().into_iter().collect()
+        } else {
+            HashMap::new()
+        };
+        let root = config::get_sync_folder_path();
+        for (path, emblem) in changes(applied, &current) {
+            let full = root.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+            if !full.exists() {
+                applied.remove(&path);
+                continue;
+            }
+            if !set_emblem(&full, emblem) {
+                log::debug!("Could not set emblem on {} (gio missing?)", path);
+                return;
+            }
+            match current.get(&path) {
+                Some(status) => applied.insert(path, *status),
+                None => applied.remove(&path),
+            };
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_paths_are_updated() {
+        let applied: HashMap<String, FileSyncStatus> = [
+            ("a.txt".to_string(), FileSyncStatus::Synced),
+            ("b.txt".to_string(), FileSyncStatus::Pending),
+            ("gone.txt".to_string(), FileSyncStatus::Synced),
+        ]
+        .into_iter()
+        .collect();
+        let current: HashMap<String, FileSyncStatus> = [
+            ("a.txt".to_string(), FileSyncStatus::Synced),
+            ("b.txt".to_string(), FileSyncStatus::Synced),
+            ("c.txt".to_string(), FileSyncStatus::Error),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            changes(&applied, &current),
+            vec![
+                ("b.txt".to_string(), Some("emblem-default")),
+                ("c.txt".to_string(), Some("emblem-important")),
+                ("gone.txt".to_string(), None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Native folder picker, opened from Rust so every platform gets its own dialog: a Windows
+//! FolderBrowserDialog through PowerShell, `choose folder` through osascript on macOS, zenity or
+//! kdialog on Linux. Blocks until the user picks a folder or cancels.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Output of a picker process: the chosen path on stdout, nothing (non-zero exit) on cancel.
+fn chosen(output: Output) -> Option<PathBuf> {
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !path.is_empty()).then(|| PathBuf::from(path))
+}
+
+#[cfg(windows)]
+fn run(title: &str, start: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; \
+         $d = New-Object System.Windows.Forms.FolderBrowserDialog; \
+         $d.Description = {}; $d.ShowNewFolderButton = $true; {} \
+         if ($d.ShowDialog() -eq 'OK') {{ [Console]::OutputEncoding = [Text.Encoding]::UTF8; $d.SelectedPath }} else {{ exit 1 }}",
+        quote(title),
+        start.map(|p| format!("$d.SelectedPath = {};", quote(&p.to_string_lossy()))).unwrap_or_default()
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-STA", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Could not open the folder picker: {}", e))?;
+    Ok(chosen(output))
+}
+
+#[cfg(target_os = "macos")]
+fn run(title: &str, start: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut script = format!("POSIX path of (choose folder with prompt {}", quote(title));
+    if let Some(start) = start {
+        script.push_str(&format!(" default location (POSIX file {})", quote(&start.to_string_lossy())));
+    }
+    script.push(')');
+    let output = Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .map_err(|e| format!("Could not open the folder picker: {}", e))?;
+    // "choose folder" ends paths with a slash
+    Ok(chosen(output).map(|p| PathBuf::from(p.to_string_lossy().trim_end_matches('/'))))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn run(title: &str, start: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    let mut zenity = Command::new("zenity");
+    zenity.args(["--file-selection", "--directory", "--title", title]);
+    if let Some(start) = start {
+        // A trailing slash makes zenity open the folder instead of selecting it in its parent
+        zenity.arg(format!("--filename={}/", start.display()));
+    }
+    match zenity.output() {
+        Ok(output) => return Ok(chosen(output)),
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(format!("Could not open the folder picker: {}", e));
+        }
+        Err(_) => {}
+    }
+    let start = start.map(|p| p.to_string_lossy().into_owned()).unwrap_or_else(|| ".".to_string());
+    let output = Command::new("kdialog")
+        .args(["--getexistingdirectory", &start, "--title", title])
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => "No folder picker found; install zenity or kdialog, or type the path".to_string(),
+            _ => format!("Could not open the folder picker: {}", e),
+        })?;
+    Ok(chosen(output))
+}
+
+/// Let the user pick a folder, starting in `start` when it exists. `None` when cancelled.
+pub fn pick_folder(title: &str, start: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    run(title, start.filter(|p| p.is_dir()))
+}
@@ -0,0 +1,64 @@
+//! Is a fullscreen app (game, video, presentation) in front? Used to hold back notifications.
+//! Windows asks the shell (`SHQueryUserNotificationState`, the same check Windows uses for its
+//! own toasts); Linux reads the active window's state with `xprop` (X11 and XWayland windows).
+//! macOS has no check and always answers false.
+
+#[cfg(windows)]
+pub fn fullscreen_app_active() -> bool {
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHQueryUserNotificationState(state: *mut i32) -> i32;
+    }
+    // QUNS_BUSY (fullscreen app), QUNS_RUNNING_D3D_FULL_SCREEN, QUNS_PRESENTATION_MODE
+    let mut state = 0;
+    // SAFETY: the function only writes one i32 to the pointer it is given
+    let ok = unsafe { SHQueryUserNotificationState(&mut state) } == 0;
+    ok && matches!(state, 2..=4)
+}
+
+#[cfg(target_os = "macos")]
+pub fn fullscreen_app_active() -> bool {
+    false
+}
+
+/// Window id from `xprop -root _NET_ACTIVE_WINDOW` output, e.g.
+/// "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007". None when no window is active.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn active_window_id(xprop_output: &str) -> Option<&str> {
+    let id = xprop_output.trim().rsplit(' ').next()?;
+    (id.starts_with("0x") && id != "0x0").then_some(id)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn fullscreen_app_active() -> bool {
+    let xprop = |args: &[&str]| {
+        std::process::Command::new("xprop")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+    };
+    if std::env::var_os("DISPLAY").is_none() {
+        return false;
+    }
+    let Some(active) = xprop(&["-root", "_NET_ACTIVE_WINDOW"]) else {
+        return false;
+    };
+    let Some(id) = active_window_id(&active) else {
+        return false;
+    };
+    xprop(&["-id", id, "_NET_WM_STATE"]).is_some_and(|state| state.contains("_NET_WM_STATE_FULLSCREEN"))
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_active_window_id_from_xprop() {
+        assert_eq!(active_window_id("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n"), Some("0x3a00007"));
+        assert_eq!(active_window_id("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x0"), None);
+        assert_eq!(active_window_id("_NET_ACTIVE_WINDOW:  not found."), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! Single-instance lock: an exclusive lock on `instance.lock` in the config directory, with the
+//! PID and start time of the instance holding it in `instance.info` next to it (Windows does not
+//! let other processes read a locked file). A lock left behind by a process that is gone (some
+//! file systems keep locks of crashed processes) is taken over.
+
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Kept open (and locked) for the lifetime of the process, with the path of its info file.
+static LOCK_FILE: OnceLock<(File, PathBuf)> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct LockInfo {
+    pid: u32,
+    /// RFC 3339.
+    started: String,
+}
+
+/// Why this process may not run as the main instance.
+#[derive(Debug)]
+pub enum Blocked {
+    /// Another instance holds the lock (`pid` and `started` when it recorded them).
+    Running { pid: Option<u32>, started: Option<String> },
+    /// The lock file cannot be created or locked at all.
+    Unavailable(String),
+}
+
+impl std::fmt::Display for Blocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Blocked::Running { pid: Some(pid), started } => {
+                write!(f, "Brandy Box is already running (process {}", pid)?;
+                if let Some(started) = started {
+                    write!(f, ", started {}", started)?;
+                }
+                write!(f, ")")
+            }
+            Blocked::Running { pid: None, .. } => write!(f, "Brandy Box is already running"),
+            Blocked::Unavailable(e) => write!(f, "Brandy Box cannot check whether it is already running: {}", e),
+        }
+    }
+}
+
+fn info_path(lock_path: &Path) -> PathBuf {
+    lock_path.with_extension("info")
+}
+
+fn read_info(lock_path: &Path) -> Option<LockInfo> {
+    serde_json::from_str(&std::fs::read_to_string(info_path(lock_path)).ok()?).ok()
+}
+
+/// True when a process with this id exists.
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains(&format!("\"{}\"", pid)))
+        // Cannot tell: assume it runs, so a live instance is never taken over
+        .unwrap_or(true)
+}
+
+/// True when a process with this id exists.
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    std::process::Command::new("ps")
+        .args(["-p", &pid.to_string()])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(true)
+}
+
+fn open(path: &Path) -> Result<File, String> {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn lock(path: &Path) -> Result<(File, Option<LockInfo>), Blocked> {
+    let file = open(path).map_err(Blocked::Unavailable)?;
+    let info = read_info(path);
+    if file.try_lock_exclusive().is_ok() {
+        return Ok((file, info));
+    }
+    match info {
+        Some(info) if info.pid != std::process::id() && !process_alive(info.pid) => {
+            // Locked by a process that is gone: replace the file, the lock goes with the old one
+            drop(file);
+            std::fs::remove_file(path).map_err(|e| Blocked::Unavailable(format!("stale lock {}: {}", path.display(), e)))?;
+            let file = open(path).map_err(Blocked::Unavailable)?;
+            file.try_lock_exclusive()
+                .map_err(|e| Blocked::Unavailable(format!("{}: {}", path.display(), e)))?;
+            Ok((file, Some(info)))
+        }
+        info => Err(Blocked::Running { pid: info.as_ref().map(|i| i.pid), started: info.map(|i| i.started) }),
+    }
+}
+
+/// Become the main instance. Ok carries the PID of a previous instance that did not exit
+/// cleanly (its lock info was still there).
+pub fn acquire(path: &Path) -> Result<Option<u32>, Blocked> {
+    let (file, previous) = lock(path)?;
+    let info = LockInfo { pid: std::process::id(), started: chrono::Local::now().to_rfc3339() };
+    let _ = std::fs::write(info_path(path), serde_json::to_string(&info).unwrap_or_default());
+    let _ = LOCK_FILE.set((file, info_path(path)));
+    Ok(previous.map(|p| p.pid))
+}
+
+/// Remove the PID on a clean exit, so the next start does not report a crash. The OS releases
+/// the lock itself when the process ends.
+pub fn release() {
+    if let Some((_, info)) = LOCK_FILE.get() {
+        let _ = std::fs::remove_file(info);
+    }
+}
+
+/// Show `text` in a native message box (the Tauri runtime is not up at this point). Falls back
+/// to stderr when no dialog tool is available.
+pub fn show_error(text: &str) {
+    eprintln!("{}", text);
+    #[cfg(windows)]
+    {
+        #[link(name = "user32")]
+        extern "system" {
+            fn MessageBoxW(hwnd: isize, text: *const u16, caption: *const u16, flags: u32) -> i32;
+        }
+        const MB_ICONWARNING: u32 = 0x30;
+        let wide = |s: &str| s.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+        let (text, caption) = (wide(text), wide("Brandy Box"));
+        // SAFETY: both strings are NUL-terminated and outlive the call
+        unsafe { MessageBoxW(0, text.as_ptr(), caption.as_ptr(), MB_ICONWARNING) };
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let quoted = format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let _ = std::process::Command::new("osascript")
+            .args(["-e", &format!("display alert \"Brandy Box\" message {} as warning", quoted)])
+            .status();
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let shown = std::process::Command::new("zenity")
+            .args(["--warning", "--title", "Brandy Box", "--text", text])
+            .status()
+            .is_ok();
+        if !shown {
+            let _ = std::process::Command::new("kdialog")
+                .args(["--title", "Brandy Box", "--sorry", text])
+                .status();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_lock_info_is_taken_over_and_live_one_is_not() {
+        let dir = std::env::temp_dir().join(format!("brandybox-instance-lock-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("instance.lock");
+        assert!(process_alive(std::process::id()));
+
+        // Left behind by a crashed instance; the OS already released its lock
+        let crashed = LockInfo { pid: u32::MAX - 1, started: "2026-01-01T00:00:00+00:00".to_string() };
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(info_path(&path), serde_json::to_string(&crashed).unwrap()).unwrap();
+        let (held, previous) = lock(&path).unwrap();
+        assert_eq!(previous, Some(crashed));
+
+        // Held by a live process: a second attempt is refused
+        let live = LockInfo { pid: 1, started: "x".to_string() };
+        std::fs::write(info_path(&path), serde_json::to_string(&live).unwrap()).unwrap();
+        match lock(&path) {
+            Err(Blocked::Running { pid, .. }) => assert_eq!(pid, Some(1)),
+            other => panic!("expected Running, got {:?}", other.map(|_| ())),
+        }
+        drop(held);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,203 @@
+//! Local control interface for scripts and integrations.
+//!
+//! Unix: socket `brandybox.sock` in the config dir (mode 0600). Windows: named pipe
+//! `\\.\pipe\brandybox-<user>`. Protocol: one JSON object per line, e.g. `{"cmd":"status"}`,
+//! answered with `{"ok":true,"result":…}` or `{"ok":false,"error":"…"}`.
+//!
+//! Commands: `status`, `sync-now`, `pause` (`minutes`, default 60), `resume`,
+//! `recent-activity` (`limit`, default 20), `show` (open the settings window),
+//! `file-status` (`path`, absolute or relative to the sync folder, or `paths` for a batch) and
+//! `sync-folder` for file manager integrations, and `metrics` (`format`: `json`, the default,
+//! or `prometheus` for the text format as a string).
+
+use crate::{activity, file_status, sync};
+use brandybox_core::metrics;
+use serde::Deserialize;
+use std::io::{BufRead, Write};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+const DEFAULT_PAUSE_MINUTES: u64 = 60;
+const DEFAULT_ACTIVITY_LIMIT: usize = 20;
+
+#[derive(Deserialize)]
+struct Request {
+    cmd: String,
+    #[serde(default)]
+    minutes: Option<u64>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    paths: Option<Vec<String>>,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Path relative to the sync folder ("/"-separated) for an absolute or already relative path.
+/// None when an absolute path lies outside the sync folder.
+fn relative_to_sync_folder(path: &str) -> Option<String> {
+    let p = std::path::Path::new(path);
+    let rel = if p.is_absolute() {
+        p.strip_prefix(crate::config::get_sync_folder_path()).ok()?
+    } else {
+        p
+    };
+    let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    Some(parts.join("/"))
+}
+
+#[cfg(unix)]
+fn socket_path() -> std::path::PathBuf {
+    crate::config::get_config_dir().join("brandybox.sock")
+}
+
+#[cfg(windows)]
+fn pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!(r"\\.\pipe\brandybox-{}", user)
+}
+
+fn file_status_entry(path: &str) -> serde_json::Value {
+    let (status, rel) = match relative_to_sync_folder(path) {
+        Some(rel) => (file_status::status_of(&rel), Some(rel)),
+        None => (file_status::FileSyncStatus::Unknown, None),
+    };
+    let error = rel.as_deref().and_then(file_status::error_of);
+    serde_json::json!({ "path": rel, "status": status, "error": error })
+}
+
+/// Run one request. Called on a blocking thread (token refresh uses blocking HTTP).
+fn dispatch(app: &tauri::AppHandle, line: &str) -> Result<serde_json::Value, String> {
+    let req: Request = serde_json::from_str(line).map_err(|e| format!("Invalid request: {}", e))?;
+    match req.cmd.as_str() {
+        "status" => {
+            let mut status = sync::get_sync_status_payload();
+            status["progress"] = serde_json::to_value(crate::get_sync_progress()).unwrap_or_default();
+            status["last_sync"] = serde_json::to_value(sync::get_last_sync()).unwrap_or_default();
+            Ok(status)
+        }
+        "sync-now" => crate::run_sync(app.clone()),
+        "pause" => {
+            crate::pause_sync(app.clone(), req.minutes.unwrap_or(DEFAULT_PAUSE_MINUTES));
+            Ok(serde_json::json!({ "paused_until": sync::paused_until() }))
+        }
+        "resume" => {
+            crate::resume_sync(app.clone());
+            Ok(serde_json::Value::Null)
+        }
+        "recent-activity" => {
+            let events = activity::get_recent(req.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT));
+            serde_json::to_value(events).map_err(|e| e.to_string())
+        }
+        "file-status" => match (req.path, req.paths) {
+            (Some(path), _) => Ok(file_status_entry(&path)),
+            // Batch form: one entry per path, in order (Finder asks for many items at once)
+            (None, Some(paths)) => Ok(serde_json::Value::Array(paths.iter().map(|p| file_status_entry(p)).collect())),
+            (None, None) => Err("Missing path".to_string()),
+        },
+        "sync-folder" => Ok(serde_json::json!({
+            "path": crate::config::get_sync_folder_path(),
+            "set": crate::config::user_has_set_sync_folder(),
+        })),
+        "metrics" => {
+            let m = metrics::collect();
+            match req.format.as_deref() {
+                None | Some("json") => serde_json::to_value(m).map_err(|e| e.to_string()),
+                Some("prometheus") => Ok(serde_json::Value::String(metrics::to_prometheus(&m))),
+                Some(other) => Err(format!("Unknown format: {}", other)),
+            }
+        }
+        "show" => {
+            crate::show_main_window(app.clone());
+            Ok(serde_json::Value::Null)
+        }
+        other => Err(format!("Unknown command: {}", other)),
+    }
+}
+
+async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(stream: S, app: tauri::AppHandle) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let app = app.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || dispatch(&app, &line))
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+        let response = match result {
+            Ok(result) => serde_json::json!({ "ok": true, "result": result }),
+            Err(error) => serde_json::json!({ "ok": false, "error": error }),
+        };
+        if writer.write_all(format!("{}\n", response).as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn serve(app: tauri::AppHandle) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let path = socket_path();
+    // We hold the single-instance lock, so an existing socket is left over from a crash
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    log::info!("IPC listening on {}", path.display());
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tauri::async_runtime::spawn(handle_connection(stream, app.clone()));
+    }
+}
+
+#[cfg(windows)]
+async fn serve(app: tauri::AppHandle) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+    let name = pipe_name();
+    let mut server = ServerOptions::new().first_pipe_instance(true).create(&name)?;
+    log::info!("IPC listening on {}", name);
+    loop {
+        server.connect().await?;
+        let connected = server;
+        server = ServerOptions::new().create(&name)?;
+        tauri::async_runtime::spawn(handle_connection(connected, app.clone()));
+    }
+}
+
+/// Send one command to the running instance and return its result (blocking).
+/// Used by a second launch to hand over to the instance that holds the lock.
+pub fn send(request: &serde_json::Value) -> Result<serde_json::Value, String> {
+    #[cfg(unix)]
+    let stream = std::os::unix::net::UnixStream::connect(socket_path()).map_err(|e| e.to_string())?;
+    #[cfg(windows)]
+    let stream = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(pipe_name())
+        .map_err(|e| e.to_string())?;
+    let mut writer = &stream;
+    writer
+        .write_all(format!("{}\n", request).as_bytes())
+        .map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    std::io::BufReader::new(&stream)
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+    let response: serde_json::Value = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+    if response["ok"].as_bool() == Some(true) {
+        Ok(response["result"].clone())
+    } else {
+        Err(response["error"].as_str().unwrap_or("IPC request failed").to_string())
+    }
+}
+
+/// Start the IPC server on the async runtime. Failures are logged; the app works without it.
+pub fn spawn_server(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app).await {
+            log::warn!("IPC server stopped: {}", e);
+        }
+    });
+}
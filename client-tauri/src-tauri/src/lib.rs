@@ -1,267 +1,1560 @@
-//! Brandy Box Tauri app: config, auth, API, sync, tray.
+//! Brandy Box Tauri app: commands, auth, tray and window handling around `brandybox-core`.
 
-mod api;
-mod config;
 mod credentials;
-mod network;
-mod sync;
+mod diagnostics;
+mod emblems;
+mod folder_picker;
+mod fullscreen;
+mod instance_lock;
+mod ipc;
+mod logging;
+mod metrics_http;
+mod migration;
+mod notify;
+mod oauth;
+mod shortcuts;
+mod wake;
+mod window_geometry;
 
+use brandybox_core::{activity, api, app_lock, auto_upload, config, crash, device, file_status, folder_check, i18n, metadata_crypto, network, password, search, secure_delete, self_test, snapshots, stats, sync, thumbnails, usage, user_import};
 use api::ApiClient;
 use serde::Serialize;
 use tauri::{Emitter, Manager};
 use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[derive(Default)]
-#[allow(dead_code)]
 struct AppState {
-    /// Cached access token (set after login or refresh). Cleared on logout.
-    access_token: Mutex<Option<String>>,
+    /// Authenticated client shared by the `api_*` commands. Cleared on login and logout.
+    api_client: Mutex<Option<SharedApiClient>>,
+    /// Set in `setup`, so a refresh token the server rejects can trigger `handle_auth_expired`.
+    app: std::sync::OnceLock<tauri::AppHandle>,
 }
 
-#[derive(Serialize)]
+/// Replace the shared client this long before its access token expires.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+/// Token lifetime assumed when the server does not send `expires_in`.
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(300);
+
+struct SharedApiClient {
+    client: ApiClient,
+    valid_until: Instant,
+}
+
+impl AppState {
+    /// The shared client, with a fresh access token when there is none yet, the server URL changed
+    /// or the token is about to expire. The lock is held during the refresh so parallel commands
+    /// do not each spend (and rotate) the refresh token.
+    fn api_client(&self) -> Result<ApiClient, String> {
+        let mut shared = self.api_client.lock().map_err(|e| e.to_string())?;
+        let base_url = network::get_base_url();
+        if let Some(s) = shared.as_ref() {
+            if s.client.base_url == base_url && Instant::now() < s.valid_until {
+                return Ok(s.client.clone());
+            }
+        }
+        let res = refresh_session().map_err(|e| self.refresh_error(e))?;
+        let lifetime = res.expires_in.map(Duration::from_secs).unwrap_or(DEFAULT_TOKEN_LIFETIME);
+        let mut client = ApiClient::new(base_url);
+        client.set_access_token(Some(res.access_token));
+        *shared = Some(SharedApiClient {
+            client: client.clone(),
+            valid_until: Instant::now() + lifetime.saturating_sub(TOKEN_EXPIRY_MARGIN),
+        });
+        Ok(client)
+    }
+
+    /// Message for a failed refresh. An expired or revoked session goes through the same
+    /// auth-expired handling as the sync loop.
+    fn refresh_error(&self, err: TokenRefreshError) -> String {
+        match err {
+            TokenRefreshError::NotLoggedIn => i18n::t("login.not_logged_in", &[]),
+            TokenRefreshError::Expired => {
+                if let Some(app) = self.app.get() {
+                    handle_auth_expired(app);
+                }
+                i18n::t("notify.auth_expired", &[])
+            }
+            TokenRefreshError::Transient(e) => e,
+        }
+    }
+
+    /// `api_client` for account and admin actions, which the app lock blocks.
+    fn account_api_client(&self) -> Result<ApiClient, String> {
+        ensure_unlocked()?;
+        self.api_client()
+    }
+
+    fn clear_api_client(&self) {
+        let _ = self.api_client.lock().map(|mut g| *g = None);
+    }
+
+    /// Run `op` with the shared client. A 401 means the token was revoked or expired early: the
+    /// client is dropped and `op` retried once with a fresh token.
+    fn with_api<T>(&self, op: impl Fn(&ApiClient) -> Result<T, String>) -> Result<T, String> {
+        match op(&self.api_client()?) {
+            Err(e) if e.starts_with("401") => {
+                self.clear_api_client();
+                op(&self.api_client()?)
+            }
+            result => result,
+        }
+    }
+
+    /// `with_api` for account and admin actions, which the app lock blocks.
+    fn with_account_api<T>(&self, op: impl Fn(&ApiClient) -> Result<T, String>) -> Result<T, String> {
+        ensure_unlocked()?;
+        self.with_api(op)
+    }
+}
+
+fn ensure_unlocked() -> Result<(), String> {
+    if app_lock::is_locked() {
+        return Err(i18n::t("app_lock.locked", &[]));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Clone)]
 pub struct SyncProgressPayload {
     pub phase: String,
     pub current: u64,
     pub total: u64,
+    pub detail: Option<String>,
+}
+
+impl From<sync::SyncProgress> for SyncProgressPayload {
+    fn from(p: sync::SyncProgress) -> Self {
+        SyncProgressPayload { phase: p.phase, current: p.current, total: p.total, detail: p.detail }
+    }
+}
+
+#[tauri::command]
+fn get_base_url() -> String {
+    network::get_base_url()
+}
+
+#[tauri::command]
+fn get_sync_folder_path() -> String {
+    config::get_sync_folder_path().to_string_lossy().to_string()
+}
+
+/// Save the sync folder after checking it. Returns an error for folders that must not be used
+/// (drive root, system folder, inside the current sync folder…). For risky ones (home folder,
+/// inside another sync app's folder) nothing is saved and `confirm` lists the reasons until the
+/// UI calls again with `confirmed: true`.
+#[tauri::command]
+fn set_sync_folder_path(folder: String, confirmed: Option<bool>) -> Result<serde_json::Value, String> {
+    let path = config::expand_tilde(&folder);
+    let current = config::user_has_set_sync_folder().then(config::get_sync_folder_path);
+    if current.as_deref() != Some(path.as_path()) {
+        let risks = folder_check::sync_folder_risks(&path, current.as_deref(), dirs::home_dir().as_deref());
+        if !risks.rejected.is_empty() {
+            return Err(risks.rejected.join("; "));
+        }
+        if !risks.needs_confirmation.is_empty() && confirmed != Some(true) {
+            return Ok(serde_json::json!({ "saved": false, "confirm": risks.needs_confirmation }));
+        }
+    }
+    config::set_sync_folder_path(PathBuf::from(folder));
+    Ok(serde_json::json!({ "saved": true, "confirm": [] }))
+}
+
+#[tauri::command]
+fn user_has_set_sync_folder() -> bool {
+    config::user_has_set_sync_folder()
+}
+
+#[tauri::command]
+fn get_default_sync_folder() -> String {
+    config::get_default_sync_folder().to_string_lossy().to_string()
+}
+
+#[tauri::command]
+fn get_autostart() -> bool {
+    config::get_autostart()
+}
+
+#[tauri::command]
+fn set_autostart(enabled: bool) {
+    config::set_autostart(enabled);
+}
+
+#[tauri::command]
+fn get_start_minimized() -> bool {
+    config::get_start_minimized()
+}
+
+#[tauri::command]
+fn set_start_minimized(enabled: bool) {
+    config::set_start_minimized(enabled);
+}
+
+/// Language of notifications and messages from the app: `setting` ("system" or a language code)
+/// and the `effective` language.
+#[tauri::command]
+fn get_language() -> serde_json::Value {
+    serde_json::json!({ "setting": config::get_language(), "effective": i18n::language() })
+}
+
+#[tauri::command]
+fn set_language(language: String) -> Result<(), String> {
+    if language != "system" && !i18n::LANGUAGES.contains(&language.as_str()) {
+        return Err(format!("Unsupported language: {}", language));
+    }
+    config::set_language(language);
+    Ok(())
+}
+
+/// Tray icon variant: `setting` ("system", "light" or "dark") and, on Windows, the `taskbar`
+/// theme that "system" follows. Without it the UI uses the system theme of the window.
+#[tauri::command]
+fn get_tray_icon_theme() -> serde_json::Value {
+    serde_json::json!({
+        "setting": config::get_tray_icon_theme(),
+        "taskbar": config::taskbar_theme(),
+    })
+}
+
+#[tauri::command]
+fn set_tray_icon_theme(app: tauri::AppHandle, theme: String) -> Result<(), String> {
+    if !config::TRAY_ICON_THEMES.contains(&theme.as_str()) {
+        return Err(format!("Unknown tray icon theme: {}", theme));
+    }
+    config::set_tray_icon_theme(theme);
+    let _ = app.emit("tray-theme-changed", ());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_auto_upload() -> serde_json::Value {
+    serde_json::json!({
+        "folders": config::get_auto_upload_folders(),
+        "subfolder": config::get_auto_upload_subfolder(),
+    })
+}
+
+/// Folders whose new photos/videos are copied into `subfolder` of the sync folder (default
+/// "Camera Uploads"). An empty list turns auto-upload off.
+#[tauri::command]
+fn set_auto_upload(folders: Vec<String>, subfolder: Option<String>) -> Result<(), String> {
+    if subfolder.as_deref().is_some_and(|s| s.split(['/', '\\']).any(|c| c == "..")) {
+        return Err("Invalid subfolder".to_string());
+    }
+    let root = config::get_sync_folder_path();
+    for folder in folders.iter().filter(|f| !f.trim().is_empty()) {
+        let path = std::path::Path::new(folder.trim());
+        if !path.is_dir() {
+            return Err(format!("Folder not found: {}", folder));
+        }
+        if path.starts_with(&root) || root.starts_with(path) {
+            return Err(format!("{} overlaps the sync folder", folder));
+        }
+    }
+    config::set_auto_upload(folders, subfolder);
+    Ok(())
+}
+
+/// Whether hidden files and folders (".obsidian", ".config", …) are synced; `.git` never is.
+#[tauri::command]
+fn get_sync_hidden_files() -> bool {
+    config::get_sync_hidden_files()
+}
+
+#[tauri::command]
+fn set_sync_hidden_files(enabled: bool) {
+    config::set_sync_hidden_files(enabled);
+    sync::reload_ignore_settings();
+}
+
+/// Size limit for synced files and the paths exempt from it.
+#[tauri::command]
+fn get_max_file_size() -> serde_json::Value {
+    serde_json::json!({
+        "limit_bytes": config::get_max_file_size_bytes(),
+        "allowlist": config::get_large_file_allowlist(),
+    })
+}
+
+/// Files larger than `limit_bytes` are skipped with a warning; None turns the limit off.
+#[tauri::command]
+fn set_max_file_size(limit_bytes: Option<u64>) {
+    config::set_max_file_size_bytes(limit_bytes.filter(|b| *b > 0));
+}
+
+/// Exempt one file (path relative to the sync folder) from the size limit, or remove the exemption.
+#[tauri::command]
+fn allow_large_file(path: String, allowed: bool) -> Result<(), String> {
+    let path = path.replace('\\', "/").trim_matches('/').to_string();
+    if path.is_empty() || path.split('/').any(|c| c == "..") {
+        return Err("Invalid path".to_string());
+    }
+    config::set_large_file_allowed(&path, allowed);
+    Ok(())
+}
+
+/// Delete snapshot setting, size cap and existing snapshots (newest first).
+#[tauri::command]
+fn get_delete_snapshots() -> serde_json::Value {
+    serde_json::json!({
+        "enabled": config::get_delete_snapshots_enabled(),
+        "limit_bytes": config::get_delete_snapshot_limit_bytes(),
+        "snapshots": snapshots::list(),
+    })
+}
+
+/// Copy files into a dated snapshot folder before sync deletes them locally; `limit_bytes`
+/// None keeps the current size cap.
+#[tauri::command]
+fn set_delete_snapshots(enabled: bool, limit_bytes: Option<u64>) {
+    config::set_delete_snapshots(enabled, limit_bytes);
+}
+
+/// Overwrite passes before local deletes (0: off) and the allowed maximum.
+#[tauri::command]
+fn get_secure_delete() -> serde_json::Value {
+    serde_json::json!({
+        "passes": config::get_secure_delete_passes(),
+        "max_passes": secure_delete::MAX_PASSES,
+    })
+}
+
+#[tauri::command]
+fn set_secure_delete(passes: u32) {
+    config::set_secure_delete_passes(passes);
+}
+
+#[tauri::command]
+fn open_delete_snapshots_folder() -> Result<(), String> {
+    let dir = config::get_delete_snapshots_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    open::that(dir).map_err(|e| e.to_string())
+}
+
+/// Kiosk (download-only) mode; `forced` when set by the `--download-only` launch argument.
+#[tauri::command]
+fn get_download_only() -> serde_json::Value {
+    serde_json::json!({
+        "enabled": config::get_download_only(),
+        "forced": config::download_only_forced(),
+    })
+}
+
+#[tauri::command]
+fn set_download_only(enabled: bool) {
+    config::set_download_only(enabled);
+}
+
+/// Files and folders the user excluded from sync (paths relative to the sync folder).
+#[tauri::command]
+fn get_ignore_paths() -> Vec<String> {
+    config::get_ignore_paths()
+}
+
+/// Exclude a file or folder from sync ("Ignore this file" in the activity or error list). Its
+/// pending transfers are dropped, also in a running cycle; nothing is deleted.
+#[tauri::command]
+fn add_ignore_path(relative_path: String) -> Result<String, String> {
+    sync::set_path_ignored(&relative_path, true)
+}
+
+/// Sync a previously excluded file or folder again from the next cycle on.
+#[tauri::command]
+fn remove_ignore_path(relative_path: String) -> Result<String, String> {
+    sync::set_path_ignored(&relative_path, false)
+}
+
+/// Linux: file manager emblems showing each file's sync status (Nautilus, Nemo, Caja).
+#[tauri::command]
+fn get_file_manager_emblems() -> bool {
+    config::get_file_manager_emblems()
+}
+
+#[tauri::command]
+fn set_file_manager_emblems(enabled: bool) {
+    config::set_file_manager_emblems(enabled);
+    emblems::refresh();
+}
+
+#[tauri::command]
+fn get_notification_settings() -> serde_json::Value {
+    serde_json::json!({
+        "enabled": config::get_notifications_enabled(),
+        "schedule": config::get_notification_schedule(),
+        "quota_warning_thresholds": config::get_quota_warning_thresholds(),
+    })
+}
+
+/// Storage usage percentages (1-100) at which a warning is shown, e.g. [80, 95].
+#[tauri::command]
+fn set_quota_warning_thresholds(thresholds: Vec<u32>) -> Result<(), String> {
+    if thresholds.iter().any(|p| !(1..=100).contains(p)) {
+        return Err("Thresholds must be percentages between 1 and 100".to_string());
+    }
+    config::set_quota_warning_thresholds(thresholds);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_notification_settings(enabled: bool, schedule: config::NotificationSchedule) -> Result<(), String> {
+    schedule.quiet_hours.iter().try_for_each(check_time_window)?;
+    config::set_notifications_enabled(enabled);
+    config::set_notification_schedule(schedule);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_base_url_mode() -> String {
+    config::get_base_url_mode()
+}
+
+#[tauri::command]
+fn set_base_url_mode(mode: String) {
+    config::set_base_url_mode(mode);
+}
+
+#[tauri::command]
+fn get_manual_base_url() -> String {
+    config::get_manual_base_url()
+}
+
+#[tauri::command]
+fn set_manual_base_url(url: String) {
+    config::set_manual_base_url(url);
+}
+
+/// Probe a manual base URL before saving it. Runs off the main thread (network timeouts).
+#[tauri::command]
+async fn validate_base_url(url: String) -> Result<network::BaseUrlCheck, String> {
+    tauri::async_runtime::spawn_blocking(move || network::validate_base_url(&url))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn export_settings(path: String) -> Result<(), String> {
+    config::export_settings(&PathBuf::from(path))
+}
+
+#[tauri::command]
+fn import_settings(path: String) -> Result<(), String> {
+    config::import_settings(&PathBuf::from(path))
+}
+
+/// Wipe config, sync state and (unless `keep_credentials`) stored credentials.
+/// Refused while a sync is running so the cycle cannot write stale state back.
+#[tauri::command]
+fn reset_app(app: tauri::AppHandle, keep_credentials: bool) -> Result<(), String> {
+    let (status, _) = sync::get_sync_status();
+    if status == "syncing" {
+        return Err(i18n::t("sync.busy", &[]));
+    }
+    config::reset_local_data();
+    activity::clear();
+    stats::clear();
+    if !keep_credentials {
+        credentials::clear_stored();
+        credentials::clear_app_token();
+    }
+    sync::set_sync_status(sync::SyncStatus::Idle);
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
+    log::info!("App reset (keep_credentials={})", keep_credentials);
+    Ok(())
+}
+
+#[tauri::command]
+fn login(state: tauri::State<'_, AppState>, email: String, password: String) -> Result<serde_json::Value, String> {
+    state.clear_api_client();
+    let base_url = network::get_base_url();
+    let client = ApiClient::new(base_url);
+    let res = client.login(email.trim(), password.trim()).map_err(|e| {
+        if e.contains("401") {
+            i18n::t("login.invalid_credentials", &[])
+        } else {
+            e
+        }
+    })?;
+    credentials::set_stored(email.trim(), &res.refresh_token);
+    sync::clear_auth_expired();
+    // Signing in proves the password as well as unlocking does
+    app_lock::unlock();
+    Ok(serde_json::json!({
+        "access_token": res.access_token,
+        "refresh_token": res.refresh_token
+    }))
+}
+
+#[tauri::command]
+fn get_metadata_encryption() -> serde_json::Value {
+    serde_json::json!({
+        "enabled": metadata_crypto::is_enabled(),
+        "key_missing": metadata_crypto::key_missing(),
+    })
+}
+
+/// Turn encryption of config.json, sync_state.json and the other metadata files on or off. The
+/// key is created in (or removed from) the OS keyring and the files are rewritten right away.
+#[tauri::command]
+fn set_metadata_encryption(enabled: bool) -> Result<serde_json::Value, String> {
+    if enabled == metadata_crypto::is_enabled() {
+        return Ok(get_metadata_encryption());
+    }
+    if enabled {
+        let key = metadata_crypto::generate_key()?;
+        credentials::set_metadata_key(Some(&key))?;
+        if let Err(e) = metadata_crypto::rewrite_all(Some(key)) {
+            let _ = credentials::set_metadata_key(None);
+            return Err(e);
+        }
+    } else {
+        metadata_crypto::rewrite_all(None)?;
+        credentials::set_metadata_key(None)?;
+    }
+    Ok(get_metadata_encryption())
+}
+
+#[tauri::command]
+fn get_app_lock() -> app_lock::AppLockStatus {
+    app_lock::status()
+}
+
+/// Turn the app lock on (`minutes` of inactivity) or off (`None`). Only while unlocked.
+#[tauri::command]
+fn set_app_lock(minutes: Option<u32>) -> Result<app_lock::AppLockStatus, String> {
+    ensure_unlocked()?;
+    config::set_app_lock_minutes(minutes);
+    app_lock::unlock();
+    Ok(app_lock::status())
+}
+
+/// Lock account and admin settings now.
+#[tauri::command]
+fn lock_app() -> app_lock::AppLockStatus {
+    app_lock::lock();
+    app_lock::status()
+}
+
+/// Activity in the settings window; restarts the idle timer while unlocked.
+#[tauri::command]
+fn app_activity() {
+    app_lock::touch();
+}
+
+/// Unlock with the account password, checked by signing in to the server.
+#[tauri::command]
+async fn unlock_app(password: String) -> Result<app_lock::AppLockStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (email, _) = credentials::get_stored().ok_or_else(|| i18n::t("login.not_logged_in", &[]))?;
+        ApiClient::new(network::get_base_url()).login(&email, &password).map_err(|e| {
+            if e.starts_with("401") {
+                i18n::t("app_lock.wrong_password", &[])
+            } else {
+                e
+            }
+        })?;
+        app_lock::unlock();
+        Ok(app_lock::status())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Forgot password: have the server email a reset code. Returns the message to show.
+#[tauri::command]
+async fn request_password_reset(email: String) -> Result<String, String> {
+    let email = email.trim().to_string();
+    if email.is_empty() {
+        return Err(i18n::t("login.reset_email_missing", &[]));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let client = ApiClient::new(network::get_base_url());
+        match client.request_password_reset(&email) {
+            Ok(()) => {
+                log::info!("Password reset requested for {}", email);
+                Ok(i18n::t("login.reset_sent", &[("email", &email)]))
+            }
+            // 404: server without the endpoint; 503: no SMTP configured
+            Err(e) if e.starts_with("404") || e.starts_with("503") => Err(i18n::t("login.reset_unavailable", &[])),
+            Err(e) if e.starts_with("429") => Err(i18n::t("login.reset_rate_limited", &[])),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Forgot password, step 2: set a new password with the emailed code. The password gets the same
+/// local checks as `api_change_password`. Returns the message to show.
+#[tauri::command]
+async fn confirm_password_reset(
+    email: String,
+    token: String,
+    new_password: String,
+    confirm_password: Option<String>,
+) -> Result<String, String> {
+    let check = password::check("", &new_password, confirm_password.as_deref(), Some(email.trim()));
+    if !check.ok {
+        return Err(check.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>().join(" "));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let client = ApiClient::new(network::get_base_url());
+        match client.confirm_password_reset(token.trim(), &new_password) {
+            Ok(()) => Ok(i18n::t("login.reset_done", &[])),
+            Err(e) if e.starts_with("400") => Err(i18n::t("login.reset_code_invalid", &[])),
+            Err(e) if e.starts_with("429") => Err(i18n::t("login.reset_rate_limited", &[])),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Sign in via the system browser (SSO). Runs the blocking loopback flow off the main thread.
+#[tauri::command]
+async fn login_with_browser(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    state.clear_api_client();
+    tauri::async_runtime::spawn_blocking(|| {
+        let base_url = network::get_base_url();
+        let mut client = ApiClient::new(base_url);
+        let res = oauth::login_with_browser(&client)?;
+        client.set_access_token(Some(res.access_token.clone()));
+        let user = client.me()?;
+        credentials::set_stored(&user.email, &res.refresh_token);
+        sync::clear_auth_expired();
+        Ok(serde_json::json!({
+            "access_token": res.access_token,
+            "refresh_token": res.refresh_token
+        }))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Authenticate with a long-lived app token instead of email + password (servers, kiosks).
+#[tauri::command]
+fn login_with_app_token(state: tauri::State<'_, AppState>, token: String) -> Result<serde_json::Value, String> {
+    state.clear_api_client();
+    let token = token.trim();
+    let base_url = network::get_base_url();
+    let mut client = ApiClient::new(base_url);
+    let res = client.login_with_token(token).map_err(|e| {
+        if is_auth_rejection(&e) {
+            i18n::t("login.invalid_app_token", &[])
+        } else {
+            e
+        }
+    })?;
+    client.set_access_token(Some(res.access_token.clone()));
+    let user = client.me()?;
+    credentials::set_app_token(token)?;
+    if !res.refresh_token.is_empty() {
+        credentials::set_stored(&user.email, &res.refresh_token);
+    }
+    sync::clear_auth_expired();
+    Ok(serde_json::json!({ "email": user.email }))
+}
+
+#[tauri::command]
+fn logout(state: tauri::State<'_, AppState>) {
+    state.clear_api_client();
+    credentials::clear_stored();
+    credentials::clear_app_token();
+}
+
+#[tauri::command]
+fn get_stored_email() -> Option<String> {
+    credentials::get_stored().map(|(email, _)| email)
+}
+
+/// Why the stored refresh token could not be exchanged for an access token.
+enum TokenRefreshError {
+    /// No credentials stored (never logged in or logged out).
+    NotLoggedIn,
+    /// Server rejected the refresh token (expired or revoked); the user must log in again.
+    Expired,
+    /// Network or server failure; the token may still be valid, retry later.
+    Transient(String),
+}
+
+/// True when a refresh error string is the server rejecting the token (401/403), not a transport failure.
+fn is_auth_rejection(err: &str) -> bool {
+    err.starts_with("401") || err.starts_with("403")
+}
+
+fn refresh_access_token() -> Result<String, TokenRefreshError> {
+    refresh_session().map(|r| r.access_token)
+}
+
+/// Exchange the app token or the stored refresh token for a new access token.
+fn refresh_session() -> Result<api::LoginResponse, TokenRefreshError> {
+    let classify = |e: String| {
+        if is_auth_rejection(&e) {
+            TokenRefreshError::Expired
+        } else {
+            TokenRefreshError::Transient(e)
+        }
+    };
+    let base_url = network::get_base_url();
+    let client = ApiClient::new(base_url);
+    // Headless installs: an app token takes precedence over the interactive refresh token
+    if let Some(app_token) = credentials::get_app_token() {
+        // 404: the server has no app token login, so retrying will not help either
+        return client.login_with_token(&app_token).map_err(|e| {
+            if e.starts_with("404") {
+                TokenRefreshError::Expired
+            } else {
+                classify(e)
+            }
+        });
+    }
+    let (email, refresh_token) = credentials::get_stored().ok_or(TokenRefreshError::NotLoggedIn)?;
+    let res = client.refresh(&refresh_token).map_err(classify)?;
+    credentials::set_stored(&email, &res.refresh_token);
+    Ok(res)
+}
+
+/// Set the auth-expired status, emit `auth-expired` and show a notification (once per expiry).
+fn handle_auth_expired(app: &tauri::AppHandle) {
+    let (status, _) = sync::get_sync_status();
+    if status == "auth_expired" {
+        return;
+    }
+    log::warn!("Refresh token expired or revoked; sync stopped until next login");
+    sync::set_sync_status(sync::SyncStatus::AuthExpired);
+    let _ = app.emit("auth-expired", ());
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
+    notify::notify(
+        app,
+        notify::Kind::AuthExpired,
+        &i18n::t("notify.auth_expired_title", &[]),
+        &i18n::t("notify.auth_expired", &[]),
+    );
+}
+
+/// The user revoked this device: wipe stored credentials, stop syncing and tell the user.
+fn handle_device_revoked(app: &tauri::AppHandle) {
+    log::warn!("This device was revoked on the server; clearing credentials");
+    logout(app.state());
+    sync::set_sync_status(sync::SyncStatus::DeviceRevoked);
+    let _ = app.emit("device-revoked", ());
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
+    notify::notify(
+        app,
+        notify::Kind::DeviceRevoked,
+        &i18n::t("notify.device_revoked_title", &[]),
+        &i18n::t("notify.device_revoked", &[]),
+    );
+}
+
+/// Check in with the server before a sync cycle. Returns false (after wiping credentials) when
+/// this device was revoked; registration failures (e.g. an older server) do not block syncing.
+fn device_allowed(app: &tauri::AppHandle, client: &ApiClient) -> bool {
+    match device::check_in(client) {
+        Ok(true) => true,
+        Ok(false) => {
+            handle_device_revoked(app);
+            false
+        }
+        Err(e) => {
+            log::debug!("Device check-in failed: {}", e);
+            true
+        }
+    }
+}
+
+#[tauri::command]
+fn get_valid_access_token(app: tauri::AppHandle) -> Option<String> {
+    match refresh_access_token() {
+        Ok(token) => Some(token),
+        Err(TokenRefreshError::Expired) => {
+            handle_auth_expired(&app);
+            None
+        }
+        Err(TokenRefreshError::Transient(e)) => {
+            log::warn!("Token refresh failed: {}", e);
+            None
+        }
+        Err(TokenRefreshError::NotLoggedIn) => None,
+    }
+}
+
+#[tauri::command]
+fn api_me(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let user = state.with_api(|c| c.me())?;
+    Ok(serde_json::json!({
+        "email": user.email,
+        "first_name": user.first_name,
+        "last_name": user.last_name,
+        "is_admin": user.is_admin
+    }))
+}
+
+#[tauri::command]
+fn api_get_storage(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let s = state.with_api(|c| c.get_storage())?;
+    Ok(serde_json::json!({
+        "used_bytes": s.used_bytes,
+        "limit_bytes": s.limit_bytes,
+        "server_disk_total_bytes": s.server_disk_total_bytes,
+        "server_disk_used_bytes": s.server_disk_used_bytes,
+        "server_disk_path": s.server_disk_path
+    }))
+}
+
+/// Rate a new password while the user types: score 0–4 and the issues that would block it.
+#[tauri::command]
+fn check_new_password(
+    current_password: String,
+    new_password: String,
+    confirm_password: Option<String>,
+) -> password::PasswordCheck {
+    let email = credentials::get_stored().map(|(email, _)| email);
+    password::check(&current_password, &new_password, confirm_password.as_deref(), email.as_deref())
+}
+
+/// Change the password after the local checks of `check_new_password` pass; their messages
+/// are returned as the error otherwise, without contacting the server.
+#[tauri::command]
+fn api_change_password(
+    state: tauri::State<'_, AppState>,
+    current_password: String,
+    new_password: String,
+    confirm_password: Option<String>,
+) -> Result<(), String> {
+    let check = check_new_password(current_password.clone(), new_password.clone(), confirm_password);
+    if !check.ok {
+        return Err(check.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>().join(" "));
+    }
+    state.with_account_api(|c| c.change_password(&current_password, &new_password))
+}
+
+#[tauri::command]
+fn api_list_users(state: tauri::State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
+    let users = state.with_account_api(|c| c.list_users())?;
+    Ok(users
+        .into_iter()
+        .map(|u| {
+            serde_json::json!({
+                "email": u.email,
+                "first_name": u.first_name,
+                "last_name": u.last_name,
+                "is_admin": u.is_admin,
+                "is_enabled": u.is_enabled.unwrap_or(true),
+                "used_bytes": u.storage_used_bytes.unwrap_or(0),
+                "storage_limit_bytes": u.storage_limit_bytes
+            })
+        })
+        .collect())
+}
+
+#[tauri::command]
+fn api_create_user(
+    state: tauri::State<'_, AppState>,
+    email: String,
+    first_name: String,
+    last_name: String,
+) -> Result<serde_json::Value, String> {
+    state.with_account_api(|c| c.create_user(&email, &first_name, &last_name))
+}
+
+#[tauri::command]
+fn api_update_user_storage_limit(
+    state: tauri::State<'_, AppState>,
+    email: String,
+    limit_bytes: Option<i64>,
+) -> Result<serde_json::Value, String> {
+    state.with_account_api(|c| c.update_user_storage_limit(&email, limit_bytes))
+}
+
+/// Move or rename a file on the server (no download and re-upload); the next cycle applies it
+/// locally, so one is started.
+#[tauri::command]
+fn api_move_file(state: tauri::State<'_, AppState>, from: String, to: String) -> Result<(), String> {
+    remote_file_op(&state, |client| client.move_file(&from, &to))?;
+    log::info!("Moved {} to {} on the server", from, to);
+    Ok(())
+}
+
+/// Copy a file on the server; the copy reaches this device with the next cycle.
+#[tauri::command]
+fn api_copy_file(state: tauri::State<'_, AppState>, from: String, to: String) -> Result<(), String> {
+    remote_file_op(&state, |client| client.copy_file(&from, &to))?;
+    log::info!("Copied {} to {} on the server", from, to);
+    Ok(())
+}
+
+/// Run a change to server files and wake the sync loop. Refused in download-only mode.
+fn remote_file_op(state: &AppState, op: impl Fn(&ApiClient) -> Result<(), String>) -> Result<(), String> {
+    if config::get_download_only() {
+        return Err(i18n::t("sync.download_only", &[]));
+    }
+    state.with_api(op)?;
+    wake::trigger("server files changed");
+    Ok(())
+}
+
+/// Download all of the user's data as a zip (default: Downloads folder). Returns the saved path.
+#[tauri::command]
+async fn api_request_account_export(app: tauri::AppHandle, path: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = path.map(PathBuf::from).unwrap_or_else(|| {
+            dirs::download_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(format!("brandybox-export-{}.zip", chrono::Local::now().format("%Y%m%d-%H%M%S")))
+        });
+        app.state::<AppState>().with_account_api(|c| c.export_account(&path))?;
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Download the server folder `prefix` ("" for all files) as a zip without syncing it. `dest`
+/// defaults to `<folder name>.zip` in the Downloads folder. Returns the saved path.
+#[tauri::command]
+async fn download_folder(app: tauri::AppHandle, prefix: String, dest: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dest = dest.map(PathBuf::from).unwrap_or_else(|| {
+            let name = prefix.trim_matches('/').rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or("brandybox");
+            dirs::download_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(format!("{}.zip", name))
+        });
+        let bytes = app.state::<AppState>().with_api(|c| c.download_folder_zip(&prefix, &dest))?;
+        log::info!("Downloaded server folder {:?} as zip ({} bytes) to {}", prefix, bytes, dest.display());
+        Ok(dest.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Permanently delete the own account (password confirmation, then the server's confirmation
+/// token). Afterwards this install is logged out and its sync state cleared; local files stay.
+#[tauri::command]
+async fn api_delete_own_account(app: tauri::AppHandle, password: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let client = app.state::<AppState>().account_api_client()?;
+        let confirmation = client.request_account_deletion(&password)?;
+        client.confirm_account_deletion(&confirmation)?;
+        log::info!("Account deleted; logging out");
+        logout(app.state());
+        config::clear_sync_state();
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Devices registered for the current user; `current` marks this install.
+#[tauri::command]
+async fn api_list_devices(app: tauri::AppHandle) -> Result<Vec<serde_json::Value>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let this_device = config::get_device_id();
+        let devices = app.state::<AppState>().with_account_api(|c| c.list_devices())?;
+        Ok(devices
+            .into_iter()
+            .map(|d| {
+                let current = d.device_id == this_device;
+                let mut v = serde_json::to_value(d).unwrap_or_default();
+                v["current"] = serde_json::Value::Bool(current);
+                v
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn api_revoke_device(app: tauri::AppHandle, device_id: String) -> Result<api::Device, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<AppState>().with_account_api(|c| c.revoke_device(&device_id))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Admin: create users from a CSV file (email, first name, last name, optional quota).
+/// Emits `bulk-import-progress` after each line and returns a per-line report.
+#[tauri::command]
+async fn api_bulk_create_users(
+    app: tauri::AppHandle,
+    csv_path: String,
+) -> Result<Vec<user_import::ImportRowResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let text = std::fs::read_to_string(&csv_path).map_err(|e| format!("Cannot read {}: {}", csv_path, e))?;
+        let client = app.state::<AppState>().account_api_client()?;
+        Ok(user_import::import_users(&client, &text, |done, total, email| {
+            let _ = app.emit(
+                "bulk-import-progress",
+                serde_json::json!({ "current": done, "total": total, "email": email }),
+            );
+        }))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn api_update_user(
+    state: tauri::State<'_, AppState>,
+    email: String,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    is_admin: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    state.with_account_api(|c| c.update_user(&email, first_name.as_deref(), last_name.as_deref(), is_admin))
+}
+
+#[tauri::command]
+fn api_set_user_enabled(state: tauri::State<'_, AppState>, email: String, enabled: bool) -> Result<serde_json::Value, String> {
+    state.with_account_api(|c| c.set_user_enabled(&email, enabled))
+}
+
+#[tauri::command]
+fn api_admin_reset_password(state: tauri::State<'_, AppState>, email: String) -> Result<serde_json::Value, String> {
+    state.with_account_api(|c| c.admin_reset_password(&email))
+}
+
+#[tauri::command]
+fn api_delete_user(state: tauri::State<'_, AppState>, email: String) -> Result<(), String> {
+    state.with_account_api(|c| c.delete_user(&email))
+}
+
+/// Re-run the import of Python client credentials, settings and sync state.
+#[tauri::command]
+fn import_legacy_client_data() -> migration::MigrationReport {
+    migration::import_legacy_client_data()
+}
+
+#[tauri::command]
+fn open_sync_folder() -> Result<(), String> {
+    let path = config::get_sync_folder_path();
+    if !path.exists() {
+        let _ = std::fs::create_dir_all(&path);
+    }
+    open::that(path).map_err(|e| e.to_string())
+}
+
+/// Last `lines` lines of the client log (default 200), oldest first.
+#[tauri::command]
+fn get_recent_logs(lines: Option<usize>) -> Vec<String> {
+    logging::recent_lines(lines.unwrap_or(200))
+}
+
+#[tauri::command]
+fn open_log_folder() -> Result<(), String> {
+    let dir = logging::log_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    open::that(dir).map_err(|e| e.to_string())
+}
+
+/// Zip logs, sanitized config and sync summary for a bug report. Returns the bundle path.
+#[tauri::command]
+async fn create_diagnostic_bundle(path: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = path.map(PathBuf::from).unwrap_or_else(diagnostics::default_bundle_path);
+        diagnostics::create_bundle(&path)?;
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Support self-test: config, keyring, server, login and a probe file round trip through the
+/// server, one pass/fail entry per step.
+#[tauri::command]
+async fn run_self_test(app: tauri::AppHandle) -> Result<self_test::SelfTestReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        self_test::run(credentials::check_keyring, || app.state::<AppState>().api_client())
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Client version/build, platform, base URL and server version for the About section.
+#[tauri::command]
+async fn get_app_info() -> Result<diagnostics::AppInfo, String> {
+    tauri::async_runtime::spawn_blocking(diagnostics::app_info)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Crash reports left by earlier runs, so the UI can offer to send them.
+#[tauri::command]
+fn get_pending_crash_reports() -> Vec<crash::CrashReport> {
+    crash::pending_reports()
+}
+
+/// Send pending crash reports to the server. Returns how many were sent.
+#[tauri::command]
+async fn send_crash_reports(app: tauri::AppHandle) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<AppState>().with_api(crash::send_pending)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn discard_crash_reports() {
+    crash::discard_pending();
+}
+
+#[tauri::command]
+fn get_log_level() -> String {
+    config::get_log_level()
+}
+
+#[tauri::command]
+fn set_log_level(level: String) -> Result<(), String> {
+    logging::set_level(&level)
+}
+
+#[tauri::command]
+fn get_http_debug() -> bool {
+    config::get_http_debug()
 }
 
+/// Log every API request (method, URL, status, duration, sizes) for diagnosing sync failures.
 #[tauri::command]
-fn get_base_url() -> String {
-    network::get_base_url()
+fn set_http_debug(enabled: bool) {
+    config::set_http_debug(enabled);
+    api::set_http_trace(config::get_http_debug());
 }
 
 #[tauri::command]
-fn get_sync_folder_path() -> String {
-    config::get_sync_folder_path().to_string_lossy().to_string()
+fn get_metrics_port() -> Option<u16> {
+    config::get_metrics_port()
 }
 
+/// Port of the local metrics endpoint (None or 0 turns it off). Takes effect after a restart.
 #[tauri::command]
-fn set_sync_folder_path(folder: String) {
-    config::set_sync_folder_path(PathBuf::from(folder));
+fn set_metrics_port(port: Option<u16>) {
+    config::set_metrics_port(port.filter(|p| *p != 0));
 }
 
 #[tauri::command]
-fn user_has_set_sync_folder() -> bool {
-    config::user_has_set_sync_folder()
+fn get_shortcuts() -> serde_json::Value {
+    serde_json::json!({
+        "show_window": config::get_shortcut_show_window(),
+        "sync_now": config::get_shortcut_sync_now(),
+    })
 }
 
+/// Global hotkeys for showing the window and syncing (None or "" turns one off). Re-registered
+/// right away; an invalid accelerator is rejected and nothing is saved.
 #[tauri::command]
-fn get_default_sync_folder() -> String {
-    config::get_default_sync_folder().to_string_lossy().to_string()
+fn set_shortcuts(app: tauri::AppHandle, show_window: Option<String>, sync_now: Option<String>) -> Result<(), String> {
+    shortcuts::parse(show_window.as_deref())?;
+    shortcuts::parse(sync_now.as_deref())?;
+    config::set_shortcuts(show_window, sync_now);
+    shortcuts::register(&app);
+    Ok(())
 }
 
+/// Setup wizard: can `path` be used as the sync folder (exists or can be created, writable, not a
+/// system folder, not inside another sync app's folder)? Also reports free space.
 #[tauri::command]
-fn get_autostart() -> bool {
-    config::get_autostart()
+fn validate_sync_folder(path: String) -> folder_check::SyncFolderCheck {
+    folder_check::check_sync_folder(&config::expand_tilde(&path))
 }
 
+/// Open the system folder picker and check the chosen folder: `check` as in
+/// `validate_sync_folder`, `risks` as `set_sync_folder_path` would apply them. Nothing is saved;
+/// the UI passes `path` on to `set_sync_folder_path`. Returns null when the user cancelled.
 #[tauri::command]
-fn set_autostart(enabled: bool) {
-    config::set_autostart(enabled);
+async fn pick_sync_folder() -> Result<Option<serde_json::Value>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let current = config::user_has_set_sync_folder().then(config::get_sync_folder_path);
+        let start = current.clone().or_else(dirs::home_dir);
+        let Some(path) = folder_picker::pick_folder("Choose the Brandy Box sync folder", start.as_deref())? else {
+            return Ok(None);
+        };
+        let check = folder_check::check_sync_folder(&path);
+        let risks = if current.as_deref() == Some(path.as_path()) {
+            folder_check::SyncFolderRisks::default()
+        } else {
+            folder_check::sync_folder_risks(&path, current.as_deref(), dirs::home_dir().as_deref())
+        };
+        Ok(Some(serde_json::json!({ "path": path.to_string_lossy(), "check": check, "risks": risks })))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Setup wizard: what the first sync into `path` (default: the configured folder) would transfer,
+/// and whether the downloads fit on the drive.
 #[tauri::command]
-fn get_base_url_mode() -> String {
-    config::get_base_url_mode()
+async fn estimate_initial_sync(app: tauri::AppHandle, path: Option<String>) -> Result<serde_json::Value, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = path
+            .map(|p| config::expand_tilde(&p))
+            .unwrap_or_else(config::get_sync_folder_path);
+        let estimate = app.state::<AppState>().with_api(|c| sync::estimate_initial_sync(c, &root))?;
+        let free_bytes = folder_check::check_sync_folder(&root).free_bytes;
+        Ok(serde_json::json!({
+            "estimate": estimate,
+            "free_bytes": free_bytes,
+            "enough_space": free_bytes.map(|f| f >= estimate.download_bytes),
+        }))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Local and server size per folder, `depth` levels deep (default 1), largest first. Served from
+/// the cache (refreshed after each sync) unless `refresh` is set.
 #[tauri::command]
-fn set_base_url_mode(mode: String) {
-    config::set_base_url_mode(mode);
+async fn get_folder_sizes(
+    app: tauri::AppHandle,
+    depth: Option<usize>,
+    refresh: Option<bool>,
+) -> Result<Vec<usage::FolderSize>, String> {
+    let depth = depth.unwrap_or(1);
+    if refresh != Some(true) {
+        if let Some(sizes) = usage::cached(depth) {
+            return Ok(sizes);
+        }
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = config::get_sync_folder_path();
+        app.state::<AppState>().with_api(|c| usage::refresh(c, &root, depth))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Files stored more than once on the server (same content), with the space the extra copies take.
 #[tauri::command]
-fn get_manual_base_url() -> String {
-    config::get_manual_base_url()
+async fn find_duplicate_files(app: tauri::AppHandle) -> Result<usage::DuplicateReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let items = app.state::<AppState>().with_api(|c| c.list_files())?;
+        Ok(usage::find_duplicates(&items))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Find files by name in the sync folder and on the server, best matches first. When the server
+/// cannot be reached, only local files are searched.
 #[tauri::command]
-fn set_manual_base_url(url: String) {
-    config::set_manual_base_url(url);
+async fn search_files(app: tauri::AppHandle, query: String, limit: Option<usize>) -> Result<Vec<search::SearchResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let local = search::local_paths(&config::get_sync_folder_path());
+        let remote: Vec<String> = match app.state::<AppState>().with_api(|c| c.list_files()) {
+            Ok(items) => items.into_iter().map(|f| f.path).collect(),
+            Err(e) => {
+                log::debug!("search_files: server listing failed: {}", e);
+                Vec::new()
+            }
+        };
+        Ok(search::search_paths(
+            &query,
+            local.iter().map(String::as_str),
+            remote.iter().map(String::as_str),
+            limit.unwrap_or(search::DEFAULT_LIMIT),
+        ))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Search files on the server by name and, with `content`, inside text files. Servers without
+/// a search endpoint fall back to a name search over the file list (no content matches).
 #[tauri::command]
-fn login(email: String, password: String) -> Result<serde_json::Value, String> {
-    let base_url = network::get_base_url();
-    let client = ApiClient::new(base_url);
-    let res = client.login(email.trim(), password.trim()).map_err(|e| {
-        if e.contains("401") {
-            "Invalid email or password.".to_string()
-        } else {
-            e
+async fn search_server_files(app: tauri::AppHandle, query: String, content: Option<bool>) -> Result<Vec<api::SearchHit>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        if let Some(hits) = state.with_api(|c| c.search(&query, content.unwrap_or(false)))? {
+            return Ok(hits);
         }
-    })?;
-    credentials::set_stored(email.trim(), &res.refresh_token);
-    Ok(serde_json::json!({
-        "access_token": res.access_token,
-        "refresh_token": res.refresh_token
-    }))
+        log::debug!("Server has no search endpoint, searching the file list");
+        let items = state.with_api(|c| c.list_files())?;
+        let by_path: std::collections::HashMap<&str, &api::FileItem> = items.iter().map(|f| (f.path.as_str(), f)).collect();
+        let results = search::search_paths(&query, [], items.iter().map(|f| f.path.as_str()), search::DEFAULT_LIMIT);
+        Ok(results
+            .into_iter()
+            .map(|r| api::SearchHit {
+                mtime: by_path.get(r.path.as_str()).map(|f| f.mtime).unwrap_or(0.0),
+                size: by_path.get(r.path.as_str()).and_then(|f| f.size),
+                path: r.path,
+                match_kind: "name".to_string(),
+                snippet: None,
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Local file path of a JPEG preview for a server file (cached on disk), or None when the file has
+/// no preview. `version` (hash or mtime from the listing) makes edited files get a new preview.
 #[tauri::command]
-fn logout() {
-    credentials::clear_stored();
+async fn get_thumbnail(
+    app: tauri::AppHandle,
+    path: String,
+    size: Option<u32>,
+    version: Option<String>,
+) -> Result<Option<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let size = size.unwrap_or(thumbnails::DEFAULT_SIZE);
+        let version = version.as_deref().unwrap_or("");
+        // Cache hits need no token: a gallery must not spend a refresh per image
+        let preview = match thumbnails::cached(&path, size, version) {
+            Some(p) => Some(p),
+            None => app.state::<AppState>().with_api(|c| thumbnails::get(c, &path, size, version))?,
+        };
+        Ok(preview.map(|p| p.to_string_lossy().to_string()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// What "Sync now" would transfer and delete, without doing it.
 #[tauri::command]
-fn get_stored_email() -> Option<String> {
-    credentials::get_stored().map(|(email, _)| email)
+async fn estimate_sync(app: tauri::AppHandle) -> Result<sync::SyncEstimate, String> {
+    if !config::user_has_set_sync_folder() {
+        return Err(i18n::t("sync.folder_not_set", &[]));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = config::get_sync_folder_path();
+        app.state::<AppState>().with_api(|c| sync::estimate_sync(c, &root))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-fn get_valid_access_token() -> Option<String> {
-    let (email, refresh_token) = credentials::get_stored()?;
-    let base_url = network::get_base_url();
-    let client = ApiClient::new(base_url);
-    let res = client.refresh(&refresh_token).ok()?;
-    credentials::set_stored(&email, &res.refresh_token);
-    Some(res.access_token)
+fn run_sync(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    if !config::user_has_set_sync_folder() {
+        return Err(i18n::t("sync.folder_not_set", &[]));
+    }
+    let client = app.state::<AppState>().api_client()?;
+    let root = config::get_sync_folder_path();
+    sync::ensure_sync_root(&root)?;
+    let Some(guard) = sync::lock_or_queue_sync() else {
+        log::info!("Sync requested while a cycle is running; it runs once more afterwards");
+        return Ok(serde_json::json!({ "started": false, "queued": true }));
+    };
+    sync::set_sync_status(sync::SyncStatus::Syncing);
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
+    std::thread::spawn(move || run_cycles(&app, guard, &root, client, true));
+    Ok(serde_json::json!({ "started": true }))
+}
+
+/// Run a sync cycle while holding `guard`, and one more each time a sync was requested during
+/// the previous one (`sync::lock_or_queue_sync`). The status must already be "syncing".
+fn run_cycles(app: &tauri::AppHandle, mut guard: sync::SyncGuard, root: &std::path::Path, mut client: ApiClient, manual: bool) {
+    loop {
+        if !device_allowed(app, &client) {
+            return;
+        }
+        import_auto_uploads(root);
+        let result = sync::run_sync_locked(&guard, &client, root);
+        let sync_ok = result.is_ok();
+        let last_sync_at = chrono::Utc::now().to_rfc3339();
+        finish_sync_cycle(app, &client, &result, manual);
+        if manual {
+            if let Err(e) = client.client_ping(Some(sync_ok), Some(last_sync_at)) {
+                log::warn!("client_ping failed: {}", e);
+            }
+        }
+        guard = match sync::finish_cycle(guard) {
+            Some(guard) => guard,
+            None => return,
+        };
+        client = match app.state::<AppState>().api_client() {
+            Ok(c) => c,
+            Err(e) => {
+                log::warn!("No client for the next sync cycle: {}", e);
+                return;
+            }
+        };
+        log::info!("Sync was requested during the cycle; running once more");
+        sync::set_sync_status(sync::SyncStatus::Syncing);
+        let _ = app.emit("sync-status", sync::get_sync_status_payload());
+    }
 }
 
+/// Restore onto a new or replaced machine: use `target_folder` as the sync folder, forget the
+/// previous sync state and run download-only until one cycle has completed. Returns the same
+/// confirmation payload as `set_sync_folder_path` when the folder needs confirming.
 #[tauri::command]
-fn api_me() -> Result<serde_json::Value, String> {
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let mut client = ApiClient::new(base_url);
-    client.set_access_token(Some(token));
-    let user = client.me()?;
-    Ok(serde_json::json!({
-        "email": user.email,
-        "first_name": user.first_name,
-        "last_name": user.last_name,
-        "is_admin": user.is_admin
-    }))
+fn start_restore(app: tauri::AppHandle, target_folder: String, confirmed: Option<bool>) -> Result<serde_json::Value, String> {
+    // A running cycle would save the old state over the cleared one
+    let guard = sync::try_lock_sync().ok_or("Sync already running")?;
+    let saved = set_sync_folder_path(target_folder, confirmed)?;
+    if saved["saved"] != true {
+        return Ok(saved);
+    }
+    config::clear_sync_state();
+    config::set_restore_pending(true);
+    log::info!("Restore started into {}", config::get_sync_folder_path().display());
+    drop(guard);
+    run_sync(app)?;
+    Ok(saved)
 }
 
+/// Let the local deletes held back by the last cycle (server listing looked incomplete) go
+/// ahead, and sync now.
 #[tauri::command]
-fn api_get_storage() -> Result<serde_json::Value, String> {
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let mut client = ApiClient::new(base_url);
-    client.set_access_token(Some(token));
-    let s = client.get_storage()?;
-    Ok(serde_json::json!({
-        "used_bytes": s.used_bytes,
-        "limit_bytes": s.limit_bytes,
-        "server_disk_total_bytes": s.server_disk_total_bytes,
-        "server_disk_used_bytes": s.server_disk_used_bytes,
-        "server_disk_path": s.server_disk_path
-    }))
+fn confirm_held_deletes(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    sync::confirm_held_deletes();
+    run_sync(app)
 }
 
+/// Pause background sync for `minutes` (tray "Pause for 1 hour"). Manual sync still works.
 #[tauri::command]
-fn api_change_password(current_password: String, new_password: String) -> Result<(), String> {
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let mut client = ApiClient::new(base_url);
-    client.set_access_token(Some(token));
-    client.change_password(&current_password, &new_password)
+fn pause_sync(app: tauri::AppHandle, minutes: u64) {
+    sync::pause_for(minutes);
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
 }
 
 #[tauri::command]
-fn api_list_users() -> Result<Vec<serde_json::Value>, String> {
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let mut client = ApiClient::new(base_url);
-    client.set_access_token(Some(token));
-    let users = client.list_users()?;
-    Ok(users
-        .into_iter()
-        .map(|u| {
-            serde_json::json!({
-                "email": u.email,
-                "first_name": u.first_name,
-                "last_name": u.last_name,
-                "is_admin": u.is_admin,
-                "storage_limit_bytes": u.storage_limit_bytes
-            })
-        })
-        .collect())
+fn resume_sync(app: tauri::AppHandle) {
+    sync::resume();
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
 }
 
+/// Recurring windows during which background sync does not run; "Sync now" ignores them.
 #[tauri::command]
-fn api_create_user(email: String, first_name: String, last_name: String) -> Result<serde_json::Value, String> {
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let mut client = ApiClient::new(base_url);
-    client.set_access_token(Some(token));
-    client.create_user(&email, &first_name, &last_name)
+fn get_sync_pause_windows() -> Vec<config::SyncWindow> {
+    config::get_sync_pause_windows()
+}
+
+/// Sync pause windows and notification quiet hours: `HH:MM` times and ISO days.
+fn check_time_window(w: &config::SyncWindow) -> Result<(), String> {
+    if sync::parse_hhmm(&w.start).is_none() || sync::parse_hhmm(&w.end).is_none() {
+        return Err(format!("Invalid time window {}–{} (use HH:MM)", w.start, w.end));
+    }
+    if w.days.iter().any(|d| !(1..=7).contains(d)) {
+        return Err("Days must be 1 (Monday) to 7 (Sunday)".to_string());
+    }
+    Ok(())
 }
 
 #[tauri::command]
-fn api_update_user_storage_limit(email: String, limit_bytes: Option<i64>) -> Result<serde_json::Value, String> {
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let mut client = ApiClient::new(base_url);
-    client.set_access_token(Some(token));
-    client.update_user_storage_limit(&email, limit_bytes)
+fn set_sync_pause_windows(app: tauri::AppHandle, windows: Vec<config::SyncWindow>) -> Result<(), String> {
+    windows.iter().try_for_each(check_time_window)?;
+    config::set_sync_pause_windows(windows);
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
+    Ok(())
 }
 
+/// Data for the tray menu: last sync time and bytes, pause state and recently transferred files.
 #[tauri::command]
-fn api_delete_user(email: String) -> Result<(), String> {
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let mut client = ApiClient::new(base_url);
-    client.set_access_token(Some(token));
-    client.delete_user(&email)
+fn get_tray_info() -> serde_json::Value {
+    serde_json::json!({
+        "last_sync": sync::get_last_sync(),
+        "paused_until": sync::paused_until(),
+        "paused_by_schedule_until": sync::active_pause_window().map(|w| w.end),
+        "recent_files": sync::get_recent_files(),
+    })
 }
 
+/// Most recent per-file sync events (uploads, downloads, deletes), newest first.
 #[tauri::command]
-fn open_sync_folder() -> Result<(), String> {
-    let path = config::get_sync_folder_path();
-    if !path.exists() {
-        let _ = std::fs::create_dir_all(&path);
+fn get_recent_activity(limit: Option<usize>) -> Vec<activity::ActivityEvent> {
+    activity::get_recent(limit.unwrap_or(50))
+}
+
+/// Lifetime and per-day sync totals for the statistics panel (last `days` days, default 30).
+#[tauri::command]
+fn get_sync_stats(days: Option<usize>) -> stats::SyncStats {
+    stats::get(days.unwrap_or(30))
+}
+
+/// Absolute path of a synced file given relative to the sync folder; rejects paths leaving it.
+fn synced_file_path(relative_path: &str) -> Result<std::path::PathBuf, String> {
+    let root = config::get_sync_folder_path();
+    let path = root.join(relative_path.replace('/', std::path::MAIN_SEPARATOR_STR));
+    if !path.starts_with(&root) || relative_path.split('/').any(|c| c == "..") {
+        return Err("Invalid path".to_string());
     }
-    open::that(path).map_err(|e| e.to_string())
+    Ok(path)
 }
 
+/// Open a synced file (relative to the sync folder) with its default application.
 #[tauri::command]
-fn run_sync(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
-    if !config::user_has_set_sync_folder() {
-        return Err("Sync folder not set".to_string());
+fn open_synced_file(relative_path: String) -> Result<(), String> {
+    open::that(synced_file_path(&relative_path)?).map_err(|e| e.to_string())
+}
+
+/// Open the file manager on the folder containing a synced file, with the file selected
+/// (Explorer `/select`, Finder `-R`, the FileManager1 D-Bus interface on Linux). Falls back to
+/// opening the containing folder when selecting is not supported.
+#[tauri::command]
+fn reveal_in_file_manager(relative_path: String) -> Result<(), String> {
+    let path = synced_file_path(&relative_path)?;
+    if !path.exists() {
+        return Err("File not found".to_string());
     }
-    let token = get_valid_access_token().ok_or("Not logged in")?;
-    let base_url = network::get_base_url();
-    let root = config::get_sync_folder_path();
-    if !root.exists() {
-        let _ = std::fs::create_dir_all(&root);
+    #[cfg(windows)]
+    let selected = std::process::Command::new("explorer")
+        .arg(format!("/select,{}", path.display()))
+        .spawn()
+        .is_ok();
+    #[cfg(target_os = "macos")]
+    let selected = std::process::Command::new("open")
+        .arg("-R")
+        .arg(&path)
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let selected = {
+        let uri = format!(
+            "file://{}",
+            path.to_string_lossy()
+                .split('/')
+                .map(|c| urlencoding::encode(c).into_owned())
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+        std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.FileManager1",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+                &format!("array:string:{}", uri),
+                "string:",
+            ])
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+    if selected {
+        return Ok(());
     }
-    sync::set_sync_status(sync::SyncStatus::Syncing);
-    let _ = app.emit("sync-status", sync::get_sync_status_payload());
-    std::thread::spawn(move || {
-        let mut client = ApiClient::new(base_url);
-        client.set_access_token(Some(token));
-        let result = sync::run_sync(&mut client, &root);
-        let sync_ok = result.is_ok();
-        let last_sync_at = chrono::Utc::now().to_rfc3339();
-        match &result {
-            Ok((bytes_downloaded, bytes_uploaded, warning)) => {
-                if let Some(msg) = warning {
-                    sync::set_sync_status(sync::SyncStatus::Warning(msg.clone()));
-                } else {
-                    sync::set_sync_status(sync::SyncStatus::Synced);
-                }
-                let _ = app.emit(
-                    "sync-completed",
-                    serde_json::json!({ "bytesDownloaded": bytes_downloaded, "bytesUploaded": bytes_uploaded }),
-                );
-            }
-            Err(e) => {
-                eprintln!("Brandy Box sync error: {}", e);
-                sync::set_sync_status(sync::SyncStatus::Error(e.clone()));
-            }
-        }
-        if let Err(e) = client.client_ping(Some(sync_ok), Some(last_sync_at)) {
-            log::warn!("client_ping failed: {}", e);
-        }
-        let _ = app.emit("sync-status", sync::get_sync_status_payload());
-    });
-    Ok(serde_json::json!({ "started": true }))
+    let folder = path.parent().map(|p| p.to_path_buf()).unwrap_or(path);
+    open::that(folder).map_err(|e| e.to_string())
 }
 
+/// How long quitting waits for a running sync cycle to finish its current file.
+const SHUTDOWN_WAIT: Duration = Duration::from_secs(30);
+
+/// Quit without cutting a transfer off halfway: stop syncing, wait (at most `SHUTDOWN_WAIT`)
+/// for the running cycle to finish its current file and save the sync state, flush the log,
+/// then exit. Windows are hidden first so quitting looks immediate.
 #[tauri::command]
-fn quit_app() {
+async fn quit_app(app: tauri::AppHandle) {
+    for win in app.webview_windows().values() {
+        let _ = win.hide();
+    }
+    log::info!("Quitting");
+    sync::request_shutdown();
+    let idle = tauri::async_runtime::spawn_blocking(|| sync::wait_until_idle(SHUTDOWN_WAIT)).await;
+    if !matches!(idle, Ok(true)) {
+        log::warn!("Sync cycle still running after {} s; quitting anyway", SHUTDOWN_WAIT.as_secs());
+    }
+    log::logger().flush();
+    instance_lock::release();
     std::process::exit(0);
 }
 
@@ -271,43 +1564,58 @@ const MIN_SETTINGS_WIDTH: u32 = 400;
 const MIN_SETTINGS_HEIGHT: u32 = 400;
 const TRAY_SIDE_MARGIN: i32 = 16;
 
-/// Restore or set main (settings) window position and size, then show it.
-#[tauri::command]
-fn show_main_window(app: tauri::AppHandle) {
-    if let Some(win) = app.get_webview_window("main") {
-        if let Some(geom) = config::get_settings_window_geometry() {
-            if let Some((x, y, w, h)) = parse_geometry(&geom) {
+/// Put the settings window at its saved geometry when that is still on a connected monitor
+/// (moved fully onto it if needed), otherwise near the tray (typically bottom-right) at the
+/// default size.
+fn place_main_window(win: &tauri::WebviewWindow) {
+    let work_areas: Vec<window_geometry::Rect> = win
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            let work = m.work_area();
+            (work.position.x, work.position.y, work.size.width, work.size.height)
+        })
+        .collect();
+    if let Some(saved) = config::get_settings_window_geometry().as_deref().and_then(window_geometry::parse) {
+        match window_geometry::fit_to_work_areas(saved, &work_areas) {
+            Some((x, y, w, h)) => {
                 let pos = tauri::PhysicalPosition::new(x, y);
                 let size = tauri::PhysicalSize::new(w, h);
                 if win.set_position(pos).is_ok() && win.set_size(size).is_ok() {
-                    log::debug!("Restored settings window geometry: {}", geom);
+                    if (x, y, w, h) == saved {
+                        log::debug!("Restored settings window geometry: {:?}", saved);
+                    } else {
+                        log::info!("Moved settings window from {:?} to {:?} to keep it on screen", saved, (x, y, w, h));
+                    }
                 }
+                return;
             }
-        } else {
-            // No stored position: place near tray (typically bottom-right) and ensure fully visible
-            if let Ok(Some(monitor)) = win.primary_monitor() {
-                let work = monitor.work_area();
-                let wa_x = work.position.x;
-                let wa_y = work.position.y;
-                let wa_w = work.size.width as i32;
-                let wa_h = work.size.height as i32;
-                let win_w = DEFAULT_SETTINGS_WIDTH as i32;
-                let win_h = DEFAULT_SETTINGS_HEIGHT as i32;
-                // Tray is usually bottom-right: position window there, clamped to work area
-                let x = (wa_x + wa_w - win_w - TRAY_SIDE_MARGIN).clamp(wa_x, wa_x + wa_w - win_w);
-                let y = (wa_y + wa_h - win_h - TRAY_SIDE_MARGIN).clamp(wa_y, wa_y + wa_h - win_h);
-                let _ = win.set_position(tauri::PhysicalPosition::new(x, y));
-                let _ = win.set_size(tauri::PhysicalSize::new(
-                    DEFAULT_SETTINGS_WIDTH,
-                    DEFAULT_SETTINGS_HEIGHT,
-                ));
-                log::debug!(
-                    "Positioned settings window near tray: ({}, {}), fully visible",
-                    x,
-                    y
-                );
-            }
+            None => log::info!("Saved settings window position {:?} is on no connected monitor; placing it near the tray", saved),
         }
+    }
+    if let Ok(Some(monitor)) = win.primary_monitor() {
+        let work = monitor.work_area();
+        let wa_x = work.position.x;
+        let wa_y = work.position.y;
+        let wa_w = work.size.width as i32;
+        let wa_h = work.size.height as i32;
+        let win_w = DEFAULT_SETTINGS_WIDTH as i32;
+        let win_h = DEFAULT_SETTINGS_HEIGHT as i32;
+        // Tray is usually bottom-right: position window there, clamped to work area
+        let x = (wa_x + wa_w - win_w - TRAY_SIDE_MARGIN).clamp(wa_x, (wa_x + wa_w - win_w).max(wa_x));
+        let y = (wa_y + wa_h - win_h - TRAY_SIDE_MARGIN).clamp(wa_y, (wa_y + wa_h - win_h).max(wa_y));
+        let _ = win.set_position(tauri::PhysicalPosition::new(x, y));
+        let _ = win.set_size(tauri::PhysicalSize::new(DEFAULT_SETTINGS_WIDTH, DEFAULT_SETTINGS_HEIGHT));
+        log::debug!("Positioned settings window near tray: ({}, {}), fully visible", x, y);
+    }
+}
+
+/// Restore or set main (settings) window position and size, then show it.
+#[tauri::command]
+fn show_main_window(app: tauri::AppHandle) {
+    if let Some(win) = app.get_webview_window("main") {
+        place_main_window(&win);
         let _ = win.show();
         let _ = win.unminimize();
         let _ = win.set_focus();
@@ -325,6 +1633,79 @@ fn hide_main_window(app: tauri::AppHandle) {
     }
 }
 
+const STATUS_POPOVER_LABEL: &str = "status";
+const STATUS_POPOVER_WIDTH: f64 = 320.0;
+const STATUS_POPOVER_HEIGHT: f64 = 380.0;
+
+/// Where the status popover goes: next to the tray icon when the platform reports where it is
+/// (Windows, macOS), else the bottom-right corner of the primary monitor. Always inside the work
+/// area.
+fn status_popover_position(app: &tauri::AppHandle, win: &tauri::WebviewWindow) -> Option<tauri::PhysicalPosition<i32>> {
+    let size = win.outer_size().ok()?;
+    let (w, h) = (size.width as i32, size.height as i32);
+    let tray = app.tray_by_id("main").and_then(|t| t.rect().ok().flatten());
+    let monitor = match &tray {
+        Some(rect) => {
+            let p = rect.position.to_physical::<f64>(1.0);
+            win.monitor_from_point(p.x, p.y).ok().flatten()
+        }
+        None => None,
+    }
+    .or_else(|| win.primary_monitor().ok().flatten())?;
+    let work = monitor.work_area();
+    let (wa_x, wa_y) = (work.position.x, work.position.y);
+    let (wa_w, wa_h) = (work.size.width as i32, work.size.height as i32);
+    let (x, y) = match tray {
+        Some(rect) => {
+            let scale = monitor.scale_factor();
+            let pos = rect.position.to_physical::<i32>(scale);
+            let tray_size = rect.size.to_physical::<i32>(scale);
+            let x = pos.x + tray_size.width / 2 - w / 2;
+            // Tray at the bottom (Windows, most Linux panels): above it; at the top (macOS): below
+            let y = if pos.y > wa_y + wa_h / 2 { pos.y - h - TRAY_SIDE_MARGIN / 2 } else { pos.y + tray_size.height + TRAY_SIDE_MARGIN / 2 };
+            (x, y)
+        }
+        None => (wa_x + wa_w - w - TRAY_SIDE_MARGIN, wa_y + wa_h - h - TRAY_SIDE_MARGIN),
+    };
+    Some(tauri::PhysicalPosition::new(
+        x.clamp(wa_x, (wa_x + wa_w - w).max(wa_x)),
+        y.clamp(wa_y, (wa_y + wa_h - h).max(wa_y)),
+    ))
+}
+
+/// Show the small status window (sync status, progress, recent activity) at the tray. Created
+/// on first use; hides again when it loses focus. Async because creating a window from a
+/// synchronous command deadlocks on Windows.
+#[tauri::command]
+async fn show_status_popover(app: tauri::AppHandle) -> Result<(), String> {
+    let win = match app.get_webview_window(STATUS_POPOVER_LABEL) {
+        Some(win) => win,
+        None => tauri::WebviewWindowBuilder::new(&app, STATUS_POPOVER_LABEL, tauri::WebviewUrl::App("index.html".into()))
+            .title("Brandy Box status")
+            .inner_size(STATUS_POPOVER_WIDTH, STATUS_POPOVER_HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()
+            .map_err(|e| e.to_string())?,
+    };
+    if let Some(pos) = status_popover_position(&app, &win) {
+        let _ = win.set_position(pos);
+    }
+    win.show().map_err(|e| e.to_string())?;
+    let _ = win.set_focus();
+    Ok(())
+}
+
+#[tauri::command]
+fn hide_status_popover(app: tauri::AppHandle) {
+    if let Some(win) = app.get_webview_window(STATUS_POPOVER_LABEL) {
+        let _ = win.hide();
+    }
+}
+
 /// Resize the settings window to fit content. Called from frontend when cards expand/collapse.
 #[tauri::command]
 fn fit_window_to_content(app: tauri::AppHandle, width: Option<u32>, height: Option<u32>) {
@@ -355,28 +1736,9 @@ fn fit_window_to_content(app: tauri::AppHandle, width: Option<u32>, height: Opti
     }
 }
 
-fn parse_geometry(s: &str) -> Option<(i32, i32, u32, u32)> {
-    let parts: Vec<&str> = s.split(',').map(str::trim).collect();
-    if parts.len() != 4 {
-        return None;
-    }
-    let x: i32 = parts[0].parse().ok()?;
-    let y: i32 = parts[1].parse().ok()?;
-    let w: u32 = parts[2].parse().ok()?;
-    let h: u32 = parts[3].parse().ok()?;
-    if w == 0 || h == 0 {
-        return None;
-    }
-    Some((x, y, w, h))
-}
-
 #[tauri::command]
 fn get_sync_progress() -> Option<SyncProgressPayload> {
-    sync::get_sync_progress().map(|p| SyncProgressPayload {
-        phase: p.phase,
-        current: p.current,
-        total: p.total,
-    })
+    sync::get_sync_progress().map(SyncProgressPayload::from)
 }
 
 #[tauri::command]
@@ -384,24 +1746,111 @@ fn get_sync_status() -> serde_json::Value {
     sync::get_sync_status_payload()
 }
 
-fn try_acquire_single_instance_lock() -> bool {
-    use fs2::FileExt;
+/// Started with `--minimized` / `--background` (e.g. from autostart): stay in the tray.
+fn launched_minimized() -> bool {
+    std::env::args().any(|a| a == config::MINIMIZED_ARG || a == config::BACKGROUND_ARG)
+}
+
+/// IPC command a second launch forwards to the running instance: `--sync-now` starts a sync,
+/// anything else (except autostart) shows the window. Lets OS hotkeys bind to the executable.
+fn forwarded_launch_command() -> Option<&'static str> {
+    if std::env::args().any(|a| a == config::SYNC_NOW_ARG) {
+        Some("sync-now")
+    } else if launched_minimized() {
+        None
+    } else {
+        Some("show")
+    }
+}
+
+/// Become the single running instance. Ok carries the PID of a previous instance that crashed.
+fn try_acquire_single_instance_lock() -> Result<Option<u32>, instance_lock::Blocked> {
     if std::env::var("BRANDYBOX_CONFIG_DIR").map(|s| !s.trim().is_empty()).unwrap_or(false) {
-        return true;
+        return Ok(None);
     }
-    let path = config::get_instance_lock_path();
-    if let Some(parent) = path.parent() {
-        let _ = std::fs::create_dir_all(parent);
+    instance_lock::acquire(&config::get_instance_lock_path())
+}
+
+/// Id of the tray icon created by the frontend (`TrayIcon.new({ id: "main" })`).
+const TRAY_ID: &str = "main";
+const TRAY_TOOLTIP_INTERVAL_SECS: u64 = 2;
+
+/// Keep the tray tooltip in line with sync status, progress and transfer rate. The only writer
+/// of the tooltip; rewritten every tick because a webview reload re-creates the tray icon.
+fn spawn_tray_tooltip_updater(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        loop {
+            if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                let text = format!("Brandy Box – {}", sync::status_summary());
+                let _ = tray.set_tooltip(Some(text.as_str()));
+            }
+            std::thread::sleep(std::time::Duration::from_secs(TRAY_TOOLTIP_INTERVAL_SECS));
+        }
+    });
+}
+
+/// Recompute the per-folder usage breakdown in the background, if the user has looked at it.
+fn refresh_folder_sizes(client: &ApiClient) {
+    if let Some(depth) = usage::cached_depth() {
+        let client = client.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = usage::refresh(&client, &config::get_sync_folder_path(), depth) {
+                log::debug!("Folder sizes refresh failed: {}", e);
+            }
+        });
     }
-    let f = match std::fs::OpenOptions::new().write(true).create(true).truncate(true).open(&path) {
-        Ok(f) => f,
-        Err(_) => return false,
-    };
-    if f.try_lock_exclusive().is_err() {
-        return false;
+}
+
+/// Copy new media from the auto-upload folders into the sync folder before a cycle uploads it.
+fn import_auto_uploads(root: &std::path::Path) {
+    match auto_upload::run(root) {
+        Ok(0) => {}
+        Ok(n) => log::info!("Auto-upload: {} new file(s) copied", n),
+        Err(e) => log::warn!("Auto-upload failed: {}", e),
+    }
+}
+
+/// Apply the result of a sync cycle: set the status, emit events and send notifications.
+/// `manual` is true for syncs started by the user (tray / settings "Sync now").
+fn finish_sync_cycle(
+    app: &tauri::AppHandle,
+    client: &ApiClient,
+    result: &Result<(u64, u64, Option<String>), sync::SyncError>,
+    manual: bool,
+) {
+    match result {
+        Ok((bytes_downloaded, bytes_uploaded, warning)) => {
+            let quota_warning = match client.get_storage() {
+                Ok(storage) => notify::check_quota(app, storage.used_bytes, storage.limit_bytes),
+                Err(e) => {
+                    log::debug!("Storage check after sync failed: {}", e);
+                    None
+                }
+            };
+            if warning.is_none() && quota_warning.is_some() {
+                sync::add_sync_issue(sync::SyncIssue::new(sync::SyncErrorCode::QuotaWarning, []));
+            }
+            if let Some(msg) = warning.clone().or(quota_warning) {
+                sync::set_sync_status(sync::SyncStatus::Warning(msg));
+            } else {
+                sync::set_sync_status(sync::SyncStatus::Synced);
+            }
+            refresh_folder_sizes(client);
+            let _ = app.emit(
+                "sync-completed",
+                serde_json::json!({ "bytesDownloaded": bytes_downloaded, "bytesUploaded": bytes_uploaded }),
+            );
+        }
+        Err(e) => {
+            log::error!("Sync failed: {}", e);
+            sync::set_sync_status(sync::SyncStatus::Error(e.clone()));
+            stats::record_failed_cycle();
+        }
     }
-    std::mem::forget(f);
-    true
+    device::record_sync_outcome(result.is_ok());
+    emblems::refresh();
+    notify::sync_finished(app, result, manual);
+    let _ = app.emit("sync-status", sync::get_sync_status_payload());
 }
 
 const BACKGROUND_SYNC_INTERVAL_SECS: u64 = 60;
@@ -417,85 +1866,117 @@ fn spawn_background_sync_loop(app: tauri::AppHandle) {
         (BACKGROUND_SYNC_INITIAL_DELAY_SECS, BACKGROUND_SYNC_INTERVAL_SECS)
     };
     std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(initial_delay));
+        wake::wait(std::time::Duration::from_secs(initial_delay));
         loop {
             let (status, _) = sync::get_sync_status();
+            // auth_expired / device_revoked: wait for the user to log in again
             if status != "syncing"
+                && status != "auth_expired"
+                && status != "device_revoked"
+                && sync::paused_until().is_none()
+                && sync::active_pause_window().is_none()
                 && config::user_has_set_sync_folder()
-                && get_valid_access_token().is_some()
             {
                 let root = config::get_sync_folder_path();
-                if root.exists() || std::fs::create_dir_all(&root).is_ok() {
-                    if let Some(token) = get_valid_access_token() {
-                        let base_url = network::get_base_url();
+                let root_ok = sync::ensure_sync_root(&root).map_err(|e| {
+                    // Drive not connected: show it instead of silently skipping cycles
+                    sync::set_sync_status(sync::SyncStatus::Error(e));
+                    let _ = app.emit("sync-status", sync::get_sync_status_payload());
+                });
+                if root_ok.is_ok() {
+                    // A manual sync may have started since the status check; the lock decides
+                    let client = app.state::<AppState>().api_client().map_err(|e| log::warn!("Token refresh failed: {}", e));
+                    if let Some((client, guard)) = client.ok().zip(sync::try_lock_sync()) {
                         sync::set_sync_status(sync::SyncStatus::Syncing);
                         let _ = app.emit("sync-status", sync::get_sync_status_payload());
-                        let mut client = ApiClient::new(base_url);
-                        client.set_access_token(Some(token));
-                        let result = sync::run_sync(&mut client, &root);
-                        match &result {
-                            Ok((bytes_downloaded, bytes_uploaded, warning)) => {
-                                if let Some(msg) = warning {
-                                    sync::set_sync_status(sync::SyncStatus::Warning(msg.clone()));
-                                } else {
-                                    sync::set_sync_status(sync::SyncStatus::Synced);
-                                }
-                                let _ = app.emit(
-                                    "sync-completed",
-                                    serde_json::json!({ "bytesDownloaded": bytes_downloaded, "bytesUploaded": bytes_uploaded }),
-                                );
-                            }
-                            Err(e) => {
-                                eprintln!("Brandy Box sync error: {}", e);
-                                sync::set_sync_status(sync::SyncStatus::Error(e.clone()));
-                            }
-                        }
-                        let _ = app.emit("sync-status", sync::get_sync_status_payload());
+                        run_cycles(&app, guard, &root, client, false);
                     }
                 }
             }
-            std::thread::sleep(std::time::Duration::from_secs(interval));
+            // Woken early after system resume or a network change
+            wake::wait(std::time::Duration::from_secs(interval));
         }
     });
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    if !try_acquire_single_instance_lock() {
-        eprintln!("Another instance is already running.");
-        std::process::exit(1);
+    let crashed_instance = match try_acquire_single_instance_lock() {
+        Ok(crashed) => crashed,
+        Err(blocked) => {
+            // Hand over to the running instance so the launch visibly does something
+            let Some(cmd) = forwarded_launch_command() else {
+                std::process::exit(0);
+            };
+            match ipc::send(&serde_json::json!({ "cmd": cmd })) {
+                Ok(_) => std::process::exit(0),
+                Err(e) => {
+                    instance_lock::show_error(&format!(
+                        "{}, but it does not respond ({}).\n\nEnd it in the task manager or restart the computer, then start Brandy Box again.",
+                        blocked, e
+                    ));
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+    // Before anything reads config.json, which may be encrypted
+    let metadata_key = credentials::get_metadata_key();
+    metadata_crypto::set_key(metadata_key);
+    logging::init();
+    crash::install_panic_hook();
+    log::info!("Brandy Box {} starting", env!("CARGO_PKG_VERSION"));
+    if metadata_key.is_some() {
+        log::info!("Metadata encryption on");
+    }
+    if let Some(pid) = crashed_instance {
+        log::warn!("Previous instance (process {}) did not exit cleanly", pid);
     }
+    migration::run_once();
     tauri::Builder::default()
+        .manage(AppState::default())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(|app| {
+            let _ = app.state::<AppState>().app.set(app.handle().clone());
+            api::set_http_trace(config::get_http_debug());
+            let progress_app = app.handle().clone();
+            sync::set_progress_listener(move |p| {
+                let _ = progress_app.emit("sync-progress", SyncProgressPayload::from(p.clone()));
+            });
             spawn_background_sync_loop(app.handle().clone());
+            wake::spawn_watcher();
+            if std::env::args().any(|a| a == config::SYNC_NOW_ARG) {
+                wake::trigger("launched with --sync-now");
+            }
+            spawn_tray_tooltip_updater(app.handle().clone());
+            shortcuts::register(app.handle());
+            ipc::spawn_server(app.handle().clone());
+            metrics_http::spawn_server();
             if let Some(win) = app.get_webview_window("main") {
-                if let Some(geom) = config::get_settings_window_geometry() {
-                    if let Some((x, y, w, h)) = parse_geometry(&geom) {
-                        let _ = win.set_position(tauri::PhysicalPosition::new(x, y));
-                        let _ = win.set_size(tauri::PhysicalSize::new(w, h));
-                    }
-                } else if let Ok(Some(monitor)) = win.primary_monitor() {
-                    let work = monitor.work_area();
-                    let wa_x = work.position.x;
-                    let wa_y = work.position.y;
-                    let wa_w = work.size.width as i32;
-                    let wa_h = work.size.height as i32;
-                    let win_w = DEFAULT_SETTINGS_WIDTH as i32;
-                    let win_h = DEFAULT_SETTINGS_HEIGHT as i32;
-                    let x = (wa_x + wa_w - win_w - TRAY_SIDE_MARGIN).clamp(wa_x, wa_x + wa_w - win_w);
-                    let y = (wa_y + wa_h - win_h - TRAY_SIDE_MARGIN).clamp(wa_y, wa_y + wa_h - win_h);
-                    let _ = win.set_position(tauri::PhysicalPosition::new(x, y));
-                    let _ = win.set_size(tauri::PhysicalSize::new(
-                        DEFAULT_SETTINGS_WIDTH,
-                        DEFAULT_SETTINGS_HEIGHT,
-                    ));
-                }
+                place_main_window(&win);
+            }
+            if config::repair_autostart() {
+                notify::notify(
+                    app.handle(),
+                    notify::Kind::Info,
+                    "Brandy Box",
+                    &i18n::t("notify.autostart_repaired", &[]),
+                );
+            }
+            // Interactive launches open the settings window; autostart launches stay in the tray
+            if !launched_minimized() {
+                show_main_window(app.handle().clone());
             }
             Ok(())
         })
         .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Focused(false) = event {
+                if window.label() == STATUS_POPOVER_LABEL {
+                    let _ = window.hide();
+                }
+            }
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                 if window.label() == "main" {
                     if let (Ok(pos), Ok(sz)) = (window.inner_position(), window.inner_size()) {
@@ -522,28 +2003,122 @@ pub fn run() {
             get_default_sync_folder,
             get_autostart,
             set_autostart,
+            get_start_minimized,
+            set_start_minimized,
+            get_tray_icon_theme,
+            get_language,
+            set_language,
+            set_tray_icon_theme,
+            get_sync_hidden_files,
+            set_sync_hidden_files,
+            get_max_file_size,
+            set_max_file_size,
+            allow_large_file,
+            get_delete_snapshots,
+            set_delete_snapshots,
+            open_delete_snapshots_folder,
+            get_secure_delete,
+            set_secure_delete,
+            get_download_only,
+            set_download_only,
+            get_ignore_paths,
+            add_ignore_path,
+            remove_ignore_path,
+            get_file_manager_emblems,
+            get_auto_upload,
+            set_auto_upload,
+            set_file_manager_emblems,
+            get_notification_settings,
+            set_notification_settings,
+            set_quota_warning_thresholds,
             get_base_url_mode,
             set_base_url_mode,
             get_manual_base_url,
             set_manual_base_url,
+            validate_base_url,
+            export_settings,
+            import_settings,
+            reset_app,
             login,
+            login_with_browser,
+            request_password_reset,
+            confirm_password_reset,
+            get_metadata_encryption,
+            set_metadata_encryption,
+            get_app_lock,
+            set_app_lock,
+            lock_app,
+            unlock_app,
+            app_activity,
+            login_with_app_token,
             logout,
             get_stored_email,
             get_valid_access_token,
             api_me,
             api_get_storage,
             api_change_password,
+            check_new_password,
             api_list_users,
             api_create_user,
             api_update_user_storage_limit,
+            api_bulk_create_users,
+            api_move_file,
+            api_copy_file,
+            api_request_account_export,
+            download_folder,
+            api_delete_own_account,
+            api_list_devices,
+            api_revoke_device,
+            api_update_user,
+            api_set_user_enabled,
+            api_admin_reset_password,
             api_delete_user,
+            import_legacy_client_data,
             open_sync_folder,
+            get_recent_logs,
+            open_log_folder,
+            create_diagnostic_bundle,
+            run_self_test,
+            get_app_info,
+            get_pending_crash_reports,
+            send_crash_reports,
+            discard_crash_reports,
+            get_log_level,
+            set_log_level,
+            get_http_debug,
+            set_http_debug,
+            get_metrics_port,
+            set_metrics_port,
+            get_shortcuts,
+            set_shortcuts,
             run_sync,
+            confirm_held_deletes,
+            start_restore,
+            estimate_sync,
+            get_folder_sizes,
+            find_duplicate_files,
+            search_files,
+            search_server_files,
+            get_thumbnail,
+            validate_sync_folder,
+            pick_sync_folder,
+            estimate_initial_sync,
             get_sync_progress,
             get_sync_status,
+            pause_sync,
+            get_sync_pause_windows,
+            set_sync_pause_windows,
+            resume_sync,
+            get_tray_info,
+            get_recent_activity,
+            get_sync_stats,
+            open_synced_file,
+            reveal_in_file_manager,
             quit_app,
             show_main_window,
             hide_main_window,
+            show_status_popover,
+            hide_status_popover,
             fit_window_to_content,
         ])
         .run(tauri::generate_context!())
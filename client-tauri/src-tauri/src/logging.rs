@@ -0,0 +1,166 @@
+//! File logger for the `log` facade: writes to `<config dir>/logs/brandybox.log`, rotated by size.
+//! Level comes from `BRANDYBOX_LOG` (if set) or config.json `log_level` (default "info").
+
+use crate::config;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const LOG_DIRNAME: &str = "logs";
+const LOG_FILENAME: &str = "brandybox.log";
+const LOG_LEVEL_ENV: &str = "BRANDYBOX_LOG";
+/// Rotate when the current file exceeds this size.
+const LOG_MAX_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated files kept (brandybox.1.log … brandybox.N.log).
+const LOG_KEEP_FILES: usize = 4;
+
+struct FileLogger {
+    file: Mutex<Option<File>>,
+}
+
+static LOGGER: FileLogger = FileLogger { file: Mutex::new(None) };
+
+pub fn log_dir() -> PathBuf {
+    config::get_config_dir().join(LOG_DIRNAME)
+}
+
+fn log_path() -> PathBuf {
+    log_dir().join(LOG_FILENAME)
+}
+
+fn rotated_path(n: usize) -> PathBuf {
+    log_dir().join(format!("brandybox.{}.log", n))
+}
+
+/// Existing log files, newest first.
+pub fn log_files() -> Vec<PathBuf> {
+    std::iter::once(log_path())
+        .chain((1..=LOG_KEEP_FILES).map(rotated_path))
+        .filter(|p| p.is_file())
+        .collect()
+}
+
+fn open_log_file() -> Option<File> {
+    let _ = std::fs::create_dir_all(log_dir());
+    OpenOptions::new().create(true).append(true).open(log_path()).ok()
+}
+
+/// Shift brandybox.log → .1 → .2 …, dropping the oldest.
+fn rotate() {
+    let _ = std::fs::remove_file(rotated_path(LOG_KEEP_FILES));
+    for n in (1..LOG_KEEP_FILES).rev() {
+        let _ = std::fs::rename(rotated_path(n), rotated_path(n + 1));
+    }
+    let _ = std::fs::rename(log_path(), rotated_path(1));
+}
+
+pub fn parse_level(s: &str) -> Option<log::LevelFilter> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "off" => Some(log::LevelFilter::Off),
+        "error" => Some(log::LevelFilter::Error),
+        "warn" | "warning" => Some(log::LevelFilter::Warn),
+        "info" => Some(log::LevelFilter::Info),
+        "debug" => Some(log::LevelFilter::Debug),
+        "trace" => Some(log::LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+fn configured_level() -> log::LevelFilter {
+    std::env::var(LOG_LEVEL_ENV)
+        .ok()
+        .and_then(|s| parse_level(&s))
+        .or_else(|| parse_level(&config::get_log_level()))
+        .unwrap_or(log::LevelFilter::Info)
+}
+
+impl log::Log for FileLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // Dependencies (reqwest, hyper, tao …) are only interesting at debug level and above
+        let ours = record.target().starts_with("brandybox");
+        if !ours && record.level() > log::Level::Warn && log::max_level() < log::LevelFilter::Debug {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} [{}] {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if cfg!(debug_assertions) {
+            eprint!("{}", line);
+        }
+        let mut guard = match self.file.lock() {
+            Ok(g) => g,
+            Err(_) => return,
+        };
+        let too_big = guard
+            .as_ref()
+            .and_then(|f| f.metadata().ok())
+            .map(|m| m.len() > LOG_MAX_BYTES)
+            .unwrap_or(false);
+        if too_big {
+            *guard = None;
+            rotate();
+        }
+        if guard.is_none() {
+            *guard = open_log_file();
+        }
+        if let Some(f) = guard.as_mut() {
+            let _ = f.write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut g) = self.file.lock() {
+            if let Some(f) = g.as_mut() {
+                let _ = f.flush();
+            }
+        }
+    }
+}
+
+/// Install the file logger. Safe to call once at startup.
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(configured_level());
+    }
+}
+
+/// Change the level at runtime and persist it.
+pub fn set_level(level: &str) -> Result<(), String> {
+    let filter = parse_level(level).ok_or_else(|| format!("Unknown log level: {}", level))?;
+    config::set_log_level(filter.to_string().to_ascii_lowercase());
+    log::set_max_level(filter);
+    Ok(())
+}
+
+/// Last `lines` lines across the current and most recent rotated log files, oldest first.
+pub fn recent_lines(lines: usize) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for path in log_files() {
+        if out.len() >= lines {
+            break;
+        }
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(_) => continue,
+        };
+        let file_lines: Vec<String> = BufReader::new(file).lines().map_while(Result::ok).collect();
+        let need = lines - out.len();
+        let start = file_lines.len().saturating_sub(need);
+        let mut chunk = file_lines[start..].to_vec();
+        chunk.append(&mut out);
+        out = chunk;
+    }
+    out
+}
@@ -0,0 +1,79 @@
+//! Local HTTP endpoint for monitoring, on `127.0.0.1:<metrics_port>` when that is set:
+//! `GET /metrics` in the Prometheus text format and `GET /metrics.json` as JSON. Read-only and
+//! bound to loopback; a Prometheus on another machine needs a reverse proxy in front of it.
+
+use brandybox_core::{config, metrics};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Status line, content type and body for a request line such as "GET /metrics HTTP/1.1".
+fn respond(request_line: &str) -> (&'static str, &'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    if method != "GET" {
+        return ("405 Method Not Allowed", "text/plain", "Only GET is supported\n".to_string());
+    }
+    match path.split('?').next().unwrap_or("") {
+        "/metrics" => ("200 OK", "text/plain; version=0.0.4", metrics::to_prometheus(&metrics::collect())),
+        "/metrics.json" => (
+            "200 OK",
+            "application/json",
+            serde_json::to_string(&metrics::collect()).unwrap_or_default(),
+        ),
+        _ => ("404 Not Found", "text/plain", "Try /metrics or /metrics.json\n".to_string()),
+    }
+}
+
+async fn handle(mut stream: tokio::net::TcpStream) -> std::io::Result<()> {
+    // The request line is all we need; bodies are not expected
+    let mut buf = [0u8; 2048];
+    let n = stream.read(&mut buf).await?;
+    let head = String::from_utf8_lossy(&buf[..n]);
+    let (status, content_type, body) = respond(head.lines().next().unwrap_or(""));
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn serve(port: u16) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    log::info!("Metrics endpoint listening on http://127.0.0.1:{}/metrics", port);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle(stream).await {
+                log::debug!("Metrics request failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Start the endpoint when `metrics_port` is set. Failures (e.g. port in use) are logged.
+pub fn spawn_server() {
+    let Some(port) = config::get_metrics_port() else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(port).await {
+            log::warn!("Metrics endpoint on port {} stopped: {}", port, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_metrics_paths_and_rejects_the_rest() {
+        assert_eq!(respond("GET /metrics HTTP/1.1").0, "200 OK");
+        assert_eq!(respond("GET /metrics.json HTTP/1.1").1, "application/json");
+        assert_eq!(respond("GET / HTTP/1.1").0, "404 Not Found");
+        assert_eq!(respond("POST /metrics HTTP/1.1").0, "405 Method Not Allowed");
+    }
+}
@@ -0,0 +1,61 @@
+//! One-time import of credentials, settings and sync state from the deprecated Python client.
+//! Runs automatically on first start; `import_legacy_client_data` can re-run it on demand.
+
+use crate::{config, credentials, sync};
+use serde::Serialize;
+
+const CONFIG_FILENAME: &str = "config.json";
+const SYNC_STATE_FILENAME: &str = "sync_state.json";
+
+#[derive(Serialize, Default)]
+pub struct MigrationReport {
+    /// Keyring credentials copied from the Python client's entries.
+    pub credentials: bool,
+    /// config.json copied from the Python client's config directory.
+    pub config: bool,
+    /// Number of synced paths imported from the Python client's sync_state.json.
+    pub sync_state_paths: usize,
+    /// A Python autostart entry was replaced or removed.
+    pub legacy_autostart: bool,
+}
+
+/// Import everything we can find. Never overwrites data the Tauri client already has.
+pub fn import_legacy_client_data() -> MigrationReport {
+    let mut report = MigrationReport {
+        credentials: credentials::import_legacy(),
+        ..Default::default()
+    };
+    let target_dir = config::get_config_dir();
+    for legacy_dir in config::legacy_config_dirs() {
+        let legacy_config = legacy_dir.join(CONFIG_FILENAME);
+        let target_config = target_dir.join(CONFIG_FILENAME);
+        if legacy_config.is_file() && !target_config.exists() {
+            let _ = std::fs::create_dir_all(&target_dir);
+            report.config = std::fs::copy(&legacy_config, &target_config).is_ok();
+        }
+        let legacy_state = legacy_dir.join(SYNC_STATE_FILENAME);
+        if legacy_state.is_file() && report.sync_state_paths == 0 {
+            report.sync_state_paths = sync::import_legacy_state(&legacy_state).unwrap_or(0);
+        }
+    }
+    report.legacy_autostart = config::cleanup_legacy_autostart();
+    log::info!(
+        "Legacy import: credentials={}, config={}, sync_state_paths={}, legacy_autostart={}",
+        report.credentials,
+        report.config,
+        report.sync_state_paths,
+        report.legacy_autostart
+    );
+    report
+}
+
+/// Run the import once per install (tracked in config.json). Skipped for E2E config dirs and
+/// portable installs, which must not pick up the machine-wide Python client data.
+pub fn run_once() {
+    let e2e = std::env::var("BRANDYBOX_CONFIG_DIR").map(|s| !s.trim().is_empty()).unwrap_or(false);
+    if e2e || config::is_portable() || config::get_legacy_import_done() {
+        return;
+    }
+    import_legacy_client_data();
+    config::set_legacy_import_done();
+}
@@ -0,0 +1,166 @@
+//! Desktop notifications sent from the Rust side (sync failures, large syncs, quota, login).
+//! All notifications go through `notify` so the user's toggle, quiet hours and fullscreen muting
+//! apply everywhere. They only silence notifications; sync keeps running.
+
+use crate::config;
+use crate::fullscreen;
+use crate::i18n;
+use crate::sync;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri_plugin_notification::NotificationExt;
+
+/// A manual sync that moved at least this many bytes gets a "finished" notification.
+pub const LARGE_SYNC_THRESHOLD_BYTES: u64 = 5 * 1024 * 1024;
+/// The same notification (kind + body) is not repeated within this window, so a failing
+/// background sync does not notify every minute.
+const REPEAT_SUPPRESS_SECS: u64 = 30 * 60;
+const MAX_BODY_CHARS: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Kind {
+    SyncFailed,
+    SyncCompleted,
+    QuotaNearlyFull,
+    AuthExpired,
+    DeviceRevoked,
+    Info,
+}
+
+static LAST_SENT: Mutex<Option<HashMap<(Kind, String), Instant>>> = Mutex::new(None);
+/// Highest quota threshold already announced; reset when usage drops below all thresholds.
+static QUOTA_NOTIFIED_PERCENT: Mutex<Option<u32>> = Mutex::new(None);
+
+/// The notification schedule check: inside the quiet hours (same rules as the sync pause
+/// windows) or, when enabled, while a fullscreen app is in front. `fullscreen` is only asked
+/// when it matters.
+pub fn muted_by_schedule(
+    schedule: &config::NotificationSchedule,
+    weekday: u32,
+    minute: u32,
+    fullscreen: impl FnOnce() -> bool,
+) -> bool {
+    schedule.quiet_hours.as_ref().is_some_and(|w| sync::in_sync_window(w, weekday, minute))
+        || (schedule.mute_fullscreen && fullscreen())
+}
+
+fn suppressed_by_settings() -> bool {
+    if !config::get_notifications_enabled() {
+        return true;
+    }
+    use chrono::{Datelike, Timelike};
+    let now = chrono::Local::now();
+    muted_by_schedule(
+        &config::get_notification_schedule(),
+        now.weekday().number_from_monday(),
+        now.hour() * 60 + now.minute(),
+        fullscreen::fullscreen_app_active,
+    )
+}
+
+fn recently_sent(kind: Kind, body: &str) -> bool {
+    let mut guard = match LAST_SENT.lock() {
+        Ok(g) => g,
+        Err(_) => return false,
+    };
+    let sent = guard.get_or_insert_with(HashMap::new);
+    let now = Instant::now();
+    let window = Duration::from_secs(REPEAT_SUPPRESS_SECS);
+    sent.retain(|_, at| now.duration_since(*at) < window);
+    let key = (kind, body.to_string());
+    if sent.contains_key(&key) {
+        return true;
+    }
+    sent.insert(key, now);
+    false
+}
+
+/// Show a desktop notification unless notifications are disabled, quiet hours are active, a
+/// fullscreen app is in front, or the identical notification was shown recently.
+pub fn notify(app: &tauri::AppHandle, kind: Kind, title: &str, body: &str) {
+    if suppressed_by_settings() {
+        log::debug!("Notification suppressed ({:?}): {}", kind, title);
+        return;
+    }
+    let body: String = body.chars().take(MAX_BODY_CHARS).collect();
+    if recently_sent(kind, &body) {
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(&body).show() {
+        log::warn!("Could not show notification: {}", e);
+    }
+}
+
+/// Notify about the outcome of a sync cycle. Completions are only announced for large manual syncs.
+pub fn sync_finished(app: &tauri::AppHandle, result: &Result<(u64, u64, Option<String>), sync::SyncError>, manual: bool) {
+    match result {
+        Ok((bytes_downloaded, bytes_uploaded, _)) => {
+            if manual && bytes_downloaded + bytes_uploaded >= LARGE_SYNC_THRESHOLD_BYTES {
+                notify(app, Kind::SyncCompleted, "Brandy Box", &i18n::t("notify.sync_finished", &[]));
+            }
+        }
+        Err(e) => notify(app, Kind::SyncFailed, &i18n::t("notify.sync_failed", &[]), &e.message),
+    }
+}
+
+/// Highest threshold (percent) reached by `used / limit`, if any.
+pub fn quota_threshold_reached(used_bytes: i64, limit_bytes: i64, thresholds: &[u32]) -> Option<u32> {
+    if limit_bytes <= 0 {
+        return None;
+    }
+    let percent = used_bytes as f64 * 100.0 / limit_bytes as f64;
+    thresholds.iter().copied().filter(|t| percent >= *t as f64).max()
+}
+
+/// Check usage against the configured thresholds. Notifies once per newly crossed threshold and
+/// returns a warning for the sync status while usage stays above the lowest threshold.
+pub fn check_quota(app: &tauri::AppHandle, used_bytes: i64, limit_bytes: Option<i64>) -> Option<String> {
+    let limit = limit_bytes.filter(|l| *l > 0)?;
+    let reached = quota_threshold_reached(used_bytes, limit, &config::get_quota_warning_thresholds());
+    let newly_crossed = match QUOTA_NOTIFIED_PERCENT.lock() {
+        Ok(mut notified) => {
+            let crossed = reached.is_some() && reached > *notified;
+            *notified = reached;
+            crossed
+        }
+        Err(_) => false,
+    };
+    reached?;
+    let percent = used_bytes as f64 * 100.0 / limit as f64;
+    let message = i18n::t("quota.warning", &[("percent", &format!("{:.0}", percent))]);
+    if newly_crossed {
+        notify(app, Kind::QuotaNearlyFull, &i18n::t("notify.quota_title", &[]), &message);
+    }
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_mutes_in_quiet_hours_and_for_fullscreen_apps() {
+        let schedule = config::NotificationSchedule {
+            quiet_hours: Some(config::SyncWindow { start: "22:00".into(), end: "07:30".into(), days: vec![] }),
+            mute_fullscreen: false,
+        };
+        let at = |h: u32, m: u32| h * 60 + m;
+        assert!(muted_by_schedule(&schedule, 3, at(23, 0), || false));
+        assert!(muted_by_schedule(&schedule, 4, at(7, 29), || false));
+        assert!(!muted_by_schedule(&schedule, 4, at(7, 30), || false));
+        assert!(!muted_by_schedule(&schedule, 4, at(12, 0), || true));
+
+        let fullscreen_only = config::NotificationSchedule::default();
+        assert!(muted_by_schedule(&fullscreen_only, 4, at(12, 0), || true));
+        assert!(!muted_by_schedule(&fullscreen_only, 4, at(12, 0), || false));
+    }
+
+    #[test]
+    fn quota_threshold_picks_highest_reached() {
+        assert_eq!(quota_threshold_reached(79, 100, &[80, 95]), None);
+        assert_eq!(quota_threshold_reached(80, 100, &[80, 95]), Some(80));
+        assert_eq!(quota_threshold_reached(99, 100, &[80, 95]), Some(95));
+        assert_eq!(quota_threshold_reached(50, 0, &[80, 95]), None);
+    }
+}
@@ -0,0 +1,111 @@
+//! Browser-based (SSO / OpenID Connect) login for the desktop client.
+//!
+//! Opens the system browser at the server's OAuth start endpoint and receives the
+//! redirect on a loopback listener (`http://127.0.0.1:<port>/callback`). The server
+//! redirects with a one-time `exchange` id that is redeemed via `/api/auth/oauth/complete`.
+//! A random `state` ties the callback to this attempt, and a PKCE verifier (RFC 7636, S256)
+//! ensures only this process can redeem the exchange id.
+
+use crate::api::{ApiClient, LoginResponse};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+/// How long we wait for the user to finish signing in in the browser.
+const LOGIN_TIMEOUT_SECS: u64 = 300;
+const CALLBACK_PATH: &str = "/callback";
+
+const SUCCESS_PAGE: &str = "<html><body style=\"font-family:sans-serif\"><h2>Brandy Box</h2>\
+<p>Sign-in complete. You can close this window and return to Brandy Box.</p></body></html>";
+const FAILURE_PAGE: &str = "<html><body style=\"font-family:sans-serif\"><h2>Brandy Box</h2>\
+<p>Sign-in failed. Please return to Brandy Box and try again.</p></body></html>";
+
+/// Run the browser login flow and return the token pair.
+/// Blocks until the callback arrives or `LOGIN_TIMEOUT_SECS` elapses.
+pub fn login_with_browser(client: &ApiClient) -> Result<LoginResponse, String> {
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| format!("loopback listener: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let redirect = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+    let state = random_token()?;
+    let verifier = random_token()?;
+    let challenge = URL_SAFE_NO_PAD.encode(ring::digest::digest(&ring::digest::SHA256, verifier.as_bytes()));
+    let start_url = format!(
+        "{}/api/auth/google/start?client_redirect={}&client_state={}&code_challenge={}",
+        client.base_url.trim_end_matches('/'),
+        urlencoding::encode(&redirect),
+        state,
+        challenge
+    );
+    log::info!("Browser login: waiting for callback on {}", redirect);
+    open::that(&start_url).map_err(|e| format!("Could not open browser: {}", e))?;
+
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let deadline = Instant::now() + Duration::from_secs(LOGIN_TIMEOUT_SECS);
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Some(result) = handle_callback(stream, &state) {
+                    let exchange = result?;
+                    return client.oauth_complete(&exchange, Some(&verifier));
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                if Instant::now() > deadline {
+                    return Err("Browser sign-in timed out".to_string());
+                }
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            Err(e) => return Err(format!("loopback listener: {}", e)),
+        }
+    }
+}
+
+/// 32 random bytes, base64url without padding (valid as OAuth state and PKCE verifier).
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| "No system random source".to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Read one HTTP request from the browser. Returns None for unrelated requests (e.g. favicon),
+/// otherwise the exchange id or the error reported by the server. A callback whose `state`
+/// does not match this attempt is rejected.
+fn handle_callback(mut stream: TcpStream, expected_state: &str) -> Option<Result<String, String>> {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line).ok()?;
+    // "GET /callback?exchange=...&error=... HTTP/1.1"
+    let target = request_line.split_whitespace().nth(1)?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != CALLBACK_PATH {
+        let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        return None;
+    }
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|kv| kv.split_once('='))
+            .find(|(k, _)| *k == name)
+            .and_then(|(_, v)| urlencoding::decode(v).ok().map(|v| v.into_owned()))
+            .filter(|v| !v.is_empty())
+    };
+    let state_ok = param("state").is_some_and(|s| s == expected_state);
+    let result = match (param("exchange"), param("error")) {
+        _ if !state_ok => Err("Sign-in failed: state mismatch in callback".to_string()),
+        (Some(exchange), _) => Ok(exchange),
+        (None, Some(err)) => Err(format!("Sign-in failed: {}", err)),
+        (None, None) => Err("Sign-in failed: no exchange in callback".to_string()),
+    };
+    let page = if result.is_ok() { SUCCESS_PAGE } else { FAILURE_PAGE };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        page.len(),
+        page
+    );
+    Some(result)
+}
@@ -0,0 +1,75 @@
+//! Global keyboard shortcuts: one hotkey shows the settings window, another starts a sync.
+//! Registered at startup from the `shortcut_show_window` / `shortcut_sync_now` settings and again
+//! whenever they change. A hotkey another app already holds is logged and skipped.
+
+use brandybox_core::config;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Parse an accelerator such as "CommandOrControl+Alt+B"; empty means off.
+pub fn parse(accelerator: Option<&str>) -> Result<Option<Shortcut>, String> {
+    match accelerator.map(str::trim).filter(|s| !s.is_empty()) {
+        None => Ok(None),
+        Some(s) => s
+            .parse::<Shortcut>()
+            .map(Some)
+            .map_err(|e| format!("Invalid shortcut \"{}\": {}", s, e)),
+    }
+}
+
+/// Replace the registered hotkeys with the ones in the config.
+pub fn register(app: &tauri::AppHandle) {
+    let manager = app.global_shortcut();
+    if let Err(e) = manager.unregister_all() {
+        log::warn!("Could not unregister keyboard shortcuts: {}", e);
+    }
+    let show = config::get_shortcut_show_window();
+    let sync = config::get_shortcut_sync_now();
+    for (accelerator, action) in [(show, Action::ShowWindow), (sync, Action::SyncNow)] {
+        let shortcut = match parse(accelerator.as_deref()) {
+            Ok(Some(s)) => s,
+            Ok(None) => continue,
+            Err(e) => {
+                log::warn!("{}", e);
+                continue;
+            }
+        };
+        let registered = manager.on_shortcut(shortcut, move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                action.run(app);
+            }
+        });
+        match registered {
+            Ok(()) => log::info!("Keyboard shortcut {} registered", accelerator.unwrap_or_default()),
+            Err(e) => log::warn!("Keyboard shortcut {} not registered: {}", accelerator.unwrap_or_default(), e),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    ShowWindow,
+    SyncNow,
+}
+
+impl Action {
+    fn run(self, app: &tauri::AppHandle) {
+        match self {
+            Action::ShowWindow => crate::show_main_window(app.clone()),
+            Action::SyncNow => crate::wake::trigger("keyboard shortcut"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_default_accelerators_and_rejects_garbage() {
+        assert!(parse(Some(config::DEFAULT_SHORTCUT_SHOW_WINDOW)).unwrap().is_some());
+        assert!(parse(Some(config::DEFAULT_SHORTCUT_SYNC_NOW)).unwrap().is_some());
+        assert!(parse(None).unwrap().is_none());
+        assert!(parse(Some("  ")).unwrap().is_none());
+        assert!(parse(Some("Ctrl+Banana")).is_err());
+    }
+}
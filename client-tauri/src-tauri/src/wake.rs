@@ -0,0 +1,93 @@
+//! Wake the background sync loop early: right after the system resumes from sleep or the
+//! network comes back / changes (Wi-Fi reconnect, LAN ↔ mobile hotspot), instead of waiting
+//! for the next interval.
+//!
+//! Detection is portable and needs no OS event hooks: sleep suspends this watcher's thread, so
+//! a wall-clock jump much larger than the tick means the machine was asleep; a change of the
+//! local address the OS would route Internet traffic from means the network changed.
+
+use brandybox_core::network;
+use std::net::IpAddr;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, SystemTime};
+
+const WATCH_TICK: Duration = Duration::from_secs(5);
+/// Wall-clock time beyond the tick that counts as a suspend (covers scheduler hiccups).
+const RESUME_SLACK: Duration = Duration::from_secs(30);
+/// Give Wi-Fi / DHCP a moment after resume or reconnect before syncing.
+const SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+static WAKE: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+
+/// Wake the background loop now.
+pub fn trigger(reason: &str) {
+    log::info!("Sync triggered early: {}", reason);
+    let (lock, cvar) = &WAKE;
+    if let Ok(mut pending) = lock.lock() {
+        *pending = true;
+        cvar.notify_all();
+    }
+}
+
+/// Sleep for `timeout` or until `trigger` is called, whichever comes first.
+pub fn wait(timeout: Duration) {
+    let (lock, cvar) = &WAKE;
+    let Ok(guard) = lock.lock() else {
+        std::thread::sleep(timeout);
+        return;
+    };
+    if let Ok((mut pending, _)) = cvar.wait_timeout_while(guard, timeout, |pending| !*pending) {
+        *pending = false;
+    }
+}
+
+/// True when `wall_elapsed` for one tick of `tick` means the machine was suspended in between.
+fn resumed_from_sleep(wall_elapsed: Duration, tick: Duration) -> bool {
+    wall_elapsed > tick + RESUME_SLACK
+}
+
+/// True when the network came up or switched (not when it went away).
+fn network_changed(before: Option<IpAddr>, now: Option<IpAddr>) -> bool {
+    now.is_some() && now != before
+}
+
+/// Start the watcher thread that calls `trigger` on resume and network changes.
+pub fn spawn_watcher() {
+    std::thread::spawn(|| {
+        let mut last_addr = network::local_route_addr();
+        let mut last_wall = SystemTime::now();
+        loop {
+            std::thread::sleep(WATCH_TICK);
+            let now_wall = SystemTime::now();
+            let wall_elapsed = now_wall.duration_since(last_wall).unwrap_or_default();
+            last_wall = now_wall;
+            let addr = network::local_route_addr();
+            let changed = network_changed(last_addr, addr);
+            last_addr = addr;
+            if resumed_from_sleep(wall_elapsed, WATCH_TICK) {
+                std::thread::sleep(SETTLE_DELAY);
+                trigger("system resumed from sleep");
+            } else if changed {
+                std::thread::sleep(SETTLE_DELAY);
+                trigger("network changed");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_resume_and_network_changes() {
+        assert!(!resumed_from_sleep(Duration::from_secs(6), WATCH_TICK));
+        assert!(resumed_from_sleep(Duration::from_secs(3600), WATCH_TICK));
+        let lan: Option<IpAddr> = "192.168.0.20".parse().ok();
+        let hotspot: Option<IpAddr> = "172.20.10.2".parse().ok();
+        assert!(network_changed(None, lan));
+        assert!(network_changed(lan, hotspot));
+        assert!(!network_changed(lan, lan));
+        assert!(!network_changed(lan, None));
+    }
+}
@@ -0,0 +1,75 @@
+//! Saved settings window geometry ("x,y,w,h" in physical pixels) and checking it against the
+//! monitors that are connected now: after undocking a laptop the saved position can be on a
+//! monitor that is gone.
+
+/// x, y, width, height in physical pixels.
+pub type Rect = (i32, i32, u32, u32);
+
+/// Parse "x,y,w,h"; None for anything else or a zero size.
+pub fn parse(s: &str) -> Option<Rect> {
+    let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+    if parts.len() != 4 {
+        return None;
+    }
+    let x: i32 = parts[0].parse().ok()?;
+    let y: i32 = parts[1].parse().ok()?;
+    let w: u32 = parts[2].parse().ok()?;
+    let h: u32 = parts[3].parse().ok()?;
+    if w == 0 || h == 0 {
+        return None;
+    }
+    Some((x, y, w, h))
+}
+
+fn overlap(a: Rect, b: Rect) -> i64 {
+    let w = (a.0 + a.2 as i32).min(b.0 + b.2 as i32) - a.0.max(b.0);
+    let h = (a.1 + a.3 as i32).min(b.1 + b.3 as i32) - a.1.max(b.1);
+    if w <= 0 || h <= 0 {
+        0
+    } else {
+        w as i64 * h as i64
+    }
+}
+
+/// `rect` moved (and shrunk if needed) to lie fully inside the work area it overlaps most. None
+/// when it overlaps none of them, so the caller places the window anew. Without any known work
+/// area the rect is kept as it is.
+pub fn fit_to_work_areas(rect: Rect, work_areas: &[Rect]) -> Option<Rect> {
+    if work_areas.is_empty() {
+        return Some(rect);
+    }
+    let area = work_areas
+        .iter()
+        .copied()
+        .map(|a| (overlap(rect, a), a))
+        .filter(|(o, _)| *o > 0)
+        .max_by_key(|(o, _)| *o)?
+        .1;
+    let w = rect.2.min(area.2);
+    let h = rect.3.min(area.3);
+    let x = rect.0.clamp(area.0, area.0 + (area.2 - w) as i32);
+    let y = rect.1.clamp(area.1, area.1 + (area.3 - h) as i32);
+    Some((x, y, w, h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_rect_is_kept_clamped_or_dropped() {
+        let laptop = (0, 0, 1920, 1040);
+        let external = (1920, 0, 2560, 1400);
+        assert_eq!(parse(" 10, 20, 600,720"), Some((10, 20, 600, 720)));
+        assert_eq!(parse("10,20,0,720"), None);
+        // Fully visible: unchanged
+        assert_eq!(fit_to_work_areas((100, 100, 600, 720), &[laptop, external]), Some((100, 100, 600, 720)));
+        // Mostly on the external monitor: pulled fully onto it
+        assert_eq!(fit_to_work_areas((4200, 900, 600, 720), &[laptop, external]), Some((3880, 680, 600, 720)));
+        // External monitor gone after undocking: not visible at all
+        assert_eq!(fit_to_work_areas((3000, 200, 600, 720), &[laptop]), None);
+        // Larger than the work area: shrunk to it
+        assert_eq!(fit_to_work_areas((-50, 10, 2000, 1200), &[laptop]), Some((0, 0, 1920, 1040)));
+        assert_eq!(fit_to_work_areas((3000, 200, 600, 720), &[]), Some((3000, 200, 600, 720)));
+    }
+}
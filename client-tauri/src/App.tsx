@@ -4,7 +4,6 @@ import { getCurrentWindow } from "@tauri-apps/api/window";
 import { defaultWindowIcon } from "@tauri-apps/api/app";
 import { resolveResource } from "@tauri-apps/api/path";
 import { listen } from "@tauri-apps/api/event";
-import { isPermissionGranted, requestPermission, sendNotification } from "@tauri-apps/plugin-notification";
 import { TrayIcon } from "@tauri-apps/api/tray";
 import { Menu } from "@tauri-apps/api/menu";
 import { Box, ThemeProvider, createTheme, CssBaseline } from "@mui/material";
@@ -12,21 +11,131 @@ import Login from "./Login";
 import Settings from "./Settings";
 import TitleBar from "./TitleBar";
 
-type SyncStatus = "idle" | "syncing" | "synced" | "warning" | "error";
+type SyncStatus = "idle" | "syncing" | "synced" | "warning" | "error" | "auth_expired" | "device_revoked";
 
 interface SyncStatusPayload {
   status: SyncStatus;
   message?: string | null;
+  paused_until?: string | null;
 }
 
-const SYNC_NOTIFY_THRESHOLD_BYTES = 5 * 1024 * 1024; // 5 MB
+interface TrayInfo {
+  last_sync: { at: string; bytes_downloaded: number; bytes_uploaded: number } | null;
+  paused_until: string | null;
+  recent_files: string[];
+}
+
+const PAUSE_MINUTES = 60;
+
+type TrayTheme = "light" | "dark";
+
+/** Taskbar theme the tray icon has to stand out on: the override from Settings, else the Windows
+ * taskbar theme, else the system theme. */
+async function currentTrayTheme(): Promise<TrayTheme> {
+  const info = await invoke<{ setting: string; taskbar: TrayTheme | null }>("get_tray_icon_theme").catch(() => null);
+  if (info?.setting === "light" || info?.setting === "dark") return info.setting;
+  if (info?.taskbar) return info.taskbar;
+  return (await getCurrentWindow().theme().catch(() => null)) ?? "light";
+}
+
+/** Status icons in the variant for `theme` (icon_synced_dark.png has a light outline for dark
+ * taskbars, _light a dark one), falling back to the plain icons. */
+async function resolveStateIcons(theme: TrayTheme, fallback: string) {
+  const resolve = async (name: string) =>
+    (await resolveResource(`icons/icon_${name}_${theme}.png`).catch(() => null)) ??
+    (await resolveResource(`icons/icon_${name}.png`).catch(() => null)) ??
+    fallback;
+  return { blue: await resolve("synced"), yellow: await resolve("syncing"), red: await resolve("error") };
+}
+
+function formatBytes(n: number): string {
+  if (n < 1024) return `${n} B`;
+  if (n < 1024 * 1024) return `${(n / 1024).toFixed(1)} KB`;
+  if (n < 1024 * 1024 * 1024) return `${(n / (1024 * 1024)).toFixed(1)} MB`;
+  return `${(n / (1024 * 1024 * 1024)).toFixed(1)} GB`;
+}
 
-interface SyncCompletedPayload {
-  bytesDownloaded: number;
-  bytesUploaded: number;
+/** Tray menu: status header, actions, recent files submenu. Rebuilt after each sync. */
+async function buildTrayMenu(): Promise<Menu> {
+  const info = await invoke<TrayInfo>("get_tray_info").catch(() => null);
+  const last = info?.last_sync;
+  const header = last
+    ? `Last sync ${new Date(last.at).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })} · ↓${formatBytes(last.bytes_downloaded)} ↑${formatBytes(last.bytes_uploaded)}`
+    : "Not synced yet";
+  const paused = info?.paused_until ?? null;
+  const recent = info?.recent_files ?? [];
+  return Menu.new({
+    items: [
+      { id: "header", text: header, enabled: false },
+      {
+        id: "status",
+        text: "Show status",
+        action: async () => {
+          await invoke("show_status_popover").catch((e) => console.error("show_status_popover failed", e));
+        },
+      },
+      { item: "Separator" },
+      {
+        id: "sync_now",
+        text: "Sync now",
+        action: async () => {
+          await invoke("run_sync").catch(() => {});
+        },
+      },
+      paused
+        ? {
+            id: "resume",
+            text: `Resume sync (paused until ${new Date(paused).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })})`,
+            action: async () => {
+              await invoke("resume_sync").catch(() => {});
+            },
+          }
+        : {
+            id: "pause",
+            text: "Pause for 1 hour",
+            action: async () => {
+              await invoke("pause_sync", { minutes: PAUSE_MINUTES }).catch(() => {});
+            },
+          },
+      {
+        id: "open_folder",
+        text: "Open folder",
+        action: async () => {
+          await invoke("open_sync_folder").catch(() => {});
+        },
+      },
+      {
+        id: "recent",
+        text: "Recent files",
+        enabled: recent.length > 0,
+        items: recent.map((path, i) => ({
+          id: `recent_${i}`,
+          text: path,
+          action: async () => {
+            await invoke("open_synced_file", { relativePath: path }).catch(() => {});
+          },
+        })),
+      },
+      {
+        id: "settings",
+        text: "Settings",
+        action: async () => {
+          await invoke("show_main_window").catch((e) => console.error("show_main_window failed", e));
+        },
+      },
+      { item: "Separator" },
+      {
+        id: "quit",
+        text: "Quit",
+        action: async () => {
+          await invoke("quit_app");
+        },
+      },
+    ],
+  });
 }
 
-const theme = createTheme({
+export const theme = createTheme({
   palette: {
     mode: "light",
     primary: { main: "#1a73e8" },
@@ -64,6 +173,14 @@ export default function App() {
     refreshAuth();
   }, [refreshAuth]);
 
+  // Refresh token expired or revoked, or this device was revoked: drop back to the login view.
+  useEffect(() => {
+    const unlisteners = ["auth-expired", "device-revoked"].map((event) => listen(event, () => refreshAuth()));
+    return () => {
+      unlisteners.forEach((p) => p.then((fn) => fn()));
+    };
+  }, [refreshAuth]);
+
   // In dev mode: auto-show window (tray doesn't work on Wayland, so show window for development).
   // In production: window only via tray "Settings" click.
   useEffect(() => {
@@ -74,32 +191,21 @@ export default function App() {
 
   /** Preloaded paths: blue = idle/synced, yellow = syncing, red = error. Set at tray init. */
   const stateIconsRef = useRef<{ blue: string; yellow: string; red: string } | null>(null);
+  /** Last status shown, to redraw the icon when the theme changes. */
+  const lastStatusRef = useRef<SyncStatusPayload>({ status: "idle" });
 
   const updateTrayFromStatus = useCallback(
     (trayIcon: TrayIcon | null, payload: SyncStatusPayload) => {
       if (!trayIcon) return;
+      lastStatusRef.current = payload;
       const icons = stateIconsRef.current;
       if (!icons) return;
-      const { status, message } = payload;
-      const tooltip =
-        status === "error" && message
-          ? `Brandy Box – Error: ${message.slice(0, 80)}`
-          : status === "warning" && message
-            ? `Brandy Box – ${message.slice(0, 80)}`
-            : status === "syncing"
-              ? "Brandy Box – Syncing…"
-              : "Brandy Box";
-      try {
-        trayIcon.setTooltip(tooltip);
-      } catch {
-        try {
-          trayIcon.setTitle(tooltip);
-        } catch (_) {}
-      }
+      // The tooltip is written only from Rust (progress, rate, last sync); only the icon is set here.
+      const { status } = payload;
       let path =
         status === "syncing" || status === "warning"
           ? icons.yellow
-          : status === "error"
+          : status === "error" || status === "auth_expired" || status === "device_revoked"
             ? icons.red
             : icons.blue;
       if (path) {
@@ -130,52 +236,15 @@ export default function App() {
     let cancelled = false;
     (async () => {
       try {
-        const menu = await Menu.new({
-          items: [
-            {
-              id: "settings",
-              text: "Settings",
-              action: async () => {
-                await invoke("show_main_window").catch((e) => console.error("show_main_window failed", e));
-              },
-            },
-            {
-              id: "open_folder",
-              text: "Open folder",
-              action: async () => {
-                await invoke("open_sync_folder").catch(() => {});
-              },
-            },
-            {
-              id: "sync_now",
-              text: "Sync now",
-              action: async () => {
-                await invoke("run_sync").catch(() => {});
-              },
-            },
-            { item: "Separator" },
-            {
-              id: "quit",
-              text: "Quit",
-              action: async () => {
-                await invoke("quit_app");
-              },
-            },
-          ],
-        });
+        const menu = await buildTrayMenu();
         if (cancelled) return;
         const fallback = await resolveResource("icons/32x32.png").catch(() => null);
         const defaultIcon = fallback ?? (await defaultWindowIcon().catch(() => null)) ?? undefined;
-        const blue =
-          (await resolveResource("icons/icon_synced.png").catch(() => null)) ?? fallback ?? "";
-        const yellow =
-          (await resolveResource("icons/icon_syncing.png").catch(() => null)) ?? fallback ?? "";
-        const red =
-          (await resolveResource("icons/icon_error.png").catch(() => null)) ?? fallback ?? "";
-        stateIconsRef.current = { blue, yellow, red };
+        const icons = await resolveStateIcons(await currentTrayTheme(), fallback ?? "");
+        stateIconsRef.current = icons;
         const trayIcon = await TrayIcon.new({
-          icon: blue || defaultIcon,
-          tooltip: "Brandy Box",
+          id: "main",
+          icon: icons.blue || defaultIcon,
           menu,
           menuOnLeftClick: true,
         });
@@ -206,23 +275,10 @@ export default function App() {
     const unlistenPromise = listen<SyncStatusPayload>("sync-status", (event) => {
       const payload = event.payload;
       updateTrayFromStatus(tray, payload);
-      if (payload.status === "error" && payload.message) {
-        const message = payload.message;
-        (async () => {
-          try {
-            let granted = await isPermissionGranted();
-            if (!granted) {
-              const perm = await requestPermission();
-              granted = perm === "granted";
-            }
-            if (granted) {
-              await sendNotification({
-                title: "Brandy Box – Sync failed",
-                body: message.slice(0, 200),
-              });
-            }
-          } catch (_) {}
-        })();
+      if (payload.status !== "syncing") {
+        buildTrayMenu()
+          .then((menu) => tray.setMenu(menu))
+          .catch(() => {});
       }
     });
     return () => {
@@ -230,31 +286,19 @@ export default function App() {
     };
   }, [tray, updateTrayFromStatus]);
 
+  // Taskbar theme switched, or the tray icon override changed in Settings: swap the icon variant
   useEffect(() => {
-    const unlistenPromise = listen<SyncCompletedPayload>("sync-completed", (event) => {
-      const { bytesDownloaded = 0, bytesUploaded = 0 } = event.payload;
-      const total = bytesDownloaded + bytesUploaded;
-      if (total < SYNC_NOTIFY_THRESHOLD_BYTES) return;
-      (async () => {
-        try {
-          let granted = await isPermissionGranted();
-          if (!granted) {
-            const perm = await requestPermission();
-            granted = perm === "granted";
-          }
-          if (granted) {
-            await sendNotification({
-              title: "Brandy Box",
-              body: "Sync finished successfully.",
-            });
-          }
-        } catch (_) {}
-      })();
-    });
+    if (!tray) return;
+    const reload = async () => {
+      const fallback = await resolveResource("icons/32x32.png").catch(() => null);
+      stateIconsRef.current = await resolveStateIcons(await currentTrayTheme(), fallback ?? "");
+      updateTrayFromStatus(tray, lastStatusRef.current);
+    };
+    const unlisteners = [getCurrentWindow().onThemeChanged(() => reload()), listen("tray-theme-changed", () => reload())];
     return () => {
-      unlistenPromise.then((fn) => fn());
+      unlisteners.forEach((p) => p.then((fn) => fn()));
     };
-  }, []);
+  }, [tray, updateTrayFromStatus]);
 
   const handleLoginSuccess = useCallback(() => {
     refreshAuth();
@@ -22,6 +22,50 @@ export default function Login({ onSuccess, onCancel }: LoginProps) {
   const [password, setPassword] = useState("");
   const [error, setError] = useState("");
   const [loading, setLoading] = useState(false);
+  const [resetMessage, setResetMessage] = useState("");
+  const [resetting, setResetting] = useState(false);
+  const [resetCodeSent, setResetCodeSent] = useState(false);
+  const [resetCode, setResetCode] = useState("");
+  const [resetPwd, setResetPwd] = useState("");
+  const [resetConfirmPwd, setResetConfirmPwd] = useState("");
+
+  const handleForgotPassword = async () => {
+    setError("");
+    setResetMessage("");
+    setResetting(true);
+    try {
+      setResetMessage(await invoke<string>("request_password_reset", { email: email.trim() }));
+      setResetCodeSent(true);
+    } catch (err) {
+      setError(formatUserFacingError(err));
+    } finally {
+      setResetting(false);
+    }
+  };
+
+  const handleConfirmReset = async () => {
+    setError("");
+    setResetting(true);
+    try {
+      setResetMessage(
+        await invoke<string>("confirm_password_reset", {
+          email: email.trim(),
+          token: resetCode.trim(),
+          newPassword: resetPwd,
+          confirmPassword: resetConfirmPwd,
+        })
+      );
+      setResetCodeSent(false);
+      setResetCode("");
+      setResetPwd("");
+      setResetConfirmPwd("");
+      setPassword("");
+    } catch (err) {
+      setError(formatUserFacingError(err));
+    } finally {
+      setResetting(false);
+    }
+  };
 
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
@@ -62,6 +106,11 @@ export default function Login({ onSuccess, onCancel }: LoginProps) {
                 {error}
               </Alert>
             )}
+            {resetMessage && (
+              <Alert severity="info" sx={{ mb: 2 }} onClose={() => setResetMessage("")}>
+                {resetMessage}
+              </Alert>
+            )}
             <TextField
               fullWidth
               label="Email"
@@ -99,6 +148,48 @@ export default function Login({ onSuccess, onCancel }: LoginProps) {
                 </Button>
               )}
             </Box>
+            <Button size="small" sx={{ mt: 1 }} onClick={handleForgotPassword} disabled={resetting || loading}>
+              {resetting ? "Sending…" : "Forgot password?"}
+            </Button>
+            {resetCodeSent && (
+              <Box sx={{ mt: 1 }}>
+                <TextField
+                  fullWidth
+                  label="Reset code from the email"
+                  value={resetCode}
+                  onChange={(e) => setResetCode(e.target.value)}
+                  margin="dense"
+                  autoComplete="one-time-code"
+                />
+                <TextField
+                  fullWidth
+                  label="New password"
+                  type="password"
+                  value={resetPwd}
+                  onChange={(e) => setResetPwd(e.target.value)}
+                  margin="dense"
+                  autoComplete="new-password"
+                />
+                <TextField
+                  fullWidth
+                  label="Repeat new password"
+                  type="password"
+                  value={resetConfirmPwd}
+                  onChange={(e) => setResetConfirmPwd(e.target.value)}
+                  margin="dense"
+                  autoComplete="new-password"
+                />
+                <Button
+                  variant="outlined"
+                  fullWidth
+                  sx={{ mt: 1 }}
+                  onClick={handleConfirmReset}
+                  disabled={resetting || !resetCode.trim() || !resetPwd}
+                >
+                  Set new password
+                </Button>
+              </Box>
+            )}
           </form>
         </CardContent>
       </Card>
@@ -27,12 +27,55 @@ import {
   CircularProgress,
   LinearProgress,
   Collapse,
+  Chip,
+  Link,
 } from "@mui/material";
 import ExpandMore from "@mui/icons-material/ExpandMore";
 import ExpandLess from "@mui/icons-material/ExpandLess";
 import Refresh from "@mui/icons-material/Refresh";
 import { formatUserFacingError } from "./errors";
 
+interface DeviceInfo {
+  device_id: string;
+  name: string;
+  platform: string;
+  client_version: string;
+  last_seen_at: string;
+  last_sync_at: string | null;
+  last_sync_ok: boolean | null;
+  revoked: boolean;
+  current: boolean;
+}
+
+interface ImportRowResult {
+  line: number;
+  email: string;
+  ok: boolean;
+  error: string | null;
+  temp_password: string | null;
+}
+
+interface PasswordCheck {
+  ok: boolean;
+  /** 0 (very weak) to 4 (very strong). */
+  score: number;
+  issues: Array<{ code: string; message: string }>;
+}
+
+interface MetadataEncryption {
+  enabled: boolean;
+  /** Encrypted files were found but the keyring has no key for them. */
+  key_missing: boolean;
+}
+
+interface AppLockStatus {
+  /** Idle minutes before locking; null when the lock is off. */
+  minutes: number | null;
+  locked: boolean;
+}
+
+const PASSWORD_STRENGTH = ["Very weak", "Weak", "Fair", "Strong", "Very strong"];
+
 function formatBytes(n: number): string {
   if (n < 0) return "0 B";
   if (n >= 1024 ** 4) return `${(n / 1024 ** 4).toFixed(1)} TiB`;
@@ -42,15 +85,129 @@ function formatBytes(n: number): string {
   return `${n} B`;
 }
 
+/** "5 minutes ago", "3 hours ago", … for an ISO timestamp. */
+function formatAgo(iso: string): string {
+  const minutes = Math.max(0, Math.round((Date.now() - new Date(iso).getTime()) / 60000));
+  if (minutes < 1) return "just now";
+  if (minutes < 60) return `${minutes} minute${minutes === 1 ? "" : "s"} ago`;
+  const hours = Math.round(minutes / 60);
+  if (hours < 48) return `${hours} hour${hours === 1 ? "" : "s"} ago`;
+  return `${Math.round(hours / 24)} days ago`;
+}
+
+interface NotificationSettings {
+  enabled: boolean;
+  schedule: {
+    /** Same format as the sync pause windows: "HH:MM" times, ISO days (empty: every day). */
+    quiet_hours: { start: string; end: string; days: number[] } | null;
+    mute_fullscreen: boolean;
+  };
+}
+
+interface SyncProgress {
+  phase: string;
+  current: number;
+  total: number;
+  detail?: string | null;
+}
+
+type SyncErrorCode =
+  | "auth_expired"
+  | "device_revoked"
+  | "network"
+  | "server"
+  | "quota_exceeded"
+  | "quota_warning"
+  | "sync_folder_unavailable"
+  | "file_too_large"
+  | "file_size_limit"
+  | "permission_denied"
+  | "held_deletes"
+  | "unsaved_deletes"
+  | "failed_deletes"
+  | "unsafe_paths"
+  | "skipped_downloads"
+  | "skipped_uploads"
+  | "unknown";
+
+interface SyncStatusInfo {
+  status: string;
+  message?: string | null;
+  error_code?: SyncErrorCode | null;
+  details?: { code: SyncErrorCode; count: number; sample_paths: string[] }[];
+  last_successful_sync?: string | null;
+  overdue_hours?: number | null;
+  volume_warning?: string | null;
+  held_local_deletes?: number;
+  restore_pending?: boolean;
+}
+
+interface SelfTestReport {
+  passed: boolean;
+  checks: { name: string; outcome: "passed" | "failed" | "skipped"; detail: string; duration_ms: number }[];
+}
+
+interface DeleteSnapshotsInfo {
+  enabled: boolean;
+  limit_bytes: number;
+  snapshots: { name: string; files: number; bytes: number }[];
+}
+
+interface DownloadOnlyInfo {
+  enabled: boolean;
+  forced: boolean;
+}
+
 interface SettingsProps {
   email: string | null;
   onLogout: () => void;
 }
 
+interface SyncTotals {
+  bytes_uploaded: number;
+  bytes_downloaded: number;
+  files_uploaded: number;
+  files_downloaded: number;
+  cycles: number;
+  errors: number;
+}
+
+interface SyncEstimate {
+  upload_files: number;
+  upload_bytes: number;
+  download_files: number;
+  download_bytes: number;
+  download_unknown_size_files: number;
+  delete_remote_files: number;
+  delete_local_files: number;
+}
+
+interface SyncStats {
+  since: string | null;
+  lifetime: SyncTotals;
+  days: Record<string, SyncTotals>;
+}
+
 export default function Settings({ email, onLogout }: SettingsProps) {
   const [appVersion, setAppVersion] = useState<string | null>(null);
+  const [appInfo, setAppInfo] = useState<{ commit: string; server_api_version: string | null } | null>(null);
   const [syncFolder, setSyncFolder] = useState("");
   const [autostart, setAutostart] = useState(false);
+  const [trayIconTheme, setTrayIconTheme] = useState<string>("system");
+  const [language, setLanguage] = useState<string>("system");
+  const [shortcutShow, setShortcutShow] = useState("");
+  const [shortcutSync, setShortcutSync] = useState("");
+  const [shortcutError, setShortcutError] = useState("");
+  const [notifications, setNotifications] = useState<NotificationSettings | null>(null);
+  const [syncHidden, setSyncHidden] = useState(true);
+  const [secureDeletePasses, setSecureDeletePasses] = useState("");
+  const [metadataEncryption, setMetadataEncryption] = useState<MetadataEncryption | null>(null);
+  const [metadataEncryptionError, setMetadataEncryptionError] = useState("");
+  const [deleteSnapshots, setDeleteSnapshots] = useState<DeleteSnapshotsInfo | null>(null);
+  const [downloadOnly, setDownloadOnly] = useState<DownloadOnlyInfo | null>(null);
+  const [syncStatusInfo, setSyncStatusInfo] = useState<SyncStatusInfo | null>(null);
+  const [maxFileSizeMb, setMaxFileSizeMb] = useState("");
+  const [ignorePaths, setIgnorePaths] = useState<string[]>([]);
   const [baseUrlMode, setBaseUrlMode] = useState<"automatic" | "manual">("automatic");
   const [manualBaseUrl, setManualBaseUrl] = useState("");
   const [storage, setStorage] = useState<{
@@ -62,21 +219,55 @@ export default function Settings({ email, onLogout }: SettingsProps) {
   } | null>(null);
   const [baseUrl, setBaseUrl] = useState("");
   const [adminOpen, setAdminOpen] = useState(false);
-  const [users, setUsers] = useState<Array<{ email: string; first_name?: string; last_name?: string; is_admin?: boolean; storage_limit_bytes?: number | null }>>([]);
+  const [devicesOpen, setDevicesOpen] = useState(false);
+  const [devices, setDevices] = useState<DeviceInfo[]>([]);
+  const [devicesError, setDevicesError] = useState<string | null>(null);
+  const [users, setUsers] = useState<Array<{ email: string; first_name?: string; last_name?: string; is_admin?: boolean; is_enabled?: boolean; used_bytes?: number; storage_limit_bytes?: number | null }>>([]);
   const [changePwdOpen, setChangePwdOpen] = useState(false);
   const [currentPwd, setCurrentPwd] = useState("");
   const [newPwd, setNewPwd] = useState("");
+  const [confirmPwd, setConfirmPwd] = useState("");
+  const [pwdCheck, setPwdCheck] = useState<PasswordCheck | null>(null);
+  const [appLock, setAppLock] = useState<AppLockStatus>({ minutes: null, locked: false });
+  const [appLockMinutes, setAppLockMinutes] = useState("");
+  const [unlockPwd, setUnlockPwd] = useState("");
+  const [unlockError, setUnlockError] = useState("");
   const [changePwdError, setChangePwdError] = useState("");
+  const [accountMessage, setAccountMessage] = useState<string | null>(null);
+  const [exporting, setExporting] = useState(false);
+  const [deleteAccountOpen, setDeleteAccountOpen] = useState(false);
+  const [deletePwd, setDeletePwd] = useState("");
+  const [deleteConfirmText, setDeleteConfirmText] = useState("");
+  const [deleteAccountError, setDeleteAccountError] = useState("");
   const [createUserOpen, setCreateUserOpen] = useState(false);
   const [createUserError, setCreateUserError] = useState("");
   const [adminActionError, setAdminActionError] = useState<string | null>(null);
+  const [adminActionMessage, setAdminActionMessage] = useState<string | null>(null);
+  const [editUser, setEditUser] = useState<{ email: string; first_name: string; last_name: string; is_admin: boolean } | null>(null);
+  const [editUserError, setEditUserError] = useState("");
+  const [importOpen, setImportOpen] = useState(false);
+  const [importPath, setImportPath] = useState("");
+  const [importing, setImporting] = useState(false);
+  const [importProgress, setImportProgress] = useState<{ current: number; total: number; email: string } | null>(null);
+  const [importReport, setImportReport] = useState<ImportRowResult[] | null>(null);
+  const [importError, setImportError] = useState("");
   const [newUserEmail, setNewUserEmail] = useState("");
   const [newUserFirst, setNewUserFirst] = useState("");
   const [newUserLast, setNewUserLast] = useState("");
   const [syncFolderError, setSyncFolderError] = useState<string | null>(null);
-  const [syncProgress, setSyncProgress] = useState<{ phase: string; current: number; total: number } | null>(null);
+  const [syncFolderConfirm, setSyncFolderConfirm] = useState<string[]>([]);
+  const [restoreOpen, setRestoreOpen] = useState(false);
+  const [restoreConfirm, setRestoreConfirm] = useState<string[]>([]);
+  const [restoreError, setRestoreError] = useState<string | null>(null);
+  const [syncProgress, setSyncProgress] = useState<SyncProgress | null>(null);
   const [syncing, setSyncing] = useState(false);
   const [syncError, setSyncError] = useState<string | null>(null);
+  const [syncStats, setSyncStats] = useState<SyncStats | null>(null);
+  const [diagnosticMessage, setDiagnosticMessage] = useState<string | null>(null);
+  const [selfTest, setSelfTest] = useState<SelfTestReport | null>(null);
+  const [selfTestRunning, setSelfTestRunning] = useState(false);
+  const [pendingCrashes, setPendingCrashes] = useState(0);
+  const [syncEstimate, setSyncEstimate] = useState<string | null>(null);
   const contentRef = useRef<HTMLDivElement>(null);
 
   const fitWindowToContent = useCallback(() => {
@@ -99,11 +290,66 @@ export default function Settings({ email, onLogout }: SettingsProps) {
     };
   }, [adminOpen, storage, fitWindowToContent]);
 
+  useEffect(() => {
+    if (!newPwd) {
+      setPwdCheck(null);
+      return;
+    }
+    invoke<PasswordCheck>("check_new_password", {
+      currentPassword: currentPwd,
+      newPassword: newPwd,
+      confirmPassword: confirmPwd || null,
+    })
+      .then(setPwdCheck)
+      .catch(() => setPwdCheck(null));
+  }, [currentPwd, newPwd, confirmPwd]);
+
+  useEffect(() => {
+    const refresh = () =>
+      invoke<AppLockStatus>("get_app_lock")
+        .then(setAppLock)
+        .catch(() => {});
+    refresh();
+    invoke<AppLockStatus>("get_app_lock")
+      .then((s) => setAppLockMinutes(s.minutes ? String(s.minutes) : ""))
+      .catch(() => {});
+    let lastReport = 0;
+    const onActivity = () => {
+      // The idle timer only needs minute precision
+      if (Date.now() - lastReport < 30_000) return;
+      lastReport = Date.now();
+      invoke("app_activity").catch(() => {});
+    };
+    window.addEventListener("pointerdown", onActivity);
+    window.addEventListener("keydown", onActivity);
+    const timer = window.setInterval(refresh, 30_000);
+    return () => {
+      window.removeEventListener("pointerdown", onActivity);
+      window.removeEventListener("keydown", onActivity);
+      window.clearInterval(timer);
+    };
+  }, []);
+
+  useEffect(() => {
+    if (appLock.locked) {
+      setDevicesOpen(false);
+      setAdminOpen(false);
+      setChangePwdOpen(false);
+    }
+  }, [appLock.locked]);
+
+  useEffect(() => {
+    invoke<unknown[]>("get_pending_crash_reports")
+      .then((reports) => setPendingCrashes(reports.length))
+      .catch(() => {});
+  }, []);
+
   const loadSettings = async () => {
     try {
-      const [folder, start, mode, manual, url, stor] = await Promise.all([
+      const [folder, start, hidden, mode, manual, url, stor, stats] = await Promise.all([
         invoke<string>("get_sync_folder_path"),
         invoke<boolean>("get_autostart"),
+        invoke<boolean>("get_sync_hidden_files"),
         invoke<string>("get_base_url_mode"),
         invoke<string>("get_manual_base_url"),
         invoke<string>("get_base_url"),
@@ -114,13 +360,39 @@ export default function Settings({ email, onLogout }: SettingsProps) {
           server_disk_total_bytes?: number | null;
           server_disk_path?: string | null;
         }>("api_get_storage").catch(() => null),
+        invoke<SyncStats>("get_sync_stats", { days: 7 }).catch(() => null),
       ]);
       setSyncFolder(folder);
       setAutostart(start);
+      setSyncHidden(hidden);
+      invoke<{ limit_bytes: number | null }>("get_max_file_size")
+        .then((m) => setMaxFileSizeMb(m.limit_bytes ? String(Math.round(m.limit_bytes / (1024 * 1024))) : ""))
+        .catch(() => {});
+      invoke<string[]>("get_ignore_paths").then(setIgnorePaths).catch(() => {});
+      invoke<{ passes: number }>("get_secure_delete")
+        .then((s) => setSecureDeletePasses(s.passes ? String(s.passes) : ""))
+        .catch(() => {});
+      invoke<MetadataEncryption>("get_metadata_encryption").then(setMetadataEncryption).catch(() => {});
+      invoke<DeleteSnapshotsInfo>("get_delete_snapshots").then(setDeleteSnapshots).catch(() => {});
+      invoke<DownloadOnlyInfo>("get_download_only").then(setDownloadOnly).catch(() => {});
+      invoke<NotificationSettings>("get_notification_settings").then(setNotifications).catch(() => {});
+      invoke<{ setting: string }>("get_language")
+        .then((l) => setLanguage(l.setting))
+        .catch(() => {});
+      invoke<{ show_window: string | null; sync_now: string | null }>("get_shortcuts")
+        .then((k) => {
+          setShortcutShow(k.show_window ?? "");
+          setShortcutSync(k.sync_now ?? "");
+        })
+        .catch(() => {});
+      invoke<{ setting: string }>("get_tray_icon_theme")
+        .then((t) => setTrayIconTheme(t.setting))
+        .catch(() => {});
       setBaseUrlMode(mode as "automatic" | "manual");
       setManualBaseUrl(manual);
       setBaseUrl(url);
       setStorage(stor);
+      setSyncStats(stats);
     } catch {
       // ignore
     }
@@ -141,11 +413,15 @@ export default function Settings({ email, onLogout }: SettingsProps) {
 
   useEffect(() => {
     getVersion().then(setAppVersion).catch(() => setAppVersion(null));
+    invoke<{ commit: string; server_api_version: string | null }>("get_app_info")
+      .then(setAppInfo)
+      .catch(() => setAppInfo(null));
   }, []);
 
   useEffect(() => {
-    invoke<{ status: string; message?: string | null }>("get_sync_status")
+    invoke<SyncStatusInfo>("get_sync_status")
       .then((s) => {
+        setSyncStatusInfo(s);
         if (s.status === "error" && s.message) setSyncError(s.message);
       })
       .catch(() => {});
@@ -155,9 +431,33 @@ export default function Settings({ email, onLogout }: SettingsProps) {
     if (adminOpen) loadUsers();
   }, [adminOpen]);
 
+  const loadDevices = async () => {
+    try {
+      setDevices(await invoke<DeviceInfo[]>("api_list_devices"));
+      setDevicesError(null);
+    } catch (e) {
+      setDevicesError(formatUserFacingError(e));
+    }
+  };
+
   useEffect(() => {
-    const unlistenPromise = listen<{ status: string; message?: string | null }>("sync-status", (event) => {
+    if (devicesOpen) loadDevices();
+  }, [devicesOpen]);
+
+  const handleRevokeDevice = async (d: DeviceInfo) => {
+    if (!confirm(`Revoke "${d.name}"? It will be signed out and stop syncing the next time it connects.`)) return;
+    try {
+      await invoke("api_revoke_device", { deviceId: d.device_id });
+      loadDevices();
+    } catch (e) {
+      setDevicesError(formatUserFacingError(e));
+    }
+  };
+
+  useEffect(() => {
+    const unlistenPromise = listen<SyncStatusInfo>("sync-status", (event) => {
       const { status, message } = event.payload;
+      setSyncStatusInfo(event.payload);
       if (status === "synced" || status === "error") {
         setSyncing(false);
         setSyncProgress(null);
@@ -170,19 +470,119 @@ export default function Settings({ email, onLogout }: SettingsProps) {
     };
   }, []);
 
+  useEffect(() => {
+    const unlistenPromise = listen<{ current: number; total: number; email: string }>("bulk-import-progress", (event) => {
+      setImportProgress(event.payload);
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+
   useEffect(() => {
     if (!syncing) return;
-    const interval = setInterval(async () => {
-      try {
-        const p = await invoke<{ phase: string; current: number; total: number } | null>("get_sync_progress");
-        if (p) setSyncProgress(p);
-      } catch {
-        // ignore
-      }
-    }, 500);
-    return () => clearInterval(interval);
+    invoke<SyncProgress | null>("get_sync_progress")
+      .then((p) => p && setSyncProgress(p))
+      .catch(() => {});
+    const unlistenPromise = listen<SyncProgress>("sync-progress", (event) => setSyncProgress(event.payload));
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
   }, [syncing]);
 
+  const handleSyncHidden = async (_: unknown, checked: boolean) => {
+    await invoke("set_sync_hidden_files", { enabled: checked });
+    setSyncHidden(checked);
+  };
+
+  const handleAppLockMinutes = async () => {
+    const minutes = parseInt(appLockMinutes, 10);
+    try {
+      const status = await invoke<AppLockStatus>("set_app_lock", {
+        minutes: Number.isFinite(minutes) && minutes > 0 ? minutes : null,
+      });
+      setAppLock(status);
+      setAppLockMinutes(status.minutes ? String(status.minutes) : "");
+    } catch (e) {
+      setAccountMessage(formatUserFacingError(e));
+    }
+  };
+
+  const handleUnlock = async () => {
+    setUnlockError("");
+    try {
+      setAppLock(await invoke<AppLockStatus>("unlock_app", { password: unlockPwd }));
+      setUnlockPwd("");
+    } catch (e) {
+      setUnlockError(formatUserFacingError(e));
+    }
+  };
+
+  const handleMaxFileSize = async () => {
+    const mb = parseInt(maxFileSizeMb, 10);
+    const limitBytes = Number.isFinite(mb) && mb > 0 ? mb * 1024 * 1024 : null;
+    await invoke("set_max_file_size", { limitBytes });
+    setMaxFileSizeMb(limitBytes ? String(mb) : "");
+  };
+
+  const handleMetadataEncryption = async (_: unknown, checked: boolean) => {
+    setMetadataEncryptionError("");
+    try {
+      setMetadataEncryption(await invoke<MetadataEncryption>("set_metadata_encryption", { enabled: checked }));
+    } catch (e) {
+      setMetadataEncryptionError(formatUserFacingError(e));
+    }
+  };
+
+  const handleSecureDelete = async () => {
+    const passes = parseInt(secureDeletePasses, 10);
+    await invoke("set_secure_delete", { passes: Number.isFinite(passes) && passes > 0 ? passes : 0 });
+    const s = await invoke<{ passes: number }>("get_secure_delete");
+    setSecureDeletePasses(s.passes ? String(s.passes) : "");
+  };
+
+  const handleDeleteSnapshots = async (_: unknown, checked: boolean) => {
+    await invoke("set_delete_snapshots", { enabled: checked, limitBytes: null });
+    setDeleteSnapshots(await invoke<DeleteSnapshotsInfo>("get_delete_snapshots"));
+  };
+
+  const handleDownloadOnly = async (_: unknown, checked: boolean) => {
+    await invoke("set_download_only", { enabled: checked });
+    setDownloadOnly(await invoke<DownloadOnlyInfo>("get_download_only"));
+  };
+
+  const handleUnignore = async (path: string) => {
+    await invoke("remove_ignore_path", { relativePath: path });
+    setIgnorePaths((paths) => paths.filter((p) => p !== path));
+  };
+
+  const saveNotifications = async (next: NotificationSettings) => {
+    setNotifications(next);
+    await invoke("set_notification_settings", {
+      enabled: next.enabled,
+      schedule: next.schedule,
+    }).catch((e) => console.error(e));
+  };
+
+  const handleLanguage = async (value: string) => {
+    await invoke("set_language", { language: value });
+    setLanguage(value);
+  };
+
+  const handleShortcuts = async () => {
+    try {
+      await invoke("set_shortcuts", { showWindow: shortcutShow, syncNow: shortcutSync });
+      setShortcutError("");
+    } catch (e) {
+      setShortcutError(formatUserFacingError(e));
+    }
+  };
+
+  const handleTrayIconTheme = async (theme: string) => {
+    await invoke("set_tray_icon_theme", { theme });
+    setTrayIconTheme(theme);
+  };
+
   const handleAutostart = async (_: unknown, checked: boolean) => {
     await invoke("set_autostart", { enabled: checked });
     setAutostart(checked);
@@ -204,22 +604,86 @@ export default function Settings({ email, onLogout }: SettingsProps) {
   const handleChangePassword = async () => {
     setChangePwdError("");
     try {
-      await invoke("api_change_password", { currentPassword: currentPwd, newPassword: newPwd });
+      await invoke("api_change_password", { currentPassword: currentPwd, newPassword: newPwd, confirmPassword: confirmPwd });
       setChangePwdOpen(false);
       setCurrentPwd("");
       setNewPwd("");
+      setConfirmPwd("");
     } catch (e) {
       setChangePwdError(formatUserFacingError(e));
     }
   };
 
+  const handleExportAccount = async () => {
+    setExporting(true);
+    setAccountMessage(null);
+    try {
+      const path = await invoke<string>("api_request_account_export");
+      setAccountMessage(`Your data was saved to ${path}`);
+    } catch (e) {
+      setAccountMessage(formatUserFacingError(e));
+    } finally {
+      setExporting(false);
+    }
+  };
+
+  const saveSyncFolder = async (folder: string) => {
+    setSyncFolderError(null);
+    try {
+      const res = await invoke<{ saved: boolean; confirm: string[] }>("set_sync_folder_path", { folder });
+      if (!res.saved) setSyncFolderConfirm(res.confirm);
+    } catch (e) {
+      setSyncFolderError(formatUserFacingError(e));
+      console.error(e);
+    }
+  };
+
+  const handleBrowseSyncFolder = async () => {
+    setSyncFolderError(null);
+    try {
+      const picked = await invoke<{
+        path: string;
+        check: { problems: string[] };
+        risks: { rejected: string[] };
+      } | null>("pick_sync_folder");
+      if (!picked) return;
+      const problems = [...picked.risks.rejected, ...picked.check.problems];
+      if (problems.length > 0) {
+        setSyncFolderError(problems.join("; "));
+        return;
+      }
+      setSyncFolder(picked.path);
+      await saveSyncFolder(picked.path);
+    } catch (e) {
+      setSyncFolderError(formatUserFacingError(e));
+      console.error(e);
+    }
+  };
+
+  const cancelSyncFolderChange = () => {
+    setSyncFolderConfirm([]);
+    invoke<string>("get_sync_folder_path").then(setSyncFolder).catch(() => {});
+  };
+
+  const handleDeleteAccount = async () => {
+    setDeleteAccountError("");
+    try {
+      await invoke("api_delete_own_account", { password: deletePwd });
+      setDeleteAccountOpen(false);
+      onLogout();
+    } catch (e) {
+      setDeleteAccountError(formatUserFacingError(e));
+      console.error(e);
+    }
+  };
+
   const handleCreateUser = async () => {
     setCreateUserError("");
     try {
       await invoke("api_create_user", {
         email: newUserEmail,
-        first_name: newUserFirst,
-        last_name: newUserLast,
+        firstName: newUserFirst,
+        lastName: newUserLast,
       });
       setCreateUserOpen(false);
       setNewUserEmail("");
@@ -233,6 +697,41 @@ export default function Settings({ email, onLogout }: SettingsProps) {
     }
   };
 
+  const handleBulkImport = async () => {
+    setImportError("");
+    setImportReport(null);
+    setImportProgress(null);
+    setImporting(true);
+    try {
+      const report = await invoke<ImportRowResult[]>("api_bulk_create_users", { csvPath: importPath.trim() });
+      setImportReport(report);
+      loadUsers();
+    } catch (e) {
+      setImportError(formatUserFacingError(e));
+      console.error(e);
+    } finally {
+      setImporting(false);
+    }
+  };
+
+  const handleUpdateUser = async () => {
+    if (!editUser) return;
+    setEditUserError("");
+    try {
+      await invoke("api_update_user", {
+        email: editUser.email,
+        firstName: editUser.first_name,
+        lastName: editUser.last_name,
+        isAdmin: editUser.is_admin,
+      });
+      setEditUser(null);
+      loadUsers();
+    } catch (e) {
+      setEditUserError(formatUserFacingError(e));
+      console.error(e);
+    }
+  };
+
   const handleDeleteUser = async (userEmail: string) => {
     if (!confirm(`Delete user ${userEmail}?`)) return;
     setAdminActionError(null);
@@ -245,14 +744,45 @@ export default function Settings({ email, onLogout }: SettingsProps) {
     }
   };
 
-  const handleSyncNow = async () => {
+  const handleSetUserEnabled = async (userEmail: string, enabled: boolean) => {
+    if (!enabled && !confirm(`Suspend ${userEmail}? They cannot log in until re-enabled; files are kept.`)) return;
+    setAdminActionError(null);
+    try {
+      await invoke("api_set_user_enabled", { email: userEmail, enabled });
+      loadUsers();
+    } catch (e) {
+      setAdminActionError(formatUserFacingError(e));
+      console.error(e);
+    }
+  };
+
+  const handleResetPassword = async (userEmail: string) => {
+    if (!confirm(`Reset the password of ${userEmail}? The current password stops working.`)) return;
+    setAdminActionError(null);
+    setAdminActionMessage(null);
+    try {
+      const res = await invoke<{ temp_password?: string | null }>("api_admin_reset_password", { email: userEmail });
+      setAdminActionMessage(
+        res.temp_password
+          ? `New temporary password for ${userEmail}: ${res.temp_password}`
+          : `A new password was sent to ${userEmail}.`
+      );
+    } catch (e) {
+      setAdminActionError(formatUserFacingError(e));
+      console.error(e);
+    }
+  };
+
+  const startSync = async (command: "run_sync" | "confirm_held_deletes") => {
     setSyncing(true);
     setSyncError(null);
     setSyncProgress({ phase: "Starting…", current: 0, total: 0 });
     try {
-      await invoke<{ started?: boolean }>("run_sync");
+      await invoke<{ started?: boolean }>(command);
       // Sync runs in background; sync-status event will set syncing false and update error
     } catch (e) {
+      // A background cycle is already running: its sync-status events finish the spinner
+      if (String(e).includes("already running")) return;
       setSyncing(false);
       setSyncProgress(null);
       setSyncError(formatUserFacingError(e));
@@ -260,6 +790,30 @@ export default function Settings({ email, onLogout }: SettingsProps) {
     }
   };
 
+  const handleSyncNow = () => startSync("run_sync");
+
+  const handleStartRestore = async () => {
+    setRestoreError(null);
+    try {
+      const res = await invoke<{ saved: boolean; confirm: string[] }>("start_restore", {
+        targetFolder: syncFolder,
+        confirmed: restoreConfirm.length > 0,
+      });
+      if (!res.saved) {
+        setRestoreConfirm(res.confirm);
+        return;
+      }
+      setRestoreOpen(false);
+      setRestoreConfirm([]);
+      setSyncing(true);
+      setSyncError(null);
+      setSyncProgress({ phase: "Starting…", current: 0, total: 0 });
+    } catch (e) {
+      setRestoreError(formatUserFacingError(e));
+      console.error(e);
+    }
+  };
+
   return (
     <Box ref={contentRef} sx={{ p: 2, maxWidth: 560, mx: "auto" }}>
       <Typography variant="h6" fontWeight={700} sx={{ mb: 2 }}>
@@ -368,19 +922,129 @@ export default function Settings({ email, onLogout }: SettingsProps) {
                 )}
               </Box>
             )}
-          <Box sx={{ mt: 1 }}>
-            <Button size="small" onClick={() => setChangePwdOpen(true)}>
-              Change password
-            </Button>
-            <Button size="small" color="error" onClick={onLogout} sx={{ ml: 1 }}>
-              Log out
-            </Button>
-          </Box>
+          {appLock.locked ? (
+            <Box sx={{ mt: 1 }}>
+              <Typography variant="body2" color="text.secondary">
+                Account settings, devices and user management are locked.
+              </Typography>
+              {unlockError && <Alert severity="error" sx={{ mt: 1 }}>{unlockError}</Alert>}
+              <Box sx={{ display: "flex", gap: 1, mt: 1, alignItems: "center" }}>
+                <TextField
+                  size="small"
+                  type="password"
+                  label="Password"
+                  value={unlockPwd}
+                  onChange={(e) => setUnlockPwd(e.target.value)}
+                  onKeyDown={(e) => e.key === "Enter" && handleUnlock()}
+                />
+                <Button variant="contained" size="small" onClick={handleUnlock} disabled={!unlockPwd}>
+                  Unlock
+                </Button>
+              </Box>
+            </Box>
+          ) : (
+            <>
+              <Box sx={{ mt: 1 }}>
+                <Button size="small" onClick={() => setChangePwdOpen(true)}>
+                  Change password
+                </Button>
+                <Button size="small" color="error" onClick={onLogout} sx={{ ml: 1 }}>
+                  Log out
+                </Button>
+                <Button size="small" sx={{ ml: 1 }} onClick={() => invoke<AppLockStatus>("lock_app").then(setAppLock)}>
+                  Lock now
+                </Button>
+              </Box>
+              <TextField
+                size="small"
+                type="number"
+                label="Lock after idle minutes"
+                placeholder="Off"
+                value={appLockMinutes}
+                onChange={(e) => setAppLockMinutes(e.target.value)}
+                onBlur={handleAppLockMinutes}
+                sx={{ mt: 1 }}
+              />
+              <Box sx={{ mt: 0.5 }}>
+                <Button size="small" onClick={handleExportAccount} disabled={exporting}>
+                  {exporting ? "Exporting…" : "Export my data"}
+                </Button>
+                <Button
+                  size="small"
+                  color="error"
+                  sx={{ ml: 1 }}
+                  onClick={() => {
+                    setDeletePwd("");
+                    setDeleteConfirmText("");
+                    setDeleteAccountError("");
+                    setDeleteAccountOpen(true);
+                  }}
+                >
+                  Delete account
+                </Button>
+              </Box>
+            </>
+          )}
+          {accountMessage && (
+            <Typography variant="caption" display="block" color="text.secondary" sx={{ mt: 0.5 }}>
+              {accountMessage}
+            </Typography>
+          )}
           {appVersion != null && (
             <Typography variant="caption" color="text.disabled" display="block" sx={{ mt: 1.5 }}>
               Client version {appVersion}
+              {appInfo && appInfo.commit !== "unknown" && ` (${appInfo.commit})`}
+              {appInfo?.server_api_version && ` · Server ${appInfo.server_api_version}`}
+            </Typography>
+          )}
+          <Button size="small" sx={{ mt: 0.5, px: 0 }} onClick={() => invoke("open_log_folder").catch(() => {})}>
+            Open log folder
+          </Button>
+          <Button
+            size="small"
+            sx={{ mt: 0.5, ml: 1 }}
+            onClick={async () => {
+              try {
+                const path = await invoke<string>("create_diagnostic_bundle");
+                setDiagnosticMessage(`Saved to ${path}`);
+              } catch (e) {
+                setDiagnosticMessage(formatUserFacingError(e));
+              }
+            }}
+          >
+            Create diagnostic bundle
+          </Button>
+          <Button
+            size="small"
+            sx={{ mt: 0.5, ml: 1 }}
+            disabled={selfTestRunning}
+            onClick={async () => {
+              setSelfTestRunning(true);
+              try {
+                setSelfTest(await invoke<SelfTestReport>("run_self_test"));
+              } catch (e) {
+                setDiagnosticMessage(formatUserFacingError(e));
+              } finally {
+                setSelfTestRunning(false);
+              }
+            }}
+          >
+            {selfTestRunning ? "Testing…" : "Run self-test"}
+          </Button>
+          {diagnosticMessage && (
+            <Typography variant="caption" color="text.secondary" display="block">
+              {diagnosticMessage}
             </Typography>
           )}
+          {selfTest && (
+            <Alert severity={selfTest.passed ? "success" : "error"} onClose={() => setSelfTest(null)} sx={{ mt: 1 }}>
+              {selfTest.checks.map((c) => (
+                <Typography key={c.name} variant="caption" display="block">
+                  {c.outcome === "passed" ? "✓" : c.outcome === "failed" ? "✗" : "–"} {c.name}: {c.detail}
+                </Typography>
+              ))}
+            </Alert>
+          )}
         </CardContent>
       </Card>
 
@@ -398,23 +1062,65 @@ export default function Settings({ email, onLogout }: SettingsProps) {
                 setSyncFolder(e.target.value);
                 setSyncFolderError(null);
               }}
-              onBlur={async () => {
-                setSyncFolderError(null);
-                try {
-                  await invoke("set_sync_folder_path", { folder: syncFolder });
-                } catch (e) {
-                  setSyncFolderError(formatUserFacingError(e));
-                  console.error(e);
-                }
-              }}
+              onBlur={() => saveSyncFolder(syncFolder)}
               placeholder="e.g. ~/brandyBox"
               error={Boolean(syncFolderError)}
               helperText={syncFolderError ?? "Path is saved when you leave this field"}
             />
+            <Button size="small" variant="outlined" sx={{ flexShrink: 0, alignSelf: "flex-start" }} onClick={handleBrowseSyncFolder}>
+              Browse…
+            </Button>
           </Box>
+          {syncStatusInfo?.volume_warning && (
+            <Alert severity="warning" sx={{ mt: 1 }}>
+              {syncStatusInfo.volume_warning}
+            </Alert>
+          )}
+          {syncStatusInfo?.restore_pending ? (
+            <Alert severity="info" sx={{ mt: 1 }}>
+              Restoring: files are downloaded from the server. Uploads and deletes on the server start once the first
+              sync has completed.
+            </Alert>
+          ) : (
+            <Button size="small" sx={{ mt: 1 }} disabled={syncing} onClick={() => setRestoreOpen(true)}>
+              Restore from server…
+            </Button>
+          )}
         </CardContent>
       </Card>
 
+      {syncStats != null && syncStats.lifetime.cycles > 0 && (() => {
+        const week = Object.values(syncStats.days).reduce(
+          (acc, d) => ({
+            up: acc.up + d.bytes_uploaded,
+            down: acc.down + d.bytes_downloaded,
+            files: acc.files + d.files_uploaded + d.files_downloaded,
+          }),
+          { up: 0, down: 0, files: 0 }
+        );
+        const life = syncStats.lifetime;
+        return (
+          <Card sx={{ mb: 2 }} variant="outlined">
+            <CardContent>
+              <Typography variant="subtitle2" color="text.secondary" gutterBottom>
+                Statistics
+              </Typography>
+              <Typography variant="body2">
+                Last 7 days: {formatBytes(week.up)} up, {formatBytes(week.down)} down, {week.files} file(s)
+              </Typography>
+              <Typography variant="body2">
+                Total: {formatBytes(life.bytes_uploaded)} up, {formatBytes(life.bytes_downloaded)} down,{" "}
+                {life.files_uploaded + life.files_downloaded} file(s)
+              </Typography>
+              <Typography variant="caption" color="text.secondary" display="block">
+                {life.cycles} sync run(s), {life.errors} failed
+                {syncStats.since && ` since ${new Date(syncStats.since).toLocaleDateString()}`}
+              </Typography>
+            </CardContent>
+          </Card>
+        );
+      })()}
+
       <Card sx={{ mb: 2 }} variant="outlined">
         <CardContent>
           <Typography variant="subtitle2" color="text.secondary" gutterBottom>
@@ -450,9 +1156,132 @@ export default function Settings({ email, onLogout }: SettingsProps) {
             control={<Switch checked={autostart} onChange={handleAutostart} />}
             label="Start Brandy Box when I log in"
           />
+          <FormControl component="fieldset" sx={{ display: "block", mt: 1 }}>
+            <Typography variant="caption" color="text.secondary">
+              Tray icon for
+            </Typography>
+            <RadioGroup row value={trayIconTheme} onChange={(_, v) => handleTrayIconTheme(v)}>
+              <FormControlLabel value="system" control={<Radio />} label="System theme" />
+              <FormControlLabel value="light" control={<Radio />} label="Light taskbar" />
+              <FormControlLabel value="dark" control={<Radio />} label="Dark taskbar" />
+            </RadioGroup>
+          </FormControl>
+          <FormControl component="fieldset" sx={{ display: "block", mt: 1 }}>
+            <Typography variant="caption" color="text.secondary">
+              Language of notifications and sync messages
+            </Typography>
+            <RadioGroup row value={language} onChange={(_, v) => handleLanguage(v)}>
+              <FormControlLabel value="system" control={<Radio />} label="System" />
+              <FormControlLabel value="en" control={<Radio />} label="English" />
+              <FormControlLabel value="de" control={<Radio />} label="Deutsch" />
+            </RadioGroup>
+          </FormControl>
+          <Box sx={{ mt: 1, display: "flex", gap: 1 }}>
+            <TextField
+              size="small"
+              label="Shortcut: show window"
+              placeholder="Off"
+              value={shortcutShow}
+              onChange={(e) => setShortcutShow(e.target.value)}
+              onBlur={handleShortcuts}
+            />
+            <TextField
+              size="small"
+              label="Shortcut: sync now"
+              placeholder="Off"
+              value={shortcutSync}
+              onChange={(e) => setShortcutSync(e.target.value)}
+              onBlur={handleShortcuts}
+            />
+          </Box>
+          {shortcutError && (
+            <Typography variant="caption" display="block" color="error" sx={{ mt: 0.5 }}>
+              {shortcutError}
+            </Typography>
+          )}
         </CardContent>
       </Card>
 
+      {notifications && (
+        <Card sx={{ mb: 2 }} variant="outlined">
+          <CardContent>
+            <Typography variant="subtitle2" color="text.secondary" gutterBottom>
+              Notifications
+            </Typography>
+            <FormControlLabel
+              sx={{ display: "flex" }}
+              control={
+                <Switch
+                  checked={notifications.enabled}
+                  onChange={(_, checked) => saveNotifications({ ...notifications, enabled: checked })}
+                />
+              }
+              label="Show notifications"
+            />
+            <FormControlLabel
+              sx={{ display: "flex" }}
+              control={
+                <Switch
+                  checked={notifications.schedule.quiet_hours != null}
+                  disabled={!notifications.enabled}
+                  onChange={(_, checked) =>
+                    saveNotifications({
+                      ...notifications,
+                      schedule: {
+                        ...notifications.schedule,
+                        quiet_hours: checked ? { start: "22:00", end: "07:00", days: [] } : null,
+                      },
+                    })
+                  }
+                />
+              }
+              label="Quiet hours"
+            />
+            {notifications.schedule.quiet_hours && (
+              <Box sx={{ display: "flex", gap: 1, alignItems: "center", ml: 6, mb: 1 }}>
+                {(["start", "end"] as const).map((key) => (
+                  <TextField
+                    key={key}
+                    size="small"
+                    type="time"
+                    label={key === "start" ? "From" : "Until"}
+                    value={notifications.schedule.quiet_hours?.[key] ?? ""}
+                    disabled={!notifications.enabled}
+                    InputLabelProps={{ shrink: true }}
+                    sx={{ width: 130 }}
+                    onChange={(e) => {
+                      const quiet = notifications.schedule.quiet_hours;
+                      if (quiet && /^\d{2}:\d{2}$/.test(e.target.value)) {
+                        saveNotifications({
+                          ...notifications,
+                          schedule: { ...notifications.schedule, quiet_hours: { ...quiet, [key]: e.target.value } },
+                        });
+                      }
+                    }}
+                  />
+                ))}
+              </Box>
+            )}
+            <FormControlLabel
+              sx={{ display: "flex" }}
+              control={
+                <Switch
+                  checked={notifications.schedule.mute_fullscreen}
+                  disabled={!notifications.enabled}
+                  onChange={(_, checked) =>
+                    saveNotifications({ ...notifications, schedule: { ...notifications.schedule, mute_fullscreen: checked } })
+                  }
+                />
+              }
+              label="Mute while a fullscreen app is open (games, videos, presentations)"
+            />
+            <Typography variant="caption" color="text.secondary">
+              Only notifications are muted; syncing continues.
+            </Typography>
+          </CardContent>
+        </Card>
+      )}
+
       <Card sx={{ mb: 2 }} variant="outlined">
         <CardContent>
           <Typography variant="subtitle2" color="text.secondary" gutterBottom>
@@ -466,67 +1295,445 @@ export default function Settings({ email, onLogout }: SettingsProps) {
           >
             Sync now
           </Button>
+          <Button
+            size="small"
+            sx={{ ml: 1 }}
+            disabled={syncing}
+            onClick={async () => {
+              try {
+                const e = await invoke<SyncEstimate>("estimate_sync");
+                const unknown = e.download_unknown_size_files > 0 ? ` (+${e.download_unknown_size_files} of unknown size)` : "";
+                setSyncEstimate(
+                  `Upload ${e.upload_files} file(s), ${formatBytes(e.upload_bytes)} · ` +
+                    `Download ${e.download_files} file(s), ${formatBytes(e.download_bytes)}${unknown} · ` +
+                    `Delete ${e.delete_remote_files} on server, ${e.delete_local_files} locally`
+                );
+              } catch (err) {
+                setSyncEstimate(formatUserFacingError(err));
+              }
+            }}
+          >
+            Estimate
+          </Button>
+          {syncStatusInfo?.held_local_deletes ? (
+            <Alert
+              severity="warning"
+              sx={{ mt: 1 }}
+              action={
+                <Button color="inherit" size="small" disabled={syncing} onClick={() => startSync("confirm_held_deletes")}>
+                  Delete here too
+                </Button>
+              }
+            >
+              {syncStatusInfo.held_local_deletes} file(s) are missing from the server listing, so their local copies were
+              kept. Check the server; if they were deleted on purpose, delete them here too.
+            </Alert>
+          ) : null}
+          {syncStatusInfo?.overdue_hours ? (
+            <Alert severity="warning" sx={{ mt: 1 }}>
+              No successful sync for {syncStatusInfo.overdue_hours} hours. Check the error above or the log.
+            </Alert>
+          ) : (
+            syncStatusInfo?.last_successful_sync && (
+              <Typography variant="caption" display="block" color="text.secondary" sx={{ mt: 1 }}>
+                Last synced {formatAgo(syncStatusInfo.last_successful_sync)}
+              </Typography>
+            )
+          )}
+          {syncEstimate && (
+            <Typography variant="caption" display="block" sx={{ mt: 1 }}>
+              {syncEstimate}
+            </Typography>
+          )}
           {syncError && (
             <Alert severity="error" role="alert" onClose={() => setSyncError(null)} sx={{ mt: 1 }}>
               {syncError}
+              {syncStatusInfo?.error_code === "network" && (
+                <Typography variant="caption" display="block">
+                  The server could not be reached. Sync retries automatically when the connection is back.
+                </Typography>
+              )}
+              {syncStatusInfo?.error_code === "quota_exceeded" && (
+                <Typography variant="caption" display="block">
+                  Your storage is full. Delete files or ask the admin for more space.
+                </Typography>
+              )}
             </Alert>
           )}
           {syncProgress && (
             <Box sx={{ mt: 1 }}>
-              <Typography variant="caption">{syncProgress.phase} {syncProgress.total > 0 ? `${syncProgress.current} / ${syncProgress.total}` : ""}</Typography>
+              <Typography variant="caption">
+                {syncProgress.detail ?? syncProgress.phase} {syncProgress.total > 0 && !syncProgress.detail ? `${syncProgress.current} / ${syncProgress.total}` : ""}
+              </Typography>
               {syncProgress.total > 0 && (
                 <LinearProgress variant="determinate" value={(syncProgress.current / syncProgress.total) * 100} sx={{ mt: 0.5 }} />
               )}
             </Box>
           )}
+          <FormControlLabel
+            sx={{ mt: 1, display: "flex" }}
+            control={<Switch checked={syncHidden} onChange={handleSyncHidden} />}
+            label="Sync hidden files and folders (e.g. .obsidian)"
+          />
+          <FormControlLabel
+            sx={{ display: "flex" }}
+            control={
+              <Switch
+                checked={metadataEncryption?.enabled ?? false}
+                disabled={metadataEncryption?.key_missing ?? false}
+                onChange={handleMetadataEncryption}
+              />
+            }
+            label="Encrypt the file list and settings on this computer (key in the system keyring)"
+          />
+          {metadataEncryption?.key_missing && (
+            <Alert severity="error" sx={{ mt: 0.5 }}>
+              The settings and sync state on this computer are encrypted, but the keyring no longer has the key. Sync is
+              stopped. Restore the keyring, or delete the files in the config folder to start over.
+            </Alert>
+          )}
+          {metadataEncryptionError && (
+            <Alert severity="error" sx={{ mt: 0.5 }} onClose={() => setMetadataEncryptionError("")}>
+              {metadataEncryptionError}
+            </Alert>
+          )}
+          <FormControlLabel
+            sx={{ display: "flex" }}
+            control={<Switch checked={deleteSnapshots?.enabled ?? false} onChange={handleDeleteSnapshots} />}
+            label="Keep a copy of files before sync deletes them here"
+          />
+          {deleteSnapshots?.enabled && (
+            <Typography variant="caption" display="block" color="text.secondary">
+              {deleteSnapshots.snapshots.length} snapshot(s),{" "}
+              {formatBytes(deleteSnapshots.snapshots.reduce((sum, s) => sum + s.bytes, 0))} of{" "}
+              {formatBytes(deleteSnapshots.limit_bytes)}; oldest removed beyond that.{" "}
+              <Link component="button" variant="caption" onClick={() => invoke("open_delete_snapshots_folder")}>
+                Open folder
+              </Link>
+            </Typography>
+          )}
+          <TextField
+            size="small"
+            type="number"
+            label="Overwrite deleted files (passes)"
+            placeholder="Off"
+            value={secureDeletePasses}
+            onChange={(e) => setSecureDeletePasses(e.target.value)}
+            onBlur={handleSecureDelete}
+            inputProps={{ min: 0, max: 7 }}
+            helperText="For sensitive files on unencrypted disks; not effective on SSDs"
+            sx={{ mt: 1, display: "flex" }}
+          />
+          <FormControlLabel
+            sx={{ display: "flex" }}
+            control={
+              <Switch
+                checked={downloadOnly?.enabled ?? false}
+                disabled={downloadOnly?.forced ?? false}
+                onChange={handleDownloadOnly}
+              />
+            }
+            label="Download only (shared display)"
+          />
+          {downloadOnly?.enabled && (
+            <Typography variant="caption" display="block" color="text.secondary">
+              The folder mirrors the server: nothing is uploaded or deleted there, and local edits are replaced by the
+              server copy.{downloadOnly.forced && " Set by the --download-only launch option."}
+            </Typography>
+          )}
+          <TextField
+            size="small"
+            type="number"
+            label="Skip files larger than (MB)"
+            placeholder="No limit"
+            value={maxFileSizeMb}
+            onChange={(e) => setMaxFileSizeMb(e.target.value)}
+            onBlur={handleMaxFileSize}
+            sx={{ mt: 1 }}
+          />
+          {ignorePaths.length > 0 && (
+            <Box sx={{ mt: 1 }}>
+              <Typography variant="caption" color="text.secondary" sx={{ display: "block" }}>
+                Excluded from sync (remove to sync again):
+              </Typography>
+              <Box sx={{ display: "flex", flexWrap: "wrap", gap: 0.5, mt: 0.5 }}>
+                {ignorePaths.map((path) => (
+                  <Chip key={path} size="small" label={path} onDelete={() => handleUnignore(path)} />
+                ))}
+              </Box>
+            </Box>
+          )}
           <Typography variant="caption" color="text.secondary" sx={{ mt: 1, display: "block" }}>
             Same account on multiple devices: if two copies of a file change before sync, the version that uploads last replaces the other (no merge dialog). Server is source of truth after you confirm the sync folder.
           </Typography>
         </CardContent>
       </Card>
 
-      <Card variant="outlined">
-        <CardContent>
+      {!appLock.locked && (
+        <>
+          <Card sx={{ mb: 2 }} variant="outlined">
+            <CardContent>
+              <Button
+                fullWidth
+                onClick={() => setDevicesOpen(!devicesOpen)}
+                endIcon={devicesOpen ? <ExpandLess /> : <ExpandMore />}
+                sx={{ justifyContent: "space-between" }}
+              >
+                Devices
+              </Button>
+              <Collapse in={devicesOpen}>
+                {devicesError && (
+                  <Alert severity="error" sx={{ mt: 1 }} onClose={() => setDevicesError(null)}>
+                    {devicesError}
+                  </Alert>
+                )}
+                <List dense>
+                  {devices.map((d) => (
+                    <ListItem key={d.device_id}>
+                      <ListItemText
+                        primary={`${d.name}${d.current ? " (this device)" : ""}${d.revoked ? " – revoked" : ""}`}
+                        secondary={
+                          `${d.platform} · v${d.client_version} · last seen ${new Date(d.last_seen_at).toLocaleString()}` +
+                          (d.last_sync_at ? ` · last sync ${d.last_sync_ok === false ? "failed" : "ok"}` : "")
+                        }
+                      />
+                      {!d.current && !d.revoked && (
+                        <ListItemSecondaryAction>
+                          <IconButton edge="end" size="small" color="error" onClick={() => handleRevokeDevice(d)}>
+                            Revoke
+                          </IconButton>
+                        </ListItemSecondaryAction>
+                      )}
+                    </ListItem>
+                  ))}
+                </List>
+              </Collapse>
+            </CardContent>
+          </Card>
+
+          <Card variant="outlined">
+            <CardContent>
+              <Button
+                fullWidth
+                onClick={() => setAdminOpen(!adminOpen)}
+                endIcon={adminOpen ? <ExpandLess /> : <ExpandMore />}
+                sx={{ justifyContent: "space-between" }}
+              >
+                Admin – User management
+              </Button>
+              <Collapse in={adminOpen}>
+                <Box sx={{ mt: 1 }}>
+                  {adminActionError && (
+                    <Alert severity="error" sx={{ mb: 1 }} onClose={() => setAdminActionError(null)}>
+                      {adminActionError}
+                    </Alert>
+                  )}
+                  {adminActionMessage && (
+                    <Alert severity="info" sx={{ mb: 1 }} onClose={() => setAdminActionMessage(null)}>
+                      {adminActionMessage}
+                    </Alert>
+                  )}
+                  <Button
+                    size="small"
+                    onClick={() => {
+                      setCreateUserError("");
+                      setCreateUserOpen(true);
+                    }}
+                  >
+                    Create user
+                  </Button>
+                  <Button
+                    size="small"
+                    onClick={() => {
+                      setImportError("");
+                      setImportReport(null);
+                      setImportProgress(null);
+                      setImportOpen(true);
+                    }}
+                  >
+                    Import CSV
+                  </Button>
+                  <List dense>
+                    {users.map((u) => (
+                      <ListItem key={u.email}>
+                        <ListItemText
+                          primary={u.is_enabled === false ? `${u.email} (suspended)` : u.email}
+                          secondaryTypographyProps={{ component: "div" }}
+                          secondary={
+                            <>
+                              {u.first_name || u.last_name ? `${u.first_name ?? ""} ${u.last_name ?? ""}`.trim() : null}
+                              <Box sx={{ mt: 0.5, pr: 20 }}>
+                                {u.storage_limit_bytes != null && u.storage_limit_bytes > 0 && (
+                                  <LinearProgress
+                                    variant="determinate"
+                                    value={Math.min(100, ((u.used_bytes ?? 0) / u.storage_limit_bytes) * 100)}
+                                    sx={{ height: 6, borderRadius: 1, mb: 0.25 }}
+                                  />
+                                )}
+                                {formatBytes(u.used_bytes ?? 0)} used
+                                {u.storage_limit_bytes != null ? ` of ${formatBytes(u.storage_limit_bytes)}` : ""}
+                              </Box>
+                            </>
+                          }
+                        />
+                        <ListItemSecondaryAction>
+                          <IconButton
+                            size="small"
+                            onClick={() => {
+                              setEditUserError("");
+                              setEditUser({
+                                email: u.email,
+                                first_name: u.first_name ?? "",
+                                last_name: u.last_name ?? "",
+                                is_admin: u.is_admin === true,
+                              });
+                            }}
+                          >
+                            Edit
+                          </IconButton>
+                          <IconButton size="small" onClick={() => handleSetUserEnabled(u.email, u.is_enabled === false)}>
+                            {u.is_enabled === false ? "Enable" : "Suspend"}
+                          </IconButton>
+                          <IconButton size="small" onClick={() => handleResetPassword(u.email)}>
+                            Reset password
+                          </IconButton>
+                          <IconButton edge="end" size="small" onClick={() => handleDeleteUser(u.email)} color="error">
+                            Delete
+                          </IconButton>
+                        </ListItemSecondaryAction>
+                      </ListItem>
+                    ))}
+                  </List>
+                </Box>
+              </Collapse>
+            </CardContent>
+          </Card>
+        </>
+      )}
+
+      <Dialog open={pendingCrashes > 0} maxWidth="xs" fullWidth>
+        <DialogTitle>Brandy Box closed unexpectedly</DialogTitle>
+        <DialogContent>
+          <Typography variant="body2">
+            A crash report was saved ({pendingCrashes}). Send it to your Brandy Box server so the administrator can
+            look into it? It contains the error and app version, no file contents.
+          </Typography>
+        </DialogContent>
+        <DialogActions>
+          <Button
+            onClick={() => {
+              invoke("discard_crash_reports").catch(() => {});
+              setPendingCrashes(0);
+            }}
+          >
+            Don&apos;t send
+          </Button>
+          <Button
+            variant="contained"
+            onClick={() => {
+              invoke("send_crash_reports").catch(() => {});
+              setPendingCrashes(0);
+            }}
+          >
+            Send
+          </Button>
+        </DialogActions>
+      </Dialog>
+
+      <Dialog open={syncFolderConfirm.length > 0} onClose={cancelSyncFolderChange} maxWidth="xs" fullWidth>
+        <DialogTitle>Use this sync folder?</DialogTitle>
+        <DialogContent>
+          {syncFolderConfirm.map((reason) => (
+            <Alert severity="warning" sx={{ mb: 1 }} key={reason}>
+              {reason}
+            </Alert>
+          ))}
+        </DialogContent>
+        <DialogActions>
+          <Button onClick={cancelSyncFolderChange}>Cancel</Button>
           <Button
+            variant="contained"
+            color="warning"
+            onClick={async () => {
+              setSyncFolderConfirm([]);
+              try {
+                await invoke("set_sync_folder_path", { folder: syncFolder, confirmed: true });
+              } catch (e) {
+                setSyncFolderError(formatUserFacingError(e));
+              }
+            }}
+          >
+            Use anyway
+          </Button>
+        </DialogActions>
+      </Dialog>
+
+      <Dialog
+        open={restoreOpen}
+        onClose={() => {
+          setRestoreOpen(false);
+          setRestoreConfirm([]);
+        }}
+        maxWidth="xs"
+        fullWidth
+      >
+        <DialogTitle>Restore onto this computer</DialogTitle>
+        <DialogContent>
+          <Typography variant="body2" sx={{ mb: 1 }}>
+            Downloads everything from the server into {syncFolder}. Until that has finished, nothing is uploaded and
+            nothing is deleted on the server; files already in the folder are kept. Then sync continues both ways.
+          </Typography>
+          {restoreConfirm.map((reason) => (
+            <Alert severity="warning" sx={{ mb: 1 }} key={reason}>
+              {reason}
+            </Alert>
+          ))}
+          {restoreError && <Alert severity="error">{restoreError}</Alert>}
+        </DialogContent>
+        <DialogActions>
+          <Button
+            onClick={() => {
+              setRestoreOpen(false);
+              setRestoreConfirm([]);
+            }}
+          >
+            Cancel
+          </Button>
+          <Button variant="contained" color={restoreConfirm.length > 0 ? "warning" : "primary"} onClick={handleStartRestore}>
+            {restoreConfirm.length > 0 ? "Restore anyway" : "Restore"}
+          </Button>
+        </DialogActions>
+      </Dialog>
+
+      <Dialog open={deleteAccountOpen} onClose={() => setDeleteAccountOpen(false)} maxWidth="xs" fullWidth>
+        <DialogTitle>Delete account</DialogTitle>
+        <DialogContent>
+          <Typography variant="body2" sx={{ mb: 1 }}>
+            This permanently deletes your Brandy Box account and all files stored on the server. Files in your local
+            sync folder are kept. Consider exporting your data first.
+          </Typography>
+          {deleteAccountError && <Alert severity="error" sx={{ mb: 1 }}>{deleteAccountError}</Alert>}
+          <TextField fullWidth label="Password" type="password" value={deletePwd} onChange={(e) => setDeletePwd(e.target.value)} margin="dense" />
+          <TextField
             fullWidth
-            onClick={() => setAdminOpen(!adminOpen)}
-            endIcon={adminOpen ? <ExpandLess /> : <ExpandMore />}
-            sx={{ justifyContent: "space-between" }}
+            label='Type "DELETE" to confirm'
+            value={deleteConfirmText}
+            onChange={(e) => setDeleteConfirmText(e.target.value)}
+            margin="dense"
+          />
+        </DialogContent>
+        <DialogActions>
+          <Button onClick={() => setDeleteAccountOpen(false)}>Cancel</Button>
+          <Button
+            variant="contained"
+            color="error"
+            disabled={!deletePwd || deleteConfirmText !== "DELETE"}
+            onClick={handleDeleteAccount}
           >
-            Admin – User management
+            Delete
           </Button>
-          <Collapse in={adminOpen}>
-            <Box sx={{ mt: 1 }}>
-              {adminActionError && (
-                <Alert severity="error" sx={{ mb: 1 }} onClose={() => setAdminActionError(null)}>
-                  {adminActionError}
-                </Alert>
-              )}
-              <Button
-                size="small"
-                onClick={() => {
-                  setCreateUserError("");
-                  setCreateUserOpen(true);
-                }}
-              >
-                Create user
-              </Button>
-              <List dense>
-                {users.map((u) => (
-                  <ListItem key={u.email}>
-                    <ListItemText primary={u.email} secondary={u.first_name || u.last_name ? `${u.first_name ?? ""} ${u.last_name ?? ""}`.trim() : undefined} />
-                    <ListItemSecondaryAction>
-                      <IconButton edge="end" size="small" onClick={() => handleDeleteUser(u.email)} color="error">
-                        Delete
-                      </IconButton>
-                    </ListItemSecondaryAction>
-                  </ListItem>
-                ))}
-              </List>
-            </Box>
-          </Collapse>
-        </CardContent>
-      </Card>
+        </DialogActions>
+      </Dialog>
 
       <Dialog open={changePwdOpen} onClose={() => setChangePwdOpen(false)} maxWidth="xs" fullWidth>
         <DialogTitle>Change password</DialogTitle>
@@ -534,10 +1741,28 @@ export default function Settings({ email, onLogout }: SettingsProps) {
           {changePwdError && <Alert severity="error" sx={{ mb: 1 }}>{changePwdError}</Alert>}
           <TextField fullWidth label="Current password" type="password" value={currentPwd} onChange={(e) => setCurrentPwd(e.target.value)} margin="dense" />
           <TextField fullWidth label="New password" type="password" value={newPwd} onChange={(e) => setNewPwd(e.target.value)} margin="dense" />
+          <TextField fullWidth label="Repeat new password" type="password" value={confirmPwd} onChange={(e) => setConfirmPwd(e.target.value)} margin="dense" />
+          {pwdCheck && (
+            <Box sx={{ mt: 1 }}>
+              <LinearProgress
+                variant="determinate"
+                value={(pwdCheck.score + 1) * 20}
+                color={pwdCheck.score >= 3 ? "success" : pwdCheck.score >= 2 ? "warning" : "error"}
+              />
+              <Typography variant="caption" color="text.secondary">
+                Strength: {PASSWORD_STRENGTH[pwdCheck.score]}
+              </Typography>
+              {pwdCheck.issues.map((i) => (
+                <Typography key={i.code} variant="body2" color="error">
+                  {i.message}
+                </Typography>
+              ))}
+            </Box>
+          )}
         </DialogContent>
         <DialogActions>
           <Button onClick={() => setChangePwdOpen(false)}>Cancel</Button>
-          <Button variant="contained" onClick={handleChangePassword}>Change</Button>
+          <Button variant="contained" onClick={handleChangePassword} disabled={!pwdCheck?.ok}>Change</Button>
         </DialogActions>
       </Dialog>
 
@@ -566,6 +1791,109 @@ export default function Settings({ email, onLogout }: SettingsProps) {
           <Button variant="contained" onClick={handleCreateUser}>Create</Button>
         </DialogActions>
       </Dialog>
+
+      <Dialog open={importOpen} onClose={() => !importing && setImportOpen(false)} maxWidth="sm" fullWidth>
+        <DialogTitle>Import users from CSV</DialogTitle>
+        <DialogContent>
+          <Typography variant="body2" color="text.secondary" sx={{ mb: 1 }}>
+            One user per line: email, first name, last name, optional quota (e.g. 10GB). A header line is allowed.
+          </Typography>
+          {importError && (
+            <Alert severity="error" sx={{ mb: 1 }}>
+              {importError}
+            </Alert>
+          )}
+          <TextField
+            fullWidth
+            label="CSV file path"
+            value={importPath}
+            onChange={(e) => setImportPath(e.target.value)}
+            disabled={importing}
+            margin="dense"
+          />
+          {importing && importProgress && (
+            <Box sx={{ mt: 1 }}>
+              <LinearProgress
+                variant="determinate"
+                value={importProgress.total > 0 ? (importProgress.current / importProgress.total) * 100 : 0}
+              />
+              <Typography variant="caption" color="text.secondary">
+                {importProgress.current} / {importProgress.total} · {importProgress.email}
+              </Typography>
+            </Box>
+          )}
+          {importReport && (
+            <>
+              <Typography variant="body2" sx={{ mt: 1 }}>
+                {importReport.filter((r) => r.ok).length} of {importReport.length} user(s) created.
+              </Typography>
+              <List dense>
+                {importReport.map((r) => (
+                  <ListItem key={r.line} disableGutters>
+                    <ListItemText
+                      primary={`Line ${r.line}: ${r.email || "(no email)"}`}
+                      secondary={
+                        r.ok
+                          ? r.temp_password
+                            ? `Created · temporary password: ${r.temp_password}`
+                            : "Created · password sent by email"
+                          : r.error
+                      }
+                      secondaryTypographyProps={{ color: r.ok ? "text.secondary" : "error" }}
+                    />
+                  </ListItem>
+                ))}
+              </List>
+            </>
+          )}
+        </DialogContent>
+        <DialogActions>
+          <Button onClick={() => setImportOpen(false)} disabled={importing}>
+            Close
+          </Button>
+          <Button variant="contained" onClick={handleBulkImport} disabled={importing || !importPath.trim()}>
+            {importing ? <CircularProgress size={20} /> : "Import"}
+          </Button>
+        </DialogActions>
+      </Dialog>
+
+      <Dialog open={editUser !== null} onClose={() => setEditUser(null)} maxWidth="xs" fullWidth>
+        <DialogTitle>Edit {editUser?.email}</DialogTitle>
+        <DialogContent>
+          {editUserError && (
+            <Alert severity="error" sx={{ mb: 1 }}>
+              {editUserError}
+            </Alert>
+          )}
+          <TextField
+            fullWidth
+            label="First name"
+            value={editUser?.first_name ?? ""}
+            onChange={(e) => setEditUser((u) => (u ? { ...u, first_name: e.target.value } : u))}
+            margin="dense"
+          />
+          <TextField
+            fullWidth
+            label="Last name"
+            value={editUser?.last_name ?? ""}
+            onChange={(e) => setEditUser((u) => (u ? { ...u, last_name: e.target.value } : u))}
+            margin="dense"
+          />
+          <FormControlLabel
+            control={
+              <Switch
+                checked={editUser?.is_admin ?? false}
+                onChange={(_, checked) => setEditUser((u) => (u ? { ...u, is_admin: checked } : u))}
+              />
+            }
+            label="Administrator"
+          />
+        </DialogContent>
+        <DialogActions>
+          <Button onClick={() => setEditUser(null)}>Cancel</Button>
+          <Button variant="contained" onClick={handleUpdateUser}>Save</Button>
+        </DialogActions>
+      </Dialog>
     </Box>
   );
 }
@@ -0,0 +1,147 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { Box, Button, CssBaseline, LinearProgress, ThemeProvider, Typography } from "@mui/material";
+import { theme } from "./App";
+
+interface StatusPayload {
+  status: string;
+  message?: string | null;
+  paused_until?: string | null;
+}
+
+interface SyncProgress {
+  phase: string;
+  current: number;
+  total: number;
+  detail?: string | null;
+}
+
+interface ActivityEvent {
+  at: string;
+  kind: "uploaded" | "downloaded" | "deleted_locally" | "deleted_remotely" | "renamed";
+  path: string;
+}
+
+const RECENT_LIMIT = 8;
+
+const STATUS_TEXT: Record<string, string> = {
+  idle: "Idle",
+  syncing: "Syncing…",
+  synced: "Up to date",
+  warning: "Synced with warnings",
+  error: "Sync error",
+  auth_expired: "Signed out – open Settings to log in",
+  device_revoked: "This device was removed",
+};
+
+const KIND_TEXT: Record<ActivityEvent["kind"], string> = {
+  uploaded: "↑",
+  downloaded: "↓",
+  deleted_locally: "✕",
+  deleted_remotely: "✕",
+  renamed: "→",
+};
+
+/** Small window at the tray (`show_status_popover`): status, progress and recent activity. */
+export default function StatusPopover() {
+  const [status, setStatus] = useState<StatusPayload | null>(null);
+  const [progress, setProgress] = useState<SyncProgress | null>(null);
+  const [recent, setRecent] = useState<ActivityEvent[]>([]);
+
+  const load = useCallback(() => {
+    invoke<StatusPayload>("get_sync_status").then(setStatus).catch(() => {});
+    invoke<SyncProgress | null>("get_sync_progress").then(setProgress).catch(() => {});
+    invoke<ActivityEvent[]>("get_recent_activity", { limit: RECENT_LIMIT }).then(setRecent).catch(() => {});
+  }, []);
+
+  useEffect(() => {
+    load();
+    const unlisteners = [
+      listen<StatusPayload>("sync-status", (event) => {
+        setStatus(event.payload);
+        if (event.payload.status !== "syncing") load();
+      }),
+      listen<SyncProgress>("sync-progress", (event) => setProgress(event.payload)),
+    ];
+    // The window is hidden, not closed, between uses: refresh when it is shown again
+    window.addEventListener("focus", load);
+    return () => {
+      unlisteners.forEach((p) => p.then((fn) => fn()));
+      window.removeEventListener("focus", load);
+    };
+  }, [load]);
+
+  const syncing = status?.status === "syncing";
+
+  return (
+    <ThemeProvider theme={theme}>
+      <CssBaseline />
+      <Box sx={{ p: 1.5, height: "100vh", display: "flex", flexDirection: "column", overflow: "hidden" }}>
+        <Typography variant="subtitle2">{STATUS_TEXT[status?.status ?? "idle"] ?? status?.status}</Typography>
+        {status?.message && (
+          <Typography variant="caption" color="text.secondary" noWrap title={status.message}>
+            {status.message}
+          </Typography>
+        )}
+        {syncing && progress && (
+          <Box sx={{ mt: 1 }}>
+            <Typography variant="caption" noWrap display="block">
+              {progress.detail ?? progress.phase} {progress.total > 0 && !progress.detail ? `${progress.current} / ${progress.total}` : ""}
+            </Typography>
+            {progress.total > 0 ? (
+              <LinearProgress variant="determinate" value={(progress.current / progress.total) * 100} sx={{ mt: 0.5 }} />
+            ) : (
+              <LinearProgress sx={{ mt: 0.5 }} />
+            )}
+          </Box>
+        )}
+        <Typography variant="caption" color="text.secondary" sx={{ mt: 1.5 }}>
+          Recent activity
+        </Typography>
+        <Box sx={{ flex: 1, overflow: "auto" }}>
+          {recent.length === 0 ? (
+            <Typography variant="body2" color="text.secondary">
+              Nothing yet
+            </Typography>
+          ) : (
+            recent.map((e) => (
+              <Typography
+                key={`${e.at}-${e.kind}-${e.path}`}
+                variant="body2"
+                noWrap
+                title={`${e.path} · ${new Date(e.at).toLocaleString()}`}
+                sx={{ cursor: e.kind === "uploaded" || e.kind === "downloaded" || e.kind === "renamed" ? "pointer" : "default" }}
+                onClick={() => {
+                  if (e.kind === "uploaded" || e.kind === "downloaded" || e.kind === "renamed") {
+                    invoke("reveal_in_file_manager", { relativePath: e.path }).catch(() => {});
+                  }
+                }}
+              >
+                {KIND_TEXT[e.kind]} {e.path}
+              </Typography>
+            ))
+          )}
+        </Box>
+        <Box sx={{ display: "flex", gap: 1, mt: 1 }}>
+          <Button size="small" variant="contained" disabled={syncing} onClick={() => invoke("run_sync").catch(() => {})}>
+            Sync now
+          </Button>
+          <Button size="small" onClick={() => invoke("open_sync_folder").catch(() => {})}>
+            Open folder
+          </Button>
+          <Button
+            size="small"
+            sx={{ ml: "auto" }}
+            onClick={async () => {
+              await invoke("hide_status_popover").catch(() => {});
+              await invoke("show_main_window").catch(() => {});
+            }}
+          >
+            Settings
+          </Button>
+        </Box>
+      </Box>
+    </ThemeProvider>
+  );
+}
@@ -1,9 +1,12 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import App from "./App";
+import StatusPopover from "./StatusPopover";
+
+// The status popover window loads the same page; its label decides what it shows
+const isStatusPopover = getCurrentWindow().label === "status";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
-  <React.StrictMode>
-    <App />
-  </React.StrictMode>,
+  <React.StrictMode>{isStatusPopover ? <StatusPopover /> : <App />}</React.StrictMode>,
 );
@@ -0,0 +1,106 @@
+#!/usr/bin/env python3
+"""Generate the Windows Explorer overlay icons (synced, syncing, error) as multi-size .ico files.
+Uses only stdlib (zlib, struct). Run from repo root: python scripts/generate_overlay_icons.py
+Explorer draws an overlay over the whole file icon, so the badge sits in the lower-left corner.
+"""
+
+import math
+import struct
+import zlib
+from pathlib import Path
+
+OUT = (
+    Path(__file__).resolve().parent.parent
+    / "client-tauri" / "src-tauri" / "crates" / "brandybox-overlay" / "icons"
+)
+SIZES = (16, 32, 48, 256)
+SUPERSAMPLE = 4
+COLORS = {
+    "synced": (46, 160, 67),
+    "syncing": (31, 111, 235),
+    "error": (207, 34, 46),
+}
+
+
+def seg_dist(px, py, ax, ay, bx, by):
+    """Distance from point p to segment a-b."""
+    dx, dy = bx - ax, by - ay
+    t = max(0.0, min(1.0, ((px - ax) * dx + (py - ay) * dy) / (dx * dx + dy * dy)))
+    return math.hypot(px - (ax + t * dx), py - (ay + t * dy))
+
+
+def glyph(kind, x, y):
+    """Is (x, y), in badge coordinates -1..1, part of the white glyph?"""
+    w = 0.16
+    if kind == "synced":
+        return seg_dist(x, y, -0.45, 0.0, -0.12, 0.35) < w or seg_dist(x, y, -0.12, 0.35, 0.48, -0.3) < w
+    if kind == "syncing":
+        r = math.hypot(x, y)
+        angle = math.degrees(math.atan2(y, x)) % 360
+        ring = abs(r - 0.5) < w and not (30 < angle < 80 or 210 < angle < 260)
+        head1 = seg_dist(x, y, 0.5, 0.05, 0.28, -0.2) < w * 0.8 or seg_dist(x, y, 0.5, 0.05, 0.72, -0.2) < w * 0.8
+        head2 = seg_dist(x, y, -0.5, -0.05, -0.28, 0.2) < w * 0.8 or seg_dist(x, y, -0.5, -0.05, -0.72, 0.2) < w * 0.8
+        return ring or head1 or head2
+    return seg_dist(x, y, 0.0, -0.55, 0.0, 0.15) < w or math.hypot(x, y - 0.45) < w * 1.1
+
+
+def pixel(kind, size, px, py):
+    """RGBA of one pixel: badge disc with a white glyph, transparent elsewhere."""
+    badge = size * 0.55
+    cx, cy = badge / 2, size - badge / 2
+    color = COLORS[kind]
+    acc = [0.0, 0.0, 0.0, 0.0]
+    for sy in range(SUPERSAMPLE):
+        for sx in range(SUPERSAMPLE):
+            x = (px + (sx + 0.5) / SUPERSAMPLE - cx) / (badge / 2)
+            y = (py + (sy + 0.5) / SUPERSAMPLE - cy) / (badge / 2)
+            r = math.hypot(x, y)
+            if r > 1.0:
+                continue
+            rgb = (255, 255, 255) if r < 0.88 and glyph(kind, x, y) else color
+            for i in range(3):
+                acc[i] += rgb[i]
+            acc[3] += 1
+    n = SUPERSAMPLE * SUPERSAMPLE
+    if acc[3] == 0:
+        return (0, 0, 0, 0)
+    return tuple(int(acc[i] / acc[3]) for i in range(3)) + (int(255 * acc[3] / n),)
+
+
+def png(kind, size):
+    """Encode one icon size as an RGBA PNG."""
+    raw = b"".join(
+        b"\x00" + b"".join(bytes(pixel(kind, size, x, y)) for x in range(size)) for y in range(size)
+    )
+
+    def chunk(chunk_type, data):
+        blob = chunk_type + data
+        return struct.pack(">I", len(data)) + blob + struct.pack(">I", zlib.crc32(blob) & 0xFFFFFFFF)
+
+    ihdr = struct.pack(">IIBBBBB", size, size, 8, 6, 0, 0, 0)
+    return b"\x89PNG\r\n\x1a\n" + chunk(b"IHDR", ihdr) + chunk(b"IDAT", zlib.compress(raw, 9)) + chunk(b"IEND", b"")
+
+
+def ico(kind):
+    """ICO container with PNG-compressed entries (supported since Windows Vista)."""
+    images = [png(kind, s) for s in SIZES]
+    header = struct.pack("<HHH", 0, 1, len(images))
+    offset = 6 + 16 * len(images)
+    entries = b""
+    for size, data in zip(SIZES, images):
+        dim = 0 if size >= 256 else size
+        entries += struct.pack("<BBBBHHII", dim, dim, 0, 0, 1, 32, len(data), offset)
+        offset += len(data)
+    return header + entries + b"".join(images)
+
+
+def main():
+    OUT.mkdir(parents=True, exist_ok=True)
+    for kind in COLORS:
+        path = OUT / f"{kind}.ico"
+        path.write_bytes(ico(kind))
+        print(f"Wrote {path}")
+
+
+if __name__ == "__main__":
+    main()
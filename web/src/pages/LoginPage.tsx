@@ -20,6 +20,7 @@ const errMap: Record<string, string> = {
   oauth_token: "Could not complete Google sign-in. Check server logs.",
   oauth_profile: "Could not read your Google profile.",
   oauth_account: "This Google account does not match the linked Brandy Box user.",
+  account_disabled: "This Brandy Box account is disabled. Contact your administrator.",
 };
 
 export default function LoginPage() {